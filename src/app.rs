@@ -1,11 +1,43 @@
 use cli_log::info;
-use crossterm::event::{self, Event, KeyCode, KeyEvent};
-use std::{env, fmt::Display, io};
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use std::{env, fmt::Display, fs, io, sync::atomic::Ordering, time::Duration};
+use tokio_postgres::Error;
 
 use crate::{
-    postgres::connection_manager::ConnectionManager,
+    clipboard,
+    postgres::{
+        connection_manager::{ApplyError, ConnectionManager, QueryOutcome},
+        data::{Column, ForeignKey},
+        edit::{check_key, CellEdit, CellValue},
+        export::{expand_home, export_rows, format_cells, CopyFormat, ExportJob, ExportOptions},
+        filter::{equality_source, Filter},
+        query::{quote_identifier, PageRequest, TableName, TableQuery},
+    },
+    state::StateFile,
     widgets::{
-        database::Database, database_cluster::DatabaseCluster, database_table::DatabaseTable,
+        cell_inspector::CellInspector,
+        change_review::ChangeReview,
+        change_set::ChangeSet,
+        column_layout::ColumnLayout,
+        data_grid::GridView,
+        database::Database,
+        database_cluster::{DatabaseCluster, UnloadedChildren},
+        database_schema::DatabaseSchema,
+        database_table::DatabaseTable,
+        ddl_view::DdlView,
+        er_diagram::ErDiagram,
+        import_wizard::{ImportStep, ImportWizard},
+        navigation::{NavigationHistory, PagePosition, TableLocation},
+        object_group::ObjectDetails,
+        query_result::{FetchState, QueryResult},
+        reference_picker::{Reference, ReferencePicker},
+        result_search::ResultSearch,
+        row_form::RowForm,
+        structure_view::StructureView,
+        text_scroll::TextScroll,
+        undo_log::{AppliedChange, UndoLog},
     },
 };
 
@@ -15,6 +47,31 @@ pub enum InputMode {
     Editing,
 }
 
+// What `y` copies.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum YankTarget {
+    Cell,
+    Row,
+    Column,
+}
+
+// What the text typed into the search bar is used for.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum InputTarget {
+    Search,
+    Query,
+    Filter,
+    Export,
+    ExportTable,
+    Cell,
+    SaveDdl,
+    ExplorerFilter,
+}
+
+// How often the screen is refreshed while an export runs.
+const EXPORT_PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Clone)]
 pub struct PSQLConnectionOptions {
     pub host: String,
     pub user: String,
@@ -28,6 +85,21 @@ pub enum FocusElement {
     SearchBar,
 }
 
+// Which result the main view shows.
+#[derive(PartialEq, Eq)]
+pub enum MainView {
+    Table,
+    QueryResult,
+    // Properties of an object that isn't a relation
+    Object,
+    // Columns, constraints, indexes and triggers of the selected table
+    Structure,
+    // Reconstructed DDL of a table or another object
+    Ddl,
+    // Tables of a schema and the foreign keys between them
+    Diagram,
+}
+
 // App should store state which are separate from widgets.
 // Widgets should read the state and determin what to render.
 pub struct App {
@@ -37,13 +109,63 @@ pub struct App {
     pub focused_element: FocusElement,
     pub input: String,
     pub input_mode: InputMode,
+    pub input_target: InputTarget,
+    pub input_error: Option<String>,
+    pub main_view: MainView,
+    pub query_result: Option<QueryResult>,
+    pub object_details: Option<ObjectDetails>,
+    pub structure_view: Option<StructureView>,
+    pub ddl_view: Option<DdlView>,
+    pub er_diagram: Option<ErDiagram>,
+    pub inspector: Option<CellInspector>,
+    pub reference_picker: Option<ReferencePicker>,
+    pub search: ResultSearch,
+    pub copy_format: CopyFormat,
+    // `y` was pressed and waits for what to copy
+    pub pending_yank: bool,
+    // Shown in the status line until the next key
+    pub status_message: Option<String>,
+    pub export: Option<ExportJob>,
+    pub import_wizard: Option<ImportWizard>,
+    pub cell_edit: Option<CellEdit>,
+    pub row_form: Option<RowForm>,
+    pub changes: ChangeSet,
+    pub change_review: Option<ChangeReview>,
+    pub undo_log: UndoLog,
+    // Inverse statements of the last applied changes waiting to be confirmed
+    pub pending_undo: Option<Vec<(String, TableQuery)>>,
+    // Show the selected row vertically instead of the grid
+    pub expanded_view: bool,
     pub show_debug: bool,
     pub show_keybinds: bool,
+    pub help_scroll: TextScroll,
     pub should_quit: bool,
     pub user: String,
     pub db_name: String,
     pub host: String,
     input_history: Vec<String>,
+    navigation_history: NavigationHistory,
+    // Cursor and pattern from before the search being typed, restored when
+    // it is cancelled
+    search_origin: Option<((usize, usize), String)>,
+    state_file: StateFile,
+}
+
+// Statement a change set failed on, if it was one, and why.
+fn apply_error_message(apply_error: ApplyError) -> (Option<usize>, String) {
+    match apply_error {
+        ApplyError::Failed(index, error) => (
+            index,
+            match error.as_db_error() {
+                Some(db_error) => db_error.message().to_string(),
+                None => error.to_string(),
+            },
+        ),
+        ApplyError::RowCount(index, count) => (
+            Some(index),
+            format!("the statement found {} rows instead of one", count),
+        ),
+    }
 }
 
 impl App {
@@ -88,10 +210,37 @@ impl App {
             focused_element: FocusElement::Explorer,
             input: String::new(),
             input_mode: InputMode::Normal,
+            input_target: InputTarget::Search,
+            main_view: MainView::Table,
+            query_result: None,
+            object_details: None,
+            structure_view: None,
+            ddl_view: None,
+            er_diagram: None,
+            inspector: None,
+            reference_picker: None,
+            search: ResultSearch::default(),
+            copy_format: CopyFormat::default(),
+            pending_yank: false,
+            status_message: None,
+            export: None,
+            import_wizard: None,
+            cell_edit: None,
+            row_form: None,
+            changes: ChangeSet::default(),
+            change_review: None,
+            undo_log: UndoLog::default(),
+            pending_undo: None,
+            search_origin: None,
+            expanded_view: false,
             input_history: Vec::new(),
+            navigation_history: NavigationHistory::default(),
+            state_file: StateFile::load(),
+            input_error: None,
             should_quit: false,
             show_debug: false,
             show_keybinds: true,
+            help_scroll: TextScroll::default(),
             user,
             db_name,
             host,
@@ -106,8 +255,38 @@ impl App {
     // 2) Focused Element
     //
     pub async fn register_keybinds(&mut self) -> io::Result<()> {
-        if let Event::Key(key) = event::read()? {
-            match self.input_mode {
+        // Keep redrawing the progress of an export while waiting for input
+        if self.export.is_some() {
+            self.check_export().await;
+            if !event::poll(EXPORT_PROGRESS_INTERVAL)? {
+                return Ok(());
+            }
+        }
+
+        let event = event::read()?;
+        if let Event::Key(_) = event {
+            self.status_message = None;
+        }
+
+        match event {
+            Event::Key(key) if self.show_keybinds => self.register_help_keybinds(key),
+            Event::Key(key) if self.inspector.is_some() => self.register_inspector_keybinds(key),
+            Event::Key(key) if self.pending_undo.is_some() => {
+                self.register_undo_keybinds(key).await
+            }
+            Event::Key(key) if self.change_review.is_some() => {
+                self.register_review_keybinds(key).await
+            }
+            Event::Key(key) if self.row_form.is_some() => {
+                self.register_row_form_keybinds(key).await
+            }
+            Event::Key(key) if self.import_wizard.is_some() => {
+                self.register_import_keybinds(key).await
+            }
+            Event::Key(key) if self.reference_picker.is_some() => {
+                self.register_reference_picker_keybinds(key).await
+            }
+            Event::Key(key) => match self.input_mode {
                 InputMode::Normal => match key.code {
                     KeyCode::Char('1') => self.focused_element = FocusElement::Explorer,
                     KeyCode::Char('2') => self.focused_element = FocusElement::SearchBar,
@@ -115,138 +294,2125 @@ impl App {
                     KeyCode::Char('q') => self.should_quit = true,
                     KeyCode::Char('?') => self.show_keybinds = !self.show_keybinds,
                     KeyCode::Char('d') => self.show_debug = !self.show_debug,
+                    KeyCode::Char(':') => self.start_editing(InputTarget::Query),
                     _ => match self.focused_element {
-                        FocusElement::Main => self.register_main_keybinds(key),
+                        FocusElement::Main => self.register_main_keybinds(key).await,
                         FocusElement::Explorer => self.register_explorer_keybinds(key).await,
                         FocusElement::SearchBar => self.register_searchbar_keybinds(key),
                     },
                 },
-                InputMode::Editing => self.register_edit_mode_keybinds(key),
-            }
+                InputMode::Editing => self.register_edit_mode_keybinds(key).await,
+            },
+            Event::Mouse(mouse) => self.register_mouse_events(mouse).await,
+            _ => {}
         }
 
         Ok(())
     }
 
-    fn register_edit_mode_keybinds(&mut self, key: KeyEvent) {
+    // Clicking a column header of the table sorts by it, shift-click adds it
+    // to the existing sort.
+    async fn register_mouse_events(&mut self, mouse: MouseEvent) {
+        // Popups take every event while they're open, like they do keys
+        if self.inspector.is_some()
+            || self.pending_undo.is_some()
+            || self.change_review.is_some()
+            || self.row_form.is_some()
+            || self.import_wizard.is_some()
+            || self.reference_picker.is_some()
+        {
+            return;
+        }
+
+        if self.input_mode != InputMode::Normal || self.main_view != MainView::Table {
+            return;
+        }
+
+        if let MouseEventKind::Down(MouseButton::Left) = mouse.kind {
+            let multi_column = mouse.modifiers.contains(KeyModifiers::SHIFT);
+            let column_index = self
+                .cluster
+                .get_current_selected_table_mut()
+                .and_then(|table| table.grid.header_column_at(mouse.column, mouse.row));
+
+            if let Some(column_index) = column_index {
+                self.focused_element = FocusElement::Main;
+                self.sort_by_column(Some(column_index), multi_column).await;
+            }
+        }
+    }
+
+    fn register_help_keybinds(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('?') | KeyCode::Char('q') => self.show_keybinds = false,
+            KeyCode::Char('j') | KeyCode::Down => self.help_scroll.next_line(),
+            KeyCode::Char('k') | KeyCode::Up => self.help_scroll.prev_line(),
+            KeyCode::PageDown => self.help_scroll.page_down(),
+            KeyCode::PageUp => self.help_scroll.page_up(),
+            KeyCode::Char('g') => self.help_scroll.top(),
+            KeyCode::Char('G') => self.help_scroll.bottom(),
+            _ => {}
+        }
+    }
+
+    fn register_inspector_keybinds(&mut self, key: KeyEvent) {
+        let inspector = match self.inspector.as_mut() {
+            Some(inspector) => inspector,
+            None => return,
+        };
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => self.inspector = None,
+            KeyCode::Char('j') | KeyCode::Down => inspector.next_line(),
+            KeyCode::Char('k') | KeyCode::Up => inspector.prev_line(),
+            KeyCode::PageDown => inspector.page_down(),
+            KeyCode::PageUp => inspector.page_up(),
+            KeyCode::Char('g') | KeyCode::Home => inspector.top(),
+            KeyCode::Char('G') | KeyCode::End => inspector.bottom(),
+            KeyCode::Char(' ') => inspector.toggle(),
+            KeyCode::Char('h') | KeyCode::Left => inspector.collapse(),
+            KeyCode::Char('l') | KeyCode::Right => inspector.expand(),
+            _ => {}
+        }
+    }
+
+    async fn register_reference_picker_keybinds(&mut self, key: KeyEvent) {
+        let reference_picker = match self.reference_picker.as_mut() {
+            Some(reference_picker) => reference_picker,
+            None => return,
+        };
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.reference_picker = None,
+            KeyCode::Char('j') | KeyCode::Down => reference_picker.next(),
+            KeyCode::Char('k') | KeyCode::Up => reference_picker.prev(),
+            KeyCode::Enter => {
+                let location =
+                    reference_picker
+                        .selected_reference()
+                        .map(|reference| TableLocation {
+                            table_name: reference.foreign_key.table.clone(),
+                            filter: Some(reference.filter.clone()),
+                            page: None,
+                            selected_row: 0,
+                            selected_column: 0,
+                        });
+                self.reference_picker = None;
+
+                if let Some(location) = location {
+                    self.navigate_to(location).await;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    async fn register_import_keybinds(&mut self, key: KeyEvent) {
+        let wizard = match self.import_wizard.as_mut() {
+            Some(wizard) => wizard,
+            None => return,
+        };
+
+        match (&wizard.step, key.code) {
+            (ImportStep::Source, KeyCode::Esc) => self.import_wizard = None,
+            (ImportStep::Source, KeyCode::Tab | KeyCode::Down) => wizard.next_field(true),
+            (ImportStep::Source, KeyCode::BackTab | KeyCode::Up) => wizard.next_field(false),
+            (ImportStep::Source, KeyCode::Backspace) => wizard.backspace(),
+            (ImportStep::Source, KeyCode::Char(c)) => wizard.type_char(c),
+            (ImportStep::Source, KeyCode::Enter) => self.load_import_file().await,
+            (ImportStep::Mapping, KeyCode::Esc) => {
+                wizard.step = ImportStep::Source;
+                wizard.error = None;
+            }
+            (ImportStep::Mapping, KeyCode::Char('j') | KeyCode::Down) => wizard.next_column(true),
+            (ImportStep::Mapping, KeyCode::Char('k') | KeyCode::Up) => wizard.next_column(false),
+            (ImportStep::Mapping, KeyCode::Char('l') | KeyCode::Right) => wizard.cycle(true),
+            (ImportStep::Mapping, KeyCode::Char('h') | KeyCode::Left) => wizard.cycle(false),
+            (ImportStep::Mapping, KeyCode::Enter) => self.run_import().await,
+            (ImportStep::Done(_), KeyCode::Esc | KeyCode::Enter) => self.import_wizard = None,
+            _ => {}
+        }
+    }
+
+    // The open table is the default target, names without a schema go to
+    // its schema or to public.
+    fn open_import_wizard(&mut self) {
+        let table_name = self
+            .cluster
+            .get_current_selected_table_mut()
+            .map(|current_table| current_table.name.clone());
+
+        let (table, default_schema) = match (&self.main_view, table_name) {
+            (MainView::Table, Some(table_name)) => (table_name.to_string(), table_name.schema),
+            (_, table_name) => (
+                String::new(),
+                table_name.map_or(String::from("public"), |table_name| table_name.schema),
+            ),
+        };
+
+        self.import_wizard = Some(ImportWizard::new(table, default_schema));
+    }
+
+    // Read and preview the file, against the columns of the target table
+    // when it exists.
+    async fn load_import_file(&mut self) {
+        let table_name = match self.import_wizard.as_mut() {
+            Some(wizard) => match wizard.table_name() {
+                Ok(table_name) => table_name,
+                Err(error) => {
+                    wizard.error = Some(error);
+                    return;
+                }
+            },
+            None => return,
+        };
+
+        let columns = if self.cluster.has_table(&table_name) {
+            match self.connection_manager.get_columns(&table_name).await {
+                Ok(columns) => Some(columns),
+                Err(error) => {
+                    if let Some(wizard) = self.import_wizard.as_mut() {
+                        wizard.error = Some(error.to_string());
+                    }
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+
+        if let Some(wizard) = self.import_wizard.as_mut() {
+            wizard.error = wizard.load(columns).err();
+        }
+    }
+
+    // Load the rows that pass the checks in one COPY. An error from the
+    // server rolls everything back and keeps the wizard on the mapping.
+    async fn run_import(&mut self) {
+        let wizard = match self.import_wizard.as_mut() {
+            Some(wizard) => wizard,
+            None => return,
+        };
+
+        let copy_sql = match wizard.copy_sql() {
+            Some(copy_sql) => copy_sql,
+            None => {
+                wizard.error = Some(String::from("map at least one column"));
+                return;
+            }
+        };
+        let create_sql = wizard.create_sql();
+        let table_name = wizard.target.clone();
+        let (data, mut report) = wizard.copy_data();
+
+        let loaded = self
+            .connection_manager
+            .import_csv(create_sql.as_deref(), &copy_sql, data)
+            .await;
+
+        let wizard = match self.import_wizard.as_mut() {
+            Some(wizard) => wizard,
+            None => return,
+        };
+
+        match loaded {
+            Ok(loaded) => {
+                report.loaded = loaded;
+                wizard.step = ImportStep::Done(report);
+            }
+            Err(error) => {
+                wizard.error = Some(match error.as_db_error() {
+                    Some(db_error) => match db_error.where_() {
+                        Some(context) => format!("{} ({})", db_error.message(), context),
+                        None => db_error.message().to_string(),
+                    },
+                    None => error.to_string(),
+                });
+                return;
+            }
+        }
+
+        if create_sql.is_some() {
+            self.cluster.add_table(&table_name);
+        }
+
+        let is_open = self
+            .cluster
+            .get_current_selected_table_mut()
+            .is_some_and(|current_table| current_table.name == table_name);
+        if is_open && self.main_view == MainView::Table {
+            self.load_page(PageRequest::First).await;
+        }
+    }
+
+    fn start_editing(&mut self, input_target: InputTarget) {
+        // The current filter is edited in place
+        if input_target == InputTarget::Filter {
+            self.input = self
+                .cluster
+                .get_current_selected_table_mut()
+                .and_then(|current_table| current_table.filter.as_ref())
+                .map(|filter| filter.source.clone())
+                .unwrap_or_default();
+        }
+
+        if matches!(input_target, InputTarget::Export | InputTarget::ExportTable) {
+            let name = match self.main_view {
+                MainView::Table if input_target == InputTarget::ExportTable => self
+                    .cluster
+                    .get_current_selected_table_mut()
+                    .map(|current_table| current_table.name.name.clone()),
+                MainView::Table => self
+                    .cluster
+                    .get_current_selected_table_mut()
+                    .map(|current_table| format!("{}-page", current_table.name.name)),
+                MainView::QueryResult
+                | MainView::Object
+                | MainView::Structure
+                | MainView::Ddl
+                | MainView::Diagram => Some(String::from("result")),
+            };
+            self.input = format!("csv {}.csv", name.unwrap_or_default());
+        }
+
+        if input_target == InputTarget::SaveDdl {
+            self.input = self
+                .ddl_view
+                .as_ref()
+                .map(|view| format!("{}.sql", view.name))
+                .unwrap_or_default();
+        }
+
+        // Left over text of another prompt must not be run as SQL
+        if input_target == InputTarget::Query {
+            self.input.clear();
+        }
+
+        if input_target == InputTarget::ExplorerFilter {
+            self.input.clear();
+            self.cluster.set_filter("");
+        }
+
+        if input_target == InputTarget::Search {
+            let cursor = self
+                .current_result_mut()
+                .map(|current_result| {
+                    (
+                        current_result.grid.selected_row,
+                        current_result.grid.selected_column,
+                    )
+                })
+                .unwrap_or_default();
+            self.search_origin = Some((cursor, self.search.pattern.clone()));
+            self.input.clear();
+        }
+
+        self.input_target = input_target;
+        self.input_error = None;
+        self.input_mode = InputMode::Editing;
+        self.focused_element = FocusElement::SearchBar;
+    }
+
+    async fn register_edit_mode_keybinds(&mut self, key: KeyEvent) {
         match key.code {
             KeyCode::Enter => {
-                self.input_history.push(self.input.drain(..).collect());
+                let input: String = self.input.drain(..).collect();
+                self.input_history.push(input.clone());
+
+                match self.input_target {
+                    InputTarget::Query => {
+                        self.input_mode = InputMode::Normal;
+                        self.focused_element = FocusElement::Main;
+                        self.run_query(input).await;
+                    }
+                    InputTarget::Filter => self.apply_filter(input).await,
+                    InputTarget::Export => self.export_result(input),
+                    InputTarget::ExportTable => self.export_table(input).await,
+                    InputTarget::Cell => self.update_cell(input),
+                    InputTarget::SaveDdl => self.save_ddl(input),
+                    InputTarget::ExplorerFilter => self.jump_to_explorer_match(input),
+                    InputTarget::Search => {
+                        self.search_origin = None;
+                        self.input_mode = InputMode::Normal;
+                        self.focused_element = FocusElement::Main;
+                    }
+                }
+            }
+            KeyCode::Char(c)
+                if self.input_target == InputTarget::Cell
+                    && key.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                let keyword = match c {
+                    'n' => CellValue::Null,
+                    'd' => CellValue::Default,
+                    _ => return,
+                };
+                if let Some(cell_edit) = self.cell_edit.as_mut() {
+                    cell_edit.keyword = Some(keyword);
+                    self.input.clear();
+                    self.input_error = None;
+                }
             }
             KeyCode::Char(c) => {
+                if let Some(cell_edit) = self.cell_edit.as_mut() {
+                    cell_edit.keyword = None;
+                }
                 self.input.push(c);
+                self.input_error = None;
+                self.update_search();
+                self.update_explorer_filter();
             }
             KeyCode::Backspace => {
                 self.input.pop();
+                self.input_error = None;
+                self.update_search();
+                self.update_explorer_filter();
+            }
+            KeyCode::Tab if self.input_target == InputTarget::Search => {
+                self.search.case_sensitive = !self.search.case_sensitive;
+                self.update_search();
             }
             KeyCode::Esc => {
                 self.input_mode = InputMode::Normal;
+                self.input_error = None;
+                self.cancel_search();
+                if self.input_target == InputTarget::ExplorerFilter {
+                    self.input.clear();
+                    self.cluster.set_filter("");
+                    self.focused_element = FocusElement::Explorer;
+                }
+                if self.cell_edit.take().is_some() {
+                    self.input.clear();
+                    self.focused_element = FocusElement::Main;
+                }
             }
             _ => {}
         }
     }
 
-    async fn register_explorer_keybinds(&mut self, key: KeyEvent) {
-        match key.code {
-            KeyCode::Enter => self.select_database().await,
-            KeyCode::Char('j') => self.cluster.next(),
-            KeyCode::Char('k') => self.cluster.prev(),
-            KeyCode::Char('o') => self.open_table().await,
-            _ => {}
+    // Open the prompt on the selected cell, filled with its current value.
+    fn edit_cell(&mut self) {
+        let current_table = match self.cluster.get_current_selected_table_mut() {
+            Some(current_table) => current_table,
+            None => return,
+        };
+
+        if let Err(error) = check_key(&current_table.name, &current_table.primary_key) {
+            self.status_message = Some(error);
+            return;
+        }
+
+        let row = current_table.grid.selected_row;
+        let (column_index, value) = match current_table.selected_column_index().and_then(|index| {
+            current_table
+                .data
+                .get(row)
+                .and_then(|values| values.get(index))
+                .map(|value| (index, value.clone()))
+        }) {
+            Some(cell) => cell,
+            None => return,
+        };
+
+        self.cell_edit = Some(CellEdit {
+            row,
+            column_index,
+            column: current_table.columns[column_index].clone(),
+            keyword: value.is_null().then_some(CellValue::Null),
+        });
+        self.start_editing(InputTarget::Cell);
+        self.input = value.to_text().unwrap_or_default();
+    }
+
+    // Stage the edited cell, errors keep the prompt open.
+    fn update_cell(&mut self, input: String) {
+        let cell_edit = match self.cell_edit.clone() {
+            Some(cell_edit) => cell_edit,
+            None => return,
+        };
+        let database = match self.cluster.connected_database_name() {
+            Some(database) => database.to_string(),
+            None => return,
+        };
+        let current_table = match self.cluster.get_current_selected_table_mut() {
+            Some(current_table) => current_table,
+            None => return,
+        };
+
+        let change = current_table.update_change(
+            &database,
+            cell_edit.row,
+            cell_edit.column_index,
+            cell_edit.value(&input),
+        );
+        match change {
+            Ok(change) if self.changes.is_deleted(&change.table, &change.key) => {
+                self.input = input;
+                self.input_error = Some(String::from("the row is staged for deletion"));
+                return;
+            }
+            Ok(change) => self.changes.stage_update(change),
+            Err(error) => {
+                self.input = input;
+                self.input_error = Some(error);
+                return;
+            }
         }
+
+        self.status_message = Some(self.pending_changes_message());
+        self.cell_edit = None;
+        self.input_mode = InputMode::Normal;
+        self.focused_element = FocusElement::Main;
     }
 
-    fn register_main_keybinds(&mut self, key: KeyEvent) {
+    async fn register_row_form_keybinds(&mut self, key: KeyEvent) {
+        let form = match self.row_form.as_mut() {
+            Some(form) => form,
+            None => return,
+        };
+
         match key.code {
+            KeyCode::Esc => self.row_form = None,
+            KeyCode::Tab | KeyCode::Down => form.next_field(true),
+            KeyCode::BackTab | KeyCode::Up => form.next_field(false),
+            KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                form.set_value(CellValue::Null)
+            }
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                form.set_value(CellValue::Default)
+            }
+            KeyCode::Char(c) => form.type_char(c),
+            KeyCode::Backspace => form.backspace(),
+            KeyCode::Enter => self.insert_row(),
             _ => {}
         }
     }
 
-    fn register_searchbar_keybinds(&mut self, key: KeyEvent) {
+    async fn register_review_keybinds(&mut self, key: KeyEvent) {
+        let review = match self.change_review.as_mut() {
+            Some(review) => review,
+            None => return,
+        };
+
         match key.code {
-            KeyCode::Char('i') => self.input_mode = InputMode::Editing,
-            KeyCode::Esc => {
-                self.input_mode = InputMode::Normal;
+            KeyCode::Esc | KeyCode::Char('q') => self.change_review = None,
+            KeyCode::Char('j') | KeyCode::Down => review.next(self.changes.len()),
+            KeyCode::Char('k') | KeyCode::Up => review.prev(),
+            KeyCode::Char('u') => {
+                self.changes.remove(review.selected);
+                review.clamp(self.changes.len());
+                if self.changes.is_empty() {
+                    self.change_review = None;
+                }
+            }
+            KeyCode::Char('X') => {
+                self.status_message = Some(format!(
+                    "Discarded {} change{}",
+                    self.changes.len(),
+                    if self.changes.len() == 1 { "" } else { "s" }
+                ));
+                self.changes.clear();
+                self.change_review = None;
             }
+            KeyCode::Enter => self.apply_changes().await,
             _ => {}
         }
     }
 
-    async fn open_table(&mut self) {
-        match self.cluster.select_focused_table().cloned() {
-            Some(mut current_table) => {
-                let columns = self.connection_manager.get_table(&current_table.name).await;
-
-                match columns {
-                    Ok(column_names_row) => {
-                        let column_names: Vec<String> =
-                            column_names_row.iter().map(|row| row.get(0)).collect();
-                        current_table.set_columns(column_names);
+    async fn open_row_form(&mut self) {
+        let database = match self.cluster.connected_database_name() {
+            Some(database) => database.to_string(),
+            None => return,
+        };
+        let (table_name, columns, key_columns) = match self.cluster.get_current_selected_table_mut()
+        {
+            Some(current_table) => (
+                current_table.name.clone(),
+                current_table.columns.clone(),
+                current_table.primary_key.clone(),
+            ),
+            None => return,
+        };
+
+        let details = self
+            .connection_manager
+            .get_column_details(&table_name)
+            .await;
+        if let Some(details) = self.handle_error_with_debug(details) {
+            self.row_form = Some(RowForm::new(
+                database,
+                table_name,
+                columns,
+                key_columns,
+                details,
+            ));
+        }
+    }
+
+    // Stage the row of the form, errors keep the form open.
+    fn insert_row(&mut self) {
+        let form = match self.row_form.as_mut() {
+            Some(form) => form,
+            None => return,
+        };
+
+        match form.insert_change() {
+            Ok(change) => {
+                self.changes.stage_insert(change);
+                self.row_form = None;
+                self.status_message = Some(self.pending_changes_message());
+            }
+            Err(error) => form.error = Some(error),
+        }
+    }
+
+    // Stage the deletion of the selected row, or take it back.
+    fn toggle_delete(&mut self) {
+        let database = match self.cluster.connected_database_name() {
+            Some(database) => database.to_string(),
+            None => return,
+        };
+        let current_table = match self.cluster.get_current_selected_table_mut() {
+            Some(current_table) => current_table,
+            None => return,
+        };
+
+        match current_table.delete_change(&database, current_table.grid.selected_row) {
+            Ok(change) => {
+                self.status_message = Some(if self.changes.toggle_delete(change) {
+                    self.pending_changes_message()
+                } else {
+                    String::from("The row is no longer staged for deletion")
+                });
+            }
+            Err(error) => self.status_message = Some(error),
+        }
+    }
+
+    fn pending_changes_message(&self) -> String {
+        format!(
+            "{} pending change{}, R to review and apply",
+            self.changes.len(),
+            if self.changes.len() == 1 { "" } else { "s" }
+        )
+    }
+
+    fn review_changes(&mut self) {
+        if self.changes.is_empty() {
+            self.status_message = Some(String::from("No pending changes"));
+        } else {
+            self.change_review = Some(ChangeReview::default());
+        }
+    }
+
+    // Apply every staged change in one transaction and keep the rows they
+    // touched to undo them. When one of them fails nothing is applied and the
+    // review stays open on it.
+    async fn apply_changes(&mut self) {
+        let connected_database = self.cluster.connected_database_name();
+        if let Some(change) = self
+            .changes
+            .changes
+            .iter()
+            .find(|change| Some(change.database.as_str()) != connected_database)
+        {
+            let message = format!(
+                "{} belongs to {}, connect to it to apply the changes",
+                change.summary(),
+                change.database
+            );
+            if let Some(review) = self.change_review.as_mut() {
+                review.error = Some(message);
+            }
+            return;
+        }
+
+        let statements: Vec<(String, &TableQuery)> = self
+            .changes
+            .changes
+            .iter()
+            .map(|change| (change.query.returning(change.sql()), &change.query))
+            .collect();
+
+        match self.connection_manager.apply_changes(&statements).await {
+            Ok(rows) => {
+                let applied = self
+                    .changes
+                    .changes
+                    .iter()
+                    .zip(rows)
+                    .map(|(change, row)| AppliedChange::new(change, row))
+                    .collect();
+                self.undo_log.push(applied);
+
+                self.status_message = Some(format!(
+                    "Applied {} change{}, U to undo",
+                    self.changes.len(),
+                    if self.changes.len() == 1 { "" } else { "s" }
+                ));
+                self.changes.clear();
+                self.change_review = None;
+                if self.main_view == MainView::Table {
+                    self.load_page(PageRequest::Reload).await;
+                }
+            }
+            Err(apply_error) => {
+                let (index, message) = apply_error_message(apply_error);
+                if let Some(review) = self.change_review.as_mut() {
+                    if let Some(index) = index {
+                        review.selected = index;
                     }
-                    Err(error) => self.show_debug_message(format!("Error: {}", error)),
+                    review.error = Some(message);
                 }
+            }
+        }
+    }
+
+    async fn register_undo_keybinds(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('y') => self.undo().await,
+            KeyCode::Char('n') | KeyCode::Esc => self.pending_undo = None,
+            _ => {}
+        }
+    }
+
+    fn confirm_undo(&mut self) {
+        if let Some(database) = self.undo_log.last_database() {
+            if self.cluster.connected_database_name() != Some(database) {
+                self.status_message = Some(format!(
+                    "The last changes were applied to {}, connect to it to undo them",
+                    database
+                ));
+                return;
+            }
+        }
 
-                let data = self.connection_manager.get_data(&current_table.name).await;
+        match self.undo_log.undo_statements() {
+            Some(Ok(statements)) => self.pending_undo = Some(statements),
+            Some(Err(error)) => self.status_message = Some(error),
+            None => self.status_message = Some(String::from("Nothing to undo")),
+        }
+    }
 
-                match data {
-                    Ok(data) => {
-                        let data_as_text: Vec<String> = data.iter().map(|row| row.get(0)).collect();
-                        current_table.set_data(data_as_text)
-                    }
+    // Take back the last applied change set in one transaction. A row that
+    // no longer is as the change left it stops the whole undo.
+    async fn undo(&mut self) {
+        let statements = match self.pending_undo.take() {
+            Some(statements) => statements,
+            None => return,
+        };
+        let statements: Vec<(String, &TableQuery)> = statements
+            .iter()
+            .map(|(sql, query)| (sql.clone(), query))
+            .collect();
 
-                    Err(error) => self.show_debug_message(format!("Got an error: {error}")),
+        match self.connection_manager.apply_changes(&statements).await {
+            Ok(_) => {
+                self.undo_log.pop();
+                self.status_message = Some(format!(
+                    "Undid {} change{}",
+                    statements.len(),
+                    if statements.len() == 1 { "" } else { "s" }
+                ));
+                if self.main_view == MainView::Table {
+                    self.load_page(PageRequest::Reload).await;
                 }
             }
-            None => (),
+            Err(ApplyError::RowCount(_, 0)) => {
+                self.status_message = Some(String::from(
+                    "A row was changed or removed since it was applied, nothing was undone",
+                ));
+            }
+            Err(apply_error) => {
+                let (_, message) = apply_error_message(apply_error);
+                self.status_message = Some(format!("Nothing was undone: {}", message));
+            }
         }
     }
 
-    async fn select_database(&mut self) {
-        self.cluster.toggle_focused_database();
+    async fn register_explorer_keybinds(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => self.toggle_focused_item().await,
+            KeyCode::Char('j') => self.cluster.next(),
+            KeyCode::Char('k') => self.cluster.prev(),
+            KeyCode::Char('h') => self.cluster.collapse_focused_item(),
+            KeyCode::Char('l') => self.expand_focused_item().await,
+            KeyCode::Char('o') => self.open_table().await,
+            KeyCode::Char('c') => self.show_ddl(true).await,
+            KeyCode::Char('e') => self.open_er_diagram().await,
+            KeyCode::Char('/') => self.start_editing(InputTarget::ExplorerFilter),
+            _ => {}
+        }
+    }
 
-        for database in self.cluster.databases.iter_mut() {
-            if database.is_connected {
-                let database_name = database.name.clone();
-                self.update_connection(&database_name).await;
+    async fn register_main_keybinds(&mut self, key: KeyEvent) {
+        if self.pending_yank {
+            self.pending_yank = false;
+            match key.code {
+                KeyCode::Char('y') => self.yank(YankTarget::Cell),
+                KeyCode::Char('r') => self.yank(YankTarget::Row),
+                KeyCode::Char('c') => self.yank(YankTarget::Column),
+                _ => {}
+            }
+            return;
+        }
 
-                break;
+        match (&self.main_view, key.code) {
+            (_, KeyCode::Tab) => self.toggle_main_view(),
+            (MainView::Ddl, KeyCode::Char('y')) => self.copy_ddl(),
+            (MainView::Ddl, KeyCode::Char('E')) => self.start_editing(InputTarget::SaveDdl),
+            (MainView::Diagram, _) => self.register_diagram_keybinds(key).await,
+            (_, KeyCode::Enter) => self.inspect_cell(),
+            (_, KeyCode::Char('x')) => self.expanded_view = !self.expanded_view,
+            (_, KeyCode::Char('v')) => {
+                if let Some(current_result) = self.current_result_mut() {
+                    current_result.grid.toggle_selection();
+                }
+            }
+            (_, KeyCode::Esc) => {
+                if let Some(current_result) = self.current_result_mut() {
+                    current_result.grid.selection_anchor = None;
+                }
+            }
+            (_, KeyCode::Char('y')) => self.pending_yank = true,
+            (_, KeyCode::Char('Y')) => self.yank(YankTarget::Row),
+            (_, KeyCode::Char('T')) => {
+                self.copy_format = self.copy_format.next();
+                self.status_message = Some(format!("Copy as {}", self.copy_format.name()));
+            }
+            (_, KeyCode::Char('I')) => self.open_import_wizard(),
+            (_, KeyCode::Char('E')) => self.start_editing(InputTarget::Export),
+            (MainView::Table, KeyCode::Char('W')) => self.start_editing(InputTarget::ExportTable),
+            (_, KeyCode::Char('/')) => self.start_editing(InputTarget::Search),
+            (_, KeyCode::Char('n')) => self.next_match(true),
+            (_, KeyCode::Char('N')) => self.next_match(false),
+            (_, KeyCode::Char('C')) => self.search.case_sensitive = !self.search.case_sensitive,
+            (MainView::Table, KeyCode::Char(']')) => self.load_page(PageRequest::Next).await,
+            (MainView::Table, KeyCode::Char('[')) => self.load_page(PageRequest::Previous).await,
+            (MainView::Table, KeyCode::Char('{')) => self.load_page(PageRequest::First).await,
+            (MainView::Table, KeyCode::Char('}')) => self.load_page(PageRequest::Last).await,
+            (MainView::Table, KeyCode::Char('+')) => self.change_page_size(true).await,
+            (MainView::Table, KeyCode::Char('-')) => self.change_page_size(false).await,
+            (MainView::Table, KeyCode::Char('i')) => self.start_editing(InputTarget::Filter),
+            (MainView::Table, KeyCode::Char('e')) => self.edit_cell(),
+            (MainView::Table, KeyCode::Char('a')) => self.open_row_form().await,
+            (MainView::Table, KeyCode::Char('D')) => self.toggle_delete(),
+            (_, KeyCode::Char('R')) => self.review_changes(),
+            (_, KeyCode::Char('U')) => self.confirm_undo(),
+            (MainView::Table, KeyCode::Char('z')) => {
+                self.change_column_layout(|layout, name, _| layout.hide(name))
+            }
+            (MainView::Table, KeyCode::Char('Z')) => {
+                self.change_column_layout(|layout, _, _| layout.show_all())
+            }
+            (MainView::Table, KeyCode::Char('p')) => {
+                self.change_column_layout(|layout, name, _| layout.toggle_pinned(name))
+            }
+            (MainView::Table, KeyCode::Char('<')) => {
+                self.change_column_layout(|layout, name, columns| {
+                    layout.move_column(name, false, columns)
+                })
+            }
+            (MainView::Table, KeyCode::Char('>')) => {
+                self.change_column_layout(|layout, name, columns| {
+                    layout.move_column(name, true, columns)
+                })
+            }
+            (MainView::Table, KeyCode::Char('s')) => self.sort_by_column(None, false).await,
+            (MainView::Table, KeyCode::Char('S')) => self.sort_by_column(None, true).await,
+            (MainView::Table, KeyCode::Char('f')) => self.follow_foreign_key().await,
+            (MainView::Table, KeyCode::Char('r')) => self.list_references().await,
+            (MainView::Table, KeyCode::Char('H')) => self.navigate_back(true).await,
+            (MainView::Table, KeyCode::Char('L')) => self.navigate_back(false).await,
+            (MainView::Table, KeyCode::Char('t')) => self.open_structure().await,
+            (MainView::Table | MainView::Structure, KeyCode::Char('c')) => {
+                self.show_ddl(false).await
+            }
+            (MainView::QueryResult, KeyCode::Char('F')) => self.fetch_all_rows().await,
+            (MainView::Structure | MainView::Ddl, _) => self.register_text_view_keybinds(key),
+            _ => {
+                self.move_grid_cursor(key);
+                self.fetch_more_rows_if_needed().await;
             }
         }
     }
 
-    async fn update_connection(&mut self, database_name: &String) {
-        let connection_options_for_databse = PSQLConnectionOptions {
-            host: String::from("localhost"),
-            user: self.user.clone(),
-            db_name: database_name.clone(),
+    // Columns, rows and grid cursor of the result shown in the main view.
+    pub fn current_result_mut(&mut self) -> Option<GridView<'_>> {
+        match self.main_view {
+            MainView::Table => self.cluster.get_current_selected_table_mut().map(
+                |DatabaseTable {
+                     columns,
+                     data,
+                     revision,
+                     grid,
+                     layout,
+                     ..
+                 }| GridView {
+                    column_order: layout.display_order(columns),
+                    columns,
+                    rows: data,
+                    revision: *revision,
+                    grid,
+                },
+            ),
+            MainView::QueryResult => self.query_result.as_mut().map(
+                |QueryResult {
+                     columns,
+                     rows,
+                     revision,
+                     grid,
+                     ..
+                 }| GridView {
+                    column_order: (0..columns.len()).collect(),
+                    columns,
+                    rows,
+                    revision: *revision,
+                    grid,
+                },
+            ),
+            MainView::Object | MainView::Structure | MainView::Ddl | MainView::Diagram => None,
+        }
+    }
+
+    fn inspect_cell(&mut self) {
+        let current_result = match self.current_result_mut() {
+            Some(current_result) => current_result,
+            None => return,
         };
 
-        let create_connection_result = self
-            .connection_manager
-            .create_database_connection(connection_options_for_databse)
-            .await;
+        let row = current_result.grid.selected_row;
+        let column = match current_result.selected_column_index() {
+            Some(column) => column,
+            None => return,
+        };
+        let inspector = match (
+            current_result.columns.get(column),
+            current_result.rows.get(row).and_then(|row| row.get(column)),
+        ) {
+            (Some(column), Some(value)) => CellInspector::new(column, value),
+            _ => return,
+        };
 
-        self.handle_error_with_debug(create_connection_result);
+        self.inspector = Some(inspector);
+    }
 
-        let result = self.connection_manager.get_tables_for_database().await;
+    // Copy part of the result to the clipboard. With a visual selection the
+    // cell target copies the selection, and rows and columns are the ones it
+    // covers.
+    fn yank(&mut self, target: YankTarget) {
+        let copy_format = self.copy_format;
+        let current_result = match self.current_result_mut() {
+            Some(current_result) => current_result,
+            None => return,
+        };
 
-        let rows = self.handle_error_with_debug(result).unwrap_or_default();
+        let row_count = current_result.rows.len();
+        let column_count = current_result.column_order.len();
+        if row_count == 0 || column_count == 0 {
+            return;
+        }
 
-        let mut table_names: Vec<String> = rows.iter().map(|row| row.get(0)).collect();
-        table_names.sort();
+        let grid = &current_result.grid;
+        let (rows, positions) = grid.selection().unwrap_or((
+            grid.selected_row..=grid.selected_row,
+            grid.selected_column..=grid.selected_column,
+        ));
+        let (rows, positions) = match target {
+            YankTarget::Cell => (rows, positions),
+            YankTarget::Row => (rows, 0..=column_count - 1),
+            YankTarget::Column => (0..=row_count - 1, positions),
+        };
 
-        for database in self.cluster.databases.iter_mut() {
-            if database.name == database_name.clone() {
-                let tables_for_database = table_names
-                    .into_iter()
-                    .map(|name| DatabaseTable::new(name, Vec::new()))
-                    .collect();
+        let (columns, block) = current_result.cells(rows, positions);
+        let text = format_cells(&columns, &block, copy_format);
+        let summary = format!(
+            "Copied {} {} × {} {} as {}",
+            block.len(),
+            if block.len() == 1 { "row" } else { "rows" },
+            columns.len(),
+            if columns.len() == 1 {
+                "column"
+            } else {
+                "columns"
+            },
+            copy_format.name()
+        );
+        current_result.grid.selection_anchor = None;
 
-                database.tables = tables_for_database;
+        if self
+            .handle_error_with_debug(clipboard::copy(&text))
+            .is_some()
+        {
+            self.status_message = Some(summary);
+        }
+    }
 
-                break;
+    // Parse the export prompt, errors keep it open for editing.
+    fn parse_export_options(&mut self, input: String) -> Option<ExportOptions> {
+        match ExportOptions::parse(&input) {
+            Ok(options) => {
+                self.input_mode = InputMode::Normal;
+                self.focused_element = FocusElement::Main;
+                Some(options)
             }
-        }
+            Err(error) => {
+                self.input = input;
+                self.input_error = Some(error);
+                None
+            }
+        }
+    }
+
+    // Write the rows of the result on screen to a file.
+    fn export_result(&mut self, input: String) {
+        let options = match self.parse_export_options(input) {
+            Some(options) => options,
+            None => return,
+        };
+
+        let table = match self.main_view {
+            MainView::Table => self
+                .cluster
+                .get_current_selected_table_mut()
+                .map(|current_table| current_table.name.quoted()),
+            MainView::QueryResult
+            | MainView::Object
+            | MainView::Structure
+            | MainView::Ddl
+            | MainView::Diagram => None,
+        }
+        .unwrap_or(quote_identifier("result"));
+
+        let path = options.path.display().to_string();
+        let current_result = match self.current_result_mut() {
+            Some(current_result) => current_result,
+            None => return,
+        };
+        if current_result.rows.is_empty() || current_result.column_order.is_empty() {
+            return;
+        }
+
+        let (columns, rows) = current_result.cells(
+            0..=current_result.rows.len() - 1,
+            0..=current_result.column_order.len() - 1,
+        );
+        let columns = columns.into_iter().cloned().collect();
+
+        let exported = export_rows(options, columns, &rows, &table);
+        if let Some(count) = self.handle_error_with_debug(exported) {
+            self.status_message = Some(format!("Exported {} rows to {}", count, path));
+        }
+    }
+
+    // Stream the whole table, filtered and sorted, to a file in the
+    // background.
+    async fn export_table(&mut self, input: String) {
+        let options = match self.parse_export_options(input) {
+            Some(options) => options,
+            None => return,
+        };
+
+        if self.export.is_some() {
+            self.show_debug_message(String::from("An export is already running"));
+            return;
+        }
+
+        let (table, columns, query, estimated_total) =
+            match self.cluster.get_current_selected_table_mut() {
+                Some(current_table) => (
+                    current_table.name.clone(),
+                    current_table.columns.clone(),
+                    current_table.export_query(),
+                    current_table.pagination.estimated_total,
+                ),
+                None => return,
+            };
+
+        let client = self.connection_manager.open_connection().await;
+        let client = match self.handle_error_with_debug(client) {
+            Some(client) => client,
+            None => return,
+        };
+
+        let export = ExportJob::start(
+            client,
+            query.to_sql(),
+            options,
+            columns,
+            &table,
+            estimated_total,
+        );
+        self.export = self.handle_error_with_debug(export);
+    }
+
+    async fn check_export(&mut self) {
+        match &self.export {
+            Some(export) if export.handle.is_finished() => {}
+            _ => return,
+        }
+
+        let export = match self.export.take() {
+            Some(export) => export,
+            None => return,
+        };
+
+        let path = export.path.display().to_string();
+        match export.handle.await {
+            Ok(Ok(count)) => {
+                self.status_message = Some(format!("Exported {} rows to {}", count, path));
+            }
+            Ok(Err(error)) => self.show_debug_message(format!("Export failed: {error}")),
+            Err(error) => self.show_debug_message(format!("Export failed: {error}")),
+        }
+    }
+
+    // "1200 of ~5000 rows" written by the running export.
+    pub fn export_progress(&self) -> Option<String> {
+        let export = self.export.as_ref()?;
+        let rows = export.rows.load(Ordering::Relaxed);
+
+        Some(match export.estimated_total {
+            Some(total) if total > 0 => format!(
+                "Exporting {} to {}: {} of ~{} rows ({}%)",
+                export.table,
+                export.path.display(),
+                rows,
+                total,
+                (rows * 100 / total as u64).min(100)
+            ),
+            _ => format!(
+                "Exporting {} to {}: {} rows",
+                export.table,
+                export.path.display(),
+                rows
+            ),
+        })
+    }
+
+    // Jump to the first match of the search being typed, starting from where
+    // the cursor was when the search began.
+    fn update_search(&mut self) {
+        let (origin, _) = match &self.search_origin {
+            Some(search_origin) if self.input_target == InputTarget::Search => {
+                search_origin.clone()
+            }
+            _ => return,
+        };
+
+        self.search.pattern = self.input.clone();
+        let mut search = std::mem::take(&mut self.search);
+
+        if let Some(current_result) = self.current_result_mut() {
+            let (row, column) = search
+                .find(
+                    current_result.rows,
+                    current_result.revision,
+                    &current_result.column_order,
+                    origin,
+                    true,
+                    true,
+                )
+                .unwrap_or(origin);
+            current_result.grid.selected_row = row;
+            current_result.grid.selected_column = column;
+        }
+        self.search = search;
+    }
+
+    fn update_explorer_filter(&mut self) {
+        if self.input_target == InputTarget::ExplorerFilter {
+            self.cluster.set_filter(&self.input);
+        }
+    }
+
+    // Focus the line of the explorer matching the typed filter best.
+    fn jump_to_explorer_match(&mut self, input: String) {
+        let item = match self.cluster.best_match(&input) {
+            Some(item) => item,
+            None => {
+                self.input = input;
+                self.input_error = Some(String::from("Nothing matches"));
+                return;
+            }
+        };
+
+        self.cluster.reveal_item(item);
+        self.cluster.set_filter("");
+        self.input_mode = InputMode::Normal;
+        self.focused_element = FocusElement::Explorer;
+    }
+
+    fn cancel_search(&mut self) {
+        let ((row, column), pattern) = match self.search_origin.take() {
+            Some(search_origin) if self.input_target == InputTarget::Search => search_origin,
+            _ => return,
+        };
+
+        self.search.pattern = pattern;
+        if let Some(current_result) = self.current_result_mut() {
+            current_result.grid.selected_row = row;
+            current_result.grid.selected_column = column;
+        }
+    }
+
+    fn next_match(&mut self, forward: bool) {
+        let mut search = std::mem::take(&mut self.search);
+        if let Some(current_result) = self.current_result_mut() {
+            let cursor = (
+                current_result.grid.selected_row,
+                current_result.grid.selected_column,
+            );
+            if let Some((row, column)) = search.find(
+                current_result.rows,
+                current_result.revision,
+                &current_result.column_order,
+                cursor,
+                forward,
+                false,
+            ) {
+                current_result.grid.selected_row = row;
+                current_result.grid.selected_column = column;
+            }
+        }
+        self.search = search;
+    }
+
+    fn toggle_main_view(&mut self) {
+        self.main_view = match self.main_view {
+            MainView::Table if self.query_result.is_some() => MainView::QueryResult,
+            _ => MainView::Table,
+        };
+    }
+
+    // Scrolling through the structure, the DDL or the diagram.
+    fn register_text_view_keybinds(&mut self, key: KeyEvent) {
+        if self.main_view == MainView::Structure && key.code == KeyCode::Char('t') {
+            self.main_view = MainView::Table;
+            return;
+        }
+
+        let scroll = match self.main_view {
+            MainView::Structure => self.structure_view.as_mut().map(|view| &mut view.scroll),
+            MainView::Ddl => self.ddl_view.as_mut().map(|view| &mut view.scroll),
+            MainView::Diagram => self.er_diagram.as_mut().map(|diagram| &mut diagram.scroll),
+            _ => None,
+        };
+        let scroll = match scroll {
+            Some(scroll) => scroll,
+            None => return,
+        };
+
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => scroll.next_line(),
+            KeyCode::Char('k') | KeyCode::Up => scroll.prev_line(),
+            KeyCode::PageDown => scroll.page_down(),
+            KeyCode::PageUp => scroll.page_up(),
+            KeyCode::Char('h') | KeyCode::Left => scroll.left(),
+            KeyCode::Char('l') | KeyCode::Right => scroll.right(),
+            KeyCode::Char('0') => scroll.column = 0,
+            KeyCode::Char('g') | KeyCode::Home => scroll.top(),
+            KeyCode::Char('G') | KeyCode::End => scroll.bottom(),
+            _ => {}
+        }
+    }
+
+    fn move_grid_cursor(&mut self, key: KeyEvent) {
+        let expanded_view = self.expanded_view;
+        let current_result = match self.current_result_mut() {
+            Some(current_result) => current_result,
+            None => return,
+        };
+
+        let row_count = current_result.rows.len();
+        let column_count = current_result.column_order.len();
+        let grid = current_result.grid;
+
+        // Fields are listed vertically in the expanded view, so moving up and
+        // down goes through the fields and left and right through the records
+        if expanded_view {
+            match key.code {
+                KeyCode::Char('j') | KeyCode::Down => grid.next_column(column_count),
+                KeyCode::Char('k') | KeyCode::Up => grid.prev_column(),
+                KeyCode::Char('l') | KeyCode::Right => grid.next_row(row_count),
+                KeyCode::Char('h') | KeyCode::Left => grid.prev_row(),
+                KeyCode::Char('g') | KeyCode::Home => grid.first_column(),
+                KeyCode::Char('G') | KeyCode::End => grid.last_column(column_count),
+                KeyCode::Char('0') => grid.first_row(),
+                KeyCode::Char('$') => grid.last_row(row_count),
+                _ => {}
+            }
+            return;
+        }
+
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => grid.next_row(row_count),
+            KeyCode::Char('k') | KeyCode::Up => grid.prev_row(),
+            KeyCode::Char('l') | KeyCode::Right => grid.next_column(column_count),
+            KeyCode::Char('h') | KeyCode::Left => grid.prev_column(),
+            KeyCode::Char('g') | KeyCode::Home => grid.first_row(),
+            KeyCode::Char('G') | KeyCode::End => grid.last_row(row_count),
+            KeyCode::Char('0') => grid.first_column(),
+            KeyCode::Char('$') => grid.last_column(column_count),
+            KeyCode::PageDown => grid.page_down(row_count),
+            KeyCode::PageUp => grid.page_up(),
+            _ => {}
+        }
+    }
+
+    fn register_searchbar_keybinds(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('i') => self.start_editing(InputTarget::Search),
+            KeyCode::Esc => {
+                self.input_mode = InputMode::Normal;
+            }
+            _ => {}
+        }
+    }
+
+    async fn open_table(&mut self) {
+        if self.cluster.current_focused_item.is_none() || !self.connect_focused_database().await {
+            return;
+        }
+
+        if self.cluster.select_focused_table().is_none() {
+            self.open_object().await;
+            return;
+        }
+
+        self.main_view = MainView::Table;
+        if self.load_table_metadata().await {
+            self.load_page(PageRequest::First).await;
+        }
+    }
+
+    // Show the properties of the focused object when it isn't a relation.
+    async fn open_object(&mut self) {
+        let (schema, kind, object) = match self.cluster.focused_object() {
+            Some(focused_object) => focused_object,
+            None => return,
+        };
+
+        let properties = self
+            .connection_manager
+            .get_object_details(kind, object.oid)
+            .await;
+        if let Some(properties) = self.handle_error_with_debug(properties) {
+            self.object_details = Some(ObjectDetails {
+                kind,
+                schema,
+                name: object.name,
+                properties,
+            });
+            self.main_view = MainView::Object;
+        }
+    }
+
+    // Show the structure of the selected table in place of its rows.
+    async fn open_structure(&mut self) {
+        let table_name = match self.cluster.get_current_selected_table_mut() {
+            Some(current_table) => current_table.name.clone(),
+            None => return,
+        };
+
+        let structure = self
+            .connection_manager
+            .get_table_structure(&table_name)
+            .await;
+        if let Some(structure) = self.handle_error_with_debug(structure) {
+            self.structure_view = Some(StructureView::new(table_name, structure));
+            self.main_view = MainView::Structure;
+        }
+    }
+
+    // Show the DDL of the object focused in the explorer, or else of the
+    // selected table.
+    async fn show_ddl(&mut self, from_explorer: bool) {
+        if from_explorer && !self.connect_focused_database().await {
+            return;
+        }
+
+        let focused_object = if from_explorer {
+            self.cluster.focused_object()
+        } else {
+            None
+        };
+        let table_name = if from_explorer {
+            self.cluster.focused_table_name()
+        } else {
+            self.cluster
+                .get_current_selected_table_mut()
+                .map(|current_table| current_table.name.clone())
+        };
+
+        let (name, sql) = match (focused_object, table_name) {
+            (Some((schema, kind, object)), _) => (
+                format!("{}.{}", schema, object.name),
+                self.connection_manager
+                    .get_object_ddl(kind, object.oid)
+                    .await,
+            ),
+            (None, Some(table_name)) => (
+                table_name.to_string(),
+                self.connection_manager.get_table_ddl(&table_name).await,
+            ),
+            (None, None) => return,
+        };
+
+        if let Some(sql) = self.handle_error_with_debug(sql) {
+            self.ddl_view = Some(DdlView::new(name, sql));
+            self.main_view = MainView::Ddl;
+        }
+    }
+
+    // Draw the tables of the focused schema, focusing the table under the
+    // explorer's focus when there's one.
+    async fn open_er_diagram(&mut self) {
+        let schema_name = match self.cluster.focused_schema_name() {
+            Some(schema_name) => schema_name,
+            None => return,
+        };
+        if !self.connect_focused_database().await {
+            return;
+        }
+
+        let tables = self
+            .connection_manager
+            .get_schema_tables(&schema_name)
+            .await;
+        let tables = match self.handle_error_with_debug(tables) {
+            Some(tables) => tables,
+            None => return,
+        };
+        let foreign_keys = self
+            .connection_manager
+            .get_schema_foreign_keys(&schema_name)
+            .await;
+        let foreign_keys = match self.handle_error_with_debug(foreign_keys) {
+            Some(foreign_keys) => foreign_keys,
+            None => return,
+        };
+
+        if tables.is_empty() {
+            self.status_message = Some(format!("No tables in {}", schema_name));
+            return;
+        }
+
+        let focused_table = self.cluster.focused_table_name();
+        self.er_diagram = Some(ErDiagram::new(
+            schema_name,
+            tables,
+            foreign_keys,
+            focused_table.as_ref(),
+        ));
+        self.main_view = MainView::Diagram;
+        self.focused_element = FocusElement::Main;
+    }
+
+    async fn register_diagram_keybinds(&mut self, key: KeyEvent) {
+        let diagram = match self.er_diagram.as_mut() {
+            Some(diagram) => diagram,
+            None => return,
+        };
+
+        match key.code {
+            KeyCode::Char(']') => diagram.focus_next(true),
+            KeyCode::Char('[') => diagram.focus_next(false),
+            KeyCode::Char('-') => diagram.fewer_hops(),
+            KeyCode::Char('+') => diagram.more_hops(),
+            KeyCode::Char('=') => diagram.all_tables(),
+            KeyCode::Enter => {
+                if let Some(table_name) = diagram.focused_table().cloned() {
+                    let location = TableLocation {
+                        table_name,
+                        filter: None,
+                        page: None,
+                        selected_row: 0,
+                        selected_column: 0,
+                    };
+                    self.open_location(&location).await;
+                }
+            }
+            _ => self.register_text_view_keybinds(key),
+        }
+    }
+
+    fn copy_ddl(&mut self) {
+        let sql = match self.ddl_view.as_ref() {
+            Some(view) => view.sql.clone(),
+            None => return,
+        };
+
+        if self
+            .handle_error_with_debug(clipboard::copy(&sql))
+            .is_some()
+        {
+            self.status_message = Some(String::from("Copied the DDL"));
+        }
+    }
+
+    fn save_ddl(&mut self, input: String) {
+        let path = input.trim();
+        if path.is_empty() {
+            self.input_error = Some(String::from("expected a file name"));
+            return;
+        }
+
+        let path = expand_home(path);
+        self.input_mode = InputMode::Normal;
+        self.focused_element = FocusElement::Main;
+
+        let sql = match self.ddl_view.as_ref() {
+            Some(view) => format!("{}\n", view.sql),
+            None => return,
+        };
+        if self
+            .handle_error_with_debug(fs::write(&path, sql))
+            .is_some()
+        {
+            self.status_message = Some(format!("Saved the DDL to {}", path.display()));
+        }
+    }
+
+    // Columns, keys and saved layout of the selected table.
+    async fn load_table_metadata(&mut self) -> bool {
+        let table_name = match self.cluster.get_current_selected_table_mut() {
+            Some(current_table) => current_table.name.clone(),
+            None => return false,
+        };
+
+        let columns = self.connection_manager.get_columns(&table_name).await;
+        let columns = match self.handle_error_with_debug(columns) {
+            Some(columns) => columns,
+            None => return false,
+        };
+
+        let primary_key = self.connection_manager.get_primary_key(&table_name).await;
+        let primary_key = self
+            .handle_error_with_debug(primary_key)
+            .unwrap_or_default();
+
+        let estimated_total = self
+            .connection_manager
+            .get_estimated_row_count(&table_name)
+            .await;
+        let estimated_total = self.handle_error_with_debug(estimated_total).flatten();
+
+        let foreign_keys = self
+            .connection_manager
+            .get_foreign_keys(&table_name, false)
+            .await;
+        let foreign_keys = self
+            .handle_error_with_debug(foreign_keys)
+            .unwrap_or_default();
+
+        let referenced_by = self
+            .connection_manager
+            .get_foreign_keys(&table_name, true)
+            .await;
+        let referenced_by = self
+            .handle_error_with_debug(referenced_by)
+            .unwrap_or_default();
+
+        let column_details = self
+            .connection_manager
+            .get_column_details(&table_name)
+            .await;
+        let column_details = self
+            .handle_error_with_debug(column_details)
+            .unwrap_or_default();
+
+        let layout = self
+            .table_key()
+            .map(|table_key| self.state_file.column_layout(&table_key))
+            .unwrap_or_default();
+
+        if let Some(current_table) = self.cluster.get_current_selected_table_mut() {
+            current_table.set_columns(columns);
+            current_table.layout = layout;
+            current_table.primary_key = primary_key;
+            current_table.foreign_keys = foreign_keys;
+            current_table.referenced_by = referenced_by;
+            current_table.column_details = column_details;
+            current_table.pagination.estimated_total = estimated_total;
+        }
+
+        true
+    }
+
+    fn current_location(&mut self) -> Option<TableLocation> {
+        if self.main_view != MainView::Table {
+            return None;
+        }
+
+        let current_table = self.cluster.get_current_selected_table_mut()?;
+        Some(TableLocation {
+            table_name: current_table.name.clone(),
+            filter: current_table
+                .filter
+                .as_ref()
+                .map(|filter| filter.source.clone()),
+            page: current_table.page_position(),
+            selected_row: current_table.grid.selected_row,
+            selected_column: current_table.grid.selected_column,
+        })
+    }
+
+    // Open a table of the connected database with the given filter on the
+    // page it was left on and put the cursor back where it was.
+    async fn open_location(&mut self, location: &TableLocation) -> bool {
+        // The filter is checked before leaving the current table, one that
+        // doesn't parse is shown rather than opening the table unfiltered
+        let filter = match &location.filter {
+            Some(source) => {
+                let columns = self
+                    .connection_manager
+                    .get_columns(&location.table_name)
+                    .await;
+                let columns = match self.handle_error_with_debug(columns) {
+                    Some(columns) => columns,
+                    None => return false,
+                };
+                match Filter::parse(source, &columns) {
+                    Ok(filter) => Some(filter),
+                    Err(error) => {
+                        self.show_debug_message(format!(
+                            "Can't filter {} by {}: {}",
+                            location.table_name, source, error
+                        ));
+                        return false;
+                    }
+                }
+            }
+            None => None,
+        };
+
+        if !self.cluster.focus_table(&location.table_name)
+            || self.cluster.select_focused_table().is_none()
+        {
+            self.show_debug_message(format!("Table {} not found", location.table_name));
+            return false;
+        }
+
+        self.main_view = MainView::Table;
+        if !self.load_table_metadata().await {
+            return false;
+        }
+
+        if let Some(current_table) = self.cluster.get_current_selected_table_mut() {
+            current_table.filter = filter;
+            if current_table.filter.is_some() {
+                current_table.pagination.estimated_total = None;
+            }
+        }
+
+        match &location.page {
+            Some(page) => self.load_page_at(page).await,
+            None => self.load_page(PageRequest::First).await,
+        }
+
+        if let Some(current_table) = self.cluster.get_current_selected_table_mut() {
+            current_table.grid.selected_row = location.selected_row;
+            current_table.grid.selected_column = location.selected_column;
+            // Rows may be gone or columns hidden since the location was left
+            let column_count = current_table.column_order().len();
+            current_table
+                .grid
+                .clamp(current_table.data.len(), column_count);
+        }
+
+        true
+    }
+
+    async fn navigate_to(&mut self, location: TableLocation) {
+        let current_location = self.current_location();
+        if self.open_location(&location).await {
+            if let Some(current_location) = current_location {
+                self.navigation_history.visit(current_location);
+            }
+        }
+    }
+
+    async fn navigate_back(&mut self, back: bool) {
+        let current_location = match self.current_location() {
+            Some(current_location) => current_location,
+            None => return,
+        };
+
+        let location = if back {
+            self.navigation_history.back(current_location)
+        } else {
+            self.navigation_history.forward(current_location)
+        };
+
+        // Put the history back when the location can't be opened
+        if let Some(location) = location {
+            if !self.open_location(&location).await {
+                if back {
+                    self.navigation_history.forward(location);
+                } else {
+                    self.navigation_history.back(location);
+                }
+            }
+        }
+    }
+
+    // Open the row the selected cell's foreign key points to.
+    async fn follow_foreign_key(&mut self) {
+        let current_table = match self.cluster.get_current_selected_table_mut() {
+            Some(current_table) => current_table,
+            None => return,
+        };
+
+        let foreign_key = match current_table.selected_foreign_key() {
+            Some(foreign_key) => foreign_key.clone(),
+            None => {
+                self.show_debug_message(String::from("The column has no foreign key"));
+                return;
+            }
+        };
+
+        let values = match current_table.selected_row_text(&foreign_key.columns) {
+            Some(values) => values,
+            None => {
+                self.show_debug_message(String::from("The foreign key is NULL"));
+                return;
+            }
+        };
+
+        let conditions: Vec<(String, String)> = foreign_key
+            .referenced_columns
+            .iter()
+            .cloned()
+            .zip(values)
+            .collect();
+
+        self.navigate_to(TableLocation {
+            table_name: foreign_key.referenced_table,
+            filter: Some(equality_source(&conditions)),
+            page: None,
+            selected_row: 0,
+            selected_column: 0,
+        })
+        .await;
+    }
+
+    // List the tables with rows referencing the selected row.
+    async fn list_references(&mut self) {
+        let current_table = match self.cluster.get_current_selected_table_mut() {
+            Some(current_table) => current_table,
+            None => return,
+        };
+
+        let candidates: Vec<(ForeignKey, String)> = current_table
+            .referenced_by
+            .iter()
+            .filter_map(|foreign_key| {
+                let values = current_table.selected_row_text(&foreign_key.referenced_columns)?;
+                let conditions: Vec<(String, String)> =
+                    foreign_key.columns.iter().cloned().zip(values).collect();
+                Some((foreign_key.clone(), equality_source(&conditions)))
+            })
+            .collect();
+
+        if candidates.is_empty() {
+            self.show_debug_message(String::from("No foreign keys reference this row"));
+            return;
+        }
+
+        let mut references = Vec::new();
+        for (foreign_key, filter) in candidates {
+            let count = self
+                .count_referencing_rows(&foreign_key.table, &filter)
+                .await;
+            match self.handle_error_with_debug(count) {
+                Some(count) => references.push(Reference {
+                    foreign_key,
+                    filter,
+                    count,
+                }),
+                None => return,
+            }
+        }
+
+        self.reference_picker = Some(ReferencePicker::new(references));
+    }
+
+    async fn count_referencing_rows(
+        &mut self,
+        table_name: &TableName,
+        filter: &str,
+    ) -> Result<Option<i64>, Error> {
+        let columns = self.connection_manager.get_columns(table_name).await?;
+        let mut query = TableQuery::new(table_name, &columns);
+        // Counting the whole table would pass for the referencing rows, so a
+        // filter that doesn't parse leaves the count unknown.
+        match Filter::parse(filter, &columns) {
+            Ok(filter) => filter.apply(&mut query),
+            Err(error) => {
+                info!("Can't count rows referencing from {table_name}: {error}");
+                return Ok(None);
+            }
+        }
+        self.connection_manager.count_rows(&query).await.map(Some)
+    }
+
+    async fn load_page(&mut self, request: PageRequest) {
+        if let Err(error) = self.fetch_page(request).await {
+            self.show_debug_message(format!("Got an error: {error}"));
+        }
+    }
+
+    // Read a page left earlier again.
+    async fn load_page_at(&mut self, page: &PagePosition) {
+        let page_query = match self
+            .cluster
+            .get_current_selected_table_mut()
+            .and_then(|current_table| current_table.restore_page_query(page))
+        {
+            Some(page_query) => page_query,
+            None => return,
+        };
+
+        let rows = self.connection_manager.get_data(&page_query.query).await;
+        match rows {
+            Ok(rows) => {
+                if let Some(current_table) = self.cluster.get_current_selected_table_mut() {
+                    current_table.set_page(page_query, rows);
+                }
+            }
+            Err(error) => self.show_debug_message(format!("Got an error: {error}")),
+        }
+    }
+
+    async fn fetch_page(&mut self, request: PageRequest) -> Result<(), Error> {
+        let current_table = match self.cluster.get_current_selected_table_mut() {
+            Some(current_table) => current_table,
+            None => return Ok(()),
+        };
+
+        // Without a key the last page can only be found by its offset, which
+        // needs an exact row count. Read by key it still needs a total to know
+        // where in the table it is.
+        if request == PageRequest::Last
+            && (!current_table.uses_keyset() || current_table.pagination.estimated_total.is_none())
+        {
+            let count_query = current_table.base_query();
+            let count = self.connection_manager.count_rows(&count_query).await?;
+            if let Some(current_table) = self.cluster.get_current_selected_table_mut() {
+                current_table.pagination.estimated_total = Some(count);
+            }
+        }
+
+        let page_query = match self
+            .cluster
+            .get_current_selected_table_mut()
+            .and_then(|current_table| current_table.page_query(request))
+        {
+            Some(page_query) => page_query,
+            None => return Ok(()),
+        };
+
+        let rows = self.connection_manager.get_data(&page_query.query).await?;
+        if let Some(current_table) = self.cluster.get_current_selected_table_mut() {
+            current_table.set_page(page_query, rows);
+        }
+
+        Ok(())
+    }
+
+    // Filter the open table. Errors in the filter or from the server are
+    // shown in the search bar and keep the filter open for editing, an empty
+    // filter removes it.
+    async fn apply_filter(&mut self, input: String) {
+        let current_table = match self.cluster.get_current_selected_table_mut() {
+            Some(current_table) => current_table,
+            None => return,
+        };
+
+        let filter = if input.trim().is_empty() {
+            None
+        } else {
+            match Filter::parse(&input, &current_table.columns) {
+                Ok(filter) => Some(filter),
+                Err(error) => {
+                    self.input = input;
+                    self.input_error = Some(error.to_string());
+                    return;
+                }
+            }
+        };
+
+        let table_name = current_table.name.clone();
+        let is_unfiltered = filter.is_none();
+        let previous_filter = std::mem::replace(&mut current_table.filter, filter);
+        let previous_total = current_table.pagination.estimated_total.take();
+
+        if let Err(error) = self.fetch_page(PageRequest::First).await {
+            if let Some(current_table) = self.cluster.get_current_selected_table_mut() {
+                current_table.filter = previous_filter;
+                current_table.pagination.estimated_total = previous_total;
+            }
+            self.input = input;
+            self.input_error = Some(match error.as_db_error() {
+                Some(db_error) => db_error.message().to_string(),
+                None => error.to_string(),
+            });
+            return;
+        }
+
+        // Without a filter the planner's estimate is good enough again
+        if is_unfiltered {
+            let estimated_total = self
+                .connection_manager
+                .get_estimated_row_count(&table_name)
+                .await;
+            let estimated_total = self.handle_error_with_debug(estimated_total).flatten();
+            if let Some(current_table) = self.cluster.get_current_selected_table_mut() {
+                let pagination = &mut current_table.pagination;
+                pagination.estimated_total = pagination.estimated_total.max(estimated_total);
+            }
+        }
+
+        self.input_mode = InputMode::Normal;
+        self.focused_element = FocusElement::Main;
+    }
+
+    async fn change_page_size(&mut self, larger: bool) {
+        match self.cluster.get_current_selected_table_mut() {
+            Some(current_table) if larger => current_table.pagination.larger_page_size(),
+            Some(current_table) => current_table.pagination.smaller_page_size(),
+            None => return,
+        }
+
+        self.load_page(PageRequest::First).await;
+    }
+
+    // Rearrange the columns of the open table around the selected column and
+    // remember the layout for the next session.
+    fn change_column_layout(&mut self, change: impl FnOnce(&mut ColumnLayout, &str, &[Column])) {
+        let table_key = self.table_key();
+        let current_table = match self.cluster.get_current_selected_table_mut() {
+            Some(current_table) => current_table,
+            None => return,
+        };
+
+        current_table.change_layout(change);
+        let layout = current_table.layout.clone();
+
+        if let Some(table_key) = table_key {
+            let saved = self.state_file.set_column_layout(&table_key, &layout);
+            self.handle_error_with_debug(saved);
+        }
+    }
+
+    // Identifies the open table in the state file.
+    fn table_key(&mut self) -> Option<String> {
+        let database_name = self
+            .cluster
+            .current_connected_database
+            .and_then(|index| self.cluster.databases.get(index))
+            .map(|database| database.name.clone())?;
+        let table_name = self.cluster.get_current_selected_table_mut()?.name.clone();
+
+        Some(format!(
+            "{}@{}/{}/{}",
+            self.user, self.host, database_name, table_name
+        ))
+    }
+
+    // Cycle the sort of a column, the selected one when no column is given,
+    // and reload from the first page.
+    async fn sort_by_column(&mut self, column_index: Option<usize>, multi_column: bool) {
+        match self.cluster.get_current_selected_table_mut() {
+            Some(current_table) => {
+                if let Some(column_index) =
+                    column_index.or_else(|| current_table.selected_column_index())
+                {
+                    current_table.cycle_sort(column_index, multi_column);
+                }
+            }
+            None => return,
+        }
+
+        self.load_page(PageRequest::First).await;
+    }
+
+    async fn run_query(&mut self, sql: String) {
+        // Dropping the previous result closes its cursor
+        self.query_result = None;
+
+        let outcome = self.connection_manager.execute_query(&sql).await;
+
+        let query_result = match self.handle_error_with_debug(outcome) {
+            Some(QueryOutcome::Cursor(columns, cursor, rows)) => {
+                let mut query_result = QueryResult::new(sql, columns, Some(cursor));
+                query_result.append_fetched(rows);
+                query_result
+            }
+            Some(QueryOutcome::Rows(columns, rows, is_truncated)) => {
+                let mut query_result = QueryResult::new(sql, columns, None);
+                query_result.append(rows);
+                if is_truncated {
+                    query_result.fetch_state = FetchState::MemoryCapReached;
+                }
+                query_result
+            }
+            Some(QueryOutcome::Command(affected)) => {
+                self.show_debug_message(format!("{} rows affected", affected));
+                return;
+            }
+            None => return,
+        };
+
+        self.query_result = Some(query_result);
+        self.main_view = MainView::QueryResult;
+    }
+
+    async fn fetch_more_rows_if_needed(&mut self) {
+        if self.main_view != MainView::QueryResult {
+            return;
+        }
+
+        if let Some(query_result) = self.query_result.as_mut() {
+            if query_result.needs_more() {
+                let result = query_result.fetch_more().await;
+                self.handle_error_with_debug(result);
+            }
+        }
+    }
+
+    async fn fetch_all_rows(&mut self) {
+        if let Some(query_result) = self.query_result.as_mut() {
+            let result = query_result.fetch_all().await;
+            self.handle_error_with_debug(result);
+        }
+    }
+
+    // Expand or collapse the focused line, or open it when it's an object.
+    async fn toggle_focused_item(&mut self) {
+        if !self.cluster.toggle_focused_item() {
+            self.open_table().await;
+            return;
+        }
+
+        self.load_focused_children().await;
+    }
+
+    async fn expand_focused_item(&mut self) {
+        if self.cluster.expand_focused_item() {
+            self.load_focused_children().await;
+        }
+    }
+
+    // Read the schemas of a database or the objects of a group the first
+    // time they're expanded.
+    async fn load_focused_children(&mut self) {
+        let unloaded_children = match self.cluster.unloaded_focused_children() {
+            Some(unloaded_children) => unloaded_children,
+            None => return,
+        };
+
+        if !self.connect_focused_database().await {
+            self.cluster.collapse_focused_item();
+            return;
+        }
+
+        match unloaded_children {
+            UnloadedChildren::Schemas => {
+                if let Some(schemas) = self.get_schemas().await {
+                    self.cluster.load_focused_database(schemas);
+                }
+            }
+            UnloadedChildren::Objects(schema_name, kind) => {
+                let objects = self
+                    .connection_manager
+                    .get_schema_objects(&schema_name, kind)
+                    .await;
+                if let Some(objects) = self.handle_error_with_debug(objects) {
+                    self.cluster.load_focused_group(objects);
+                }
+            }
+        }
+    }
+
+    // Open the connection to the database of the focused line, unless it's
+    // open already.
+    async fn connect_focused_database(&mut self) -> bool {
+        let database_index = match self.cluster.current_focused_database {
+            Some(database_index) => database_index,
+            None => return false,
+        };
+        if self.cluster.current_connected_database == Some(database_index) {
+            return true;
+        }
+
+        // Staged changes are bound to the database they were made on
+        if !self.changes.is_empty() {
+            self.status_message = Some(format!(
+                "{} pending change{} on {}, apply or discard them (R) before switching databases",
+                self.changes.len(),
+                if self.changes.len() == 1 { "" } else { "s" },
+                self.cluster.connected_database_name().unwrap_or_default()
+            ));
+            return false;
+        }
+
+        let connection_options_for_databse = PSQLConnectionOptions {
+            host: self.host.clone(),
+            user: self.user.clone(),
+            db_name: self.cluster.databases[database_index].name.clone(),
+        };
+
+        let create_connection_result = self
+            .connection_manager
+            .create_database_connection(connection_options_for_databse)
+            .await;
+
+        if self
+            .handle_error_with_debug(create_connection_result)
+            .is_none()
+        {
+            return false;
+        }
+        self.cluster.connect(database_index);
+        // The visited tables are in the database being left
+        self.navigation_history.clear();
+        true
+    }
+
+    async fn get_schemas(&mut self) -> Option<Vec<DatabaseSchema>> {
+        let result = self.connection_manager.get_tables_for_database().await;
+
+        let rows = self.handle_error_with_debug(result)?;
+
+        // Rows come sorted by schema, then by table
+        let mut tables_by_schema: Vec<(String, Vec<DatabaseTable>)> = Vec::new();
+        for row in rows.iter() {
+            let schema_name: String = row.get(0);
+            let table_name: Option<String> = row.get(1);
+
+            if tables_by_schema
+                .last()
+                .is_none_or(|(name, _)| *name != schema_name)
+            {
+                tables_by_schema.push((schema_name.clone(), Vec::new()));
+            }
+            if let (Some((_, tables)), Some(table_name)) = (tables_by_schema.last_mut(), table_name)
+            {
+                tables.push(DatabaseTable::new(
+                    TableName::new(&schema_name, &table_name),
+                    Vec::new(),
+                ));
+            }
+        }
+
+        Some(
+            tables_by_schema
+                .into_iter()
+                .map(|(name, tables)| DatabaseSchema::new(name, tables))
+                .collect(),
+        )
     }
 
     fn show_debug_message(&mut self, message: String) {
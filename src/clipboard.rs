@@ -0,0 +1,22 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::{
+    env,
+    io::{self, Write},
+};
+
+// Put text on the clipboard of the terminal with the OSC 52 escape sequence.
+// This goes through the terminal itself, so it also works over SSH. Inside
+// tmux the sequence is wrapped to be passed through to the outer terminal.
+pub fn copy(text: &str) -> io::Result<()> {
+    let sequence = format!("\x1b]52;c;{}\x07", STANDARD.encode(text));
+
+    let sequence = if env::var_os("TMUX").is_some() {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    };
+
+    let mut stdout = io::stdout();
+    stdout.write_all(sequence.as_bytes())?;
+    stdout.flush()
+}
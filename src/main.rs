@@ -1,5 +1,7 @@
 mod app;
+mod clipboard;
 mod postgres;
+mod state;
 mod ui;
 mod widgets;
 
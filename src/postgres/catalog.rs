@@ -0,0 +1,221 @@
+// Kinds of objects the explorer lists under a schema, each in its own group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectKind {
+    Table,
+    View,
+    MaterializedView,
+    ForeignTable,
+    Sequence,
+    Function,
+    Type,
+    Extension,
+}
+
+pub const OBJECT_KINDS: [ObjectKind; 8] = [
+    ObjectKind::Table,
+    ObjectKind::View,
+    ObjectKind::MaterializedView,
+    ObjectKind::ForeignTable,
+    ObjectKind::Sequence,
+    ObjectKind::Function,
+    ObjectKind::Type,
+    ObjectKind::Extension,
+];
+
+impl ObjectKind {
+    pub fn group_name(self) -> &'static str {
+        match self {
+            ObjectKind::Table => "Tables",
+            ObjectKind::View => "Views",
+            ObjectKind::MaterializedView => "Materialized views",
+            ObjectKind::ForeignTable => "Foreign tables",
+            ObjectKind::Sequence => "Sequences",
+            ObjectKind::Function => "Functions",
+            ObjectKind::Type => "Types",
+            ObjectKind::Extension => "Extensions",
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            ObjectKind::Table => "▦",
+            ObjectKind::View => "◇",
+            ObjectKind::MaterializedView => "◆",
+            ObjectKind::ForeignTable => "⇄",
+            ObjectKind::Sequence => "#",
+            ObjectKind::Function => "ƒ",
+            ObjectKind::Type => "τ",
+            ObjectKind::Extension => "⊕",
+        }
+    }
+
+    // Relations have rows that can be browsed like the rows of a table.
+    pub fn is_relation(self) -> bool {
+        matches!(
+            self,
+            ObjectKind::Table
+                | ObjectKind::View
+                | ObjectKind::MaterializedView
+                | ObjectKind::ForeignTable
+        )
+    }
+
+    // Oid and name of the objects of this kind in a schema, $1 being the
+    // schema name. Functions are named with their arguments to tell
+    // overloads apart, types only cover enums, domains and composite types
+    // that aren't the row type of a relation.
+    pub fn list_sql(self) -> String {
+        let relations = |relkinds: &str| {
+            format!(
+                "SELECT c.oid, c.relname::text
+                FROM pg_class c
+                JOIN pg_namespace n ON n.oid = c.relnamespace
+                WHERE n.nspname = $1 AND c.relkind IN ({})
+                ORDER BY c.relname COLLATE \"C\"",
+                relkinds
+            )
+        };
+
+        match self {
+            ObjectKind::Table => relations("'r', 'p'"),
+            ObjectKind::View => relations("'v'"),
+            ObjectKind::MaterializedView => relations("'m'"),
+            ObjectKind::ForeignTable => relations("'f'"),
+            ObjectKind::Sequence => relations("'S'"),
+            ObjectKind::Function => String::from(
+                "SELECT p.oid, p.proname || '(' || pg_get_function_identity_arguments(p.oid) || ')'
+                FROM pg_proc p
+                JOIN pg_namespace n ON n.oid = p.pronamespace
+                WHERE n.nspname = $1
+                ORDER BY p.proname COLLATE \"C\", p.oid",
+            ),
+            ObjectKind::Type => String::from(
+                "SELECT t.oid, t.typname::text
+                FROM pg_type t
+                JOIN pg_namespace n ON n.oid = t.typnamespace
+                LEFT JOIN pg_class c ON c.oid = t.typrelid
+                WHERE n.nspname = $1 AND t.typtype IN ('e', 'd', 'c')
+                    AND (t.typtype <> 'c' OR c.relkind = 'c')
+                ORDER BY t.typname COLLATE \"C\"",
+            ),
+            ObjectKind::Extension => String::from(
+                "SELECT e.oid, e.extname::text
+                FROM pg_extension e
+                JOIN pg_namespace n ON n.oid = e.extnamespace
+                WHERE n.nspname = $1
+                ORDER BY e.extname COLLATE \"C\"",
+            ),
+        }
+    }
+
+    // Properties shown in the detail view of an object, one row whose
+    // column names are the labels. $1 is the oid of the object. Relations
+    // have none, they open in the table view.
+    pub fn details_sql(self) -> Option<&'static str> {
+        let sql = match self {
+            ObjectKind::Table
+            | ObjectKind::View
+            | ObjectKind::MaterializedView
+            | ObjectKind::ForeignTable => return None,
+            ObjectKind::Sequence => {
+                "SELECT format_type(s.seqtypid, NULL) AS \"data type\",
+                    s.seqstart::text AS start,
+                    s.seqincrement::text AS increment,
+                    s.seqmin::text AS minimum,
+                    s.seqmax::text AS maximum,
+                    s.seqcache::text AS cache,
+                    CASE WHEN s.seqcycle THEN 'yes' ELSE 'no' END AS cycles,
+                    CASE WHEN has_sequence_privilege(s.seqrelid, 'SELECT,USAGE')
+                        THEN pg_sequence_last_value(s.seqrelid)::text
+                    END AS \"last value\",
+                    (
+                        SELECT d.refobjid::regclass::text || '.' || quote_ident(a.attname)
+                        FROM pg_depend d
+                        JOIN pg_attribute a ON a.attrelid = d.refobjid AND a.attnum = d.refobjsubid
+                        WHERE d.classid = 'pg_class'::regclass AND d.objid = s.seqrelid
+                            AND d.refclassid = 'pg_class'::regclass AND d.deptype IN ('a', 'i')
+                    ) AS \"owned by\",
+                    pg_get_userbyid(c.relowner)::text AS owner,
+                    obj_description(c.oid, 'pg_class') AS description
+                FROM pg_sequence s
+                JOIN pg_class c ON c.oid = s.seqrelid
+                WHERE s.seqrelid = $1"
+            }
+            ObjectKind::Function => {
+                "SELECT CASE p.prokind
+                        WHEN 'p' THEN 'procedure'
+                        WHEN 'a' THEN 'aggregate'
+                        WHEN 'w' THEN 'window function'
+                        ELSE 'function'
+                    END AS kind,
+                    pg_get_function_arguments(p.oid) AS arguments,
+                    pg_get_function_result(p.oid) AS returns,
+                    l.lanname::text AS language,
+                    CASE p.provolatile
+                        WHEN 'i' THEN 'immutable'
+                        WHEN 's' THEN 'stable'
+                        ELSE 'volatile'
+                    END AS volatility,
+                    CASE p.proparallel
+                        WHEN 's' THEN 'safe'
+                        WHEN 'r' THEN 'restricted'
+                        ELSE 'unsafe'
+                    END AS parallel,
+                    CASE WHEN p.proisstrict THEN 'yes' ELSE 'no' END AS strict,
+                    CASE WHEN p.prosecdef THEN 'definer' ELSE 'invoker' END AS security,
+                    pg_get_userbyid(p.proowner)::text AS owner,
+                    obj_description(p.oid, 'pg_proc') AS description
+                FROM pg_proc p
+                JOIN pg_language l ON l.oid = p.prolang
+                WHERE p.oid = $1"
+            }
+            ObjectKind::Type => {
+                "SELECT CASE t.typtype
+                        WHEN 'e' THEN 'enum'
+                        WHEN 'd' THEN 'domain'
+                        ELSE 'composite'
+                    END AS kind,
+                    (
+                        SELECT string_agg(quote_literal(e.enumlabel), ', ' ORDER BY e.enumsortorder)
+                        FROM pg_enum e
+                        WHERE e.enumtypid = t.oid
+                    ) AS labels,
+                    (
+                        SELECT string_agg(
+                            quote_ident(a.attname) || ' ' || format_type(a.atttypid, a.atttypmod),
+                            ', ' ORDER BY a.attnum
+                        )
+                        FROM pg_attribute a
+                        WHERE a.attrelid = t.typrelid AND a.attnum > 0 AND NOT a.attisdropped
+                    ) AS attributes,
+                    CASE WHEN t.typtype = 'd'
+                        THEN format_type(t.typbasetype, t.typtypmod)
+                    END AS \"base type\",
+                    CASE WHEN t.typtype = 'd'
+                        THEN CASE WHEN t.typnotnull THEN 'yes' ELSE 'no' END
+                    END AS \"not null\",
+                    t.typdefault AS default,
+                    (
+                        SELECT string_agg(pg_get_constraintdef(con.oid), ', ' ORDER BY con.conname)
+                        FROM pg_constraint con
+                        WHERE con.contypid = t.oid
+                    ) AS checks,
+                    pg_get_userbyid(t.typowner)::text AS owner,
+                    obj_description(t.oid, 'pg_type') AS description
+                FROM pg_type t
+                WHERE t.oid = $1"
+            }
+            ObjectKind::Extension => {
+                "SELECT e.extversion AS version,
+                    a.default_version AS \"default version\",
+                    CASE WHEN e.extrelocatable THEN 'yes' ELSE 'no' END AS relocatable,
+                    pg_get_userbyid(e.extowner)::text AS owner,
+                    obj_description(e.oid, 'pg_extension') AS description
+                FROM pg_extension e
+                LEFT JOIN pg_available_extensions a ON a.name = e.extname
+                WHERE e.oid = $1"
+            }
+        };
+        Some(sql)
+    }
+}
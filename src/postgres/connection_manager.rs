@@ -1,44 +1,201 @@
-use deadpool_postgres::Pool;
-use std::collections::HashMap;
-use tokio_postgres::{connect, Client, Error, NoTls, Row};
+use bytes::Bytes;
+use futures_util::{pin_mut, SinkExt, TryStreamExt};
+use tokio_postgres::{
+    connect, error::SqlState, types::ToSql, Client, Error, NoTls, Row, SimpleQueryMessage,
+};
 
-use crate::app::PSQLConnectionOptions;
+use crate::{
+    app::PSQLConnectionOptions,
+    postgres::{
+        catalog::ObjectKind,
+        cursor::QueryCursor,
+        data::{
+            decode_row, is_decodable, parse_text_row, Column, ColumnDetails, ForeignKey, Generated,
+            Value,
+        },
+        ddl::{object_ddl, relation_ddl},
+        query::{TableName, TableQuery},
+        structure::{
+            ColumnDefinition, DiagramColumn, TableConstraint, TableIndex, TableStructure,
+            TableTrigger,
+        },
+    },
+    widgets::query_result::{FETCH_SIZE, MEMORY_CAP_BYTES},
+};
 use cli_log::{error, info};
 
+const COPY_CHUNK_BYTES: usize = 64 * 1024;
+
+// Why a set of changes wasn't applied, nothing of it was.
+pub enum ApplyError {
+    // The statement at the index failed, or the transaction itself
+    Failed(Option<usize>, Error),
+    // The statement at the index didn't find exactly one row
+    RowCount(usize, usize),
+}
+
 pub struct ConnectionManager {
-    pools: HashMap<String, Pool>,
-    configs: HashMap<String, PSQLConnectionOptions>,
-    current_connection: String,
     client: Client,
+    connection_options: PSQLConnectionOptions,
+}
+
+// What running an ad-hoc statement produced.
+pub enum QueryOutcome {
+    // A read-only query, read through a cursor on its own connection, with
+    // the first rows fetched from it
+    Cursor(Vec<Column>, QueryCursor, Vec<Vec<Value>>),
+    // A row returning statement run in the session itself (INSERT ...
+    // RETURNING, SHOW, EXPLAIN, queries that write or lock rows...),
+    // truncated at the memory cap and flagged when it was
+    Rows(Vec<Column>, Vec<Vec<Value>>, bool),
+    // Any other statement along with the number of rows it affected
+    Command(u64),
+}
+
+async fn open_client(connection_options: &PSQLConnectionOptions) -> Result<Client, Error> {
+    let (client, connection) = connect(
+        format!(
+            "host={} user={} dbname={}",
+            connection_options.host, connection_options.user, connection_options.db_name,
+        )
+        .as_str(),
+        NoTls,
+    )
+    .await?;
+
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            error!("Connection error: {}", e);
+        }
+    });
+
+    Ok(client)
+}
+
+// Lowercased words of a statement, leaving out string literals, quoted
+// identifiers and comments.
+fn sql_words(sql: &str) -> Vec<String> {
+    let chars: Vec<char> = sql.chars().collect();
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    let mut words = Vec::new();
+    let mut index = 0;
+
+    while index < chars.len() {
+        let c = chars[index];
+        let next = chars.get(index + 1).copied();
+
+        if c == '\'' || c == '"' {
+            // E'...' strings escape with backslashes, the others by doubling
+            let escapes = c == '\''
+                && words.last().is_some_and(|word: &String| word == "e")
+                && index > 0
+                && chars[index - 1].eq_ignore_ascii_case(&'e');
+            index += 1;
+            while index < chars.len() {
+                if (escapes && chars[index] == '\\')
+                    || (chars[index] == c && chars.get(index + 1) == Some(&c))
+                {
+                    index += 2;
+                } else if chars[index] == c {
+                    break;
+                } else {
+                    index += 1;
+                }
+            }
+            index += 1;
+        } else if c == '-' && next == Some('-') {
+            while index < chars.len() && chars[index] != '\n' {
+                index += 1;
+            }
+        } else if c == '/' && next == Some('*') {
+            index += 2;
+            while index < chars.len()
+                && !(chars[index] == '*' && chars.get(index + 1) == Some(&'/'))
+            {
+                index += 1;
+            }
+            index += 2;
+        } else if c == '$' {
+            // $tag$...$tag$ quoting, $1 is a parameter
+            let tag_end = chars[index + 1..]
+                .iter()
+                .position(|c| !is_word_char(*c) || *c == '$')
+                .map(|position| index + 1 + position)
+                .filter(|end| chars[*end] == '$' && !next.is_some_and(|c| c.is_ascii_digit()));
+            match tag_end {
+                Some(tag_end) => {
+                    let tag = &chars[index..=tag_end];
+                    index = tag_end + 1;
+                    while index < chars.len() && !chars[index..].starts_with(tag) {
+                        index += 1;
+                    }
+                    index += tag.len();
+                }
+                None => index += 1,
+            }
+        } else if c.is_alphabetic() || c == '_' {
+            let start = index;
+            while index < chars.len() && is_word_char(chars[index]) {
+                index += 1;
+            }
+            words.push(
+                chars[start..index]
+                    .iter()
+                    .collect::<String>()
+                    .to_lowercase(),
+            );
+        } else {
+            index += 1;
+        }
+    }
+
+    words
+}
+
+// Only queries that just read can be declared as a cursor on a connection of
+// their own. Data-modifying WITH, SELECT INTO and row locks have to run in the
+// user's session.
+fn is_cursor_query(sql: &str) -> bool {
+    let words = sql_words(sql);
+    let is_query = matches!(
+        words.first().map(String::as_str),
+        Some("select" | "with" | "values" | "table")
+    );
+
+    is_query
+        && !words
+            .iter()
+            .enumerate()
+            .any(|(index, word)| match word.as_str() {
+                "insert" | "update" | "delete" | "merge" | "into" => true,
+                // FOR SHARE, FOR KEY SHARE and FOR NO KEY UPDATE
+                "for" => matches!(
+                    words.get(index + 1).map(String::as_str),
+                    Some("share" | "key" | "no")
+                ),
+                _ => false,
+            })
+}
+
+fn query_params(query: &TableQuery) -> Vec<&(dyn ToSql + Sync)> {
+    query
+        .params
+        .iter()
+        .map(|param| param as &(dyn ToSql + Sync))
+        .collect()
 }
 
 impl ConnectionManager {
     pub async fn new(
         connection_options: PSQLConnectionOptions,
     ) -> Result<ConnectionManager, Error> {
-        let (client, connection) = connect(
-            format!(
-                "host={} user={} dbname={}",
-                connection_options.host, connection_options.user, connection_options.db_name,
-            )
-            .as_str(),
-            NoTls,
-        )
-        .await?;
+        let client = open_client(&connection_options).await?;
 
         info!("Connected to database");
 
-        tokio::spawn(async move {
-            if let Err(e) = connection.await {
-                error!("Connection error: {}", e);
-            }
-        });
-
         Ok(ConnectionManager {
             client,
-            current_connection: String::new(),
-            pools: HashMap::new(),
-            configs: HashMap::new(),
+            connection_options,
         })
     }
 
@@ -54,11 +211,20 @@ impl ConnectionManager {
         Ok(databases)
     }
 
+    // Schemas the user can look into with their tables, one row per table
+    // and a row with a NULL table for an empty schema. The system schemas
+    // come last.
     pub async fn get_tables_for_database(&mut self) -> Result<Vec<Row>, Error> {
         let tables = self
             .client
             .query(
-                "SELECT tablename FROM pg_tables where schemaname = 'public'",
+                "SELECT n.nspname::text, t.tablename::text
+                FROM pg_namespace n
+                LEFT JOIN pg_tables t ON t.schemaname = n.nspname
+                WHERE has_schema_privilege(n.oid, 'USAGE')
+                    AND n.nspname NOT LIKE 'pg\\_toast%' AND n.nspname NOT LIKE 'pg\\_temp\\_%'
+                ORDER BY n.nspname IN ('pg_catalog', 'information_schema'),
+                    n.nspname COLLATE \"C\", t.tablename COLLATE \"C\"",
                 &[],
             )
             .await?;
@@ -66,35 +232,642 @@ impl ConnectionManager {
         Ok(tables)
     }
 
+    // Oid and name of the objects of a kind in the schema.
+    pub async fn get_schema_objects(
+        &mut self,
+        schema_name: &str,
+        kind: ObjectKind,
+    ) -> Result<Vec<(u32, String)>, Error> {
+        let rows = self.client.query(&kind.list_sql(), &[&schema_name]).await?;
+
+        Ok(rows.iter().map(|row| (row.get(0), row.get(1))).collect())
+    }
+
+    // Labels and values of the properties of an object, leaving out the ones
+    // that don't apply to it.
+    pub async fn get_object_details(
+        &mut self,
+        kind: ObjectKind,
+        oid: u32,
+    ) -> Result<Vec<(String, String)>, Error> {
+        let sql = match kind.details_sql() {
+            Some(sql) => sql,
+            None => return Ok(Vec::new()),
+        };
+        let rows = self.client.query(sql, &[&oid]).await?;
+
+        Ok(rows
+            .first()
+            .map(|row| {
+                row.columns()
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, column)| {
+                        let value: Option<String> = row.get(index);
+                        Some((column.name().to_string(), value?))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    pub async fn get_table_ddl(&mut self, table_name: &TableName) -> Result<String, Error> {
+        relation_ddl(&self.client, table_name).await
+    }
+
+    pub async fn get_object_ddl(&mut self, kind: ObjectKind, oid: u32) -> Result<String, Error> {
+        object_ddl(&self.client, kind, oid).await
+    }
+
     pub async fn create_database_connection(
         &mut self,
         connection_options: PSQLConnectionOptions,
     ) -> Result<(), Error> {
-        connect(
-            format!(
-                "host={} user={} dbname={}",
-                connection_options.host, connection_options.user, connection_options.db_name
-            )
-            .as_str(),
-            NoTls,
-        )
-        .await?;
+        self.client = open_client(&connection_options).await?;
+        self.connection_options = connection_options;
 
         Ok(())
     }
 
-    pub async fn get_table(&mut self, table_name: &String) -> Result<Vec<Row>, Error> {
-        self.client
+    // A new connection to the current database, for work that runs alongside
+    // the rest of the app.
+    pub async fn open_connection(&self) -> Result<Client, Error> {
+        open_client(&self.connection_options).await
+    }
+
+    pub async fn get_columns(&mut self, table_name: &TableName) -> Result<Vec<Column>, Error> {
+        let statement = self
+            .client
+            .prepare(&format!("SELECT * FROM {}", table_name.quoted()))
+            .await?;
+
+        Ok(statement
+            .columns()
+            .iter()
+            .map(|column| Column::new(column.name().to_string(), column.type_().clone()))
+            .collect())
+    }
+
+    pub async fn get_column_details(
+        &mut self,
+        table_name: &TableName,
+    ) -> Result<Vec<ColumnDetails>, Error> {
+        let rows = self
+            .client
+            .query(
+                "SELECT a.attname::text, format_type(a.atttypid, a.atttypmod), a.attnotnull,
+                    CASE
+                        WHEN a.attgenerated = 's'
+                            THEN 'generated always as (' || pg_get_expr(d.adbin, d.adrelid) || ')'
+                        WHEN a.attidentity = 'a' THEN 'generated always as identity'
+                        WHEN a.attidentity = 'd' THEN 'generated by default as identity'
+                        ELSE pg_get_expr(d.adbin, d.adrelid)
+                    END,
+                    a.attgenerated = 's', a.attidentity = 'a'
+                FROM pg_attribute a
+                LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+                WHERE a.attrelid = $1::text::regclass AND a.attnum > 0 AND NOT a.attisdropped
+                ORDER BY a.attnum",
+                &[&table_name.quoted()],
+            )
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| ColumnDetails {
+                name: row.get(0),
+                type_name: row.get(1),
+                not_null: row.get(2),
+                default: row.get(3),
+                generated: if row.get(4) {
+                    Some(Generated::Stored)
+                } else if row.get(5) {
+                    Some(Generated::AlwaysIdentity)
+                } else {
+                    None
+                },
+            })
+            .collect())
+    }
+
+    // Columns that identify a row: the primary key, or without one the
+    // smallest unique index over NOT NULL columns. Empty when the table has
+    // neither.
+    pub async fn get_primary_key(&mut self, table_name: &TableName) -> Result<Vec<String>, Error> {
+        let rows = self
+            .client
             .query(
-                "SELECT column_name FROM information_schema.columns where table_name = ($1)",
-                &[&table_name],
+                "SELECT a.attname::text
+                FROM (
+                    SELECT u.key
+                    FROM (
+                        SELECT (i.indkey::int2[])[0:i.indnkeyatts - 1] AS key,
+                            i.indisprimary, i.indnkeyatts, i.indexrelid
+                        FROM pg_index i
+                        WHERE i.indrelid = $1::text::regclass AND i.indisunique
+                            AND i.indimmediate AND i.indpred IS NULL AND i.indexprs IS NULL
+                    ) u
+                    WHERE NOT EXISTS (
+                        SELECT FROM pg_attribute n
+                        WHERE n.attrelid = $1::text::regclass AND n.attnum = ANY(u.key)
+                            AND NOT n.attnotnull
+                    )
+                    ORDER BY u.indisprimary DESC, u.indnkeyatts, u.indexrelid
+                    LIMIT 1
+                ) k
+                JOIN pg_attribute a ON a.attrelid = $1::text::regclass AND a.attnum = ANY(k.key)
+                ORDER BY array_position(k.key, a.attnum)",
+                &[&table_name.quoted()],
             )
+            .await?;
+
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    // Foreign keys defined on the table, or with `referencing` the foreign
+    // keys of other tables that point to it.
+    pub async fn get_foreign_keys(
+        &mut self,
+        table_name: &TableName,
+        referencing: bool,
+    ) -> Result<Vec<ForeignKey>, Error> {
+        let condition = if referencing {
+            "con.confrelid = $1::text::regclass"
+        } else {
+            "con.conrelid = $1::text::regclass"
+        };
+
+        self.query_foreign_keys(condition, &table_name.quoted())
             .await
     }
 
-    pub async fn get_data(&mut self, table_name: &String) -> Result<Vec<Row>, Error> {
-        self.client
-            .query(&format!("SELECT * FROM {} LIMIT 10", table_name), &[])
+    // Foreign keys from or to the tables of a schema.
+    pub async fn get_schema_foreign_keys(
+        &mut self,
+        schema_name: &str,
+    ) -> Result<Vec<ForeignKey>, Error> {
+        self.query_foreign_keys(
+            "(source_schema.nspname = $1 OR target_schema.nspname = $1)",
+            schema_name,
+        )
+        .await
+    }
+
+    async fn query_foreign_keys(
+        &mut self,
+        condition: &str,
+        param: &str,
+    ) -> Result<Vec<ForeignKey>, Error> {
+        let rows = self
+            .client
+            .query(
+                &format!(
+                    "SELECT con.conname::text, source_schema.nspname::text, source.relname::text,
+                        target_schema.nspname::text, target.relname::text,
+                        array(
+                            SELECT a.attname::text
+                            FROM unnest(con.conkey) WITH ORDINALITY AS k(attnum, position)
+                            JOIN pg_attribute a ON a.attrelid = con.conrelid AND a.attnum = k.attnum
+                            ORDER BY k.position
+                        ),
+                        array(
+                            SELECT a.attname::text
+                            FROM unnest(con.confkey) WITH ORDINALITY AS k(attnum, position)
+                            JOIN pg_attribute a ON a.attrelid = con.confrelid AND a.attnum = k.attnum
+                            ORDER BY k.position
+                        )
+                    FROM pg_constraint con
+                    JOIN pg_class source ON source.oid = con.conrelid
+                    JOIN pg_class target ON target.oid = con.confrelid
+                    JOIN pg_namespace source_schema ON source_schema.oid = source.relnamespace
+                    JOIN pg_namespace target_schema ON target_schema.oid = target.relnamespace
+                    WHERE con.contype = 'f' AND {}
+                    ORDER BY source_schema.nspname, source.relname, con.conname",
+                    condition
+                ),
+                &[&param],
+            )
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| ForeignKey {
+                name: row.get(0),
+                table: TableName::new(row.get(1), row.get(2)),
+                referenced_table: TableName::new(row.get(3), row.get(4)),
+                columns: row.get(5),
+                referenced_columns: row.get(6),
+            })
+            .collect())
+    }
+
+    // Tables of a schema with their columns, partitions being left out.
+    pub async fn get_schema_tables(
+        &mut self,
+        schema_name: &str,
+    ) -> Result<Vec<(String, Vec<DiagramColumn>)>, Error> {
+        let rows = self
+            .client
+            .query(
+                "SELECT c.relname::text, a.attname::text, format_type(a.atttypid, a.atttypmod),
+                    EXISTS (
+                        SELECT FROM pg_constraint con
+                        WHERE con.conrelid = c.oid AND con.contype = 'p'
+                            AND a.attnum = ANY(con.conkey)
+                    ),
+                    EXISTS (
+                        SELECT FROM pg_constraint con
+                        WHERE con.conrelid = c.oid AND con.contype = 'f'
+                            AND a.attnum = ANY(con.conkey)
+                    )
+                FROM pg_class c
+                JOIN pg_namespace n ON n.oid = c.relnamespace
+                LEFT JOIN pg_attribute a ON a.attrelid = c.oid AND a.attnum > 0
+                    AND NOT a.attisdropped
+                WHERE n.nspname = $1 AND c.relkind IN ('r', 'p') AND NOT c.relispartition
+                ORDER BY c.relname COLLATE \"C\", a.attnum",
+                &[&schema_name],
+            )
+            .await?;
+
+        let mut tables: Vec<(String, Vec<DiagramColumn>)> = Vec::new();
+        for row in rows.iter() {
+            let table_name: String = row.get(0);
+            if tables.last().map(|(name, _)| name) != Some(&table_name) {
+                tables.push((table_name, Vec::new()));
+            }
+            if let (Some(name), Some((_, columns))) = (row.get(1), tables.last_mut()) {
+                columns.push(DiagramColumn {
+                    name,
+                    type_name: row.get(2),
+                    is_primary_key: row.get(3),
+                    is_foreign_key: row.get(4),
+                });
+            }
+        }
+
+        Ok(tables)
+    }
+
+    // Columns, constraints, indexes and triggers of a relation.
+    pub async fn get_table_structure(
+        &mut self,
+        table_name: &TableName,
+    ) -> Result<TableStructure, Error> {
+        let table = table_name.quoted();
+
+        let columns = self
+            .client
+            .query(
+                "SELECT a.attname::text, format_type(a.atttypid, a.atttypmod), a.attnotnull,
+                    CASE
+                        WHEN a.attgenerated = 's'
+                            THEN 'generated always as (' || pg_get_expr(d.adbin, d.adrelid) || ')'
+                        WHEN a.attidentity = 'a' THEN 'generated always as identity'
+                        WHEN a.attidentity = 'd' THEN 'generated by default as identity'
+                        ELSE pg_get_expr(d.adbin, d.adrelid)
+                    END,
+                    CASE WHEN a.attcollation <> t.typcollation
+                        THEN quote_ident(co.collname)
+                    END,
+                    col_description(a.attrelid, a.attnum)
+                FROM pg_attribute a
+                JOIN pg_type t ON t.oid = a.atttypid
+                LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+                LEFT JOIN pg_collation co ON co.oid = a.attcollation
+                WHERE a.attrelid = $1::text::regclass AND a.attnum > 0 AND NOT a.attisdropped
+                ORDER BY a.attnum",
+                &[&table],
+            )
+            .await?;
+
+        let constraints = self
+            .client
+            .query(
+                "SELECT con.conname::text,
+                    CASE con.contype
+                        WHEN 'p' THEN 'primary key'
+                        WHEN 'u' THEN 'unique'
+                        WHEN 'c' THEN 'check'
+                        WHEN 'f' THEN 'foreign key'
+                        ELSE 'exclusion'
+                    END,
+                    pg_get_constraintdef(con.oid)
+                FROM pg_constraint con
+                WHERE con.conrelid = $1::text::regclass AND con.contype IN ('p', 'u', 'c', 'f', 'x')
+                ORDER BY array_position(ARRAY['p', 'u', 'f', 'c', 'x'], con.contype::text),
+                    con.conname COLLATE \"C\"",
+                &[&table],
+            )
+            .await?;
+
+        let indexes = self
+            .client
+            .query(
+                "SELECT c.relname::text, pg_get_indexdef(i.indexrelid),
+                    pg_size_pretty(pg_relation_size(i.indexrelid)), i.indisvalid
+                FROM pg_index i
+                JOIN pg_class c ON c.oid = i.indexrelid
+                WHERE i.indrelid = $1::text::regclass
+                ORDER BY i.indisprimary DESC, c.relname COLLATE \"C\"",
+                &[&table],
+            )
+            .await?;
+
+        let triggers = self
+            .client
+            .query(
+                "SELECT t.tgname::text, pg_get_triggerdef(t.oid), t.tgenabled <> 'D'
+                FROM pg_trigger t
+                WHERE t.tgrelid = $1::text::regclass AND NOT t.tgisinternal
+                ORDER BY t.tgname COLLATE \"C\"",
+                &[&table],
+            )
+            .await?;
+
+        Ok(TableStructure {
+            columns: columns
+                .iter()
+                .map(|row| ColumnDefinition {
+                    name: row.get(0),
+                    type_name: row.get(1),
+                    not_null: row.get(2),
+                    default: row.get(3),
+                    collation: row.get(4),
+                    comment: row.get(5),
+                })
+                .collect(),
+            constraints: constraints
+                .iter()
+                .map(|row| TableConstraint {
+                    name: row.get(0),
+                    kind: row.get(1),
+                    definition: row.get(2),
+                })
+                .collect(),
+            indexes: indexes
+                .iter()
+                .map(|row| TableIndex {
+                    name: row.get(0),
+                    definition: row.get(1),
+                    size: row.get(2),
+                    is_valid: row.get(3),
+                })
+                .collect(),
+            triggers: triggers
+                .iter()
+                .map(|row| TableTrigger {
+                    name: row.get(0),
+                    definition: row.get(1),
+                    is_enabled: row.get(2),
+                })
+                .collect(),
+        })
+    }
+
+    // Row count estimate from the planner statistics, None when the table
+    // has never been analyzed.
+    pub async fn get_estimated_row_count(
+        &mut self,
+        table_name: &TableName,
+    ) -> Result<Option<i64>, Error> {
+        let row = self
+            .client
+            .query_one(
+                "SELECT reltuples::bigint FROM pg_class WHERE oid = $1::text::regclass",
+                &[&table_name.quoted()],
+            )
+            .await?;
+
+        let estimate: i64 = row.get(0);
+        Ok(if estimate < 0 { None } else { Some(estimate) })
+    }
+
+    pub async fn count_rows(&mut self, query: &TableQuery) -> Result<i64, Error> {
+        let row = self
+            .client
+            .query_one(&query.to_count_sql(), &query_params(query))
+            .await?;
+
+        Ok(row.get(0))
+    }
+
+    pub async fn get_data(&mut self, query: &TableQuery) -> Result<Vec<Vec<Value>>, Error> {
+        let rows = self
+            .client
+            .query(&query.to_sql(), &query_params(query))
+            .await?;
+
+        Ok(rows.iter().map(decode_row).collect())
+    }
+
+    // Run the statements, each with the parameters of its query, in one
+    // transaction. Every statement has to return exactly one row, the rows
+    // are returned in the order of the statements.
+    pub async fn apply_changes(
+        &mut self,
+        statements: &[(String, &TableQuery)],
+    ) -> Result<Vec<Vec<Value>>, ApplyError> {
+        let transaction = self
+            .client
+            .transaction()
+            .await
+            .map_err(|error| ApplyError::Failed(None, error))?;
+
+        let mut returned = Vec::new();
+        for (index, (sql, query)) in statements.iter().enumerate() {
+            let rows = transaction
+                .query(sql.as_str(), &query_params(query))
+                .await
+                .map_err(|error| ApplyError::Failed(Some(index), error))?;
+            if rows.len() != 1 {
+                return Err(ApplyError::RowCount(index, rows.len()));
+            }
+            returned.push(decode_row(&rows[0]));
+        }
+
+        transaction
+            .commit()
             .await
+            .map_err(|error| ApplyError::Failed(None, error))?;
+        Ok(returned)
+    }
+
+    // Load CSV data with COPY FROM STDIN, creating the table first when
+    // given a CREATE TABLE statement. Both happen in one transaction so a
+    // failed import leaves nothing behind.
+    pub async fn import_csv(
+        &mut self,
+        create_sql: Option<&str>,
+        copy_sql: &str,
+        data: Vec<u8>,
+    ) -> Result<u64, Error> {
+        let transaction = self.client.transaction().await?;
+        if let Some(create_sql) = create_sql {
+            transaction.batch_execute(create_sql).await?;
+        }
+
+        let sink = transaction.copy_in(copy_sql).await?;
+        pin_mut!(sink);
+        for chunk in data.chunks(COPY_CHUNK_BYTES) {
+            sink.send(Bytes::copy_from_slice(chunk)).await?;
+        }
+        let rows = sink.finish().await?;
+
+        transaction.commit().await?;
+        Ok(rows)
+    }
+
+    // Declare a cursor over the query and fetch its first rows. None when the
+    // query has to run in the user's session after all: the cursor's
+    // connection can't see what it reads, like temp tables, or the query
+    // turns out to write, like nextval() does.
+    async fn declare_cursor(
+        &self,
+        sql: &str,
+        columns: &[Column],
+    ) -> Result<Option<(QueryCursor, Vec<Vec<Value>>)>, Error> {
+        let settings: Vec<(String, String)> = self
+            .client
+            .query(
+                "SELECT name, setting FROM pg_settings WHERE source = 'session'",
+                &[],
+            )
+            .await?
+            .iter()
+            .map(|row| (row.get(0), row.get(1)))
+            .collect();
+
+        let client = open_client(&self.connection_options).await?;
+        let mut cursor = match QueryCursor::declare(client, sql, columns, &settings).await {
+            Ok(cursor) => cursor,
+            Err(error) => {
+                info!("Running the query in the session, it can't be declared: {error}");
+                return Ok(None);
+            }
+        };
+
+        match cursor.fetch(columns, FETCH_SIZE).await {
+            Ok(rows) => Ok(Some((cursor, rows))),
+            Err(error) if error.code() == Some(&SqlState::READ_ONLY_SQL_TRANSACTION) => {
+                info!("Running the query in the session, it writes: {error}");
+                Ok(None)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    pub async fn execute_query(&mut self, sql: &str) -> Result<QueryOutcome, Error> {
+        let sql = sql.trim().trim_end_matches(';');
+        let statement = self.client.prepare(sql).await?;
+
+        let columns: Vec<Column> = statement
+            .columns()
+            .iter()
+            .map(|column| Column::new(column.name().to_string(), column.type_().clone()))
+            .collect();
+
+        if columns.is_empty() {
+            let affected = self.client.execute(&statement, &[]).await?;
+            return Ok(QueryOutcome::Command(affected));
+        }
+
+        if is_cursor_query(sql) {
+            if let Some((cursor, rows)) = self.declare_cursor(sql, &columns).await? {
+                return Ok(QueryOutcome::Cursor(columns, cursor, rows));
+            }
+        }
+
+        let mut rows = Vec::new();
+        let mut estimated_bytes = 0;
+        let mut is_truncated = false;
+        // Whether the row fits under the memory cap, it's kept if it does
+        let mut keep = |row: Vec<Value>| {
+            estimated_bytes += row.iter().map(Value::estimated_size).sum::<usize>();
+            if estimated_bytes > MEMORY_CAP_BYTES {
+                return false;
+            }
+            rows.push(row);
+            true
+        };
+
+        if columns.iter().all(|column| is_decodable(&column.type_)) {
+            let stream = self
+                .client
+                .query_raw(&statement, std::iter::empty::<&(dyn ToSql + Sync)>())
+                .await?;
+            pin_mut!(stream);
+
+            while let Some(row) = stream.try_next().await? {
+                if !keep(decode_row(&row)) {
+                    is_truncated = true;
+                    break;
+                }
+            }
+        } else {
+            // Types the binary decoder can't handle are read as text, as the
+            // cursor does
+            let stream = self.client.simple_query_raw(sql).await?;
+            pin_mut!(stream);
+
+            while let Some(message) = stream.try_next().await? {
+                if let SimpleQueryMessage::Row(row) = message {
+                    if !keep(parse_text_row(&row, &columns)) {
+                        is_truncated = true;
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(QueryOutcome::Rows(columns, rows, is_truncated))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_plain_queries_use_a_cursor() {
+        assert!(is_cursor_query("SELECT * FROM orders"));
+        assert!(is_cursor_query("\n  with t AS (SELECT 1) SELECT * FROM t"));
+        assert!(is_cursor_query("VALUES (1), (2)"));
+        assert!(is_cursor_query("table orders"));
+        assert!(!is_cursor_query("UPDATE orders SET note = ''"));
+        assert!(!is_cursor_query("EXPLAIN SELECT 1"));
+        assert!(!is_cursor_query(""));
+    }
+
+    #[test]
+    fn queries_that_write_or_lock_run_in_the_session() {
+        assert!(!is_cursor_query(
+            "WITH gone AS (DELETE FROM orders RETURNING *) SELECT count(*) FROM gone"
+        ));
+        assert!(!is_cursor_query(
+            "with t as (insert into log values (1) returning id) select * from t"
+        ));
+        assert!(!is_cursor_query("SELECT * INTO archived FROM orders"));
+        assert!(!is_cursor_query("SELECT * FROM orders FOR UPDATE"));
+        assert!(!is_cursor_query("SELECT * FROM orders FOR NO KEY UPDATE"));
+        assert!(!is_cursor_query(
+            "SELECT * FROM orders for share skip locked"
+        ));
+        assert!(!is_cursor_query("SELECT * FROM orders FOR KEY SHARE"));
+    }
+
+    #[test]
+    fn keywords_in_strings_identifiers_and_comments_are_ignored() {
+        assert!(is_cursor_query("SELECT 'delete from orders' AS note"));
+        assert!(is_cursor_query("SELECT 'it''s into' AS note"));
+        assert!(is_cursor_query(r"SELECT E'it\'s for update' AS note"));
+        assert!(is_cursor_query(r#"SELECT "update", "Into" FROM orders"#));
+        assert!(is_cursor_query("SELECT 1 -- for update\n"));
+        assert!(is_cursor_query("SELECT /* into */ 1"));
+        assert!(is_cursor_query("SELECT $body$ delete $body$, $1"));
+        assert!(is_cursor_query(
+            "SELECT updated_at, substring(note for 2) FROM orders"
+        ));
+        assert!(!is_cursor_query("SELECT $a$ x $a$ FROM orders FOR UPDATE"));
     }
 }
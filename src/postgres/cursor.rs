@@ -0,0 +1,82 @@
+use tokio_postgres::{Client, Error, SimpleQueryMessage};
+
+use crate::postgres::data::{decode_row, is_decodable, parse_text_row, Column, Value};
+
+const CURSOR_NAME: &str = "postgres_tui_cursor";
+
+// A server side cursor over the result of an ad-hoc query.
+//
+// The cursor lives in a read-only transaction on its own connection so that
+// browsing the result doesn't block the connection used by the rest of the
+// app. The connection is given the settings the user changed in their session
+// first. Dropping the cursor closes the connection, which rolls the
+// transaction back and releases the cursor on the server.
+pub struct QueryCursor {
+    client: Client,
+    text_protocol: bool,
+    pub is_exhausted: bool,
+}
+
+impl QueryCursor {
+    pub async fn declare(
+        client: Client,
+        sql: &str,
+        columns: &[Column],
+        settings: &[(String, String)],
+    ) -> Result<Self, Error> {
+        for (name, setting) in settings {
+            client
+                .execute("SELECT set_config($1, $2, false)", &[name, setting])
+                .await?;
+        }
+        client.batch_execute("BEGIN READ ONLY").await?;
+        client
+            .batch_execute(&format!(
+                "DECLARE {} NO SCROLL CURSOR FOR {}",
+                CURSOR_NAME, sql
+            ))
+            .await?;
+
+        Ok(Self {
+            client,
+            // Results containing types the binary decoder can't handle are
+            // fetched through the simple query protocol, which returns every
+            // value as text.
+            text_protocol: !columns.iter().all(|column| is_decodable(&column.type_)),
+            is_exhausted: false,
+        })
+    }
+
+    pub async fn fetch(
+        &mut self,
+        columns: &[Column],
+        count: usize,
+    ) -> Result<Vec<Vec<Value>>, Error> {
+        let fetch = format!("FETCH FORWARD {} FROM {}", count, CURSOR_NAME);
+
+        let rows: Vec<Vec<Value>> = if self.text_protocol {
+            self.client
+                .simple_query(&fetch)
+                .await?
+                .iter()
+                .filter_map(|message| match message {
+                    SimpleQueryMessage::Row(row) => Some(parse_text_row(row, columns)),
+                    _ => None,
+                })
+                .collect()
+        } else {
+            self.client
+                .query(&fetch, &[])
+                .await?
+                .iter()
+                .map(decode_row)
+                .collect()
+        };
+
+        if rows.len() < count {
+            self.is_exhausted = true;
+        }
+
+        Ok(rows)
+    }
+}
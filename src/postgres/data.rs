@@ -0,0 +1,1311 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use fallible_iterator::FallibleIterator;
+use postgres_protocol::types::{self as protocol, Range, RangeBound};
+use std::{
+    error::Error,
+    fmt::{self, Display, Write},
+};
+use tokio_postgres::{
+    types::{FromSql, Kind, Type},
+    Row, SimpleQueryRow,
+};
+
+use crate::postgres::query::TableName;
+
+type DecodeResult<T> = Result<T, Box<dyn Error + Sync + Send>>;
+
+// Oids below this value are assigned to built-in objects, everything above
+// belongs to user defined or extension types.
+const FIRST_NORMAL_OBJECT_ID: u32 = 16384;
+
+const NUMERIC_NEG: u16 = 0x4000;
+const NUMERIC_NAN: u16 = 0xC000;
+const NUMERIC_PINF: u16 = 0xD000;
+const NUMERIC_NINF: u16 = 0xF000;
+
+// Display model of a single cell.
+//
+// Every postgres value is decoded from the binary protocol into one of these
+// variants. Types without a dedicated variant (dates, uuids, network
+// addresses, geometric types, ranges...) are rendered into their postgres
+// text representation and stored as Text so they can be sent back to the
+// server as `$1::text::<type>`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Numeric(String),
+    Text(String),
+    Json(String),
+    Bytes(Vec<u8>),
+    Array(Vec<Value>),
+    Composite(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    // Rough number of bytes the value occupies in memory, used to cap the
+    // size of streamed results.
+    pub fn estimated_size(&self) -> usize {
+        let content = match self {
+            Value::Numeric(value) | Value::Text(value) | Value::Json(value) => value.len(),
+            Value::Bytes(value) => value.len(),
+            Value::Array(values) => values.iter().map(Value::estimated_size).sum(),
+            Value::Composite(fields) => fields
+                .iter()
+                .map(|(name, value)| name.len() + value.estimated_size())
+                .sum(),
+            _ => 0,
+        };
+        std::mem::size_of::<Value>() + content
+    }
+
+    // Text representation used when binding the value as a parameter, None
+    // stands for NULL.
+    pub fn to_text(&self) -> Option<String> {
+        match self {
+            Value::Null => None,
+            value => Some(value.to_string()),
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => f.write_str("NULL"),
+            Value::Bool(value) => f.write_str(if *value { "true" } else { "false" }),
+            Value::Int(value) => write!(f, "{}", value),
+            Value::Float(value) => f.write_str(&format_float(*value)),
+            Value::Numeric(value) | Value::Text(value) | Value::Json(value) => f.write_str(value),
+            Value::Bytes(value) => {
+                f.write_str("\\x")?;
+                for byte in value {
+                    write!(f, "{:02x}", byte)?;
+                }
+                Ok(())
+            }
+            Value::Array(values) => {
+                f.write_char('{')?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        f.write_char(',')?;
+                    }
+                    match value {
+                        Value::Null => f.write_str("NULL")?,
+                        Value::Array(_) => write!(f, "{}", value)?,
+                        value => write_quoted(f, &value.to_string(), true)?,
+                    }
+                }
+                f.write_char('}')
+            }
+            Value::Composite(fields) => {
+                f.write_char('(')?;
+                for (index, (_, value)) in fields.iter().enumerate() {
+                    if index > 0 {
+                        f.write_char(',')?;
+                    }
+                    if !value.is_null() {
+                        write_quoted(f, &value.to_string(), false)?;
+                    }
+                }
+                f.write_char(')')
+            }
+        }
+    }
+}
+
+// Quote array elements and composite fields the same way postgres' output
+// functions do so the text can be parsed back by the server.
+fn write_quoted(f: &mut fmt::Formatter<'_>, text: &str, is_array_element: bool) -> fmt::Result {
+    let special: &[char] = if is_array_element {
+        &['{', '}', ',', '"', '\\']
+    } else {
+        &['(', ')', ',', '"', '\\']
+    };
+
+    let needs_quotes = text.is_empty()
+        || (is_array_element && text.eq_ignore_ascii_case("NULL"))
+        || text
+            .chars()
+            .any(|c| c.is_whitespace() || special.contains(&c));
+
+    if !needs_quotes {
+        return f.write_str(text);
+    }
+
+    f.write_char('"')?;
+    for c in text.chars() {
+        if c == '"' || c == '\\' {
+            f.write_char('\\')?;
+        }
+        f.write_char(c)?;
+    }
+    f.write_char('"')
+}
+
+fn format_float(value: f64) -> String {
+    if value.is_nan() {
+        String::from("NaN")
+    } else if value.is_infinite() && value > 0.0 {
+        String::from("Infinity")
+    } else if value.is_infinite() {
+        String::from("-Infinity")
+    } else {
+        value.to_string()
+    }
+}
+
+impl<'a> FromSql<'a> for Value {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> DecodeResult<Value> {
+        Ok(decode(ty, raw))
+    }
+
+    fn from_sql_null(_ty: &Type) -> DecodeResult<Value> {
+        Ok(Value::Null)
+    }
+
+    fn accepts(_ty: &Type) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Column {
+    pub name: String,
+    pub type_: Type,
+}
+
+impl Column {
+    pub fn new(name: String, type_: Type) -> Self {
+        Self { name, type_ }
+    }
+}
+
+// How the server fills a column that can't be given a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Generated {
+    // GENERATED ALWAYS AS (...) STORED, computed from the rest of the row
+    Stored,
+    // GENERATED ALWAYS AS IDENTITY, only written with OVERRIDING SYSTEM VALUE
+    AlwaysIdentity,
+}
+
+// Catalog definition of a table column. Generated columns and identity
+// columns that are always generated can't be given a value.
+#[derive(Debug, Clone)]
+pub struct ColumnDetails {
+    pub name: String,
+    pub type_name: String,
+    pub not_null: bool,
+    pub default: Option<String>,
+    pub generated: Option<Generated>,
+}
+
+// A foreign key constraint, `columns` of `table` reference
+// `referenced_columns` of `referenced_table` in the same order.
+#[derive(Debug, Clone)]
+pub struct ForeignKey {
+    pub name: String,
+    pub table: TableName,
+    pub columns: Vec<String>,
+    pub referenced_table: TableName,
+    pub referenced_columns: Vec<String>,
+}
+
+pub fn decode_row(row: &Row) -> Vec<Value> {
+    (0..row.len())
+        .map(|index| row.get::<usize, Value>(index))
+        .collect()
+}
+
+// A row read through the simple query protocol, every value as text.
+pub fn parse_text_row(row: &SimpleQueryRow, columns: &[Column]) -> Vec<Value> {
+    columns
+        .iter()
+        .enumerate()
+        .map(|(index, column)| match row.get(index) {
+            Some(text) => parse_text(&column.type_, text),
+            None => Value::Null,
+        })
+        .collect()
+}
+
+// Build a value from postgres' text output, used when a result has to be
+// read through the simple query protocol.
+pub fn parse_text(ty: &Type, text: &str) -> Value {
+    let base = match ty.kind() {
+        Kind::Domain(base) => base,
+        _ => ty,
+    };
+
+    match *base {
+        Type::BOOL => Value::Bool(text == "t"),
+        Type::INT2 | Type::INT4 | Type::INT8 | Type::OID => text
+            .parse()
+            .map(Value::Int)
+            .unwrap_or_else(|_| Value::Text(text.to_string())),
+        Type::FLOAT4 | Type::FLOAT8 => text
+            .parse()
+            .map(Value::Float)
+            .unwrap_or_else(|_| Value::Text(text.to_string())),
+        Type::NUMERIC => Value::Numeric(text.to_string()),
+        Type::JSON | Type::JSONB => Value::Json(text.to_string()),
+        Type::BYTEA => match parse_hex(text.trim_start_matches("\\x")) {
+            Some(bytes) => Value::Bytes(bytes),
+            None => Value::Text(text.to_string()),
+        },
+        _ => Value::Text(text.to_string()),
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+// Whether values of this type can be decoded from the binary protocol.
+//
+// Types that can't (reg* types, text search types, extension types...) have
+// to be requested as text by casting them in the select list.
+pub fn is_decodable(ty: &Type) -> bool {
+    match ty.kind() {
+        Kind::Enum(_) => true,
+        Kind::Domain(base) => is_decodable(base),
+        Kind::Array(member) | Kind::Range(member) | Kind::Multirange(member) => {
+            is_decodable(member)
+        }
+        Kind::Composite(fields) => fields.iter().all(|field| is_decodable(field.type_())),
+        Kind::Pseudo => *ty == Type::RECORD || *ty == Type::UNKNOWN,
+        Kind::Simple => ty.oid() < FIRST_NORMAL_OBJECT_ID && is_decodable_simple(ty),
+        _ => false,
+    }
+}
+
+fn is_decodable_simple(ty: &Type) -> bool {
+    !matches!(
+        *ty,
+        Type::REGPROC
+            | Type::REGPROCEDURE
+            | Type::REGOPER
+            | Type::REGOPERATOR
+            | Type::REGCLASS
+            | Type::REGTYPE
+            | Type::REGCONFIG
+            | Type::REGDICTIONARY
+            | Type::REGNAMESPACE
+            | Type::REGROLE
+            | Type::REGCOLLATION
+            | Type::TS_VECTOR
+            | Type::GTS_VECTOR
+            | Type::TSQUERY
+            | Type::TXID_SNAPSHOT
+            | Type::PG_SNAPSHOT
+            | Type::ACLITEM
+            | Type::PG_NDISTINCT
+            | Type::PG_DEPENDENCIES
+            | Type::PG_MCV_LIST
+            | Type::PG_BRIN_BLOOM_SUMMARY
+            | Type::PG_BRIN_MINMAX_MULTI_SUMMARY
+    )
+}
+
+// Decode a non null value, falling back to the raw bytes as text (or hex when
+// they aren't valid utf-8) for anything the typed decoder can't handle.
+pub fn decode(ty: &Type, raw: &[u8]) -> Value {
+    decode_typed(ty, raw).unwrap_or_else(|_| match std::str::from_utf8(raw) {
+        Ok(text) => Value::Text(text.to_string()),
+        Err(_) => Value::Bytes(raw.to_vec()),
+    })
+}
+
+fn decode_typed(ty: &Type, raw: &[u8]) -> DecodeResult<Value> {
+    match ty.kind() {
+        Kind::Enum(_) => return Ok(Value::Text(protocol::text_from_sql(raw)?.to_string())),
+        Kind::Domain(base) => return decode_typed(base, raw),
+        Kind::Array(member) => return decode_array(member, raw),
+        Kind::Range(subtype) => return Ok(Value::Text(decode_range(subtype, raw)?)),
+        Kind::Multirange(subtype) => return decode_multirange(subtype, raw),
+        Kind::Composite(fields) => {
+            let fields: Vec<(String, Option<Type>)> = fields
+                .iter()
+                .map(|field| (field.name().to_string(), Some(field.type_().clone())))
+                .collect();
+            return decode_composite(&fields, raw);
+        }
+        _ => {}
+    }
+
+    let value = match *ty {
+        Type::BOOL => Value::Bool(protocol::bool_from_sql(raw)?),
+        Type::INT2 => Value::Int(protocol::int2_from_sql(raw)? as i64),
+        Type::INT4 => Value::Int(protocol::int4_from_sql(raw)? as i64),
+        Type::INT8 => Value::Int(protocol::int8_from_sql(raw)?),
+        Type::OID | Type::XID | Type::CID => Value::Int(protocol::oid_from_sql(raw)? as i64),
+        Type::XID8 => Value::Numeric(protocol::lsn_from_sql(raw)?.to_string()),
+        Type::FLOAT4 => Value::Float(protocol::float4_from_sql(raw)?.to_string().parse()?),
+        Type::FLOAT8 => Value::Float(protocol::float8_from_sql(raw)?),
+        Type::NUMERIC => Value::Numeric(decode_numeric(raw)?),
+        Type::MONEY => Value::Numeric(format_money(protocol::int8_from_sql(raw)?)),
+        Type::CHAR => Value::Text(((protocol::char_from_sql(raw)? as u8) as char).to_string()),
+        Type::TEXT
+        | Type::VARCHAR
+        | Type::BPCHAR
+        | Type::NAME
+        | Type::UNKNOWN
+        | Type::XML
+        | Type::REFCURSOR
+        | Type::PG_NODE_TREE => Value::Text(protocol::text_from_sql(raw)?.to_string()),
+        Type::JSON => Value::Json(protocol::text_from_sql(raw)?.to_string()),
+        Type::JSONB | Type::JSONPATH => match raw.split_first() {
+            Some((1, json)) if *ty == Type::JSONB => {
+                Value::Json(protocol::text_from_sql(json)?.to_string())
+            }
+            Some((1, path)) => Value::Text(protocol::text_from_sql(path)?.to_string()),
+            _ => return Err("unsupported jsonb version".into()),
+        },
+        Type::BYTEA => Value::Bytes(protocol::bytea_from_sql(raw).to_vec()),
+        Type::UUID => Value::Text(format_uuid(protocol::uuid_from_sql(raw)?)),
+        Type::DATE => Value::Text(format_date(protocol::date_from_sql(raw)?)),
+        Type::TIME => Value::Text(format_time(protocol::time_from_sql(raw)?)?),
+        Type::TIMETZ => Value::Text(decode_timetz(raw)?),
+        Type::TIMESTAMP => Value::Text(format_timestamp(protocol::timestamp_from_sql(raw)?, "")),
+        // The server sends the instant in UTC and it's shown that way. The
+        // session's TimeZone would need its rules from a time zone database
+        // to be applied.
+        Type::TIMESTAMPTZ => {
+            Value::Text(format_timestamp(protocol::timestamp_from_sql(raw)?, "+00"))
+        }
+        Type::INTERVAL => Value::Text(decode_interval(raw)?),
+        Type::INET | Type::CIDR => {
+            let inet = protocol::inet_from_sql(raw)?;
+            let full_mask = if inet.addr().is_ipv4() { 32 } else { 128 };
+            if *ty == Type::INET && inet.netmask() == full_mask {
+                Value::Text(inet.addr().to_string())
+            } else {
+                Value::Text(format!("{}/{}", inet.addr(), inet.netmask()))
+            }
+        }
+        Type::MACADDR => Value::Text(format_hex_bytes(&protocol::macaddr_from_sql(raw)?, ":")),
+        Type::MACADDR8 if raw.len() == 8 => Value::Text(format_hex_bytes(raw, ":")),
+        Type::BIT | Type::VARBIT => {
+            let varbit = protocol::varbit_from_sql(raw)?;
+            let bits = (0..varbit.len())
+                .map(|bit| {
+                    let byte = varbit.bytes()[bit / 8];
+                    if byte & (0x80 >> (bit % 8)) != 0 {
+                        '1'
+                    } else {
+                        '0'
+                    }
+                })
+                .collect();
+            Value::Text(bits)
+        }
+        Type::PG_LSN => {
+            let lsn = protocol::lsn_from_sql(raw)?;
+            Value::Text(format!("{:X}/{:X}", lsn >> 32, lsn & 0xFFFF_FFFF))
+        }
+        Type::TID if raw.len() == 6 => {
+            let block = u32::from_be_bytes([raw[0], raw[1], raw[2], raw[3]]);
+            let offset = u16::from_be_bytes([raw[4], raw[5]]);
+            Value::Text(format!("({},{})", block, offset))
+        }
+        Type::POINT => {
+            let point = protocol::point_from_sql(raw)?;
+            Value::Text(format_point(point.x(), point.y()))
+        }
+        Type::BOX => {
+            let pg_box = protocol::box_from_sql(raw)?;
+            let (upper, lower) = (pg_box.upper_right(), pg_box.lower_left());
+            Value::Text(format!(
+                "{},{}",
+                format_point(upper.x(), upper.y()),
+                format_point(lower.x(), lower.y())
+            ))
+        }
+        Type::LSEG => {
+            let values = read_floats(raw, 4)?;
+            Value::Text(format!(
+                "[{},{}]",
+                format_point(values[0], values[1]),
+                format_point(values[2], values[3])
+            ))
+        }
+        Type::LINE => {
+            let values = read_floats(raw, 3)?;
+            Value::Text(format!(
+                "{{{},{},{}}}",
+                format_float(values[0]),
+                format_float(values[1]),
+                format_float(values[2])
+            ))
+        }
+        Type::CIRCLE => {
+            let values = read_floats(raw, 3)?;
+            Value::Text(format!(
+                "<{},{}>",
+                format_point(values[0], values[1]),
+                format_float(values[2])
+            ))
+        }
+        Type::PATH => {
+            let path = protocol::path_from_sql(raw)?;
+            let points: Vec<String> = path
+                .points()
+                .map(|point| Ok(format_point(point.x(), point.y())))
+                .collect()?;
+            let (open, close) = if path.closed() {
+                ("(", ")")
+            } else {
+                ("[", "]")
+            };
+            Value::Text(format!("{}{}{}", open, points.join(","), close))
+        }
+        Type::POLYGON if raw.len() >= 4 => {
+            let count = i32::from_be_bytes([raw[0], raw[1], raw[2], raw[3]]).max(0) as usize;
+            let values = read_floats(&raw[4..], count * 2)?;
+            let points: Vec<String> = values
+                .chunks(2)
+                .map(|point| format_point(point[0], point[1]))
+                .collect();
+            Value::Text(format!("({})", points.join(",")))
+        }
+        Type::RECORD => decode_composite(&[], raw)?,
+        _ => return Err(format!("no binary decoder for type {}", ty.name()).into()),
+    };
+
+    Ok(value)
+}
+
+fn decode_array(member: &Type, raw: &[u8]) -> DecodeResult<Value> {
+    let array = protocol::array_from_sql(raw)?;
+    let dimensions: Vec<usize> = array
+        .dimensions()
+        .map(|dimension| Ok(dimension.len.max(0) as usize))
+        .collect()?;
+    let values: Vec<Value> = array
+        .values()
+        .map(|value| {
+            Ok(match value {
+                Some(raw) => decode(member, raw),
+                None => Value::Null,
+            })
+        })
+        .collect()?;
+
+    if dimensions.is_empty() {
+        return Ok(Value::Array(Vec::new()));
+    }
+
+    Ok(nest_array(&dimensions, &mut values.into_iter()))
+}
+
+// Values of multidimensional arrays come in row-major order, rebuild the
+// nesting from the dimension lengths.
+fn nest_array(dimensions: &[usize], values: &mut impl Iterator<Item = Value>) -> Value {
+    match dimensions.split_first() {
+        Some((length, [])) => Value::Array(values.take(*length).collect()),
+        Some((length, inner)) => {
+            Value::Array((0..*length).map(|_| nest_array(inner, values)).collect())
+        }
+        None => Value::Array(Vec::new()),
+    }
+}
+
+fn decode_range(subtype: &Type, raw: &[u8]) -> DecodeResult<String> {
+    let (lower, upper) = match protocol::range_from_sql(raw)? {
+        Range::Empty => return Ok(String::from("empty")),
+        Range::Nonempty(lower, upper) => (lower, upper),
+    };
+
+    let bound_text = |value: Option<&[u8]>| match value {
+        Some(raw) => decode(subtype, raw).to_string(),
+        None => String::new(),
+    };
+
+    let (open, lower_text) = match lower {
+        RangeBound::Inclusive(value) => ('[', bound_text(value)),
+        RangeBound::Exclusive(value) => ('(', bound_text(value)),
+        RangeBound::Unbounded => ('(', String::new()),
+    };
+
+    let (close, upper_text) = match upper {
+        RangeBound::Inclusive(value) => (']', bound_text(value)),
+        RangeBound::Exclusive(value) => (')', bound_text(value)),
+        RangeBound::Unbounded => (')', String::new()),
+    };
+
+    Ok(format!("{}{},{}{}", open, lower_text, upper_text, close))
+}
+
+fn decode_multirange(subtype: &Type, mut raw: &[u8]) -> DecodeResult<Value> {
+    let count = read_i32(&mut raw)?;
+    let mut ranges = Vec::new();
+
+    for _ in 0..count {
+        let length = read_i32(&mut raw)?.max(0) as usize;
+        if raw.len() < length {
+            return Err("invalid multirange length".into());
+        }
+        let (range, rest) = raw.split_at(length);
+        ranges.push(decode_range(subtype, range)?);
+        raw = rest;
+    }
+
+    Ok(Value::Text(format!("{{{}}}", ranges.join(","))))
+}
+
+// Composite values carry the oid of every field. Named composite types give
+// us the field names and full type information, anonymous records only the
+// oids.
+fn decode_composite(fields: &[(String, Option<Type>)], mut raw: &[u8]) -> DecodeResult<Value> {
+    let count = read_i32(&mut raw)?.max(0) as usize;
+    let mut values = Vec::with_capacity(count);
+
+    for index in 0..count {
+        let oid = read_i32(&mut raw)? as u32;
+        let length = read_i32(&mut raw)?;
+
+        let (name, field_type) = match fields.get(index) {
+            Some((name, field_type)) => (name.clone(), field_type.clone()),
+            None => (format!("f{}", index + 1), Type::from_oid(oid)),
+        };
+
+        let value = if length < 0 {
+            Value::Null
+        } else {
+            let length = length as usize;
+            if raw.len() < length {
+                return Err("invalid composite field length".into());
+            }
+            let (field, rest) = raw.split_at(length);
+            raw = rest;
+            match field_type {
+                Some(field_type) => decode(&field_type, field),
+                None => decode(&Type::UNKNOWN, field),
+            }
+        };
+
+        values.push((name, value));
+    }
+
+    Ok(Value::Composite(values))
+}
+
+fn decode_numeric(mut raw: &[u8]) -> DecodeResult<String> {
+    let digit_count = read_i16(&mut raw)?.max(0) as usize;
+    let weight = read_i16(&mut raw)? as i32;
+    let sign = read_i16(&mut raw)? as u16;
+    let scale = read_i16(&mut raw)?.max(0) as usize;
+
+    match sign {
+        NUMERIC_NAN => return Ok(String::from("NaN")),
+        NUMERIC_PINF => return Ok(String::from("Infinity")),
+        NUMERIC_NINF => return Ok(String::from("-Infinity")),
+        _ => {}
+    }
+
+    let mut digits = Vec::with_capacity(digit_count);
+    for _ in 0..digit_count {
+        digits.push(read_i16(&mut raw)?);
+    }
+
+    let digit_at = |index: i32| -> i16 {
+        if index >= 0 && (index as usize) < digits.len() {
+            digits[index as usize]
+        } else {
+            0
+        }
+    };
+
+    let mut text = String::new();
+    if sign == NUMERIC_NEG {
+        text.push('-');
+    }
+
+    if weight < 0 {
+        text.push('0');
+    } else {
+        for index in 0..=weight {
+            if index == 0 {
+                write!(text, "{}", digit_at(index))?;
+            } else {
+                write!(text, "{:04}", digit_at(index))?;
+            }
+        }
+    }
+
+    if scale > 0 {
+        let mut fraction = String::new();
+        let mut index = weight + 1;
+        while fraction.len() < scale {
+            write!(fraction, "{:04}", digit_at(index))?;
+            index += 1;
+        }
+        fraction.truncate(scale);
+        text.push('.');
+        text.push_str(&fraction);
+    }
+
+    Ok(text)
+}
+
+fn format_money(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    let cents = cents.unsigned_abs();
+    format!("{}{}.{:02}", sign, cents / 100, cents % 100)
+}
+
+fn format_uuid(bytes: [u8; 16]) -> String {
+    let hex = format_hex_bytes(&bytes, "");
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+fn format_hex_bytes(bytes: &[u8], separator: &str) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<String>>()
+        .join(separator)
+}
+
+fn format_point(x: f64, y: f64) -> String {
+    format!("({},{})", format_float(x), format_float(y))
+}
+
+fn postgres_epoch() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2000, 1, 1)
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .unwrap_or_default()
+}
+
+// Postgres counts the years before 1 AD from 1 without a year 0, and marks
+// them with a BC after the rest of the value.
+fn format_ymd(date: NaiveDate) -> (String, &'static str) {
+    let (year, era) = match date.year() {
+        year if year <= 0 => (1 - year, " BC"),
+        year => (year, ""),
+    };
+    (
+        format!("{:04}-{:02}-{:02}", year, date.month(), date.day()),
+        era,
+    )
+}
+
+fn format_date(days: i32) -> String {
+    match days {
+        i32::MAX => String::from("infinity"),
+        i32::MIN => String::from("-infinity"),
+        days => match postgres_epoch()
+            .date()
+            .checked_add_signed(Duration::days(days as i64))
+        {
+            Some(date) => {
+                let (date, era) = format_ymd(date);
+                format!("{}{}", date, era)
+            }
+            None => days.to_string(),
+        },
+    }
+}
+
+fn format_time(microseconds: i64) -> DecodeResult<String> {
+    let seconds = (microseconds / 1_000_000) as u32;
+    let nanoseconds = (microseconds % 1_000_000) as u32 * 1000;
+
+    // 24:00:00 is a valid postgres time but not a valid NaiveTime
+    if seconds == 86400 {
+        return Ok(String::from("24:00:00"));
+    }
+
+    match NaiveTime::from_num_seconds_from_midnight_opt(seconds, nanoseconds) {
+        Some(time) => Ok(format!(
+            "{}{}",
+            time.format("%H:%M:%S"),
+            format_fraction(microseconds % 1_000_000)
+        )),
+        None => Err("invalid time".into()),
+    }
+}
+
+fn decode_timetz(mut raw: &[u8]) -> DecodeResult<String> {
+    let microseconds = protocol::time_from_sql(&raw[..raw.len().min(8)])?;
+    raw = &raw[raw.len().min(8)..];
+    // The zone is stored as seconds west of UTC
+    let offset = -read_i32(&mut raw)?;
+
+    let sign = if offset < 0 { '-' } else { '+' };
+    let offset = offset.abs();
+    let mut zone = format!("{}{:02}", sign, offset / 3600);
+    if offset % 3600 != 0 {
+        write!(zone, ":{:02}", offset % 3600 / 60)?;
+    }
+
+    Ok(format!("{}{}", format_time(microseconds)?, zone))
+}
+
+fn format_timestamp(microseconds: i64, zone: &str) -> String {
+    match microseconds {
+        i64::MAX => String::from("infinity"),
+        i64::MIN => String::from("-infinity"),
+        microseconds => {
+            match postgres_epoch().checked_add_signed(Duration::microseconds(microseconds)) {
+                Some(timestamp) => {
+                    let (date, era) = format_ymd(timestamp.date());
+                    format!(
+                        "{} {}{}{}{}",
+                        date,
+                        timestamp.format("%H:%M:%S"),
+                        format_fraction(microseconds.rem_euclid(1_000_000)),
+                        zone,
+                        era
+                    )
+                }
+                None => microseconds.to_string(),
+            }
+        }
+    }
+}
+
+// Postgres prints fractional seconds without trailing zeros
+fn format_fraction(microseconds: i64) -> String {
+    if microseconds == 0 {
+        return String::new();
+    }
+    let fraction = format!("{:06}", microseconds);
+    format!(".{}", fraction.trim_end_matches('0'))
+}
+
+fn decode_interval(mut raw: &[u8]) -> DecodeResult<String> {
+    let microseconds = read_i64(&mut raw)?;
+    let days = read_i32(&mut raw)?;
+    let months = read_i32(&mut raw)?;
+
+    let mut parts: Vec<String> = Vec::new();
+    let (years, months) = (months / 12, months % 12);
+
+    // Like postgres, a part following a negative one gets an explicit sign
+    // and only 1 is singular
+    let mut follows_negative = false;
+    let mut push_unit = |value: i32, unit: &str| {
+        if value != 0 {
+            let sign = if follows_negative && value > 0 {
+                "+"
+            } else {
+                ""
+            };
+            let plural = if value == 1 { "" } else { "s" };
+            parts.push(format!("{}{} {}{}", sign, value, unit, plural));
+            follows_negative = value < 0;
+        }
+    };
+
+    push_unit(years, "year");
+    push_unit(months, "mon");
+    push_unit(days, "day");
+
+    if microseconds != 0 || parts.is_empty() {
+        let sign = if microseconds < 0 {
+            "-"
+        } else if follows_negative {
+            "+"
+        } else {
+            ""
+        };
+        let total = microseconds.unsigned_abs();
+        let hours = total / 3_600_000_000;
+        let minutes = total / 60_000_000 % 60;
+        let seconds = total / 1_000_000 % 60;
+        let fraction = total % 1_000_000;
+
+        parts.push(format!(
+            "{}{:02}:{:02}:{:02}{}",
+            sign,
+            hours,
+            minutes,
+            seconds,
+            format_fraction(fraction as i64)
+        ));
+    }
+
+    Ok(parts.join(" "))
+}
+
+fn read_floats(mut raw: &[u8], count: usize) -> DecodeResult<Vec<f64>> {
+    let mut values = Vec::with_capacity(count);
+    for _ in 0..count {
+        let (bytes, rest) = split_fixed::<8>(raw)?;
+        values.push(f64::from_be_bytes(bytes));
+        raw = rest;
+    }
+    Ok(values)
+}
+
+fn read_i16(raw: &mut &[u8]) -> DecodeResult<i16> {
+    let (bytes, rest) = split_fixed::<2>(raw)?;
+    *raw = rest;
+    Ok(i16::from_be_bytes(bytes))
+}
+
+fn read_i32(raw: &mut &[u8]) -> DecodeResult<i32> {
+    let (bytes, rest) = split_fixed::<4>(raw)?;
+    *raw = rest;
+    Ok(i32::from_be_bytes(bytes))
+}
+
+fn read_i64(raw: &mut &[u8]) -> DecodeResult<i64> {
+    let (bytes, rest) = split_fixed::<8>(raw)?;
+    *raw = rest;
+    Ok(i64::from_be_bytes(bytes))
+}
+
+fn split_fixed<const N: usize>(raw: &[u8]) -> DecodeResult<([u8; N], &[u8])> {
+    if raw.len() < N {
+        return Err("unexpected end of value".into());
+    }
+    let (bytes, rest) = raw.split_at(N);
+    let mut fixed = [0u8; N];
+    fixed.copy_from_slice(bytes);
+    Ok((fixed, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postgres::filter::{check_value, equality_source, Filter};
+
+    fn numeric(weight: i16, sign: u16, scale: i16, digits: &[i16]) -> Vec<u8> {
+        let mut raw = Vec::new();
+        raw.extend((digits.len() as i16).to_be_bytes());
+        raw.extend(weight.to_be_bytes());
+        raw.extend(sign.to_be_bytes());
+        raw.extend(scale.to_be_bytes());
+        for digit in digits {
+            raw.extend(digit.to_be_bytes());
+        }
+        raw
+    }
+
+    fn interval(microseconds: i64, days: i32, months: i32) -> Vec<u8> {
+        let mut raw = Vec::new();
+        raw.extend(microseconds.to_be_bytes());
+        raw.extend(days.to_be_bytes());
+        raw.extend(months.to_be_bytes());
+        raw
+    }
+
+    // Array of int4 or text elements with the given dimension lengths, None
+    // is a NULL element.
+    fn array(member: &Type, dimensions: &[i32], elements: &[Option<&[u8]>]) -> Vec<u8> {
+        let mut raw = Vec::new();
+        raw.extend((dimensions.len() as i32).to_be_bytes());
+        raw.extend((elements.iter().any(Option::is_none) as i32).to_be_bytes());
+        raw.extend(member.oid().to_be_bytes());
+        for length in dimensions {
+            raw.extend(length.to_be_bytes());
+            raw.extend(1i32.to_be_bytes());
+        }
+        for element in elements {
+            match element {
+                Some(bytes) => {
+                    raw.extend((bytes.len() as i32).to_be_bytes());
+                    raw.extend(*bytes);
+                }
+                None => raw.extend((-1i32).to_be_bytes()),
+            }
+        }
+        raw
+    }
+
+    fn composite(fields: &[(Type, Option<&[u8]>)]) -> Vec<u8> {
+        let mut raw = Vec::new();
+        raw.extend((fields.len() as i32).to_be_bytes());
+        for (field_type, value) in fields {
+            raw.extend(field_type.oid().to_be_bytes());
+            match value {
+                Some(bytes) => {
+                    raw.extend((bytes.len() as i32).to_be_bytes());
+                    raw.extend(*bytes);
+                }
+                None => raw.extend((-1i32).to_be_bytes()),
+            }
+        }
+        raw
+    }
+
+    fn decoded_text(ty: &Type, raw: &[u8]) -> String {
+        decode(ty, raw).to_string()
+    }
+
+    #[test]
+    fn numeric_digits_and_scale() {
+        let cases: [(i16, u16, i16, &[i16], &str); 8] = [
+            (1, 0, 3, &[1, 2345, 6780], "12345.678"),
+            (0, 0, 2, &[1, 5000], "1.50"),
+            (1, 0, 0, &[100], "1000000"),
+            (0, 0, 0, &[], "0"),
+            (0, 0, 3, &[], "0.000"),
+            (-1, NUMERIC_NEG, 4, &[12], "-0.0012"),
+            (-2, 0, 6, &[100], "0.000001"),
+            (-1, NUMERIC_NEG, 2, &[1200], "-0.12"),
+        ];
+
+        for (weight, sign, scale, digits, text) in cases {
+            assert_eq!(
+                decode_numeric(&numeric(weight, sign, scale, digits)).unwrap(),
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn numeric_special_values() {
+        assert_eq!(
+            decode_numeric(&numeric(0, NUMERIC_NAN, 0, &[])).unwrap(),
+            "NaN"
+        );
+        assert_eq!(
+            decode_numeric(&numeric(0, NUMERIC_PINF, 0, &[])).unwrap(),
+            "Infinity"
+        );
+        assert_eq!(
+            decode_numeric(&numeric(0, NUMERIC_NINF, 0, &[])).unwrap(),
+            "-Infinity"
+        );
+        assert_eq!(
+            decode(&Type::NUMERIC, &numeric(0, NUMERIC_NAN, 0, &[])),
+            Value::Numeric(String::from("NaN"))
+        );
+    }
+
+    #[test]
+    fn truncated_numeric_is_an_error() {
+        let mut raw = numeric(1, 0, 0, &[1, 2345]);
+        raw.truncate(raw.len() - 1);
+        assert!(decode_numeric(&raw).is_err());
+    }
+
+    #[test]
+    fn intervals_print_like_postgres() {
+        let hour = 3_600_000_000;
+        let cases = [
+            (0, 0, 0, "00:00:00"),
+            (
+                4 * hour + 5 * 60_000_000 + 6_500_000,
+                3,
+                14,
+                "1 year 2 mons 3 days 04:05:06.5",
+            ),
+            (0, 1, 13, "1 year 1 mon 1 day"),
+            (-hour, -1, -13, "-1 years -1 mons -1 days -01:00:00"),
+            (0, 0, -14, "-1 years -2 mons"),
+            (2 * hour, -1, 0, "-1 days +02:00:00"),
+            (0, 1, -1, "-1 mons +1 day"),
+            (-1_000_000, 1, 0, "1 day -00:00:01"),
+            (-500_000, 0, 0, "-00:00:00.5"),
+            (100 * hour, 0, 0, "100:00:00"),
+        ];
+
+        for (microseconds, days, months, text) in cases {
+            assert_eq!(
+                decode_interval(&interval(microseconds, days, months)).unwrap(),
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn timestamps_and_dates() {
+        let timestamp = |microseconds: i64| microseconds.to_be_bytes();
+
+        assert_eq!(
+            decoded_text(&Type::TIMESTAMP, &timestamp(0)),
+            "2000-01-01 00:00:00"
+        );
+        assert_eq!(
+            decoded_text(&Type::TIMESTAMP, &timestamp(-1)),
+            "1999-12-31 23:59:59.999999"
+        );
+        assert_eq!(
+            decoded_text(&Type::TIMESTAMPTZ, &timestamp(86_400_000_000 + 120_000)),
+            "2000-01-02 00:00:00.12+00"
+        );
+        assert_eq!(
+            decoded_text(&Type::TIMESTAMP, &timestamp(i64::MAX)),
+            "infinity"
+        );
+        assert_eq!(
+            decoded_text(&Type::TIMESTAMPTZ, &timestamp(i64::MIN)),
+            "-infinity"
+        );
+
+        assert_eq!(
+            decoded_text(&Type::DATE, &(-1i32).to_be_bytes()),
+            "1999-12-31"
+        );
+        assert_eq!(
+            decoded_text(&Type::DATE, &i32::MAX.to_be_bytes()),
+            "infinity"
+        );
+        assert_eq!(
+            decoded_text(&Type::DATE, &i32::MIN.to_be_bytes()),
+            "-infinity"
+        );
+    }
+
+    #[test]
+    fn dates_before_1_ad_are_bc() {
+        let days = |year: i32, month: u32, day: u32| {
+            let date = NaiveDate::from_ymd_opt(year, month, day).unwrap();
+            (date - postgres_epoch().date()).num_days()
+        };
+
+        assert_eq!(
+            decoded_text(&Type::DATE, &(days(-44, 3, 15) as i32).to_be_bytes()),
+            "0045-03-15 BC"
+        );
+        assert_eq!(
+            decoded_text(&Type::DATE, &(days(0, 12, 31) as i32).to_be_bytes()),
+            "0001-12-31 BC"
+        );
+        assert_eq!(
+            decoded_text(&Type::DATE, &(days(1, 1, 1) as i32).to_be_bytes()),
+            "0001-01-01"
+        );
+        assert_eq!(
+            decoded_text(
+                &Type::TIMESTAMPTZ,
+                &(days(-44, 3, 15) * 86_400_000_000 + 36_000_000_000).to_be_bytes()
+            ),
+            "0045-03-15 10:00:00+00 BC"
+        );
+        assert_eq!(
+            decoded_text(
+                &Type::TIMESTAMP,
+                &(days(12345, 6, 7) * 86_400_000_000).to_be_bytes()
+            ),
+            "12345-06-07 00:00:00"
+        );
+    }
+
+    #[test]
+    fn bc_dates_are_read_back() {
+        for (ty, value) in [
+            (Type::DATE, "0045-03-15 BC"),
+            (Type::TIMESTAMP, "0045-03-15 10:00:00 BC"),
+            (Type::TIMESTAMPTZ, "0045-03-15 10:00:00+00 BC"),
+        ] {
+            assert_eq!(check_value(&ty, value), Ok(()), "{}", value);
+        }
+
+        let columns = vec![Column::new(String::from("born"), Type::DATE)];
+        let source = equality_source(&[(String::from("born"), String::from("0045-03-15 BC"))]);
+        assert!(Filter::parse(&source, &columns).is_ok());
+    }
+
+    #[test]
+    fn times() {
+        assert_eq!(
+            decoded_text(&Type::TIME, &86_400_000_000i64.to_be_bytes()),
+            "24:00:00"
+        );
+        assert_eq!(
+            decoded_text(&Type::TIME, &(13 * 3_600_000_000i64 + 1).to_be_bytes()),
+            "13:00:00.000001"
+        );
+
+        let mut timetz = (3_600_000_000i64).to_be_bytes().to_vec();
+        timetz.extend((-19_800i32).to_be_bytes());
+        assert_eq!(decoded_text(&Type::TIMETZ, &timetz), "01:00:00+05:30");
+    }
+
+    #[test]
+    fn arrays_with_nulls() {
+        let raw = array(
+            &Type::INT4,
+            &[3],
+            &[Some(&1i32.to_be_bytes()), None, Some(&3i32.to_be_bytes())],
+        );
+        let value = decode(&Type::INT4_ARRAY, &raw);
+
+        assert_eq!(
+            value,
+            Value::Array(vec![Value::Int(1), Value::Null, Value::Int(3)])
+        );
+        assert_eq!(value.to_string(), "{1,NULL,3}");
+    }
+
+    #[test]
+    fn nested_arrays() {
+        let elements: Vec<[u8; 4]> = (1..=6).map(|n: i32| n.to_be_bytes()).collect();
+        let elements: Vec<Option<&[u8]>> = elements.iter().map(|e| Some(&e[..])).collect();
+        let value = decode(&Type::INT4_ARRAY, &array(&Type::INT4, &[2, 3], &elements));
+
+        assert_eq!(
+            value,
+            Value::Array(vec![
+                Value::Array(vec![Value::Int(1), Value::Int(2), Value::Int(3)]),
+                Value::Array(vec![Value::Int(4), Value::Int(5), Value::Int(6)]),
+            ])
+        );
+        assert_eq!(value.to_string(), "{{1,2,3},{4,5,6}}");
+    }
+
+    #[test]
+    fn empty_array() {
+        let value = decode(&Type::INT4_ARRAY, &array(&Type::INT4, &[], &[]));
+        assert_eq!(value, Value::Array(Vec::new()));
+        assert_eq!(value.to_string(), "{}");
+    }
+
+    #[test]
+    fn array_elements_are_quoted_when_needed() {
+        let raw = array(
+            &Type::TEXT,
+            &[5],
+            &[
+                Some(b"plain"),
+                Some(b"a b"),
+                Some(b"NULL"),
+                Some(b""),
+                Some(b"q\"\\"),
+            ],
+        );
+        assert_eq!(
+            decoded_text(&Type::TEXT_ARRAY, &raw),
+            r#"{plain,"a b","NULL","","q\"\\"}"#
+        );
+    }
+
+    #[test]
+    fn anonymous_records() {
+        let raw = composite(&[
+            (Type::INT4, Some(&5i32.to_be_bytes())),
+            (Type::TEXT, None),
+            (Type::TEXT, Some(b"x,y")),
+        ]);
+        let value = decode(&Type::RECORD, &raw);
+
+        assert_eq!(
+            value,
+            Value::Composite(vec![
+                (String::from("f1"), Value::Int(5)),
+                (String::from("f2"), Value::Null),
+                (String::from("f3"), Value::Text(String::from("x,y"))),
+            ])
+        );
+        assert_eq!(value.to_string(), r#"(5,,"x,y")"#);
+    }
+
+    #[test]
+    fn named_composites_use_their_field_names() {
+        let fields = vec![
+            (String::from("amount"), Some(Type::NUMERIC)),
+            (String::from("tags"), Some(Type::TEXT_ARRAY)),
+        ];
+        let tags = array(&Type::TEXT, &[1], &[Some(b"a")]);
+        let raw = composite(&[
+            (Type::NUMERIC, Some(&numeric(0, 0, 1, &[2, 5000]))),
+            (Type::TEXT_ARRAY, Some(&tags)),
+        ]);
+
+        assert_eq!(
+            decode_composite(&fields, &raw).unwrap(),
+            Value::Composite(vec![
+                (String::from("amount"), Value::Numeric(String::from("2.5"))),
+                (
+                    String::from("tags"),
+                    Value::Array(vec![Value::Text(String::from("a"))])
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn truncated_composite_is_an_error() {
+        let mut raw = composite(&[(Type::TEXT, Some(b"abc"))]);
+        raw.truncate(raw.len() - 1);
+        assert!(decode_composite(&[], &raw).is_err());
+    }
+
+    #[test]
+    fn undecodable_values_fall_back_to_text_or_bytes() {
+        assert_eq!(decode(&Type::INT4, b"12"), Value::Text(String::from("12")));
+        assert_eq!(
+            decode(&Type::INT4, &[0xff, 0xfe]),
+            Value::Bytes(vec![0xff, 0xfe])
+        );
+    }
+
+    #[test]
+    fn special_floats() {
+        assert_eq!(decoded_text(&Type::FLOAT8, &f64::NAN.to_be_bytes()), "NaN");
+        assert_eq!(
+            decoded_text(&Type::FLOAT8, &f64::NEG_INFINITY.to_be_bytes()),
+            "-Infinity"
+        );
+        assert_eq!(decoded_text(&Type::FLOAT4, &0.1f32.to_be_bytes()), "0.1");
+    }
+
+    #[test]
+    fn parse_text_by_type() {
+        assert_eq!(parse_text(&Type::BOOL, "t"), Value::Bool(true));
+        assert_eq!(parse_text(&Type::BOOL, "f"), Value::Bool(false));
+        assert_eq!(parse_text(&Type::INT8, "-42"), Value::Int(-42));
+        assert_eq!(
+            parse_text(&Type::INT4, "nope"),
+            Value::Text(String::from("nope"))
+        );
+        assert_eq!(parse_text(&Type::FLOAT8, "1.5"), Value::Float(1.5));
+        assert_eq!(
+            parse_text(&Type::NUMERIC, "1.50"),
+            Value::Numeric(String::from("1.50"))
+        );
+        assert_eq!(
+            parse_text(&Type::JSONB, r#"{"a": 1}"#),
+            Value::Json(String::from(r#"{"a": 1}"#))
+        );
+        assert_eq!(
+            parse_text(&Type::BYTEA, "\\x00ff"),
+            Value::Bytes(vec![0x00, 0xff])
+        );
+        assert_eq!(
+            parse_text(&Type::BYTEA, "\\x0"),
+            Value::Text(String::from("\\x0"))
+        );
+        assert_eq!(
+            parse_text(&Type::DATE, "2024-01-31"),
+            Value::Text(String::from("2024-01-31"))
+        );
+    }
+
+    #[test]
+    fn parse_text_of_a_domain_uses_its_base_type() {
+        let domain = Type::new(
+            String::from("positive"),
+            FIRST_NORMAL_OBJECT_ID + 1,
+            Kind::Domain(Type::INT4),
+            String::from("public"),
+        );
+        assert_eq!(parse_text(&domain, "7"), Value::Int(7));
+    }
+}
@@ -0,0 +1,1186 @@
+use tokio_postgres::{Client, Error, Row};
+
+use crate::postgres::{catalog::ObjectKind, query::TableName};
+
+const INDENT: &str = "    ";
+
+// Identity, generation expression or default of a column as a clause of its
+// definition, for the attribute `a` and its default `d`.
+const COLUMN_DEFAULT_SQL: &str = "CASE
+        WHEN {a}.attgenerated = 's'
+            THEN 'GENERATED ALWAYS AS (' || pg_get_expr({d}.adbin, {d}.adrelid) || ') STORED'
+        WHEN {a}.attidentity = 'a' THEN 'GENERATED ALWAYS AS IDENTITY'
+        WHEN {a}.attidentity = 'd' THEN 'GENERATED BY DEFAULT AS IDENTITY'
+        WHEN {d}.adbin IS NOT NULL THEN 'DEFAULT ' || pg_get_expr({d}.adbin, {d}.adrelid)
+    END";
+
+// A relation as read from the catalog, names quoted and the comment a
+// literal.
+#[derive(Debug, Clone, Default)]
+struct Relation {
+    kind: String,
+    name: String,
+    is_unlogged: bool,
+    // The parent and the bound of a partition
+    partition_of: Option<(String, String)>,
+    partition_key: Option<String>,
+    // Parents of a table inheriting from others
+    parents: Option<String>,
+    view_definition: Option<String>,
+    is_populated: bool,
+    options: Vec<String>,
+    server: Option<String>,
+    owner: Option<String>,
+    comment: Option<String>,
+}
+
+impl Relation {
+    fn from_row(row: &Row) -> Self {
+        let is_partition: bool = row.get(3);
+        let parent: Option<String> = row.get(4);
+        let bound: Option<String> = row.get(5);
+
+        Self {
+            kind: row.get(0),
+            name: row.get(1),
+            is_unlogged: row.get(2),
+            partition_of: is_partition
+                .then(|| (parent.unwrap_or_default(), bound.unwrap_or_default())),
+            partition_key: row.get(6),
+            parents: row.get(7),
+            view_definition: row.get(8),
+            is_populated: row.get(9),
+            options: row.get::<_, Option<Vec<String>>>(10).unwrap_or_default(),
+            server: row.get(11),
+            owner: row.get(12),
+            comment: row.get(13),
+        }
+    }
+
+    fn object_type(&self) -> &'static str {
+        match self.kind.as_str() {
+            "v" => "VIEW",
+            "m" => "MATERIALIZED VIEW",
+            "f" => "FOREIGN TABLE",
+            _ => "TABLE",
+        }
+    }
+}
+
+// A column of a relation, with what the parent has for it when the relation
+// is a partition.
+#[derive(Debug, Clone, Default)]
+struct RelationColumn {
+    name: String,
+    type_name: String,
+    collation: Option<String>,
+    default: Option<String>,
+    not_null: bool,
+    is_local: bool,
+    parent_default: Option<String>,
+    parent_not_null: bool,
+    comment: Option<String>,
+}
+
+impl RelationColumn {
+    fn from_row(row: &Row) -> Self {
+        Self {
+            name: row.get(0),
+            type_name: row.get(1),
+            collation: row.get(2),
+            default: row.get(3),
+            not_null: row.get(4),
+            is_local: row.get(5),
+            parent_default: row.get(6),
+            parent_not_null: row.get(7),
+            comment: row.get(8),
+        }
+    }
+}
+
+// ALTER ... OWNER TO and COMMENT ON statements of an object, `owner` being
+// quoted and `comment` already a literal.
+fn owner_and_comment(
+    object_type: &str,
+    name: &str,
+    owner: Option<String>,
+    comment: Option<String>,
+) -> Vec<String> {
+    let mut statements = Vec::new();
+    if let Some(owner) = owner {
+        statements.push(format!(
+            "ALTER {} {} OWNER TO {};",
+            object_type, name, owner
+        ));
+    }
+    if let Some(comment) = comment {
+        statements.push(format!(
+            "COMMENT ON {} {} IS {};",
+            object_type, name, comment
+        ));
+    }
+    statements
+}
+
+fn column_definition(column: &RelationColumn) -> String {
+    let mut definition = format!("{}{} {}", INDENT, column.name, column.type_name);
+    if let Some(collation) = &column.collation {
+        definition.push_str(&format!(" COLLATE {}", collation));
+    }
+    if let Some(default) = &column.default {
+        definition.push_str(&format!(" {}", default));
+    }
+    if column.not_null {
+        definition.push_str(" NOT NULL");
+    }
+    definition
+}
+
+// The default and NOT NULL a partition sets on a column on top of what it
+// gets from its parent.
+fn partition_column_options(column: &RelationColumn) -> Option<String> {
+    let mut options = Vec::new();
+    if let Some(default) = column
+        .default
+        .as_ref()
+        .filter(|default| column.parent_default.as_ref() != Some(*default))
+    {
+        options.push(default.clone());
+    }
+    if column.not_null && !column.parent_not_null {
+        options.push(String::from("NOT NULL"));
+    }
+
+    (!options.is_empty()).then(|| {
+        format!(
+            "{}{} WITH OPTIONS {}",
+            INDENT,
+            column.name,
+            options.join(" ")
+        )
+    })
+}
+
+// Storage parameters go into WITH (...), the check option of a view has a
+// clause of its own after the query.
+fn split_check_option(options: &[String]) -> (Vec<String>, Option<String>) {
+    let mut check_option = None;
+    let options = options
+        .iter()
+        .filter(|option| match option.strip_prefix("check_option=") {
+            Some(value) => {
+                check_option = Some(value.to_uppercase());
+                false
+            }
+            None => true,
+        })
+        .cloned()
+        .collect();
+    (options, check_option)
+}
+
+fn with_options(options: &[String]) -> String {
+    if options.is_empty() {
+        String::new()
+    } else {
+        format!("\nWITH ({})", options.join(", "))
+    }
+}
+
+fn view_statement(relation: &Relation, view_definition: &str) -> String {
+    let view_definition = view_definition.trim_end().trim_end_matches(';');
+    let (options, check_option) = split_check_option(&relation.options);
+
+    if relation.kind == "m" {
+        format!(
+            "CREATE MATERIALIZED VIEW {}{} AS\n{}\nWITH {}DATA;",
+            relation.name,
+            with_options(&options),
+            view_definition,
+            if relation.is_populated { "" } else { "NO " }
+        )
+    } else {
+        format!(
+            "CREATE VIEW {}{} AS\n{}{};",
+            relation.name,
+            with_options(&options),
+            view_definition,
+            check_option
+                .map(|check_option| format!("\nWITH {} CHECK OPTION", check_option))
+                .unwrap_or_default()
+        )
+    }
+}
+
+// CREATE statement of a relation, followed by the constraints of a
+// partition, which are added to it afterwards.
+fn create_statements(
+    relation: &Relation,
+    columns: &[RelationColumn],
+    constraints: &[(String, String)],
+) -> Vec<String> {
+    if let Some(view_definition) = &relation.view_definition {
+        return vec![view_statement(relation, view_definition)];
+    }
+
+    let mut statement = match &relation.partition_of {
+        Some((parent, bound)) => {
+            let options: Vec<String> = columns
+                .iter()
+                .filter_map(partition_column_options)
+                .collect();
+            let mut statement = format!("CREATE TABLE {} PARTITION OF {}", relation.name, parent);
+            if !options.is_empty() {
+                statement.push_str(&format!(" (\n{}\n)", options.join(",\n")));
+            }
+            statement.push_str(&format!("\n{}{}", INDENT, bound));
+            statement
+        }
+        None => {
+            let mut definitions: Vec<String> = columns
+                .iter()
+                .filter(|column| column.is_local)
+                .map(column_definition)
+                .collect();
+            definitions.extend(constraints.iter().map(|(constraint_name, definition)| {
+                format!("{}CONSTRAINT {} {}", INDENT, constraint_name, definition)
+            }));
+
+            let mut statement = format!(
+                "CREATE {}{} {} (\n{}\n)",
+                if relation.is_unlogged {
+                    "UNLOGGED "
+                } else {
+                    ""
+                },
+                relation.object_type(),
+                relation.name,
+                definitions.join(",\n")
+            );
+            if let Some(parents) = &relation.parents {
+                statement.push_str(&format!("\nINHERITS ({})", parents));
+            }
+            statement
+        }
+    };
+
+    if let Some(partition_key) = &relation.partition_key {
+        statement.push_str(&format!("\nPARTITION BY {}", partition_key));
+    }
+    if let Some(server) = &relation.server {
+        statement.push_str(&format!("\nSERVER {}", server));
+    }
+    statement.push_str(&with_options(&relation.options));
+    statement.push(';');
+
+    let mut statements = vec![statement];
+    if relation.partition_of.is_some() {
+        statements.extend(constraints.iter().map(|(constraint_name, definition)| {
+            format!(
+                "ALTER TABLE {} ADD CONSTRAINT {} {};",
+                relation.name, constraint_name, definition
+            )
+        }));
+    }
+    statements
+}
+
+fn relation_statements(
+    relation: &Relation,
+    columns: &[RelationColumn],
+    constraints: &[(String, String)],
+    indexes: Vec<String>,
+    triggers: Vec<String>,
+) -> String {
+    let mut statements = create_statements(relation, columns, constraints);
+    statements.extend(indexes);
+    statements.extend(triggers);
+    statements.extend(owner_and_comment(
+        relation.object_type(),
+        &relation.name,
+        relation.owner.clone(),
+        relation.comment.clone(),
+    ));
+    statements.extend(columns.iter().filter_map(|column| {
+        Some(format!(
+            "COMMENT ON COLUMN {}.{} IS {};",
+            relation.name,
+            column.name,
+            column.comment.as_ref()?
+        ))
+    }));
+
+    statements.join("\n\n")
+}
+
+// CREATE statement of a relation followed by its indexes, triggers,
+// ownership and comments, rebuilt from the catalog.
+pub async fn relation_ddl(client: &Client, table_name: &TableName) -> Result<String, Error> {
+    let table = table_name.quoted();
+    let relation = client
+        .query_one(
+            "SELECT c.relkind::text, quote_ident(n.nspname) || '.' || quote_ident(c.relname),
+                c.relpersistence = 'u', c.relispartition,
+                (
+                    SELECT quote_ident(pn.nspname) || '.' || quote_ident(p.relname)
+                    FROM pg_inherits i
+                    JOIN pg_class p ON p.oid = i.inhparent
+                    JOIN pg_namespace pn ON pn.oid = p.relnamespace
+                    WHERE i.inhrelid = c.oid AND c.relispartition
+                ),
+                pg_get_expr(c.relpartbound, c.oid),
+                CASE WHEN c.relkind = 'p' THEN pg_get_partkeydef(c.oid) END,
+                (
+                    SELECT string_agg(
+                        quote_ident(pn.nspname) || '.' || quote_ident(p.relname), ', '
+                        ORDER BY i.inhseqno
+                    )
+                    FROM pg_inherits i
+                    JOIN pg_class p ON p.oid = i.inhparent
+                    JOIN pg_namespace pn ON pn.oid = p.relnamespace
+                    WHERE i.inhrelid = c.oid AND NOT c.relispartition
+                ),
+                CASE WHEN c.relkind IN ('v', 'm') THEN pg_get_viewdef(c.oid, true) END,
+                c.relispopulated,
+                c.reloptions,
+                (
+                    SELECT quote_ident(s.srvname) || CASE
+                        WHEN ft.ftoptions IS NULL THEN ''
+                        ELSE ' OPTIONS (' || array_to_string(array(
+                            SELECT quote_ident(o.option_name) || ' ' || quote_literal(o.option_value)
+                            FROM pg_options_to_table(ft.ftoptions) o
+                        ), ', ') || ')'
+                    END
+                    FROM pg_foreign_table ft
+                    JOIN pg_foreign_server s ON s.oid = ft.ftserver
+                    WHERE ft.ftrelid = c.oid
+                ),
+                quote_ident(pg_get_userbyid(c.relowner)),
+                quote_literal(obj_description(c.oid, 'pg_class'))
+            FROM pg_class c
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            WHERE c.oid = $1::text::regclass",
+            &[&table],
+        )
+        .await?;
+
+    let relation = Relation::from_row(&relation);
+    if relation.kind == "S" {
+        return sequence_ddl(client, &table).await;
+    }
+
+    let columns: Vec<RelationColumn> = client
+        .query(
+            &format!(
+                "SELECT quote_ident(a.attname), format_type(a.atttypid, a.atttypmod),
+                    CASE WHEN a.attcollation <> t.typcollation
+                        THEN quote_ident(cn.nspname) || '.' || quote_ident(co.collname)
+                    END,
+                    {},
+                    a.attnotnull, a.attislocal,
+                    {},
+                    coalesce(pa.attnotnull, false),
+                    quote_literal(col_description(a.attrelid, a.attnum))
+                FROM pg_attribute a
+                JOIN pg_class c ON c.oid = a.attrelid
+                JOIN pg_type t ON t.oid = a.atttypid
+                LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+                LEFT JOIN pg_collation co ON co.oid = a.attcollation
+                LEFT JOIN pg_namespace cn ON cn.oid = co.collnamespace
+                LEFT JOIN pg_inherits i ON i.inhrelid = c.oid AND c.relispartition
+                LEFT JOIN pg_attribute pa ON pa.attrelid = i.inhparent AND pa.attname = a.attname
+                LEFT JOIN pg_attrdef pd ON pd.adrelid = pa.attrelid AND pd.adnum = pa.attnum
+                WHERE a.attrelid = $1::text::regclass AND a.attnum > 0 AND NOT a.attisdropped
+                ORDER BY a.attnum",
+                COLUMN_DEFAULT_SQL.replace("{a}", "a").replace("{d}", "d"),
+                COLUMN_DEFAULT_SQL.replace("{a}", "pa").replace("{d}", "pd"),
+            ),
+            &[&table],
+        )
+        .await?
+        .iter()
+        .map(RelationColumn::from_row)
+        .collect();
+
+    let constraints: Vec<(String, String)> = client
+        .query(
+            "SELECT quote_ident(con.conname), pg_get_constraintdef(con.oid)
+            FROM pg_constraint con
+            WHERE con.conrelid = $1::text::regclass AND con.contype IN ('p', 'u', 'c', 'f', 'x')
+                AND con.conislocal
+            ORDER BY array_position(ARRAY['p', 'u', 'f', 'c', 'x'], con.contype::text),
+                con.conname COLLATE \"C\"",
+            &[&table],
+        )
+        .await?
+        .iter()
+        .map(|row| (row.get(0), row.get(1)))
+        .collect();
+
+    // Indexes that back a constraint come with it, the ones of a partition
+    // with the index of the partitioned table. That one is created on the
+    // partitions too, not ONLY on the partitioned table.
+    let indexes: Vec<String> = client
+        .query(
+            "SELECT pg_get_indexdef(i.indexrelid) || ';'
+            FROM pg_index i
+            JOIN pg_class c ON c.oid = i.indexrelid
+            WHERE i.indrelid = $1::text::regclass AND NOT EXISTS (
+                SELECT FROM pg_constraint con
+                WHERE con.conindid = i.indexrelid AND con.conrelid = i.indrelid
+                    AND con.contype IN ('p', 'u', 'x')
+            ) AND NOT EXISTS (
+                SELECT FROM pg_inherits inh WHERE inh.inhrelid = i.indexrelid
+            )
+            ORDER BY c.relname COLLATE \"C\"",
+            &[&table],
+        )
+        .await?
+        .iter()
+        .map(|row| row.get::<_, String>(0).replacen(" ON ONLY ", " ON ", 1))
+        .collect();
+
+    let triggers: Vec<String> = client
+        .query(
+            "SELECT pg_get_triggerdef(t.oid) || ';'
+            FROM pg_trigger t
+            WHERE t.tgrelid = $1::text::regclass AND NOT t.tgisinternal
+            ORDER BY t.tgname COLLATE \"C\"",
+            &[&table],
+        )
+        .await?
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+
+    Ok(relation_statements(
+        &relation,
+        &columns,
+        &constraints,
+        indexes,
+        triggers,
+    ))
+}
+
+// A sequence as read from the catalog, its numbers as text.
+#[derive(Debug, Clone, Default)]
+struct Sequence {
+    name: String,
+    is_unlogged: bool,
+    type_name: String,
+    start: String,
+    increment: String,
+    min: String,
+    max: String,
+    cache: String,
+    cycle: bool,
+    owned_by: Option<String>,
+    owner: Option<String>,
+    comment: Option<String>,
+}
+
+fn sequence_statements(sequence: &Sequence) -> String {
+    let mut statements = vec![format!(
+        "CREATE {}SEQUENCE {}\n{indent}AS {}\n{indent}START WITH {}\n{indent}INCREMENT BY {}\n\
+        {indent}MINVALUE {}\n{indent}MAXVALUE {}\n{indent}CACHE {}\n{indent}{};",
+        if sequence.is_unlogged {
+            "UNLOGGED "
+        } else {
+            ""
+        },
+        sequence.name,
+        sequence.type_name,
+        sequence.start,
+        sequence.increment,
+        sequence.min,
+        sequence.max,
+        sequence.cache,
+        if sequence.cycle { "CYCLE" } else { "NO CYCLE" },
+        indent = INDENT,
+    )];
+    if let Some(owned_by) = &sequence.owned_by {
+        statements.push(format!(
+            "ALTER SEQUENCE {} OWNED BY {};",
+            sequence.name, owned_by
+        ));
+    }
+    statements.extend(owner_and_comment(
+        "SEQUENCE",
+        &sequence.name,
+        sequence.owner.clone(),
+        sequence.comment.clone(),
+    ));
+
+    statements.join("\n\n")
+}
+
+async fn sequence_ddl(client: &Client, table: &str) -> Result<String, Error> {
+    let row = client
+        .query_one(
+            "SELECT quote_ident(n.nspname) || '.' || quote_ident(c.relname),
+                c.relpersistence = 'u',
+                format_type(s.seqtypid, NULL), s.seqstart::text, s.seqincrement::text,
+                s.seqmin::text, s.seqmax::text, s.seqcache::text, s.seqcycle,
+                (
+                    SELECT quote_ident(tn.nspname) || '.' || quote_ident(t.relname) || '.'
+                        || quote_ident(a.attname)
+                    FROM pg_depend d
+                    JOIN pg_class t ON t.oid = d.refobjid
+                    JOIN pg_namespace tn ON tn.oid = t.relnamespace
+                    JOIN pg_attribute a ON a.attrelid = d.refobjid AND a.attnum = d.refobjsubid
+                    WHERE d.classid = 'pg_class'::regclass AND d.objid = s.seqrelid
+                        AND d.refclassid = 'pg_class'::regclass AND d.deptype = 'a'
+                ),
+                quote_ident(pg_get_userbyid(c.relowner)),
+                quote_literal(obj_description(c.oid, 'pg_class'))
+            FROM pg_sequence s
+            JOIN pg_class c ON c.oid = s.seqrelid
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            WHERE s.seqrelid = $1::text::regclass",
+            &[&table],
+        )
+        .await?;
+
+    Ok(sequence_statements(&Sequence {
+        name: row.get(0),
+        is_unlogged: row.get(1),
+        type_name: row.get(2),
+        start: row.get(3),
+        increment: row.get(4),
+        min: row.get(5),
+        max: row.get(6),
+        cache: row.get(7),
+        cycle: row.get(8),
+        owned_by: row.get(9),
+        owner: row.get(10),
+        comment: row.get(11),
+    }))
+}
+
+// A function, procedure or aggregate as read from the catalog. Functions
+// come with their whole definition, aggregates with the options of theirs.
+#[derive(Debug, Clone, Default)]
+struct Function {
+    kind: String,
+    name: String,
+    definition: Option<String>,
+    aggregate_options: Vec<String>,
+    owner: Option<String>,
+    comment: Option<String>,
+}
+
+fn function_statements(function: &Function) -> String {
+    let (object_type, definition) = match function.kind.as_str() {
+        "a" => (
+            "AGGREGATE",
+            format!(
+                "CREATE AGGREGATE {} (\n{}{}\n);",
+                function.name,
+                INDENT,
+                function.aggregate_options.join(&format!(",\n{}", INDENT))
+            ),
+        ),
+        kind => (
+            if kind == "p" { "PROCEDURE" } else { "FUNCTION" },
+            format!(
+                "{};",
+                function
+                    .definition
+                    .as_deref()
+                    .unwrap_or_default()
+                    .trim_end()
+            ),
+        ),
+    };
+
+    let mut statements = vec![definition];
+    statements.extend(owner_and_comment(
+        object_type,
+        &function.name,
+        function.owner.clone(),
+        function.comment.clone(),
+    ));
+    statements.join("\n\n")
+}
+
+async fn function_ddl(client: &Client, oid: u32) -> Result<String, Error> {
+    let row = client
+        .query_one(
+            "SELECT p.prokind::text,
+                quote_ident(n.nspname) || '.' || quote_ident(p.proname)
+                    || '(' || pg_get_function_identity_arguments(p.oid) || ')',
+                CASE WHEN p.prokind <> 'a' THEN pg_get_functiondef(p.oid) END,
+                array(
+                    SELECT unnest(array_remove(ARRAY[
+                        'SFUNC = ' || a.aggtransfn::regproc::text,
+                        'STYPE = ' || format_type(a.aggtranstype, NULL),
+                        CASE WHEN a.aggtransspace <> 0
+                            THEN 'SSPACE = ' || a.aggtransspace END,
+                        CASE WHEN a.aggfinalfn <> 0
+                            THEN 'FINALFUNC = ' || a.aggfinalfn::regproc::text END,
+                        CASE WHEN a.aggfinalextra THEN 'FINALFUNC_EXTRA' END,
+                        CASE WHEN a.aggcombinefn <> 0
+                            THEN 'COMBINEFUNC = ' || a.aggcombinefn::regproc::text END,
+                        CASE WHEN a.aggserialfn <> 0
+                            THEN 'SERIALFUNC = ' || a.aggserialfn::regproc::text END,
+                        CASE WHEN a.aggdeserialfn <> 0
+                            THEN 'DESERIALFUNC = ' || a.aggdeserialfn::regproc::text END,
+                        CASE WHEN a.agginitval IS NOT NULL
+                            THEN 'INITCOND = ' || quote_literal(a.agginitval) END,
+                        CASE WHEN a.aggsortop <> 0
+                            THEN 'SORTOP = OPERATOR(' || a.aggsortop::regoper::text || ')' END,
+                        CASE p.proparallel
+                            WHEN 's' THEN 'PARALLEL = SAFE'
+                            WHEN 'r' THEN 'PARALLEL = RESTRICTED'
+                        END
+                    ], NULL))
+                    FROM pg_aggregate a
+                    WHERE a.aggfnoid = p.oid
+                ),
+                quote_ident(pg_get_userbyid(p.proowner)),
+                quote_literal(obj_description(p.oid, 'pg_proc'))
+            FROM pg_proc p
+            JOIN pg_namespace n ON n.oid = p.pronamespace
+            WHERE p.oid = $1",
+            &[&oid],
+        )
+        .await?;
+
+    Ok(function_statements(&Function {
+        kind: row.get(0),
+        name: row.get(1),
+        definition: row.get(2),
+        aggregate_options: row.get(3),
+        owner: row.get(4),
+        comment: row.get(5),
+    }))
+}
+
+// An enum, domain or composite type as read from the catalog. Labels are
+// literals, attributes and checks are ready to be listed.
+#[derive(Debug, Clone, Default)]
+struct TypeDefinition {
+    kind: String,
+    name: String,
+    labels: Vec<String>,
+    attributes: Vec<String>,
+    base_type: String,
+    collation: Option<String>,
+    default: Option<String>,
+    not_null: bool,
+    checks: Vec<String>,
+    owner: Option<String>,
+    comment: Option<String>,
+}
+
+fn type_statements(definition: &TypeDefinition) -> String {
+    let list = |items: &[String]| {
+        if items.is_empty() {
+            String::from("()")
+        } else {
+            format!("(\n{}{}\n)", INDENT, items.join(&format!(",\n{}", INDENT)))
+        }
+    };
+
+    let (object_type, statement) = match definition.kind.as_str() {
+        "e" => (
+            "TYPE",
+            format!(
+                "CREATE TYPE {} AS ENUM {};",
+                definition.name,
+                list(&definition.labels)
+            ),
+        ),
+        "d" => {
+            let mut statement = format!(
+                "CREATE DOMAIN {} AS {}",
+                definition.name, definition.base_type
+            );
+            if let Some(collation) = &definition.collation {
+                statement.push_str(&format!("\n{}COLLATE {}", INDENT, collation));
+            }
+            if let Some(default) = &definition.default {
+                statement.push_str(&format!("\n{}DEFAULT {}", INDENT, default));
+            }
+            if definition.not_null {
+                statement.push_str(&format!("\n{}NOT NULL", INDENT));
+            }
+            for check in &definition.checks {
+                statement.push_str(&format!("\n{}{}", INDENT, check));
+            }
+            statement.push(';');
+            ("DOMAIN", statement)
+        }
+        _ => (
+            "TYPE",
+            format!(
+                "CREATE TYPE {} AS {};",
+                definition.name,
+                list(&definition.attributes)
+            ),
+        ),
+    };
+
+    let mut statements = vec![statement];
+    statements.extend(owner_and_comment(
+        object_type,
+        &definition.name,
+        definition.owner.clone(),
+        definition.comment.clone(),
+    ));
+    statements.join("\n\n")
+}
+
+async fn type_ddl(client: &Client, oid: u32) -> Result<String, Error> {
+    let row = client
+        .query_one(
+            "SELECT t.typtype::text, quote_ident(n.nspname) || '.' || quote_ident(t.typname),
+                array(
+                    SELECT quote_literal(e.enumlabel)
+                    FROM pg_enum e
+                    WHERE e.enumtypid = t.oid
+                    ORDER BY e.enumsortorder
+                ),
+                array(
+                    SELECT quote_ident(a.attname) || ' ' || format_type(a.atttypid, a.atttypmod)
+                        || CASE WHEN a.attcollation <> at.typcollation
+                            THEN ' COLLATE ' || quote_ident(acn.nspname) || '.'
+                                || quote_ident(aco.collname)
+                            ELSE ''
+                        END
+                    FROM pg_attribute a
+                    JOIN pg_type at ON at.oid = a.atttypid
+                    LEFT JOIN pg_collation aco ON aco.oid = a.attcollation
+                    LEFT JOIN pg_namespace acn ON acn.oid = aco.collnamespace
+                    WHERE a.attrelid = t.typrelid AND a.attnum > 0 AND NOT a.attisdropped
+                    ORDER BY a.attnum
+                ),
+                format_type(t.typbasetype, t.typtypmod),
+                CASE WHEN t.typcollation <> b.typcollation
+                    THEN quote_ident(cn.nspname) || '.' || quote_ident(co.collname)
+                END,
+                t.typdefault, t.typnotnull,
+                array(
+                    SELECT 'CONSTRAINT ' || quote_ident(con.conname) || ' '
+                        || pg_get_constraintdef(con.oid)
+                    FROM pg_constraint con
+                    WHERE con.contypid = t.oid
+                    ORDER BY con.conname
+                ),
+                quote_ident(pg_get_userbyid(t.typowner)),
+                quote_literal(obj_description(t.oid, 'pg_type'))
+            FROM pg_type t
+            JOIN pg_namespace n ON n.oid = t.typnamespace
+            LEFT JOIN pg_type b ON b.oid = t.typbasetype
+            LEFT JOIN pg_collation co ON co.oid = t.typcollation
+            LEFT JOIN pg_namespace cn ON cn.oid = co.collnamespace
+            WHERE t.oid = $1",
+            &[&oid],
+        )
+        .await?;
+
+    Ok(type_statements(&TypeDefinition {
+        kind: row.get(0),
+        name: row.get(1),
+        labels: row.get(2),
+        attributes: row.get(3),
+        base_type: row.get(4),
+        collation: row.get(5),
+        default: row.get(6),
+        not_null: row.get(7),
+        checks: row.get(8),
+        owner: row.get(9),
+        comment: row.get(10),
+    }))
+}
+
+fn extension_statements(
+    name: &str,
+    schema: &str,
+    version: &str,
+    comment: Option<String>,
+) -> String {
+    let mut statements = vec![format!(
+        "CREATE EXTENSION IF NOT EXISTS {} WITH SCHEMA {} VERSION {};",
+        name, schema, version
+    )];
+    statements.extend(owner_and_comment("EXTENSION", name, None, comment));
+    statements.join("\n\n")
+}
+
+async fn extension_ddl(client: &Client, oid: u32) -> Result<String, Error> {
+    let row = client
+        .query_one(
+            "SELECT quote_ident(e.extname), quote_ident(n.nspname), quote_literal(e.extversion),
+                quote_literal(obj_description(e.oid, 'pg_extension'))
+            FROM pg_extension e
+            JOIN pg_namespace n ON n.oid = e.extnamespace
+            WHERE e.oid = $1",
+            &[&oid],
+        )
+        .await?;
+
+    Ok(extension_statements(
+        row.get(0),
+        row.get(1),
+        row.get(2),
+        row.get(3),
+    ))
+}
+
+// DDL of an object that isn't a relation, by oid. Relations are named
+// instead and go through `relation_ddl`.
+pub async fn object_ddl(client: &Client, kind: ObjectKind, oid: u32) -> Result<String, Error> {
+    match kind {
+        ObjectKind::Function => function_ddl(client, oid).await,
+        ObjectKind::Type => type_ddl(client, oid).await,
+        ObjectKind::Extension => extension_ddl(client, oid).await,
+        _ => {
+            let row = client
+                .query_one(
+                    "SELECT n.nspname::text, c.relname::text
+                    FROM pg_class c
+                    JOIN pg_namespace n ON n.oid = c.relnamespace
+                    WHERE c.oid = $1",
+                    &[&oid],
+                )
+                .await?;
+            relation_ddl(client, &TableName::new(row.get(0), row.get(1))).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relation(kind: &str) -> Relation {
+        Relation {
+            kind: kind.to_string(),
+            name: String::from(r#"public."Orders""#),
+            is_populated: true,
+            ..Relation::default()
+        }
+    }
+
+    fn column(name: &str, type_name: &str) -> RelationColumn {
+        RelationColumn {
+            name: name.to_string(),
+            type_name: type_name.to_string(),
+            is_local: true,
+            ..RelationColumn::default()
+        }
+    }
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[test]
+    fn owner_and_comment_are_only_set_when_known() {
+        assert_eq!(
+            owner_and_comment(
+                "TABLE",
+                r#""public"."orders""#,
+                Some(String::from(r#""app""#)),
+                Some(String::from("'Placed orders'")),
+            ),
+            vec![
+                r#"ALTER TABLE "public"."orders" OWNER TO "app";"#,
+                r#"COMMENT ON TABLE "public"."orders" IS 'Placed orders';"#,
+            ]
+        );
+        assert!(owner_and_comment("VIEW", r#""totals""#, None, None).is_empty());
+    }
+
+    #[test]
+    fn table_lists_its_columns_and_constraints() {
+        let mut table = relation("r");
+        table.is_unlogged = true;
+        table.parents = Some(String::from("public.base"));
+        table.options = strings(&["fillfactor=70"]);
+        table.owner = Some(String::from("app"));
+
+        let mut id = column("id", "bigint");
+        id.default = Some(String::from("GENERATED ALWAYS AS IDENTITY"));
+        id.not_null = true;
+        let mut note = column("note", "text");
+        note.collation = Some(String::from(r#"pg_catalog."C""#));
+        note.default = Some(String::from("DEFAULT ''::text"));
+        note.comment = Some(String::from("'Free text'"));
+        let mut inherited = column("created_at", "timestamp with time zone");
+        inherited.is_local = false;
+
+        assert_eq!(
+            relation_statements(
+                &table,
+                &[id, note, inherited],
+                &[
+                    (
+                        String::from("orders_pkey"),
+                        String::from("PRIMARY KEY (id)")
+                    ),
+                    (
+                        String::from("note_length"),
+                        String::from("CHECK (length(note) < 200)")
+                    ),
+                ],
+                strings(&["CREATE INDEX orders_note ON public.\"Orders\" USING btree (note);"]),
+                Vec::new(),
+            ),
+            r#"CREATE UNLOGGED TABLE public."Orders" (
+    id bigint GENERATED ALWAYS AS IDENTITY NOT NULL,
+    note text COLLATE pg_catalog."C" DEFAULT ''::text,
+    CONSTRAINT orders_pkey PRIMARY KEY (id),
+    CONSTRAINT note_length CHECK (length(note) < 200)
+)
+INHERITS (public.base)
+WITH (fillfactor=70);
+
+CREATE INDEX orders_note ON public."Orders" USING btree (note);
+
+ALTER TABLE public."Orders" OWNER TO app;
+
+COMMENT ON COLUMN public."Orders".note IS 'Free text';"#
+        );
+    }
+
+    #[test]
+    fn partitioned_table_has_its_key_and_foreign_table_its_server() {
+        let mut partitioned = relation("p");
+        partitioned.partition_key = Some(String::from("RANGE (placed_on)"));
+        assert_eq!(
+            create_statements(&partitioned, &[column("placed_on", "date")], &[]),
+            vec!["CREATE TABLE public.\"Orders\" (\n    placed_on date\n)\nPARTITION BY RANGE (placed_on);"]
+        );
+
+        let mut foreign = relation("f");
+        foreign.server = Some(String::from("archive OPTIONS (table_name 'orders')"));
+        assert_eq!(
+            create_statements(&foreign, &[column("id", "integer")], &[]),
+            vec!["CREATE FOREIGN TABLE public.\"Orders\" (\n    id integer\n)\nSERVER archive OPTIONS (table_name 'orders');"]
+        );
+    }
+
+    #[test]
+    fn partition_keeps_its_own_defaults_and_not_null() {
+        let mut partition = relation("r");
+        partition.name = String::from("public.orders_2024");
+        partition.partition_of = Some((
+            String::from("public.orders"),
+            String::from("FOR VALUES FROM ('2024-01-01') TO ('2025-01-01')"),
+        ));
+
+        let mut id = column("id", "bigint");
+        id.is_local = false;
+        id.default = Some(String::from("DEFAULT nextval('orders_id_seq'::regclass)"));
+        id.parent_default = id.default.clone();
+        id.not_null = true;
+        id.parent_not_null = true;
+        let mut status = column("status", "text");
+        status.is_local = false;
+        status.default = Some(String::from("DEFAULT 'archived'::text"));
+        status.parent_default = Some(String::from("DEFAULT 'new'::text"));
+        status.not_null = true;
+        let mut note = column("note", "text");
+        note.is_local = false;
+
+        assert_eq!(
+            create_statements(
+                &partition,
+                &[id, status, note],
+                &[(String::from("id_positive"), String::from("CHECK (id > 0)"))],
+            ),
+            vec![
+                "CREATE TABLE public.orders_2024 PARTITION OF public.orders (\n    \
+                status WITH OPTIONS DEFAULT 'archived'::text NOT NULL\n)\n    \
+                FOR VALUES FROM ('2024-01-01') TO ('2025-01-01');",
+                "ALTER TABLE public.orders_2024 ADD CONSTRAINT id_positive CHECK (id > 0);",
+            ]
+        );
+
+        partition.partition_key = Some(String::from("LIST (region)"));
+        assert_eq!(
+            create_statements(&partition, &[column("note", "text")], &[]),
+            vec![
+                "CREATE TABLE public.orders_2024 PARTITION OF public.orders\n    \
+            FOR VALUES FROM ('2024-01-01') TO ('2025-01-01')\nPARTITION BY LIST (region);"
+            ]
+        );
+    }
+
+    #[test]
+    fn views_keep_their_options_and_check_option() {
+        let mut view = relation("v");
+        view.view_definition = Some(String::from(" SELECT id\n   FROM orders\n  WHERE open;"));
+        view.options = strings(&["security_barrier=true", "check_option=cascaded"]);
+        assert_eq!(
+            create_statements(&view, &[], &[]),
+            vec![
+                "CREATE VIEW public.\"Orders\"\nWITH (security_barrier=true) AS\n \
+            SELECT id\n   FROM orders\n  WHERE open\nWITH CASCADED CHECK OPTION;"
+            ]
+        );
+
+        view.options = Vec::new();
+        assert_eq!(
+            create_statements(&view, &[], &[]),
+            vec!["CREATE VIEW public.\"Orders\" AS\n SELECT id\n   FROM orders\n  WHERE open;"]
+        );
+
+        let mut materialized = relation("m");
+        materialized.view_definition = view.view_definition.clone();
+        materialized.options = strings(&["fillfactor=50"]);
+        materialized.is_populated = false;
+        assert_eq!(
+            create_statements(&materialized, &[], &[]),
+            vec![
+                "CREATE MATERIALIZED VIEW public.\"Orders\"\nWITH (fillfactor=50) AS\n \
+            SELECT id\n   FROM orders\n  WHERE open\nWITH NO DATA;"
+            ]
+        );
+    }
+
+    #[test]
+    fn sequence_lists_every_option() {
+        let sequence = Sequence {
+            name: String::from("public.orders_id_seq"),
+            is_unlogged: false,
+            type_name: String::from("bigint"),
+            start: String::from("1"),
+            increment: String::from("1"),
+            min: String::from("1"),
+            max: String::from("9223372036854775807"),
+            cache: String::from("1"),
+            cycle: false,
+            owned_by: Some(String::from("public.orders.id")),
+            owner: None,
+            comment: Some(String::from("'Order ids'")),
+        };
+
+        assert_eq!(
+            sequence_statements(&sequence),
+            "CREATE SEQUENCE public.orders_id_seq
+    AS bigint
+    START WITH 1
+    INCREMENT BY 1
+    MINVALUE 1
+    MAXVALUE 9223372036854775807
+    CACHE 1
+    NO CYCLE;
+
+ALTER SEQUENCE public.orders_id_seq OWNED BY public.orders.id;
+
+COMMENT ON SEQUENCE public.orders_id_seq IS 'Order ids';"
+        );
+    }
+
+    #[test]
+    fn functions_procedures_and_aggregates() {
+        let function = Function {
+            kind: String::from("f"),
+            name: String::from("public.add(integer, integer)"),
+            definition: Some(String::from(
+                "CREATE OR REPLACE FUNCTION public.add(a integer, b integer)\n RETURNS integer\n \
+                LANGUAGE sql\nAS $function$SELECT a + b$function$\n",
+            )),
+            owner: Some(String::from("app")),
+            ..Function::default()
+        };
+        assert_eq!(
+            function_statements(&function),
+            "CREATE OR REPLACE FUNCTION public.add(a integer, b integer)\n RETURNS integer\n \
+            LANGUAGE sql\nAS $function$SELECT a + b$function$;\n\n\
+            ALTER FUNCTION public.add(integer, integer) OWNER TO app;"
+        );
+
+        let procedure = Function {
+            kind: String::from("p"),
+            name: String::from("public.tidy()"),
+            definition: Some(String::from("CREATE OR REPLACE PROCEDURE public.tidy()")),
+            comment: Some(String::from("'Nightly'")),
+            ..Function::default()
+        };
+        assert_eq!(
+            function_statements(&procedure),
+            "CREATE OR REPLACE PROCEDURE public.tidy();\n\n\
+            COMMENT ON PROCEDURE public.tidy() IS 'Nightly';"
+        );
+
+        let aggregate = Function {
+            kind: String::from("a"),
+            name: String::from("public.total(numeric)"),
+            aggregate_options: strings(&[
+                "SFUNC = numeric_add",
+                "STYPE = numeric",
+                "INITCOND = '0'",
+            ]),
+            ..Function::default()
+        };
+        assert_eq!(
+            function_statements(&aggregate),
+            "CREATE AGGREGATE public.total(numeric) (\n    SFUNC = numeric_add,\n    \
+            STYPE = numeric,\n    INITCOND = '0'\n);"
+        );
+    }
+
+    #[test]
+    fn enums_domains_and_composite_types() {
+        let status = TypeDefinition {
+            kind: String::from("e"),
+            name: String::from("public.status"),
+            labels: strings(&["'new'", "'done'"]),
+            ..TypeDefinition::default()
+        };
+        assert_eq!(
+            type_statements(&status),
+            "CREATE TYPE public.status AS ENUM (\n    'new',\n    'done'\n);"
+        );
+
+        let empty = TypeDefinition {
+            labels: Vec::new(),
+            ..status
+        };
+        assert_eq!(
+            type_statements(&empty),
+            "CREATE TYPE public.status AS ENUM ();"
+        );
+
+        let positive = TypeDefinition {
+            kind: String::from("d"),
+            name: String::from("public.positive"),
+            base_type: String::from("numeric(10,2)"),
+            default: Some(String::from("1")),
+            not_null: true,
+            checks: strings(&["CONSTRAINT positive_check CHECK (VALUE > 0)"]),
+            owner: Some(String::from("app")),
+            ..TypeDefinition::default()
+        };
+        assert_eq!(
+            type_statements(&positive),
+            "CREATE DOMAIN public.positive AS numeric(10,2)\n    DEFAULT 1\n    NOT NULL\n    \
+            CONSTRAINT positive_check CHECK (VALUE > 0);\n\n\
+            ALTER DOMAIN public.positive OWNER TO app;"
+        );
+
+        let address = TypeDefinition {
+            kind: String::from("c"),
+            name: String::from("public.address"),
+            attributes: strings(&["street text COLLATE pg_catalog.\"C\"", "zip integer"]),
+            ..TypeDefinition::default()
+        };
+        assert_eq!(
+            type_statements(&address),
+            "CREATE TYPE public.address AS (\n    street text COLLATE pg_catalog.\"C\",\n    \
+            zip integer\n);"
+        );
+    }
+
+    #[test]
+    fn extension_is_created_if_missing() {
+        assert_eq!(
+            extension_statements("pgcrypto", "public", "'1.3'", None),
+            "CREATE EXTENSION IF NOT EXISTS pgcrypto WITH SCHEMA public VERSION '1.3';"
+        );
+    }
+}
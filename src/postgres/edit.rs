@@ -0,0 +1,249 @@
+use tokio_postgres::types::Type;
+
+use crate::postgres::{
+    data::{Column, Value},
+    filter::check_value,
+    query::{quote_identifier, TableName, TableQuery},
+};
+
+// New content for a cell.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CellValue {
+    Text(String),
+    Null,
+    Default,
+}
+
+impl CellValue {
+    // Catch text the column type would reject before sending it, NULL and
+    // DEFAULT are left to the server's constraints.
+    pub fn check(&self, column: &Column) -> Result<(), String> {
+        match self {
+            CellValue::Text(text) => check_value(&column.type_, text)
+                .map_err(|error| format!("{} {}", column.name, error)),
+            CellValue::Null | CellValue::Default => Ok(()),
+        }
+    }
+
+    // Expression for the value in a statement, text is bound as a parameter.
+    pub fn bind(&self, query: &mut TableQuery, ty: &Type) -> String {
+        match self {
+            CellValue::Text(text) => query.bind(Some(text.clone()), ty),
+            CellValue::Null => String::from("NULL"),
+            CellValue::Default => String::from("DEFAULT"),
+        }
+    }
+}
+
+pub fn check_key(table_name: &TableName, key: &[String]) -> Result<(), String> {
+    if key.is_empty() {
+        Err(format!(
+            "{} has no primary key or unique NOT NULL columns to identify rows by",
+            table_name
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+// Restrict the query to a single row by the values of its key columns.
+pub fn match_row(
+    query: &mut TableQuery,
+    table_name: &TableName,
+    columns: &[Column],
+    key: &[String],
+    row: &[Value],
+) -> Result<(), String> {
+    check_key(table_name, key)?;
+
+    for name in key {
+        let index = columns
+            .iter()
+            .position(|column| &column.name == name)
+            .ok_or_else(|| format!("key column {} is not loaded", name))?;
+        let value = row
+            .get(index)
+            .and_then(Value::to_text)
+            .ok_or_else(|| format!("key column {} is NULL", name))?;
+
+        let placeholder = query.bind(Some(value), &columns[index].type_);
+        query
+            .conditions
+            .push(format!("{} = {}", quote_identifier(name), placeholder));
+    }
+
+    Ok(())
+}
+
+// Restrict the query to the row identified by its key only while every
+// column still holds the given values. Both sides are compared as the text
+// postgres renders for the column type, which works for types without an
+// equality operator too.
+pub fn match_image(
+    query: &mut TableQuery,
+    table_name: &TableName,
+    columns: &[Column],
+    key: &[String],
+    row: &[Value],
+) -> Result<(), String> {
+    match_row(query, table_name, columns, key, row)?;
+
+    for (column, value) in columns.iter().zip(row) {
+        let placeholder = query.bind(value.to_text(), &column.type_);
+        query.conditions.push(format!(
+            "{}::text IS NOT DISTINCT FROM ({})::text",
+            quote_identifier(&column.name),
+            placeholder
+        ));
+    }
+
+    Ok(())
+}
+
+// A cell being edited in the prompt, the value is the typed text unless
+// NULL or DEFAULT was picked instead.
+#[derive(Debug, Clone)]
+pub struct CellEdit {
+    pub row: usize,
+    pub column_index: usize,
+    pub column: Column,
+    pub keyword: Option<CellValue>,
+}
+
+impl CellEdit {
+    pub fn value(&self, input: &str) -> CellValue {
+        self.keyword
+            .clone()
+            .unwrap_or_else(|| CellValue::Text(input.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new(String::from("id"), Type::INT4),
+            Column::new(String::from("line"), Type::INT4),
+            Column::new(String::from("note"), Type::TEXT),
+        ]
+    }
+
+    fn orders() -> TableName {
+        TableName::new("public", "orders")
+    }
+
+    fn key(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn row() -> Vec<Value> {
+        vec![Value::Int(7), Value::Null, Value::Text(String::from("x"))]
+    }
+
+    #[test]
+    fn values_are_checked_and_bound() {
+        let columns = columns();
+        let mut query = TableQuery::new(&orders(), &columns);
+
+        assert_eq!(
+            CellValue::Text(String::from("seven")).check(&columns[0]),
+            Err(String::from("id expects a int4, got \"seven\""))
+        );
+        assert_eq!(CellValue::Null.check(&columns[0]), Ok(()));
+
+        let assignments = [
+            CellValue::Text(String::from("8")).bind(&mut query, &Type::INT4),
+            CellValue::Null.bind(&mut query, &Type::INT4),
+            CellValue::Default.bind(&mut query, &Type::TEXT),
+        ];
+        assert_eq!(
+            assignments,
+            [r#"$1::text::"pg_catalog"."int4""#, "NULL", "DEFAULT"]
+        );
+        assert_eq!(query.params, vec![Some(String::from("8"))]);
+    }
+
+    #[test]
+    fn update_matches_the_row_by_its_key() {
+        let columns = columns();
+        let mut query = TableQuery::new(&orders(), &columns);
+        let value = CellValue::Text(String::from("y")).bind(&mut query, &Type::TEXT);
+        query.assignments.push((quote_identifier("note"), value));
+
+        match_row(&mut query, &orders(), &columns, &key(&["id"]), &row()).unwrap();
+
+        assert_eq!(
+            query.to_update_sql(),
+            r#"UPDATE "public"."orders" SET "note" = $1::text::"pg_catalog"."text" WHERE "id" = $2::text::"pg_catalog"."int4""#
+        );
+        assert_eq!(
+            query.params,
+            vec![Some(String::from("y")), Some(String::from("7"))]
+        );
+    }
+
+    #[test]
+    fn rows_without_a_usable_key_are_rejected() {
+        let columns = columns();
+        let mut query = TableQuery::new(&orders(), &columns);
+
+        assert_eq!(
+            match_row(&mut query, &orders(), &columns, &[], &row()),
+            Err(String::from(
+                "public.orders has no primary key or unique NOT NULL columns to identify rows by"
+            ))
+        );
+        assert_eq!(
+            match_row(
+                &mut query,
+                &orders(),
+                &columns,
+                &key(&["id", "line"]),
+                &row()
+            ),
+            Err(String::from("key column line is NULL"))
+        );
+        assert_eq!(
+            match_row(&mut query, &orders(), &columns, &key(&["code"]), &row()),
+            Err(String::from("key column code is not loaded"))
+        );
+    }
+
+    #[test]
+    fn image_matches_every_column_as_text() {
+        let columns = columns();
+        let mut query = TableQuery::new(&orders(), &columns);
+
+        match_image(&mut query, &orders(), &columns, &key(&["id"]), &row()).unwrap();
+
+        assert_eq!(
+            query.to_delete_sql(),
+            r#"DELETE FROM "public"."orders" WHERE "id" = $1::text::"pg_catalog"."int4" AND "id"::text IS NOT DISTINCT FROM ($2::text::"pg_catalog"."int4")::text AND "line"::text IS NOT DISTINCT FROM ($3::text::"pg_catalog"."int4")::text AND "note"::text IS NOT DISTINCT FROM ($4::text::"pg_catalog"."text")::text"#
+        );
+        assert_eq!(
+            query.params,
+            vec![
+                Some(String::from("7")),
+                Some(String::from("7")),
+                None,
+                Some(String::from("x"))
+            ]
+        );
+    }
+
+    #[test]
+    fn keywords_replace_the_typed_text() {
+        let mut edit = CellEdit {
+            row: 0,
+            column_index: 2,
+            column: columns()[2].clone(),
+            keyword: None,
+        };
+        assert_eq!(edit.value("typed"), CellValue::Text(String::from("typed")));
+
+        edit.keyword = Some(CellValue::Default);
+        assert_eq!(edit.value("typed"), CellValue::Default);
+    }
+}
@@ -0,0 +1,902 @@
+use futures_util::{pin_mut, TryStreamExt};
+use serde_json::{Map, Number};
+use std::{
+    env,
+    error::Error,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+use tokio::task::JoinHandle;
+use tokio_postgres::Client;
+
+use crate::postgres::{
+    data::{parse_text, Column, Value},
+    query::{quote_identifier, quote_literal, TableName},
+};
+
+// Text formats rows can be copied as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CopyFormat {
+    #[default]
+    Tsv,
+    Csv,
+    Json,
+    SqlIn,
+}
+
+impl CopyFormat {
+    pub fn next(self) -> Self {
+        match self {
+            CopyFormat::Tsv => CopyFormat::Csv,
+            CopyFormat::Csv => CopyFormat::Json,
+            CopyFormat::Json => CopyFormat::SqlIn,
+            CopyFormat::SqlIn => CopyFormat::Tsv,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CopyFormat::Tsv => "TSV",
+            CopyFormat::Csv => "CSV",
+            CopyFormat::Json => "JSON",
+            CopyFormat::SqlIn => "SQL IN",
+        }
+    }
+}
+
+// Format a block of cells. A header line is only written for more than one
+// column, a single column is most likely a list of IDs.
+pub fn format_cells(columns: &[&Column], rows: &[Vec<&Value>], format: CopyFormat) -> String {
+    let with_header = columns.len() > 1;
+
+    match format {
+        CopyFormat::Tsv => {
+            let mut lines = Vec::new();
+            if with_header {
+                lines.push(
+                    columns
+                        .iter()
+                        .map(|column| tsv_field(&column.name))
+                        .collect::<Vec<String>>()
+                        .join("\t"),
+                );
+            }
+            for row in rows {
+                lines.push(
+                    row.iter()
+                        .map(|value| match value.to_text() {
+                            Some(text) => tsv_field(&text),
+                            None => String::new(),
+                        })
+                        .collect::<Vec<String>>()
+                        .join("\t"),
+                );
+            }
+            lines.join("\n")
+        }
+        CopyFormat::Csv => {
+            let mut lines = Vec::new();
+            if with_header {
+                lines.push(
+                    columns
+                        .iter()
+                        .map(|column| csv_field(Some(&column.name)))
+                        .collect::<Vec<String>>()
+                        .join(","),
+                );
+            }
+            for row in rows {
+                lines.push(
+                    row.iter()
+                        .map(|value| csv_field(value.to_text().as_deref()))
+                        .collect::<Vec<String>>()
+                        .join(","),
+                );
+            }
+            lines.join("\n")
+        }
+        CopyFormat::Json => {
+            let json_rows: Vec<serde_json::Value> = rows
+                .iter()
+                .map(|row| {
+                    if columns.len() == 1 {
+                        row.first().map(|value| to_json(value)).unwrap_or_default()
+                    } else {
+                        json_object(columns, row)
+                    }
+                })
+                .collect();
+
+            let json = match json_rows.len() {
+                1 => json_rows.into_iter().next().unwrap_or_default(),
+                _ => serde_json::Value::Array(json_rows),
+            };
+            serde_json::to_string_pretty(&json).unwrap_or_default()
+        }
+        CopyFormat::SqlIn => {
+            // NULL never matches in an IN list
+            let mut items: Vec<String> = Vec::new();
+            for row in rows {
+                if row.iter().any(|value| value.is_null()) {
+                    continue;
+                }
+                let literals: Vec<String> = row.iter().map(|value| sql_literal(value)).collect();
+                let item = if literals.len() == 1 {
+                    literals.into_iter().next().unwrap_or_default()
+                } else {
+                    format!("({})", literals.join(", "))
+                };
+                if !items.contains(&item) {
+                    items.push(item);
+                }
+            }
+            format!("IN ({})", items.join(", "))
+        }
+    }
+}
+
+pub fn json_object(columns: &[&Column], row: &[&Value]) -> serde_json::Value {
+    let mut object = Map::new();
+    for (column, value) in columns.iter().zip(row) {
+        object.insert(column.name.clone(), to_json(value));
+    }
+    serde_json::Value::Object(object)
+}
+
+// JSON value the way PostgreSQL's to_json would write it.
+pub fn to_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::Null => serde_json::Value::Null,
+        Value::Bool(value) => serde_json::Value::Bool(*value),
+        Value::Int(value) => serde_json::Value::from(*value),
+        Value::Float(value) => match Number::from_f64(*value) {
+            Some(number) => serde_json::Value::Number(number),
+            None => serde_json::Value::String(value.to_string()),
+        },
+        // Numbers with more digits than a float holds stay strings rather
+        // than losing precision
+        Value::Numeric(text) => match serde_json::from_str::<Number>(text) {
+            Ok(number) if text.chars().filter(char::is_ascii_digit).count() <= 15 => {
+                serde_json::Value::Number(number)
+            }
+            _ => serde_json::Value::String(text.clone()),
+        },
+        Value::Json(text) => {
+            serde_json::from_str(text).unwrap_or_else(|_| serde_json::Value::String(text.clone()))
+        }
+        Value::Array(values) => serde_json::Value::Array(values.iter().map(to_json).collect()),
+        Value::Composite(fields) => serde_json::Value::Object(
+            fields
+                .iter()
+                .map(|(name, value)| (name.clone(), to_json(value)))
+                .collect(),
+        ),
+        value => serde_json::Value::String(value.to_string()),
+    }
+}
+
+// SQL literal for a value, numbers and booleans are left unquoted.
+pub fn sql_literal(value: &Value) -> String {
+    match value {
+        Value::Null => String::from("NULL"),
+        Value::Bool(_) | Value::Int(_) => value.to_string(),
+        Value::Float(number) if number.is_finite() => value.to_string(),
+        Value::Numeric(text) if text.parse::<f64>().is_ok_and(f64::is_finite) => text.clone(),
+        value => quote_literal(&value.to_string()),
+    }
+}
+
+// Field of tab separated values, escaped like COPY's text format.
+fn tsv_field(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+// Field of comma separated values. NULL is left empty while an empty string
+// is quoted, like COPY's CSV format.
+pub fn csv_field(text: Option<&str>) -> String {
+    delimited_field(text, ',', false)
+}
+
+fn delimited_field(text: Option<&str>, delimiter: char, quote_all: bool) -> String {
+    match text {
+        None => String::new(),
+        Some(text)
+            if quote_all
+                || text.is_empty()
+                || text.contains([delimiter, '"', '\n', '\r'])
+                || text.starts_with(' ')
+                || text.ends_with(' ') =>
+        {
+            format!("\"{}\"", text.replace('"', "\"\""))
+        }
+        Some(text) => text.to_string(),
+    }
+}
+
+// File formats results can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Ndjson,
+    Markdown,
+    Insert,
+}
+
+impl ExportFormat {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "json" => Some(ExportFormat::Json),
+            "ndjson" | "jsonl" => Some(ExportFormat::Ndjson),
+            "markdown" | "md" => Some(ExportFormat::Markdown),
+            "insert" | "sql" => Some(ExportFormat::Insert),
+            _ => None,
+        }
+    }
+
+    // Rows are written as JSON objects rather than as fields of text.
+    pub fn is_json(self) -> bool {
+        matches!(self, ExportFormat::Json | ExportFormat::Ndjson)
+    }
+}
+
+// What to export to where, parsed from the export prompt:
+//
+//     <format> [option=value...] <path>
+//
+// with the options `delimiter`, `quote=needed|all` and `header=yes|no` for
+// CSV and `table` for the target of INSERT statements.
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    pub path: PathBuf,
+    pub delimiter: char,
+    pub quote_all: bool,
+    pub header: bool,
+    pub table: Option<TableName>,
+}
+
+impl ExportOptions {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut words = input.split_whitespace();
+        let format = match words.next() {
+            Some(name) => ExportFormat::parse(name).ok_or(format!(
+                "unknown format {}, use csv, json, ndjson, markdown or insert",
+                name
+            ))?,
+            None => return Err(String::from("expected a format and a file name")),
+        };
+
+        let mut options = Self {
+            format,
+            path: PathBuf::new(),
+            delimiter: ',',
+            quote_all: false,
+            header: true,
+            table: None,
+        };
+
+        let mut path = Vec::new();
+        for word in words {
+            let (key, value) = match word.split_once('=') {
+                Some(option) if path.is_empty() => option,
+                _ => {
+                    path.push(word);
+                    continue;
+                }
+            };
+
+            match key {
+                "delimiter" => {
+                    options.delimiter = match value {
+                        "tab" => '\t',
+                        "comma" => ',',
+                        "semicolon" => ';',
+                        "pipe" => '|',
+                        value if value.chars().count() == 1 => value.chars().next().unwrap_or(','),
+                        _ => return Err(format!("the delimiter must be one character: {}", value)),
+                    }
+                }
+                "quote" => {
+                    options.quote_all = match value {
+                        "all" => true,
+                        "needed" => false,
+                        _ => return Err(format!("quote must be all or needed: {}", value)),
+                    }
+                }
+                "header" => {
+                    options.header = match value {
+                        "yes" | "true" | "on" => true,
+                        "no" | "false" | "off" => false,
+                        _ => return Err(format!("header must be yes or no: {}", value)),
+                    }
+                }
+                "table" => options.table = Some(TableName::parse(value, "public")?),
+                _ => return Err(format!("unknown option {}", key)),
+            }
+        }
+
+        let path = path.join(" ");
+        if path.is_empty() {
+            return Err(String::from("expected a file name"));
+        }
+        options.path = expand_home(&path);
+
+        Ok(options)
+    }
+}
+
+// A path typed in by the user, ~/ standing for the home directory.
+pub fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => PathBuf::from(env::var("HOME").unwrap_or_default()).join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+// Writes rows to the output in the export format as they arrive.
+pub struct ExportWriter<W: Write> {
+    output: W,
+    options: ExportOptions,
+    columns: Vec<Column>,
+    table: String,
+    rows: u64,
+}
+
+impl<W: Write> ExportWriter<W> {
+    // `table` is the quoted target of INSERT statements unless the options
+    // name one.
+    pub fn new(output: W, options: ExportOptions, columns: Vec<Column>, table: &str) -> Self {
+        let table = match &options.table {
+            Some(table) => table.quoted(),
+            None => table.to_string(),
+        };
+        Self {
+            output,
+            options,
+            columns,
+            table,
+            rows: 0,
+        }
+    }
+
+    pub fn begin(&mut self) -> io::Result<()> {
+        let names: Vec<&str> = self
+            .columns
+            .iter()
+            .map(|column| column.name.as_str())
+            .collect();
+
+        match self.options.format {
+            ExportFormat::Csv if self.options.header => {
+                let header: Vec<Option<String>> =
+                    names.iter().map(|name| Some(name.to_string())).collect();
+                let line = self.csv_line(&header);
+                writeln!(self.output, "{}", line)
+            }
+            ExportFormat::Json => write!(self.output, "["),
+            ExportFormat::Markdown => {
+                let header: Vec<String> = names.iter().map(|name| markdown_cell(name)).collect();
+                writeln!(self.output, "| {} |", header.join(" | "))?;
+                writeln!(self.output, "|{}", " --- |".repeat(names.len()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // A row as the text of its fields, None being NULL.
+    pub fn write_fields(&mut self, fields: &[Option<String>]) -> io::Result<()> {
+        match self.options.format {
+            ExportFormat::Csv => {
+                let line = self.csv_line(fields);
+                writeln!(self.output, "{}", line)?;
+            }
+            ExportFormat::Markdown => {
+                let cells: Vec<String> = fields
+                    .iter()
+                    .map(|field| match field {
+                        Some(text) => markdown_cell(text),
+                        None => String::from("*NULL*"),
+                    })
+                    .collect();
+                writeln!(self.output, "| {} |", cells.join(" | "))?;
+            }
+            ExportFormat::Insert => {
+                let names: Vec<String> = self
+                    .columns
+                    .iter()
+                    .map(|column| quote_identifier(&column.name))
+                    .collect();
+                let values: Vec<String> = self
+                    .columns
+                    .iter()
+                    .zip(fields)
+                    .map(|(column, field)| match field {
+                        Some(text) => sql_literal(&parse_text(&column.type_, text)),
+                        None => String::from("NULL"),
+                    })
+                    .collect();
+                writeln!(
+                    self.output,
+                    "INSERT INTO {} ({}) VALUES ({});",
+                    self.table,
+                    names.join(", "),
+                    values.join(", ")
+                )?;
+            }
+            ExportFormat::Json | ExportFormat::Ndjson => {
+                let object: Map<String, serde_json::Value> = self
+                    .columns
+                    .iter()
+                    .zip(fields)
+                    .map(|(column, field)| {
+                        let value = match field {
+                            Some(text) => to_json(&parse_text(&column.type_, text)),
+                            None => serde_json::Value::Null,
+                        };
+                        (column.name.clone(), value)
+                    })
+                    .collect();
+                return self.write_json(&serde_json::Value::Object(object).to_string());
+            }
+        }
+
+        self.rows += 1;
+        Ok(())
+    }
+
+    // A row as a JSON object.
+    pub fn write_json(&mut self, object: &str) -> io::Result<()> {
+        match self.options.format {
+            ExportFormat::Json => {
+                let separator = if self.rows == 0 { "" } else { "," };
+                write!(self.output, "{}\n  {}", separator, object)?;
+            }
+            _ => writeln!(self.output, "{}", object)?,
+        }
+
+        self.rows += 1;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<u64> {
+        if self.options.format == ExportFormat::Json {
+            let end = if self.rows == 0 { "]" } else { "\n]" };
+            writeln!(self.output, "{}", end)?;
+        }
+        self.output.flush()?;
+        Ok(self.rows)
+    }
+
+    fn csv_line(&self, fields: &[Option<String>]) -> String {
+        let delimiter = self.options.delimiter;
+        fields
+            .iter()
+            .map(|field| delimited_field(field.as_deref(), delimiter, self.options.quote_all))
+            .collect::<Vec<String>>()
+            .join(&delimiter.to_string())
+    }
+}
+
+fn markdown_cell(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+// Export the given rows of a result that's already loaded, `table` is the
+// quoted target of INSERT statements.
+pub fn export_rows(
+    options: ExportOptions,
+    columns: Vec<Column>,
+    rows: &[Vec<&Value>],
+    table: &str,
+) -> io::Result<u64> {
+    let output = BufWriter::new(File::create(&options.path)?);
+    let is_json = options.format.is_json();
+    let mut writer = ExportWriter::new(output, options, columns.clone(), table);
+    let json_columns: Vec<&Column> = columns.iter().collect();
+
+    writer.begin()?;
+    for row in rows {
+        if is_json {
+            writer.write_json(&json_object(&json_columns, row).to_string())?;
+        } else {
+            let fields: Vec<Option<String>> = row.iter().map(|value| value.to_text()).collect();
+            writer.write_fields(&fields)?;
+        }
+    }
+    writer.finish()
+}
+
+// An export of a whole table running in the background on its own
+// connection, `rows` counts the rows written so far.
+pub struct ExportJob {
+    pub table: TableName,
+    pub path: PathBuf,
+    pub estimated_total: Option<i64>,
+    pub rows: Arc<AtomicU64>,
+    pub handle: JoinHandle<Result<u64, Box<dyn Error + Send + Sync>>>,
+}
+
+impl ExportJob {
+    // Stream the result of the query with COPY TO STDOUT into the file.
+    pub fn start(
+        client: Client,
+        sql: String,
+        options: ExportOptions,
+        columns: Vec<Column>,
+        table: &TableName,
+        estimated_total: Option<i64>,
+    ) -> Result<Self, io::Error> {
+        let path = options.path.clone();
+        let output = BufWriter::new(File::create(&path)?);
+        let writer = ExportWriter::new(output, options, columns, &table.quoted());
+        let rows = Arc::new(AtomicU64::new(0));
+
+        let handle = tokio::spawn(copy_out(client, sql, writer, rows.clone()));
+
+        Ok(Self {
+            table: table.clone(),
+            path,
+            estimated_total,
+            rows,
+            handle,
+        })
+    }
+}
+
+async fn copy_out<W: Write>(
+    client: Client,
+    sql: String,
+    mut writer: ExportWriter<W>,
+    rows: Arc<AtomicU64>,
+) -> Result<u64, Box<dyn Error + Send + Sync>> {
+    let is_json = writer.options.format.is_json();
+    let sql = if is_json {
+        format!("SELECT row_to_json(q) FROM ({}) q", sql)
+    } else {
+        sql
+    };
+
+    let stream = client
+        .copy_out(&format!("COPY ({}) TO STDOUT", sql))
+        .await?;
+    pin_mut!(stream);
+
+    writer.begin()?;
+
+    // Rows can be split across the chunks of the stream
+    let mut buffer: Vec<u8> = Vec::new();
+    while let Some(chunk) = stream.try_next().await? {
+        buffer.extend_from_slice(&chunk);
+
+        let mut start = 0;
+        while let Some(end) = buffer[start..].iter().position(|byte| *byte == b'\n') {
+            let line = String::from_utf8_lossy(&buffer[start..start + end]);
+            let fields = parse_copy_line(&line);
+            if is_json {
+                let object = fields.into_iter().next().flatten().unwrap_or_default();
+                writer.write_json(&object)?;
+            } else {
+                writer.write_fields(&fields)?;
+            }
+            start += end + 1;
+            rows.fetch_add(1, Ordering::Relaxed);
+        }
+        buffer.drain(..start);
+    }
+
+    Ok(writer.finish()?)
+}
+
+// Fields of a line of COPY's text format, undoing the backslash escapes COPY
+// TO writes. `\N` stands for NULL.
+fn parse_copy_line(line: &str) -> Vec<Option<String>> {
+    line.split('\t')
+        .map(|field| {
+            if field == "\\N" {
+                return None;
+            }
+
+            let mut text = String::with_capacity(field.len());
+            let mut chars = field.chars();
+            while let Some(c) = chars.next() {
+                if c != '\\' {
+                    text.push(c);
+                    continue;
+                }
+
+                match chars.next() {
+                    Some('b') => text.push('\u{8}'),
+                    Some('f') => text.push('\u{c}'),
+                    Some('n') => text.push('\n'),
+                    Some('r') => text.push('\r'),
+                    Some('t') => text.push('\t'),
+                    Some('v') => text.push('\u{b}'),
+                    Some(c) => text.push(c),
+                    None => text.push('\\'),
+                }
+            }
+            Some(text)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_postgres::types::Type;
+
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new(String::from("id"), Type::INT4),
+            Column::new(String::from("note"), Type::TEXT),
+        ]
+    }
+
+    fn rows() -> Vec<Vec<Value>> {
+        vec![
+            vec![Value::Int(1), Value::Text(String::from("it's, \"quoted\""))],
+            vec![Value::Int(2), Value::Null],
+            vec![Value::Int(3), Value::Text(String::from("tab\tand\nline"))],
+        ]
+    }
+
+    fn copy(columns: &[Column], rows: &[Vec<Value>], format: CopyFormat) -> String {
+        let columns: Vec<&Column> = columns.iter().collect();
+        let rows: Vec<Vec<&Value>> = rows.iter().map(|row| row.iter().collect()).collect();
+        format_cells(&columns, &rows, format)
+    }
+
+    fn ids(rows: &[Vec<Value>]) -> Vec<Vec<Value>> {
+        rows.iter().map(|row| vec![row[0].clone()]).collect()
+    }
+
+    #[test]
+    fn formats_cycle_through_all() {
+        let mut format = CopyFormat::default();
+        let mut names = Vec::new();
+        for _ in 0..4 {
+            names.push(format.name());
+            format = format.next();
+        }
+
+        assert_eq!(names, vec!["TSV", "CSV", "JSON", "SQL IN"]);
+        assert_eq!(format, CopyFormat::Tsv);
+    }
+
+    #[test]
+    fn tsv_escapes_like_copy() {
+        assert_eq!(
+            copy(&columns(), &rows(), CopyFormat::Tsv),
+            "id\tnote\n1\tit's, \"quoted\"\n2\t\n3\ttab\\tand\\nline"
+        );
+    }
+
+    #[test]
+    fn csv_quotes_only_when_needed() {
+        assert_eq!(
+            copy(&columns(), &rows(), CopyFormat::Csv),
+            "id,note\n1,\"it's, \"\"quoted\"\"\"\n2,\n3,\"tab\tand\nline\""
+        );
+        assert_eq!(csv_field(Some("")), "\"\"");
+        assert_eq!(csv_field(Some(" padded")), "\" padded\"");
+        assert_eq!(csv_field(Some("plain")), "plain");
+        assert_eq!(csv_field(None), "");
+    }
+
+    #[test]
+    fn single_column_has_no_header() {
+        assert_eq!(
+            copy(&columns()[..1], &ids(&rows()), CopyFormat::Tsv),
+            "1\n2\n3"
+        );
+        assert_eq!(
+            copy(&columns()[..1], &ids(&rows()), CopyFormat::Csv),
+            "1\n2\n3"
+        );
+    }
+
+    #[test]
+    fn json_objects_for_rows_and_plain_values_for_a_column() {
+        assert_eq!(
+            copy(&columns(), &rows()[1..2], CopyFormat::Json),
+            "{\n  \"id\": 2,\n  \"note\": null\n}"
+        );
+        assert_eq!(
+            copy(&columns()[..1], &ids(&rows()), CopyFormat::Json),
+            "[\n  1,\n  2,\n  3\n]"
+        );
+    }
+
+    #[test]
+    fn sql_in_skips_nulls_and_duplicates() {
+        let rows = vec![
+            vec![Value::Int(2), Value::Text(String::from("it's"))],
+            vec![Value::Int(2), Value::Text(String::from("it's"))],
+            vec![Value::Int(3), Value::Null],
+        ];
+
+        assert_eq!(
+            copy(&columns(), &rows, CopyFormat::SqlIn),
+            "IN ((2, 'it''s'))"
+        );
+        assert_eq!(
+            copy(&columns()[..1], &ids(&rows), CopyFormat::SqlIn),
+            "IN (2, 3)"
+        );
+    }
+
+    #[test]
+    fn json_values_like_to_json() {
+        assert_eq!(to_json(&Value::Float(1.5)), serde_json::json!(1.5));
+        assert_eq!(to_json(&Value::Float(f64::NAN)), serde_json::json!("NaN"));
+        assert_eq!(
+            to_json(&Value::Numeric(String::from("12.50"))),
+            serde_json::json!(12.50)
+        );
+        assert_eq!(
+            to_json(&Value::Numeric(String::from("1234567890.1234567890"))),
+            serde_json::json!("1234567890.1234567890")
+        );
+        assert_eq!(
+            to_json(&Value::Json(String::from(r#"{"a": [1]}"#))),
+            serde_json::json!({"a": [1]})
+        );
+        assert_eq!(
+            to_json(&Value::Array(vec![Value::Bool(true), Value::Null])),
+            serde_json::json!([true, null])
+        );
+        assert_eq!(
+            to_json(&Value::Composite(vec![(String::from("x"), Value::Int(1))])),
+            serde_json::json!({"x": 1})
+        );
+    }
+
+    #[test]
+    fn sql_literals_leave_numbers_unquoted() {
+        assert_eq!(sql_literal(&Value::Null), "NULL");
+        assert_eq!(sql_literal(&Value::Bool(false)), "false");
+        assert_eq!(sql_literal(&Value::Int(-7)), "-7");
+        assert_eq!(sql_literal(&Value::Float(f64::INFINITY)), "'Infinity'");
+        assert_eq!(sql_literal(&Value::Numeric(String::from("1.50"))), "1.50");
+        assert_eq!(sql_literal(&Value::Numeric(String::from("NaN"))), "'NaN'");
+        assert_eq!(sql_literal(&Value::Text(String::from("it's"))), "'it''s'");
+    }
+
+    fn export(input: &str) -> String {
+        let orders = TableName::new("public", "orders").quoted();
+        let mut output = Vec::new();
+        let options = ExportOptions::parse(input).unwrap();
+        let mut writer = ExportWriter::new(&mut output, options, columns(), &orders);
+        writer.begin().unwrap();
+        writer
+            .write_fields(&[Some(String::from("1")), Some(String::from("a|b"))])
+            .unwrap();
+        writer
+            .write_fields(&[Some(String::from("2")), None])
+            .unwrap();
+        assert_eq!(writer.finish().unwrap(), 2);
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn export_options_are_parsed_before_the_path() {
+        let options =
+            ExportOptions::parse("csv delimiter=tab quote=all header=no out file.csv").unwrap();
+
+        assert_eq!(options.format, ExportFormat::Csv);
+        assert_eq!(options.delimiter, '\t');
+        assert!(options.quote_all);
+        assert!(!options.header);
+        assert_eq!(options.path, PathBuf::from("out file.csv"));
+
+        let options = ExportOptions::parse("SQL table=archive out a=b.sql").unwrap();
+        assert_eq!(options.format, ExportFormat::Insert);
+        assert_eq!(options.table, Some(TableName::new("public", "archive")));
+        assert_eq!(options.path, PathBuf::from("out a=b.sql"));
+    }
+
+    #[test]
+    fn export_options_errors() {
+        assert_eq!(
+            ExportOptions::parse("xml out.xml").unwrap_err(),
+            "unknown format xml, use csv, json, ndjson, markdown or insert"
+        );
+        assert_eq!(
+            ExportOptions::parse("").unwrap_err(),
+            "expected a format and a file name"
+        );
+        assert_eq!(
+            ExportOptions::parse("csv delimiter=;;").unwrap_err(),
+            "the delimiter must be one character: ;;"
+        );
+        assert_eq!(
+            ExportOptions::parse("csv header=maybe x").unwrap_err(),
+            "header must be yes or no: maybe"
+        );
+        assert_eq!(
+            ExportOptions::parse("csv encoding=utf8 x").unwrap_err(),
+            "unknown option encoding"
+        );
+        assert_eq!(
+            ExportOptions::parse("insert table=archive. x").unwrap_err(),
+            "archive. is not a table name"
+        );
+        assert_eq!(
+            ExportOptions::parse("json").unwrap_err(),
+            "expected a file name"
+        );
+    }
+
+    #[test]
+    fn export_writes_every_format() {
+        assert_eq!(export("csv delimiter=| x"), "id|note\n1|\"a|b\"\n2|\n");
+        assert_eq!(
+            export("csv quote=all header=off x"),
+            "\"1\",\"a|b\"\n\"2\",\n"
+        );
+        assert_eq!(
+            export("markdown x"),
+            "| id | note |\n| --- | --- |\n| 1 | a\\|b |\n| 2 | *NULL* |\n"
+        );
+        assert_eq!(
+            export("insert x"),
+            "INSERT INTO \"public\".\"orders\" (\"id\", \"note\") VALUES (1, 'a|b');\n\
+             INSERT INTO \"public\".\"orders\" (\"id\", \"note\") VALUES (2, NULL);\n"
+        );
+        assert_eq!(
+            export("insert table=archive x"),
+            "INSERT INTO \"public\".\"archive\" (\"id\", \"note\") VALUES (1, 'a|b');\n\
+             INSERT INTO \"public\".\"archive\" (\"id\", \"note\") VALUES (2, NULL);\n"
+        );
+        assert_eq!(
+            export("insert table=audit.\"Orders\" x"),
+            "INSERT INTO \"audit\".\"Orders\" (\"id\", \"note\") VALUES (1, 'a|b');\n\
+             INSERT INTO \"audit\".\"Orders\" (\"id\", \"note\") VALUES (2, NULL);\n"
+        );
+        assert_eq!(
+            export("json x"),
+            "[\n  {\"id\":1,\"note\":\"a|b\"},\n  {\"id\":2,\"note\":null}\n]\n"
+        );
+        assert_eq!(
+            export("ndjson x"),
+            "{\"id\":1,\"note\":\"a|b\"}\n{\"id\":2,\"note\":null}\n"
+        );
+    }
+
+    #[test]
+    fn empty_json_export_is_an_empty_array() {
+        let orders = TableName::new("public", "orders").quoted();
+        let mut output = Vec::new();
+        let options = ExportOptions::parse("json x").unwrap();
+        let mut writer = ExportWriter::new(&mut output, options, columns(), &orders);
+        writer.begin().unwrap();
+        assert_eq!(writer.finish().unwrap(), 0);
+
+        assert_eq!(String::from_utf8(output).unwrap(), "[]\n");
+    }
+
+    #[test]
+    fn copy_lines_are_unescaped() {
+        assert_eq!(
+            parse_copy_line("1\ta\\tb\\\\c\\nd\t\\N"),
+            vec![
+                Some(String::from("1")),
+                Some(String::from("a\tb\\c\nd")),
+                None
+            ]
+        );
+    }
+}
@@ -0,0 +1,879 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+use std::fmt::{self, Display};
+use tokio_postgres::types::{Kind, Type};
+
+use crate::postgres::{
+    data::Column,
+    query::{quote_identifier, TableQuery},
+};
+
+// Row filter typed into the search bar, e.g.
+//
+//   status = 'active' and created_at > 2024-01-01 and name ~ foo
+//
+// Comparisons are =, !=, <>, <, <=, >, >=, ~ (contains, case insensitive),
+// !~, like, ilike, is [not] null and [not] in (a, b, ...). They can be
+// combined with and, or, not and parentheses. Values are either quoted with
+// single quotes or written as a bare word.
+//
+// The filter is checked against the column types before it is sent, values
+// become parameters of the table query and never end up in the SQL text.
+#[derive(Debug, Clone)]
+pub struct Filter {
+    pub source: String,
+    expression: Expression,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterError {
+    pub message: String,
+    pub position: usize,
+}
+
+impl Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (at {})", self.message, self.position + 1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Contains,
+    NotContains,
+    Like,
+    ILike,
+}
+
+impl Operator {
+    fn sql(&self) -> &'static str {
+        match self {
+            Operator::Equal => "=",
+            Operator::NotEqual => "<>",
+            Operator::Less => "<",
+            Operator::LessOrEqual => "<=",
+            Operator::Greater => ">",
+            Operator::GreaterOrEqual => ">=",
+            Operator::Contains | Operator::ILike => "ILIKE",
+            Operator::NotContains => "NOT ILIKE",
+            Operator::Like => "LIKE",
+        }
+    }
+
+    // Operators that work on the text of a value rather than the value.
+    fn is_textual(&self) -> bool {
+        matches!(
+            self,
+            Operator::Contains | Operator::NotContains | Operator::Like | Operator::ILike
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expression {
+    And(Box<Expression>, Box<Expression>),
+    Or(Box<Expression>, Box<Expression>),
+    Not(Box<Expression>),
+    Compare(Column, Operator, String),
+    IsNull(Column, bool),
+    In(Column, Vec<String>, bool),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Identifier(String),
+    Literal(String),
+    Symbol(&'static str),
+    End,
+}
+
+const SYMBOLS: [&str; 12] = [
+    "<=", ">=", "<>", "!=", "!~", "=", "<", ">", "~", "(", ")", ",",
+];
+
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, FilterError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some(&(position, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        if c == '\'' || c == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    // A doubled quote is a quote inside the string
+                    Some((_, next)) if next == c => match chars.peek() {
+                        Some(&(_, after)) if after == c => {
+                            chars.next();
+                            text.push(c);
+                        }
+                        _ => break,
+                    },
+                    Some((_, next)) => text.push(next),
+                    None => {
+                        return Err(FilterError {
+                            message: String::from("unterminated quote"),
+                            position,
+                        })
+                    }
+                }
+            }
+
+            let token = if c == '\'' {
+                Token::Literal(text)
+            } else {
+                Token::Identifier(text)
+            };
+            tokens.push((token, position));
+            continue;
+        }
+
+        if let Some(symbol) = SYMBOLS
+            .iter()
+            .find(|symbol| input[position..].starts_with(**symbol))
+        {
+            for _ in 0..symbol.len() {
+                chars.next();
+            }
+            tokens.push((Token::Symbol(symbol), position));
+            continue;
+        }
+
+        let mut word = String::new();
+        while let Some(&(_, c)) = chars.peek() {
+            if c.is_whitespace() || c == '\'' || c == '"' || "()=<>!~,".contains(c) {
+                break;
+            }
+            word.push(c);
+            chars.next();
+        }
+
+        if word.is_empty() {
+            return Err(FilterError {
+                message: format!("unexpected \"{}\"", c),
+                position,
+            });
+        }
+        tokens.push((Token::Word(word), position));
+    }
+
+    tokens.push((Token::End, input.len()));
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<(Token, usize)>,
+    index: usize,
+    columns: &'a [Column],
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.index].0
+    }
+
+    fn position(&self) -> usize {
+        self.tokens[self.index].1
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.index].0.clone();
+        if token != Token::End {
+            self.index += 1;
+        }
+        token
+    }
+
+    fn error<T>(&self, message: String) -> Result<T, FilterError> {
+        Err(FilterError {
+            message,
+            position: self.position(),
+        })
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Token::Word(word) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), FilterError> {
+        if !self.is_keyword(keyword) {
+            return self.error(format!("expected \"{}\"", keyword));
+        }
+        self.advance();
+        Ok(())
+    }
+
+    fn expect_symbol(&mut self, symbol: &'static str) -> Result<(), FilterError> {
+        if *self.peek() != Token::Symbol(symbol) {
+            return self.error(format!("expected \"{}\"", symbol));
+        }
+        self.advance();
+        Ok(())
+    }
+
+    fn parse_or(&mut self) -> Result<Expression, FilterError> {
+        let mut expression = self.parse_and()?;
+        while self.is_keyword("or") {
+            self.advance();
+            let right = self.parse_and()?;
+            expression = Expression::Or(Box::new(expression), Box::new(right));
+        }
+        Ok(expression)
+    }
+
+    fn parse_and(&mut self) -> Result<Expression, FilterError> {
+        let mut expression = self.parse_unary()?;
+        while self.is_keyword("and") {
+            self.advance();
+            let right = self.parse_unary()?;
+            expression = Expression::And(Box::new(expression), Box::new(right));
+        }
+        Ok(expression)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expression, FilterError> {
+        if self.is_keyword("not") {
+            self.advance();
+            return Ok(Expression::Not(Box::new(self.parse_unary()?)));
+        }
+
+        if *self.peek() == Token::Symbol("(") {
+            self.advance();
+            let expression = self.parse_or()?;
+            self.expect_symbol(")")?;
+            return Ok(expression);
+        }
+
+        self.parse_comparison()
+    }
+
+    fn parse_column(&mut self) -> Result<Column, FilterError> {
+        let position = self.position();
+        let column = match self.advance() {
+            Token::Identifier(name) => self.columns.iter().find(|column| column.name == name),
+            Token::Word(name) => self
+                .columns
+                .iter()
+                .find(|column| column.name == name)
+                .or_else(|| {
+                    self.columns
+                        .iter()
+                        .find(|column| column.name.eq_ignore_ascii_case(&name))
+                }),
+            _ => {
+                return Err(FilterError {
+                    message: String::from("expected a column name"),
+                    position,
+                })
+            }
+        };
+
+        match column {
+            Some(column) => Ok(column.clone()),
+            None => Err(FilterError {
+                message: format!("unknown column \"{}\"", self.tokens[self.index - 1].0),
+                position,
+            }),
+        }
+    }
+
+    // A value compared against the column, checked against its type unless
+    // the operator works on the value's text.
+    fn parse_value(&mut self, column: &Column, textual: bool) -> Result<String, FilterError> {
+        let position = self.position();
+        let value = match self.advance() {
+            Token::Literal(value) => value,
+            Token::Word(word) if word.eq_ignore_ascii_case("null") => {
+                return Err(FilterError {
+                    message: String::from("use \"is null\" to compare with null"),
+                    position,
+                })
+            }
+            Token::Word(word) => word,
+            _ => {
+                return Err(FilterError {
+                    message: format!("expected a value for \"{}\"", column.name),
+                    position,
+                })
+            }
+        };
+
+        if !textual {
+            if let Err(message) = check_value(&column.type_, &value) {
+                return Err(FilterError {
+                    message: format!("\"{}\" {}", column.name, message),
+                    position,
+                });
+            }
+        }
+
+        Ok(value)
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expression, FilterError> {
+        let column = self.parse_column()?;
+
+        if self.is_keyword("is") {
+            self.advance();
+            let negated = self.is_keyword("not");
+            if negated {
+                self.advance();
+            }
+            self.expect_keyword("null")?;
+            return Ok(Expression::IsNull(column, negated));
+        }
+
+        let negated = self.is_keyword("not");
+        if negated {
+            self.advance();
+            if !self.is_keyword("in") {
+                return self.error(String::from("expected \"in\" after \"not\""));
+            }
+        }
+
+        if self.is_keyword("in") {
+            self.advance();
+            check_comparable(&column, self.position())?;
+            self.expect_symbol("(")?;
+            let mut values = vec![self.parse_value(&column, false)?];
+            while *self.peek() == Token::Symbol(",") {
+                self.advance();
+                values.push(self.parse_value(&column, false)?);
+            }
+            self.expect_symbol(")")?;
+            return Ok(Expression::In(column, values, negated));
+        }
+
+        let position = self.position();
+        let operator = match self.advance() {
+            Token::Symbol("=") => Operator::Equal,
+            Token::Symbol("!=") | Token::Symbol("<>") => Operator::NotEqual,
+            Token::Symbol("<") => Operator::Less,
+            Token::Symbol("<=") => Operator::LessOrEqual,
+            Token::Symbol(">") => Operator::Greater,
+            Token::Symbol(">=") => Operator::GreaterOrEqual,
+            Token::Symbol("~") => Operator::Contains,
+            Token::Symbol("!~") => Operator::NotContains,
+            Token::Word(word) if word.eq_ignore_ascii_case("like") => Operator::Like,
+            Token::Word(word) if word.eq_ignore_ascii_case("ilike") => Operator::ILike,
+            _ => {
+                return Err(FilterError {
+                    message: format!("expected an operator after \"{}\"", column.name),
+                    position,
+                })
+            }
+        };
+
+        if !operator.is_textual() {
+            check_comparable(&column, position)?;
+        }
+
+        let value = self.parse_value(&column, operator.is_textual())?;
+        Ok(Expression::Compare(column, operator, value))
+    }
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(text) | Token::Identifier(text) | Token::Literal(text) => {
+                write!(f, "{}", text)
+            }
+            Token::Symbol(symbol) => write!(f, "{}", symbol),
+            Token::End => Ok(()),
+        }
+    }
+}
+
+fn base_type(ty: &Type) -> &Type {
+    match ty.kind() {
+        Kind::Domain(base) => base_type(base),
+        _ => ty,
+    }
+}
+
+// Types without an equality operator, these can only be searched as text.
+fn check_comparable(column: &Column, position: usize) -> Result<(), FilterError> {
+    match *base_type(&column.type_) {
+        Type::JSON | Type::XML | Type::POINT | Type::BOX | Type::POLYGON | Type::LINE => {
+            Err(FilterError {
+                message: format!(
+                    "\"{}\" of type {} can't be compared, use ~ to search its text",
+                    column.name,
+                    column.type_.name()
+                ),
+                position,
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+// Catch values postgres would reject for the column type before sending the
+// query. Types not listed here are left to the server.
+pub fn check_value(ty: &Type, value: &str) -> Result<(), String> {
+    let value = value.trim();
+    let special = |names: &[&str]| names.iter().any(|name| value.eq_ignore_ascii_case(name));
+
+    let is_valid = match *base_type(ty) {
+        Type::INT2 => value.parse::<i16>().is_ok(),
+        Type::INT4 => value.parse::<i32>().is_ok(),
+        Type::INT8 => value.parse::<i64>().is_ok(),
+        Type::OID => value.parse::<u32>().is_ok(),
+        Type::FLOAT4 | Type::FLOAT8 => {
+            value.parse::<f64>().is_ok() || special(&["NaN", "Infinity", "-Infinity"])
+        }
+        Type::NUMERIC => value.parse::<f64>().is_ok() || special(&["NaN"]),
+        Type::BOOL => special(&[
+            "t", "f", "true", "false", "y", "n", "yes", "no", "on", "off", "1", "0",
+        ]),
+        Type::DATE => {
+            NaiveDate::parse_from_str(without_era(value), "%Y-%m-%d").is_ok()
+                || special(&[
+                    "today",
+                    "tomorrow",
+                    "yesterday",
+                    "epoch",
+                    "infinity",
+                    "-infinity",
+                ])
+        }
+        Type::TIME => ["%H:%M", "%H:%M:%S", "%H:%M:%S%.f"]
+            .iter()
+            .any(|format| NaiveTime::parse_from_str(value, format).is_ok()),
+        Type::TIMESTAMP | Type::TIMESTAMPTZ => {
+            is_timestamp(without_era(value))
+                || special(&[
+                    "now",
+                    "today",
+                    "tomorrow",
+                    "yesterday",
+                    "epoch",
+                    "infinity",
+                    "-infinity",
+                ])
+        }
+        Type::UUID => {
+            let digits: String = value
+                .trim_start_matches('{')
+                .trim_end_matches('}')
+                .chars()
+                .filter(|c| *c != '-')
+                .collect();
+            digits.len() == 32 && digits.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        _ => true,
+    };
+
+    if is_valid {
+        Ok(())
+    } else {
+        Err(format!("expects a {}, got \"{}\"", ty.name(), value))
+    }
+}
+
+// Years before 1 AD are followed by BC, the rest of the value reads as usual.
+fn without_era(value: &str) -> &str {
+    value
+        .strip_suffix(" BC")
+        .or_else(|| value.strip_suffix(" bc"))
+        .unwrap_or(value)
+}
+
+fn is_timestamp(value: &str) -> bool {
+    if NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok() {
+        return true;
+    }
+
+    let normalized = value.replacen('T', " ", 1);
+    let local = [
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M:%S%.f",
+    ];
+    let zoned = [
+        "%Y-%m-%d %H:%M%#z",
+        "%Y-%m-%d %H:%M:%S%#z",
+        "%Y-%m-%d %H:%M:%S%.f%#z",
+    ];
+
+    local
+        .iter()
+        .any(|format| NaiveDateTime::parse_from_str(&normalized, format).is_ok())
+        || zoned
+            .iter()
+            .any(|format| DateTime::parse_from_str(&normalized, format).is_ok())
+}
+
+// Escape the LIKE wildcards so the value is matched literally.
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+impl Expression {
+    fn to_sql(&self, query: &mut TableQuery) -> String {
+        match self {
+            Expression::And(left, right) => {
+                format!("({} AND {})", left.to_sql(query), right.to_sql(query))
+            }
+            Expression::Or(left, right) => {
+                format!("({} OR {})", left.to_sql(query), right.to_sql(query))
+            }
+            Expression::Not(expression) => format!("NOT {}", expression.to_sql(query)),
+            Expression::Compare(column, operator, value) => {
+                let identifier = quote_identifier(&column.name);
+                match operator {
+                    Operator::Contains | Operator::NotContains => {
+                        let placeholder = query.bind(Some(escape_like(value)), &Type::TEXT);
+                        format!(
+                            "{}::text {} '%' || {} || '%'",
+                            identifier,
+                            operator.sql(),
+                            placeholder
+                        )
+                    }
+                    Operator::Like | Operator::ILike => {
+                        let placeholder = query.bind(Some(value.clone()), &Type::TEXT);
+                        format!("{}::text {} {}", identifier, operator.sql(), placeholder)
+                    }
+                    _ => {
+                        let placeholder = query.bind(Some(value.clone()), &column.type_);
+                        format!("{} {} {}", identifier, operator.sql(), placeholder)
+                    }
+                }
+            }
+            Expression::IsNull(column, negated) => format!(
+                "{} IS {}NULL",
+                quote_identifier(&column.name),
+                if *negated { "NOT " } else { "" }
+            ),
+            Expression::In(column, values, negated) => {
+                let placeholders = values
+                    .iter()
+                    .map(|value| query.bind(Some(value.clone()), &column.type_))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                format!(
+                    "{} {}IN ({})",
+                    quote_identifier(&column.name),
+                    if *negated { "NOT " } else { "" },
+                    placeholders
+                )
+            }
+        }
+    }
+}
+
+fn parse_expression(source: &str, columns: &[Column]) -> Result<Expression, FilterError> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        index: 0,
+        columns,
+    };
+
+    if *parser.peek() == Token::End {
+        return parser.error(String::from("empty filter"));
+    }
+
+    let expression = parser.parse_or()?;
+    if *parser.peek() != Token::End {
+        return parser.error(format!("unexpected \"{}\"", parser.peek()));
+    }
+
+    Ok(expression)
+}
+
+// Filter source matching rows where every column equals the given value.
+pub fn equality_source(conditions: &[(String, String)]) -> String {
+    conditions
+        .iter()
+        .map(|(column, value)| {
+            format!(
+                "{} = '{}'",
+                quote_identifier(column),
+                value.replace('\'', "''")
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(" and ")
+}
+
+impl Filter {
+    pub fn parse(source: &str, columns: &[Column]) -> Result<Filter, FilterError> {
+        // Errors point at a byte offset, report it as a character position
+        parse_expression(source, columns)
+            .map(|expression| Filter {
+                source: source.trim().to_string(),
+                expression,
+            })
+            .map_err(|error| FilterError {
+                position: source[..error.position].chars().count(),
+                ..error
+            })
+    }
+
+    // Add the filter as a condition of the query, binding its values.
+    pub fn apply(&self, query: &mut TableQuery) {
+        let condition = self.expression.to_sql(query);
+        query.conditions.push(format!("({})", condition));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postgres::query::TableName;
+
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new(String::from("id"), Type::INT4),
+            Column::new(String::from("name"), Type::TEXT),
+            Column::new(String::from("created"), Type::TIMESTAMPTZ),
+            Column::new(String::from("payload"), Type::JSON),
+            Column::new(String::from("Mixed Case"), Type::TEXT),
+        ]
+    }
+
+    // The condition the filter adds to a query and the values it binds.
+    fn condition(source: &str) -> (String, Vec<Option<String>>) {
+        let columns = columns();
+        let filter = Filter::parse(source, &columns).unwrap();
+        let mut query = TableQuery::new(&TableName::new("public", "t"), &columns);
+        filter.apply(&mut query);
+        (query.conditions.join(" AND "), query.params)
+    }
+
+    fn params(values: &[&str]) -> Vec<Option<String>> {
+        values.iter().map(|value| Some(value.to_string())).collect()
+    }
+
+    fn error(source: &str) -> FilterError {
+        Filter::parse(source, &columns()).unwrap_err()
+    }
+
+    #[test]
+    fn values_become_typed_parameters() {
+        assert_eq!(
+            condition("id = 5"),
+            (
+                String::from(r#"("id" = $1::text::"pg_catalog"."int4")"#),
+                params(&["5"])
+            )
+        );
+        assert_eq!(
+            condition("name = 'O''Brien'"),
+            (
+                String::from(r#"("name" = $1::text::"pg_catalog"."text")"#),
+                params(&["O'Brien"])
+            )
+        );
+    }
+
+    #[test]
+    fn quoted_and_case_insensitive_column_names() {
+        assert_eq!(
+            condition(r#""Mixed Case" = x"#).0,
+            r#"("Mixed Case" = $1::text::"pg_catalog"."text")"#
+        );
+        assert_eq!(
+            condition("ID = 1").0,
+            r#"("id" = $1::text::"pg_catalog"."int4")"#
+        );
+        assert_eq!(error(r#""ID" = 1"#).message, r#"unknown column "ID""#);
+    }
+
+    #[test]
+    fn comparison_operators() {
+        for (source, sql) in [
+            ("id = 1", "="),
+            ("id != 1", "<>"),
+            ("id <> 1", "<>"),
+            ("id < 1", "<"),
+            ("id <= 1", "<="),
+            ("id > 1", ">"),
+            ("id >= 1", ">="),
+        ] {
+            assert_eq!(
+                condition(source).0,
+                format!(r#"("id" {} $1::text::"pg_catalog"."int4")"#, sql),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn null_and_in_checks() {
+        assert_eq!(condition("name is null").0, r#"("name" IS NULL)"#);
+        assert_eq!(condition("name IS NOT NULL").0, r#"("name" IS NOT NULL)"#);
+        assert_eq!(
+            condition("id not in (1, 2)"),
+            (
+                String::from(
+                    r#"("id" NOT IN ($1::text::"pg_catalog"."int4", $2::text::"pg_catalog"."int4"))"#
+                ),
+                params(&["1", "2"])
+            )
+        );
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(
+            condition("id = 1 or id = 2 and not (name = a or name = b)").0,
+            r#"(("id" = $1::text::"pg_catalog"."int4" OR ("id" = $2::text::"pg_catalog"."int4" AND NOT ("name" = $3::text::"pg_catalog"."text" OR "name" = $4::text::"pg_catalog"."text"))))"#
+        );
+    }
+
+    #[test]
+    fn contains_escapes_like_wildcards() {
+        assert_eq!(
+            condition(r"name ~ '50%_a\b'"),
+            (
+                String::from(r#"("name"::text ILIKE '%' || $1::text::"pg_catalog"."text" || '%')"#),
+                params(&[r"50\%\_a\\b"])
+            )
+        );
+        assert_eq!(
+            condition("name !~ x").0,
+            r#"("name"::text NOT ILIKE '%' || $1::text::"pg_catalog"."text" || '%')"#
+        );
+    }
+
+    #[test]
+    fn like_keeps_its_wildcards() {
+        assert_eq!(
+            condition("name like 'a%_'"),
+            (
+                String::from(r#"("name"::text LIKE $1::text::"pg_catalog"."text")"#),
+                params(&["a%_"])
+            )
+        );
+        assert_eq!(
+            condition("name ILIKE a%").0,
+            r#"("name"::text ILIKE $1::text::"pg_catalog"."text")"#
+        );
+    }
+
+    #[test]
+    fn values_are_checked_against_the_column_type() {
+        assert_eq!(
+            error("id = abc"),
+            FilterError {
+                message: String::from(r#""id" expects a int4, got "abc""#),
+                position: 5,
+            }
+        );
+        assert_eq!(
+            error("id in (1, 99999999999)").message,
+            r#""id" expects a int4, got "99999999999""#
+        );
+        assert_eq!(
+            error("created > 2024-13-01").message,
+            r#""created" expects a timestamptz, got "2024-13-01""#
+        );
+        assert!(Filter::parse("created > '2024-01-31 12:30:00+02'", &columns()).is_ok());
+        assert!(Filter::parse("created < now", &columns()).is_ok());
+        // Searching the text of a value doesn't care about its type
+        assert!(Filter::parse("id ~ abc", &columns()).is_ok());
+    }
+
+    #[test]
+    fn check_value_by_type() {
+        assert!(check_value(&Type::BOOL, "yes").is_ok());
+        assert!(check_value(&Type::BOOL, "maybe").is_err());
+        assert!(check_value(&Type::FLOAT8, "-Infinity").is_ok());
+        assert!(check_value(&Type::NUMERIC, "1.5e3").is_ok());
+        assert!(check_value(&Type::NUMERIC, "12abc").is_err());
+        assert!(check_value(&Type::DATE, "2024-02-29").is_ok());
+        assert!(check_value(&Type::DATE, "2023-02-29").is_err());
+        assert!(check_value(&Type::TIME, "25:00").is_err());
+        assert!(check_value(&Type::UUID, "{a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11}").is_ok());
+        assert!(check_value(&Type::UUID, "a0eebc99").is_err());
+        assert!(check_value(&Type::INET, "anything goes").is_ok());
+    }
+
+    #[test]
+    fn types_without_equality_are_searched_as_text() {
+        assert_eq!(
+            error("payload = x").message,
+            r#""payload" of type json can't be compared, use ~ to search its text"#
+        );
+        assert!(Filter::parse("payload ~ x", &columns()).is_ok());
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_token() {
+        assert_eq!(error("").message, "empty filter");
+        assert_eq!(
+            error("name = 'open"),
+            FilterError {
+                message: String::from("unterminated quote"),
+                position: 7,
+            }
+        );
+        assert_eq!(
+            error("nope = 1"),
+            FilterError {
+                message: String::from(r#"unknown column "nope""#),
+                position: 0,
+            }
+        );
+        assert_eq!(
+            error("name = null").message,
+            r#"use "is null" to compare with null"#
+        );
+        assert_eq!(
+            error("name"),
+            FilterError {
+                message: String::from(r#"expected an operator after "name""#),
+                position: 4,
+            }
+        );
+        assert_eq!(error("name is nul").message, r#"expected "null""#);
+        assert_eq!(error("id not 1").message, r#"expected "in" after "not""#);
+        assert_eq!(error("(id = 1").message, r#"expected ")""#);
+        assert_eq!(error("id = 1 )").message, r#"unexpected ")""#);
+        assert_eq!(error("id = ").message, r#"expected a value for "id""#);
+        assert_eq!(error("name = a; drop").message, r#"unexpected "drop""#);
+    }
+
+    #[test]
+    fn error_positions_count_characters() {
+        let error = error("name = 'é' and ö = 1");
+        assert_eq!(error.position, 15);
+        assert_eq!(error.to_string(), r#"unknown column "ö" (at 16)"#);
+    }
+
+    #[test]
+    fn equality_source_round_trips() {
+        let source = equality_source(&[
+            (String::from("Mixed Case"), String::from("it's")),
+            (String::from("id"), String::from("7")),
+        ]);
+        assert_eq!(source, r#""Mixed Case" = 'it''s' and "id" = '7'"#);
+        assert_eq!(
+            condition(&source),
+            (
+                String::from(
+                    r#"(("Mixed Case" = $1::text::"pg_catalog"."text" AND "id" = $2::text::"pg_catalog"."int4"))"#
+                ),
+                params(&["it's", "7"])
+            )
+        );
+    }
+}
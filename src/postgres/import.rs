@@ -0,0 +1,272 @@
+use tokio_postgres::types::Type;
+
+use crate::postgres::{
+    export::csv_field,
+    filter::check_value,
+    query::{quote_identifier, type_cast, TableName},
+};
+
+// Types a column of a new table can be given, in the order they're tried
+// when inferring the type from the data.
+pub const IMPORT_TYPES: [Type; 8] = [
+    Type::INT8,
+    Type::NUMERIC,
+    Type::BOOL,
+    Type::DATE,
+    Type::TIMESTAMPTZ,
+    Type::UUID,
+    Type::JSONB,
+    Type::TEXT,
+];
+
+// How a CSV file is read.
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    pub delimiter: char,
+    pub header: bool,
+    // Unquoted fields with this text are NULL, quoted ones never are, like
+    // COPY's CSV format
+    pub null_string: String,
+}
+
+// A record of the file along with the line it starts on.
+#[derive(Debug, Clone)]
+pub struct CsvRecord {
+    pub line: usize,
+    pub fields: Vec<Option<String>>,
+}
+
+// Split CSV text into records. Quoted fields can contain delimiters, line
+// breaks and doubled quotes.
+pub fn parse_csv(text: &str, options: &CsvOptions) -> Vec<CsvRecord> {
+    let mut records = Vec::new();
+    let mut fields: Vec<Option<String>> = Vec::new();
+    let mut field = String::new();
+    let mut is_quoted = false;
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+
+    let end_field = |field: &mut String, is_quoted: &mut bool, fields: &mut Vec<Option<String>>| {
+        let value = std::mem::take(field);
+        if !*is_quoted && value == options.null_string {
+            fields.push(None);
+        } else {
+            fields.push(Some(value));
+        }
+        *is_quoted = false;
+    };
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                c => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            }
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_quotes = true;
+                is_quoted = true;
+            }
+            c if c == options.delimiter => end_field(&mut field, &mut is_quoted, &mut fields),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                line += 1;
+                if !(fields.is_empty() && field.is_empty() && !is_quoted) {
+                    end_field(&mut field, &mut is_quoted, &mut fields);
+                    records.push(CsvRecord {
+                        line: record_line,
+                        fields: std::mem::take(&mut fields),
+                    });
+                }
+                record_line = line;
+            }
+            c => field.push(c),
+        }
+    }
+
+    if !(fields.is_empty() && field.is_empty() && !is_quoted) {
+        end_field(&mut field, &mut is_quoted, &mut fields);
+        records.push(CsvRecord {
+            line: record_line,
+            fields,
+        });
+    }
+
+    records
+}
+
+// The most specific of the import types every non NULL value fits.
+pub fn infer_type(values: &[&str]) -> Type {
+    let values: Vec<&&str> = values
+        .iter()
+        .filter(|value| !value.trim().is_empty())
+        .collect();
+    if values.is_empty() {
+        return Type::TEXT;
+    }
+
+    IMPORT_TYPES
+        .iter()
+        .find(|ty| {
+            values.iter().all(|value| match **ty {
+                Type::JSONB => {
+                    let value = value.trim_start();
+                    (value.starts_with('{') || value.starts_with('['))
+                        && serde_json::from_str::<serde_json::Value>(value).is_ok()
+                }
+                Type::TEXT => true,
+                _ => check_value(ty, value).is_ok(),
+            })
+        })
+        .cloned()
+        .unwrap_or(Type::TEXT)
+}
+
+// A CSV line of the given fields as COPY reads it with its default NULL: an
+// empty unquoted field is NULL so every other value is quoted.
+pub fn copy_csv_line(fields: &[Option<&str>]) -> String {
+    let mut line = fields
+        .iter()
+        .map(|field| match field {
+            Some(text) => format!("\"{}\"", text.replace('"', "\"\"")),
+            None => csv_field(None),
+        })
+        .collect::<Vec<String>>()
+        .join(",");
+    line.push('\n');
+    line
+}
+
+pub fn create_table_sql(table: &TableName, columns: &[(String, Type)]) -> String {
+    format!(
+        "CREATE TABLE {} ({})",
+        table.quoted(),
+        columns
+            .iter()
+            .map(|(name, ty)| format!("{} {}", quote_identifier(name), type_cast(ty)))
+            .collect::<Vec<String>>()
+            .join(", ")
+    )
+}
+
+pub fn copy_from_sql(table: &TableName, columns: &[&str]) -> String {
+    format!(
+        "COPY {} ({}) FROM STDIN WITH (FORMAT csv)",
+        table.quoted(),
+        columns
+            .iter()
+            .map(|name| quote_identifier(name))
+            .collect::<Vec<String>>()
+            .join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(delimiter: char, null_string: &str) -> CsvOptions {
+        CsvOptions {
+            delimiter,
+            header: true,
+            null_string: null_string.to_string(),
+        }
+    }
+
+    fn fields(records: &[CsvRecord]) -> Vec<(usize, Vec<Option<&str>>)> {
+        records
+            .iter()
+            .map(|record| {
+                (
+                    record.line,
+                    record.fields.iter().map(|field| field.as_deref()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn quoted_fields_keep_delimiters_quotes_and_line_breaks() {
+        let records = parse_csv(
+            "id,note\r\n1,\"a, \"\"b\"\"\"\n2,\"two\nlines\"\n\n3,last",
+            &options(',', ""),
+        );
+
+        assert_eq!(
+            fields(&records),
+            vec![
+                (1, vec![Some("id"), Some("note")]),
+                (2, vec![Some("1"), Some("a, \"b\"")]),
+                (3, vec![Some("2"), Some("two\nlines")]),
+                (6, vec![Some("3"), Some("last")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn only_unquoted_fields_are_null() {
+        let records = parse_csv("\\N;\"\\N\";;\"\"", &options(';', "\\N"));
+        assert_eq!(
+            fields(&records),
+            vec![(1, vec![None, Some("\\N"), Some(""), Some("")])]
+        );
+
+        let records = parse_csv("a,,\"\"", &options(',', ""));
+        assert_eq!(fields(&records), vec![(1, vec![Some("a"), None, Some("")])]);
+    }
+
+    #[test]
+    fn types_are_inferred_from_the_most_specific() {
+        assert_eq!(infer_type(&["1", "-20", ""]), Type::INT8);
+        assert_eq!(infer_type(&["1", "2.5"]), Type::NUMERIC);
+        assert_eq!(infer_type(&["true", "f"]), Type::BOOL);
+        assert_eq!(infer_type(&["2024-01-31"]), Type::DATE);
+        assert_eq!(
+            infer_type(&["a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11"]),
+            Type::UUID
+        );
+        assert_eq!(infer_type(&[r#"{"a": 1}"#, "[]"]), Type::JSONB);
+        assert_eq!(infer_type(&["1", "one"]), Type::TEXT);
+        assert_eq!(infer_type(&["", " "]), Type::TEXT);
+    }
+
+    #[test]
+    fn copy_lines_quote_everything_but_null() {
+        assert_eq!(
+            copy_csv_line(&[Some("1"), None, Some(""), Some("say \"hi\"")]),
+            "\"1\",,\"\",\"say \"\"hi\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn statements_quote_names() {
+        assert_eq!(
+            create_table_sql(
+                &TableName::new("public", "New Table"),
+                &[
+                    (String::from("id"), Type::INT8),
+                    (String::from("Note"), Type::TEXT)
+                ]
+            ),
+            r#"CREATE TABLE "public"."New Table" ("id" "pg_catalog"."int8", "Note" "pg_catalog"."text")"#
+        );
+        assert_eq!(
+            copy_from_sql(&TableName::new("sales", "orders"), &["id", "Note"]),
+            r#"COPY "sales"."orders" ("id", "Note") FROM STDIN WITH (FORMAT csv)"#
+        );
+    }
+}
@@ -1 +1,11 @@
+pub mod catalog;
 pub mod connection_manager;
+pub mod cursor;
+pub mod data;
+pub mod ddl;
+pub mod edit;
+pub mod export;
+pub mod filter;
+pub mod import;
+pub mod query;
+pub mod structure;
@@ -0,0 +1,813 @@
+use std::fmt::{self, Display};
+use tokio_postgres::types::Type;
+
+use crate::postgres::data::{is_decodable, Column, Value};
+
+pub const PAGE_SIZES: [usize; 7] = [10, 25, 50, 100, 250, 500, 1000];
+const DEFAULT_PAGE_SIZE: usize = 50;
+
+// Quote an identifier the way postgres' quote_ident does, doubling any
+// embedded double quotes.
+pub fn quote_identifier(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+// An identifier as postgres prints it, quoted only when it has to be.
+fn display_identifier(identifier: &str) -> String {
+    let is_plain = identifier
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+        && identifier
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '$');
+
+    if is_plain {
+        identifier.to_string()
+    } else {
+        quote_identifier(identifier)
+    }
+}
+
+// A table qualified by its schema.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TableName {
+    pub schema: String,
+    pub name: String,
+}
+
+impl TableName {
+    pub fn new(schema: &str, name: &str) -> Self {
+        Self {
+            schema: schema.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    // Parse a name as written in SQL, `orders`, `sales.orders` or
+    // `"Sales"."Big Orders"`. Unquoted parts fold to lower case and a name
+    // without a schema is looked up in `default_schema`.
+    pub fn parse(text: &str, default_schema: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut chars = text.trim().chars().peekable();
+
+        loop {
+            let mut part = String::new();
+            if chars.peek() == Some(&'"') {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            part.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => part.push(c),
+                        None => return Err(format!("unterminated quoted name in {}", text)),
+                    }
+                }
+            } else {
+                while let Some(c) = chars.next_if(|c| *c != '.') {
+                    part.push(c);
+                }
+                part = part.trim().to_lowercase();
+            }
+
+            if part.is_empty() {
+                return Err(format!("{} is not a table name", text.trim()));
+            }
+            parts.push(part);
+
+            match chars.next() {
+                Some('.') => {}
+                Some(_) => return Err(format!("{} is not a table name", text.trim())),
+                None => break,
+            }
+        }
+
+        match parts.as_slice() {
+            [name] => Ok(Self::new(default_schema, name)),
+            [schema, name] => Ok(Self::new(schema, name)),
+            _ => Err(format!("{} is not a table name", text.trim())),
+        }
+    }
+
+    // The name for use in a statement, e.g. "public"."Mixed Case".
+    pub fn quoted(&self) -> String {
+        format!(
+            "{}.{}",
+            quote_identifier(&self.schema),
+            quote_identifier(&self.name)
+        )
+    }
+}
+
+impl Display for TableName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}",
+            display_identifier(&self.schema),
+            display_identifier(&self.name)
+        )
+    }
+}
+
+// Quote a string literal the way postgres' quote_literal does, literals
+// containing backslashes are written as escape strings.
+pub fn quote_literal(text: &str) -> String {
+    let quoted = text.replace('\'', "''");
+    if quoted.contains('\\') {
+        format!("E'{}'", quoted.replace('\\', "\\\\"))
+    } else {
+        format!("'{}'", quoted)
+    }
+}
+
+// Fully qualified name of a type usable in a cast, e.g. "pg_catalog"."int4".
+pub fn type_cast(ty: &Type) -> String {
+    format!(
+        "{}.{}",
+        quote_identifier(ty.schema()),
+        quote_identifier(ty.name())
+    )
+}
+
+// Select list for the given columns. Columns the binary decoder can't handle
+// are selected as ::text so postgres renders them for us.
+pub fn select_list(columns: &[Column]) -> String {
+    if columns.iter().all(|column| is_decodable(&column.type_)) {
+        return String::from("*");
+    }
+
+    columns
+        .iter()
+        .map(|column| {
+            let identifier = quote_identifier(&column.name);
+            if is_decodable(&column.type_) {
+                identifier
+            } else {
+                format!("{}::text", identifier)
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+// A SELECT against a single table.
+//
+// Parameters are always sent as text and cast to the column type inside the
+// statement, which lets us bind values of any type from their display text.
+#[derive(Debug, Clone, Default)]
+pub struct TableQuery {
+    pub table: String,
+    pub select_list: String,
+    pub conditions: Vec<String>,
+    pub order_by: Vec<String>,
+    pub params: Vec<Option<String>>,
+    // Quoted columns and value expressions written by an INSERT or UPDATE
+    pub assignments: Vec<(String, String)>,
+    // Let an INSERT write identity columns that are always generated
+    pub overriding_system_value: bool,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+    // Write bound values into the statement as literals instead of
+    // parameters, for statements that can't take any like COPY
+    pub inline_params: bool,
+}
+
+impl TableQuery {
+    pub fn new(table_name: &TableName, columns: &[Column]) -> Self {
+        Self {
+            table: table_name.quoted(),
+            select_list: select_list(columns),
+            ..TableQuery::default()
+        }
+    }
+
+    // Add a parameter and return the placeholder that references it.
+    pub fn bind(&mut self, value: Option<String>, ty: &Type) -> String {
+        if self.inline_params {
+            let literal = match value {
+                Some(value) => quote_literal(&value),
+                None => String::from("NULL"),
+            };
+            return format!("{}::text::{}", literal, type_cast(ty));
+        }
+
+        self.params.push(value);
+        format!("${}::text::{}", self.params.len(), type_cast(ty))
+    }
+
+    fn where_clause(&self) -> String {
+        if self.conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", self.conditions.join(" AND "))
+        }
+    }
+
+    pub fn to_sql(&self) -> String {
+        let mut sql = format!(
+            "SELECT {} FROM {}{}",
+            self.select_list,
+            self.table,
+            self.where_clause()
+        );
+
+        if !self.order_by.is_empty() {
+            sql.push_str(&format!(" ORDER BY {}", self.order_by.join(", ")));
+        }
+
+        if let Some(limit) = self.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+
+        if let Some(offset) = self.offset {
+            sql.push_str(&format!(" OFFSET {}", offset));
+        }
+
+        sql
+    }
+
+    // Number of rows matching the conditions, ignoring order and limits.
+    pub fn to_count_sql(&self) -> String {
+        format!("SELECT count(*) FROM {}{}", self.table, self.where_clause())
+    }
+
+    // UPDATE of the rows matching the conditions.
+    pub fn to_update_sql(&self) -> String {
+        let assignments: Vec<String> = self
+            .assignments
+            .iter()
+            .map(|(column, value)| format!("{} = {}", column, value))
+            .collect();
+
+        format!(
+            "UPDATE {} SET {}{}",
+            self.table,
+            assignments.join(", "),
+            self.where_clause()
+        )
+    }
+
+    // INSERT of a single row, columns left out take their default.
+    pub fn to_insert_sql(&self) -> String {
+        if self.assignments.is_empty() {
+            return format!("INSERT INTO {} DEFAULT VALUES", self.table);
+        }
+
+        let (columns, values): (Vec<&str>, Vec<&str>) = self
+            .assignments
+            .iter()
+            .map(|(column, value)| (column.as_str(), value.as_str()))
+            .unzip();
+        format!(
+            "INSERT INTO {} ({}){} VALUES ({})",
+            self.table,
+            columns.join(", "),
+            if self.overriding_system_value {
+                " OVERRIDING SYSTEM VALUE"
+            } else {
+                ""
+            },
+            values.join(", ")
+        )
+    }
+
+    pub fn to_delete_sql(&self) -> String {
+        format!("DELETE FROM {}{}", self.table, self.where_clause())
+    }
+
+    // A data-modifying statement returning the rows it touched as they are
+    // selected.
+    pub fn returning(&self, sql: String) -> String {
+        format!("{} RETURNING {}", sql, self.select_list)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortKey {
+    pub column: String,
+    pub descending: bool,
+}
+
+// Cycle a column through ascending, descending and unsorted.
+//
+// A single column sort replaces any other sort keys, a multi column sort adds
+// the column to the end of the existing keys or cycles it in place.
+pub fn cycle_sort(sort: &mut Vec<SortKey>, column: &str, multi_column: bool) {
+    if !multi_column {
+        sort.retain(|key| key.column == column);
+    }
+
+    match sort.iter().position(|key| key.column == column) {
+        Some(index) if sort[index].descending => {
+            sort.remove(index);
+        }
+        Some(index) => sort[index].descending = true,
+        None => sort.push(SortKey {
+            column: column.to_string(),
+            descending: false,
+        }),
+    }
+}
+
+// ORDER BY items for the sort keys.
+pub fn sort_order(sort: &[SortKey]) -> Vec<String> {
+    sort.iter()
+        .map(|key| {
+            let identifier = quote_identifier(&key.column);
+            if key.descending {
+                format!("{} DESC", identifier)
+            } else {
+                identifier
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageRequest {
+    First,
+    Next,
+    Previous,
+    Last,
+    Reload,
+}
+
+#[derive(Debug, Clone)]
+pub struct Pagination {
+    pub page_size: usize,
+    pub page_index: usize,
+    // Position of the first row on the page, which isn't always a multiple of
+    // the page size when paging by key
+    pub first_row: usize,
+    pub estimated_total: Option<i64>,
+}
+
+impl Default for Pagination {
+    fn default() -> Self {
+        Self {
+            page_size: DEFAULT_PAGE_SIZE,
+            page_index: 0,
+            first_row: 0,
+            estimated_total: None,
+        }
+    }
+}
+
+impl Pagination {
+    pub fn larger_page_size(&mut self) {
+        if let Some(size) = PAGE_SIZES.iter().find(|size| **size > self.page_size) {
+            self.page_size = *size;
+        }
+    }
+
+    pub fn smaller_page_size(&mut self) {
+        if let Some(size) = PAGE_SIZES.iter().rev().find(|size| **size < self.page_size) {
+            self.page_size = *size;
+        }
+    }
+
+    pub fn last_page_index(&self) -> usize {
+        match self.estimated_total {
+            Some(total) if total > 0 => (total as usize - 1) / self.page_size,
+            _ => 0,
+        }
+    }
+}
+
+// The query for a page together with how to interpret its result.
+//
+// Pages before the current one are fetched in reverse key order, their rows
+// have to be reversed again before being displayed.
+#[derive(Debug, Clone)]
+pub struct PageQuery {
+    pub request: PageRequest,
+    pub query: TableQuery,
+    pub reversed: bool,
+}
+
+// Build the query for the requested page.
+//
+// With a primary key the page is located with a row comparison against the
+// key of the first or last row on the current page (keyset pagination), so
+// deep pages don't have to skip over all previous rows. Without one, or when
+// the user sorts by other columns, we fall back to LIMIT/OFFSET with the key
+// appended to the sort to keep the order stable.
+pub fn page_query(
+    mut query: TableQuery,
+    columns: &[Column],
+    primary_key: &[String],
+    sort: &[SortKey],
+    rows: &[Vec<Value>],
+    pagination: &Pagination,
+    request: PageRequest,
+) -> Option<PageQuery> {
+    let page_size = pagination.page_size;
+    query.limit = Some(page_size);
+
+    let key_indexes: Option<Vec<usize>> = primary_key
+        .iter()
+        .map(|key| columns.iter().position(|column| &column.name == key))
+        .collect();
+
+    let key_indexes = match key_indexes {
+        Some(key_indexes) if !key_indexes.is_empty() && sort.is_empty() => key_indexes,
+        _ => {
+            query.order_by = sort_order(sort);
+
+            if !sort.is_empty() {
+                for key in primary_key {
+                    if !sort.iter().any(|sort_key| &sort_key.column == key) {
+                        query.order_by.push(quote_identifier(key));
+                    }
+                }
+            }
+
+            let page_index = match request {
+                PageRequest::First => 0,
+                PageRequest::Next => pagination.page_index + 1,
+                PageRequest::Previous if pagination.page_index == 0 => return None,
+                PageRequest::Previous => pagination.page_index - 1,
+                PageRequest::Last => pagination.last_page_index(),
+                PageRequest::Reload => pagination.page_index,
+            };
+            query.offset = Some(page_index * page_size);
+
+            return Some(PageQuery {
+                request,
+                query,
+                reversed: false,
+            });
+        }
+    };
+
+    let key_list = key_indexes
+        .iter()
+        .map(|index| quote_identifier(&columns[*index].name))
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    let order = |descending: bool| -> Vec<String> {
+        key_indexes
+            .iter()
+            .map(|index| {
+                let identifier = quote_identifier(&columns[*index].name);
+                if descending {
+                    format!("{} DESC", identifier)
+                } else {
+                    identifier
+                }
+            })
+            .collect()
+    };
+
+    let compare_with_row = |query: &mut TableQuery, operator: &str, row: &Vec<Value>| {
+        let values = key_indexes
+            .iter()
+            .map(|index| {
+                let value = row.get(*index).and_then(|value| value.to_text());
+                query.bind(value, &columns[*index].type_)
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+        query
+            .conditions
+            .push(format!("({}) {} ({})", key_list, operator, values));
+    };
+
+    let reversed = match (request, rows.first(), rows.last()) {
+        (PageRequest::Next, _, Some(last_row)) => {
+            compare_with_row(&mut query, ">", last_row);
+            false
+        }
+        (PageRequest::Previous, _, _) if pagination.page_index == 0 => return None,
+        (PageRequest::Previous, Some(first_row), _) => {
+            compare_with_row(&mut query, "<", first_row);
+            true
+        }
+        (PageRequest::Reload, Some(first_row), _) => {
+            compare_with_row(&mut query, ">=", first_row);
+            false
+        }
+        (PageRequest::Last, _, _) => true,
+        _ => false,
+    };
+
+    query.order_by = order(reversed);
+
+    Some(PageQuery {
+        request,
+        query,
+        reversed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn orders_columns() -> Vec<Column> {
+        vec![
+            Column::new(String::from("id"), Type::INT4),
+            Column::new(String::from("line"), Type::INT4),
+            Column::new(String::from("note"), Type::TEXT),
+        ]
+    }
+
+    fn orders_query() -> TableQuery {
+        TableQuery::new(&TableName::new("public", "orders"), &orders_columns())
+    }
+
+    fn rows(values: &[(i64, i64)]) -> Vec<Vec<Value>> {
+        values
+            .iter()
+            .map(|(id, line)| {
+                vec![
+                    Value::Int(*id),
+                    Value::Int(*line),
+                    Value::Text(String::from("x")),
+                ]
+            })
+            .collect()
+    }
+
+    fn pagination(page_size: usize, page_index: usize, estimated_total: Option<i64>) -> Pagination {
+        Pagination {
+            page_size,
+            page_index,
+            first_row: page_index * page_size,
+            estimated_total,
+        }
+    }
+
+    fn keyset_page(
+        primary_key: &[&str],
+        rows: &[Vec<Value>],
+        pagination: &Pagination,
+        request: PageRequest,
+    ) -> Option<PageQuery> {
+        let primary_key: Vec<String> = primary_key.iter().map(|key| key.to_string()).collect();
+        page_query(
+            orders_query(),
+            &orders_columns(),
+            &primary_key,
+            &[],
+            rows,
+            pagination,
+            request,
+        )
+    }
+
+    fn sort_key(column: &str, descending: bool) -> SortKey {
+        SortKey {
+            column: column.to_string(),
+            descending,
+        }
+    }
+
+    #[test]
+    fn first_page_by_key() {
+        let page = keyset_page(&["id"], &[], &pagination(10, 0, None), PageRequest::First).unwrap();
+
+        assert!(!page.reversed);
+        assert_eq!(
+            page.query.to_sql(),
+            r#"SELECT * FROM "public"."orders" ORDER BY "id" LIMIT 10"#
+        );
+        assert!(page.query.params.is_empty());
+    }
+
+    #[test]
+    fn next_page_compares_with_the_last_row() {
+        let rows = rows(&[(1, 1), (2, 1)]);
+        let page = keyset_page(&["id"], &rows, &pagination(2, 0, None), PageRequest::Next).unwrap();
+
+        assert!(!page.reversed);
+        assert_eq!(
+            page.query.to_sql(),
+            r#"SELECT * FROM "public"."orders" WHERE ("id") > ($1::text::"pg_catalog"."int4") ORDER BY "id" LIMIT 2"#
+        );
+        assert_eq!(page.query.params, vec![Some(String::from("2"))]);
+    }
+
+    #[test]
+    fn next_page_compares_composite_keys_as_a_row() {
+        let rows = rows(&[(1, 1), (1, 2)]);
+        let page = keyset_page(
+            &["id", "line"],
+            &rows,
+            &pagination(2, 0, None),
+            PageRequest::Next,
+        )
+        .unwrap();
+
+        assert_eq!(
+            page.query.to_sql(),
+            r#"SELECT * FROM "public"."orders" WHERE ("id", "line") > ($1::text::"pg_catalog"."int4", $2::text::"pg_catalog"."int4") ORDER BY "id", "line" LIMIT 2"#
+        );
+        assert_eq!(
+            page.query.params,
+            vec![Some(String::from("1")), Some(String::from("2"))]
+        );
+    }
+
+    #[test]
+    fn previous_page_is_read_backwards_from_the_first_row() {
+        let rows = rows(&[(3, 1), (3, 2)]);
+        let page = keyset_page(
+            &["id", "line"],
+            &rows,
+            &pagination(2, 1, None),
+            PageRequest::Previous,
+        )
+        .unwrap();
+
+        assert!(page.reversed);
+        assert_eq!(
+            page.query.to_sql(),
+            r#"SELECT * FROM "public"."orders" WHERE ("id", "line") < ($1::text::"pg_catalog"."int4", $2::text::"pg_catalog"."int4") ORDER BY "id" DESC, "line" DESC LIMIT 2"#
+        );
+        assert_eq!(
+            page.query.params,
+            vec![Some(String::from("3")), Some(String::from("1"))]
+        );
+    }
+
+    #[test]
+    fn no_previous_page_before_the_first() {
+        let rows = rows(&[(1, 1)]);
+        let page = keyset_page(
+            &["id"],
+            &rows,
+            &pagination(2, 0, None),
+            PageRequest::Previous,
+        );
+
+        assert!(page.is_none());
+    }
+
+    #[test]
+    fn last_page_is_read_backwards_from_the_end() {
+        let rows = rows(&[(1, 1)]);
+        let page = keyset_page(
+            &["id", "line"],
+            &rows,
+            &pagination(2, 0, None),
+            PageRequest::Last,
+        )
+        .unwrap();
+
+        assert!(page.reversed);
+        assert_eq!(
+            page.query.to_sql(),
+            r#"SELECT * FROM "public"."orders" ORDER BY "id" DESC, "line" DESC LIMIT 2"#
+        );
+        assert!(page.query.params.is_empty());
+    }
+
+    #[test]
+    fn reload_starts_at_the_first_row() {
+        let rows = rows(&[(5, 1), (6, 1)]);
+        let page =
+            keyset_page(&["id"], &rows, &pagination(2, 2, None), PageRequest::Reload).unwrap();
+
+        assert_eq!(
+            page.query.to_sql(),
+            r#"SELECT * FROM "public"."orders" WHERE ("id") >= ($1::text::"pg_catalog"."int4") ORDER BY "id" LIMIT 2"#
+        );
+        assert_eq!(page.query.params, vec![Some(String::from("5"))]);
+    }
+
+    #[test]
+    fn sorted_pages_use_offsets_with_the_key_appended() {
+        let primary_key = vec![String::from("id")];
+        let sort = vec![sort_key("note", true)];
+        let page = page_query(
+            orders_query(),
+            &orders_columns(),
+            &primary_key,
+            &sort,
+            &rows(&[(1, 1)]),
+            &pagination(10, 1, None),
+            PageRequest::Next,
+        )
+        .unwrap();
+
+        assert!(!page.reversed);
+        assert_eq!(
+            page.query.to_sql(),
+            r#"SELECT * FROM "public"."orders" ORDER BY "note" DESC, "id" LIMIT 10 OFFSET 20"#
+        );
+    }
+
+    #[test]
+    fn last_page_without_a_key_is_found_from_the_total() {
+        let page = page_query(
+            orders_query(),
+            &orders_columns(),
+            &[],
+            &[],
+            &[],
+            &pagination(10, 0, Some(45)),
+            PageRequest::Last,
+        )
+        .unwrap();
+
+        assert_eq!(page.query.offset, Some(40));
+        assert_eq!(
+            page.query.to_sql(),
+            r#"SELECT * FROM "public"."orders" LIMIT 10 OFFSET 40"#
+        );
+    }
+
+    #[test]
+    fn cycle_sort_goes_ascending_descending_unsorted() {
+        let mut sort = Vec::new();
+
+        cycle_sort(&mut sort, "id", false);
+        assert_eq!(sort, vec![sort_key("id", false)]);
+        cycle_sort(&mut sort, "id", false);
+        assert_eq!(sort, vec![sort_key("id", true)]);
+        cycle_sort(&mut sort, "id", false);
+        assert!(sort.is_empty());
+    }
+
+    #[test]
+    fn single_column_sort_replaces_the_others() {
+        let mut sort = vec![sort_key("id", false), sort_key("line", true)];
+
+        cycle_sort(&mut sort, "note", false);
+        assert_eq!(sort, vec![sort_key("note", false)]);
+
+        let mut sort = vec![sort_key("id", false), sort_key("line", false)];
+        cycle_sort(&mut sort, "line", false);
+        assert_eq!(sort, vec![sort_key("line", true)]);
+    }
+
+    #[test]
+    fn multi_column_sort_appends_and_cycles_in_place() {
+        let mut sort = vec![sort_key("id", false)];
+
+        cycle_sort(&mut sort, "line", true);
+        assert_eq!(sort, vec![sort_key("id", false), sort_key("line", false)]);
+        cycle_sort(&mut sort, "id", true);
+        assert_eq!(sort, vec![sort_key("id", true), sort_key("line", false)]);
+        cycle_sort(&mut sort, "id", true);
+        assert_eq!(sort, vec![sort_key("line", false)]);
+        assert_eq!(sort_order(&sort), vec![String::from(r#""line""#)]);
+    }
+
+    #[test]
+    fn table_names_parse_like_sql() {
+        assert_eq!(
+            TableName::parse(" Orders ", "public"),
+            Ok(TableName::new("public", "orders"))
+        );
+        assert_eq!(
+            TableName::parse("Sales.Orders", "public"),
+            Ok(TableName::new("sales", "orders"))
+        );
+        assert_eq!(
+            TableName::parse(r#""Sales"."Big ""Orders"".v2""#, "public"),
+            Ok(TableName::new("Sales", r#"Big "Orders".v2"#))
+        );
+        assert_eq!(
+            TableName::parse("a.b.c", "public"),
+            Err(String::from("a.b.c is not a table name"))
+        );
+        assert_eq!(
+            TableName::parse("sales.", "public"),
+            Err(String::from("sales. is not a table name"))
+        );
+        assert_eq!(
+            TableName::parse(r#""x"y"#, "public"),
+            Err(String::from(r#""x"y is not a table name"#))
+        );
+        assert_eq!(
+            TableName::parse(r#""open"#, "public"),
+            Err(String::from(r#"unterminated quoted name in "open"#))
+        );
+    }
+
+    #[test]
+    fn table_names_are_quoted_only_where_needed_for_display() {
+        let table = TableName::new("public", "orders");
+        assert_eq!(table.to_string(), "public.orders");
+        assert_eq!(table.quoted(), r#""public"."orders""#);
+
+        let table = TableName::new("Sales", "big orders$1");
+        assert_eq!(table.to_string(), r#""Sales"."big orders$1""#);
+        assert_eq!(table.quoted(), r#""Sales"."big orders$1""#);
+        assert_eq!(TableName::new("s", "_t$1").to_string(), "s._t$1");
+        assert_eq!(TableName::new("s", "1t").to_string(), r#"s."1t""#);
+    }
+
+    #[test]
+    fn literals_are_quoted_like_quote_literal() {
+        assert_eq!(quote_literal("it's"), "'it''s'");
+        assert_eq!(quote_literal(r"C:\tmp"), r"E'C:\\tmp'");
+    }
+}
@@ -0,0 +1,53 @@
+// A column as defined in the catalog. The collation is only set when it
+// isn't the default one of the type.
+#[derive(Debug, Clone)]
+pub struct ColumnDefinition {
+    pub name: String,
+    pub type_name: String,
+    pub not_null: bool,
+    pub default: Option<String>,
+    pub collation: Option<String>,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TableConstraint {
+    pub name: String,
+    // "primary key", "unique", "check", "foreign key" or "exclusion"
+    pub kind: String,
+    pub definition: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct TableIndex {
+    pub name: String,
+    pub definition: String,
+    pub size: String,
+    pub is_valid: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct TableTrigger {
+    pub name: String,
+    pub definition: String,
+    pub is_enabled: bool,
+}
+
+// Everything the structure view shows about a relation, read from
+// pg_catalog.
+#[derive(Debug, Clone, Default)]
+pub struct TableStructure {
+    pub columns: Vec<ColumnDefinition>,
+    pub constraints: Vec<TableConstraint>,
+    pub indexes: Vec<TableIndex>,
+    pub triggers: Vec<TableTrigger>,
+}
+
+// A column as the diagram of a schema shows it.
+#[derive(Debug, Clone)]
+pub struct DiagramColumn {
+    pub name: String,
+    pub type_name: String,
+    pub is_primary_key: bool,
+    pub is_foreign_key: bool,
+}
@@ -0,0 +1,76 @@
+use serde_json::{Map, Value};
+use std::{env, fs, io, path::PathBuf};
+
+use crate::widgets::column_layout::ColumnLayout;
+
+const STATE_DIRECTORY: &str = "postgres-tui";
+const STATE_FILE: &str = "state.json";
+
+// Settings remembered across sessions, kept as JSON in the user's state
+// directory ($XDG_STATE_HOME or ~/.local/state).
+#[derive(Default)]
+pub struct StateFile {
+    path: Option<PathBuf>,
+    column_layouts: Map<String, Value>,
+}
+
+fn state_path() -> Option<PathBuf> {
+    let state_home = match env::var("XDG_STATE_HOME") {
+        Ok(state_home) if !state_home.is_empty() => PathBuf::from(state_home),
+        _ => PathBuf::from(env::var("HOME").ok()?).join(".local/state"),
+    };
+
+    Some(state_home.join(STATE_DIRECTORY).join(STATE_FILE))
+}
+
+impl StateFile {
+    // A missing or unreadable state file starts out empty, it is replaced on
+    // the next save.
+    pub fn load() -> Self {
+        let path = state_path();
+
+        let column_layouts = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str::<Value>(&contents).ok())
+            .and_then(|state| state["column_layouts"].as_object().cloned())
+            .unwrap_or_default();
+
+        Self {
+            path,
+            column_layouts,
+        }
+    }
+
+    pub fn column_layout(&self, table_key: &str) -> ColumnLayout {
+        self.column_layouts
+            .get(table_key)
+            .map(ColumnLayout::from_json)
+            .unwrap_or_default()
+    }
+
+    pub fn set_column_layout(&mut self, table_key: &str, layout: &ColumnLayout) -> io::Result<()> {
+        if layout.is_default() {
+            self.column_layouts.remove(table_key);
+        } else {
+            self.column_layouts
+                .insert(table_key.to_string(), layout.to_json());
+        }
+
+        self.save()
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+
+        let state = serde_json::json!({ "column_layouts": self.column_layouts });
+        fs::write(path, serde_json::to_string_pretty(&state)?)
+    }
+}
@@ -0,0 +1,120 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{
+        block::{Position, Title},
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap,
+    },
+    Frame,
+};
+
+use crate::{
+    ui::{centered_rect, statement_lines},
+    widgets::{
+        change_review::ChangeReview,
+        change_set::{staged_value, Change, ChangeKind, ChangeSet},
+    },
+};
+
+// Statement of the change with its parameters, followed by the values it
+// removes and the ones it writes.
+fn change_lines(change: &Change) -> Vec<Line<'static>> {
+    let removed_style = Style::default().fg(Color::LightRed);
+    let added_style = Style::default().fg(Color::LightGreen);
+
+    let mut lines = statement_lines(change.sql(), &change.query.params);
+    lines.push(Line::default());
+
+    let diff_line = |sign: &str, column: &str, value: String, style: Style| {
+        Line::from(vec![
+            Span::styled(format!("{} {}: ", sign, column), style),
+            Span::raw(value),
+        ])
+    };
+    match &change.kind {
+        ChangeKind::Update {
+            column,
+            before,
+            after,
+        } => {
+            lines.push(diff_line("-", column, before.to_string(), removed_style));
+            lines.push(diff_line(
+                "+",
+                column,
+                staged_value(after).to_string(),
+                added_style,
+            ));
+        }
+        ChangeKind::Insert(values) => {
+            lines.extend(values.iter().map(|(column, value)| {
+                diff_line("+", column, staged_value(value).to_string(), added_style)
+            }));
+        }
+        ChangeKind::Delete(values) => {
+            lines.extend(
+                values.iter().map(|(column, value)| {
+                    diff_line("-", column, value.to_string(), removed_style)
+                }),
+            );
+        }
+    }
+
+    lines
+}
+
+pub fn render(f: &mut Frame, review: &ChangeReview, changes: &ChangeSet) {
+    let area = centered_rect(80, 80, f.size());
+
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(format!(" Pending changes ({}) ", changes.len()))
+        .title(
+            Title::from(
+                " j/k: move · u: unstage · enter: apply all · X: discard all · esc: close ",
+            )
+            .position(Position::Bottom)
+            .alignment(Alignment::Right),
+        );
+
+    if let Some(error) = &review.error {
+        block = block.title(
+            Title::from(Line::styled(
+                format!(" {} ", error),
+                Style::default().fg(Color::LightRed),
+            ))
+            .position(Position::Bottom)
+            .alignment(Alignment::Left),
+        );
+    }
+
+    let inner_area = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+
+    // At least one line even when the popup is shorter than three lines
+    let list_height = (changes.len() as u16).min(inner_area.height / 3).max(1);
+    let areas = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(list_height + 1), Constraint::Min(0)])
+        .split(inner_area);
+
+    let items: Vec<ListItem> = changes
+        .changes
+        .iter()
+        .map(|change| ListItem::new(change.summary()))
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::BOTTOM))
+        .highlight_style(Style::default().bg(Color::DarkGray));
+    let mut state = ListState::default().with_selected(Some(review.selected));
+    f.render_stateful_widget(list, areas[0], &mut state);
+
+    if let Some(change) = changes.changes.get(review.selected) {
+        f.render_widget(
+            Paragraph::new(change_lines(change)).wrap(Wrap { trim: false }),
+            areas[1],
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use ratatui::{
+    layout::Alignment,
+    style::{Color, Style},
+    text::Line,
+    widgets::{
+        block::{Position, Title},
+        Block, Borders, Clear, Paragraph, Wrap,
+    },
+    Frame,
+};
+
+use crate::{
+    postgres::query::TableQuery,
+    ui::{centered_rect, statement_lines},
+    widgets::undo_log::AppliedChange,
+};
+
+// Confirmation listing what undoing the last applied changes does and the
+// statements it runs.
+pub fn render(f: &mut Frame, changes: &[AppliedChange], statements: &[(String, TableQuery)]) {
+    let area = centered_rect(70, 60, f.size());
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::LightRed))
+        .title(format!(
+            " Undo the last {} applied change{} ",
+            changes.len(),
+            if changes.len() == 1 { "" } else { "s" }
+        ))
+        .title(
+            Title::from(" y: undo · n/esc: cancel ")
+                .position(Position::Bottom)
+                .alignment(Alignment::Right),
+        );
+
+    let mut lines = Vec::new();
+    for (change, (sql, query)) in changes.iter().rev().zip(statements) {
+        lines.push(Line::styled(
+            change.summary(),
+            Style::default().fg(Color::Yellow),
+        ));
+        lines.extend(statement_lines(sql.clone(), &query.params));
+        lines.push(Line::default());
+    }
+
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
@@ -1,44 +1,225 @@
-use crate::app::{App, FocusElement};
-use cli_log::info;
+use crate::{
+    app::{App, FocusElement, MainView},
+    postgres::data::Value,
+    ui::{ddl, er_diagram, object_details, structure},
+    widgets::{
+        data_grid::{truncate, DataGrid, GridState},
+        record_view::RecordView,
+        result_search::ResultSearch,
+    },
+};
 use ratatui::{
-    layout::Rect,
+    layout::{Alignment, Rect},
     style::{Color, Style},
-    widgets::{Block, Borders, Paragraph},
+    text::Line,
+    widgets::{
+        block::{Position, Title},
+        Block, Borders,
+    },
     Frame,
 };
 
-pub fn render(f: &mut Frame, app: &App, area: Rect) {
+pub fn render(f: &mut Frame, app: &mut App, area: Rect) {
     let (render_color, title) = match app.focused_element {
         FocusElement::Main => (Color::Green, " Main View (focused) "),
         _ => (Color::Red, " Main View "),
     };
 
     let default_style = Style::default().fg(render_color);
+    let expanded_view = app.expanded_view;
+    let search = &mut app.search;
+    let changes = &app.changes;
 
     let block = Block::default()
         .title(title)
         .borders(Borders::ALL)
-        .style(default_style);
+        .border_style(default_style)
+        .title_style(default_style);
 
-    match app.cluster.get_current_selected_table() {
-        Some(current_table) => {
-            let column_names = current_table.columns.clone().join(",");
-            info!("Column names: {}", column_names);
-            let names = Paragraph::new(column_names).block(Block::default().borders(Borders::ALL));
-            f.render_widget(names, area);
+    if app.main_view == MainView::Object {
+        if let Some(details) = app.object_details.as_ref() {
+            object_details::render(f, details, block, area);
+            return;
         }
-        None => {}
-    };
+    }
 
-    match app.cluster.get_current_data() {
-        Some(current_data) => {
-            let names = Paragraph::new(current_data.join("\n"))
-                .block(Block::default().borders(Borders::ALL));
-            info!("Data table Data: {:?}", current_data);
-            f.render_widget(names, area);
+    if app.main_view == MainView::Structure {
+        if let Some(view) = app.structure_view.as_mut() {
+            structure::render(f, view, block, area);
+            return;
         }
-        None => {}
-    };
+    }
+
+    if app.main_view == MainView::Ddl {
+        if let Some(view) = app.ddl_view.as_mut() {
+            ddl::render(f, view, block, area);
+            return;
+        }
+    }
+
+    if app.main_view == MainView::Diagram {
+        if let Some(diagram) = app.er_diagram.as_mut() {
+            er_diagram::render(f, diagram, block, area);
+            return;
+        }
+    }
+
+    if app.main_view == MainView::QueryResult {
+        if let Some(query_result) = app.query_result.as_mut() {
+            let block = block
+                .title(
+                    Title::from(format!(
+                        " {} ",
+                        truncate(
+                            &query_result.sql.replace('\n', " "),
+                            area.width as usize / 2
+                        )
+                    ))
+                    .alignment(Alignment::Right),
+                )
+                .title(
+                    Title::from(format!(" {} ", query_result.summary()))
+                        .position(Position::Bottom)
+                        .alignment(Alignment::Right),
+                );
+            let column_order: Vec<usize> = (0..query_result.columns.len()).collect();
+            let block = with_grid_titles(
+                block,
+                search,
+                &query_result.rows,
+                query_result.revision,
+                &column_order,
+                &query_result.grid,
+            );
+
+            if expanded_view {
+                f.render_stateful_widget(
+                    RecordView::new(&query_result.columns, &query_result.rows)
+                        .search(Some(search))
+                        .block(block),
+                    area,
+                    &mut query_result.grid,
+                );
+            } else {
+                f.render_stateful_widget(
+                    DataGrid::new(&query_result.columns, &query_result.rows)
+                        .search(Some(search))
+                        .block(block),
+                    area,
+                    &mut query_result.grid,
+                );
+            }
+            return;
+        }
+    }
+
+    match app.cluster.get_current_selected_table_mut() {
+        Some(current_table) => {
+            let page_summary = format!(
+                " {} · {} per page{} ",
+                current_table.page_summary(),
+                current_table.pagination.page_size,
+                if current_table.uses_keyset() {
+                    ""
+                } else {
+                    " · using offset"
+                }
+            );
+
+            let mut block = block.title(
+                Title::from(page_summary)
+                    .position(Position::Bottom)
+                    .alignment(Alignment::Right),
+            );
+
+            if let Some(filter) = &current_table.filter {
+                block = block.title(
+                    Title::from(format!(
+                        " filter: {} ",
+                        truncate(&filter.source, area.width as usize / 2)
+                    ))
+                    .position(Position::Bottom)
+                    .alignment(Alignment::Left),
+                );
+            }
+
+            let column_order = current_table.column_order();
+            let staged = changes.staged_cells(current_table);
+            let block = with_grid_titles(
+                block,
+                search,
+                &current_table.data,
+                current_table.revision,
+                &column_order,
+                &current_table.grid,
+            );
+
+            if expanded_view {
+                f.render_stateful_widget(
+                    RecordView::new(&current_table.columns, &current_table.data)
+                        .column_order(&column_order)
+                        .first_row(current_table.pagination.first_row)
+                        .search(Some(search))
+                        .staged(Some(&staged))
+                        .block(block),
+                    area,
+                    &mut current_table.grid,
+                );
+            } else {
+                f.render_stateful_widget(
+                    DataGrid::new(&current_table.columns, &current_table.data)
+                        .column_order(&column_order, current_table.pinned_columns())
+                        .sort(&current_table.sort)
+                        .search(Some(search))
+                        .staged(Some(&staged))
+                        .block(block),
+                    area,
+                    &mut current_table.grid,
+                );
+            }
+        }
+        None => f.render_widget(block, area),
+    }
+}
+
+// Show the search with the position of the match under the cursor, and the
+// size of the visual selection.
+fn with_grid_titles<'a>(
+    mut block: Block<'a>,
+    search: &mut ResultSearch,
+    rows: &[Vec<Value>],
+    revision: u64,
+    column_order: &[usize],
+    grid: &GridState,
+) -> Block<'a> {
+    if !search.pattern.is_empty() {
+        let summary = search.summary(
+            rows,
+            revision,
+            column_order,
+            (grid.selected_row, grid.selected_column),
+        );
+        block = block.title(
+            Title::from(format!(" {} ", summary))
+                .position(Position::Bottom)
+                .alignment(Alignment::Center),
+        );
+    }
+
+    if let Some((rows, positions)) = grid.selection() {
+        block = block.title(
+            Title::from(Line::styled(
+                format!(
+                    " VISUAL {}×{} ",
+                    rows.end() - rows.start() + 1,
+                    positions.end() - positions.start() + 1
+                ),
+                Style::default().fg(Color::Cyan),
+            ))
+            .position(Position::Bottom)
+            .alignment(Alignment::Center),
+        );
+    }
 
-    f.render_widget(block, area);
+    block
 }
@@ -0,0 +1,39 @@
+use ratatui::{
+    layout::{Alignment, Rect},
+    text::Line,
+    widgets::{
+        block::{Position, Title},
+        Block, Paragraph,
+    },
+    Frame,
+};
+
+use crate::{ui::sql_highlight::highlight, widgets::ddl_view::DdlView};
+
+pub fn render(f: &mut Frame, view: &mut DdlView, block: Block, area: Rect) {
+    let lines: Vec<Line> = view.sql.lines().map(highlight).collect();
+
+    let block = block
+        .title(Title::from(format!(" DDL of {} ", view.name)).alignment(Alignment::Right))
+        .title(
+            Title::from(" y: copy · E: save · h/l: scroll sideways · Tab: back ")
+                .position(Position::Bottom)
+                .alignment(Alignment::Right),
+        );
+
+    let inner_area = block.inner(area);
+    let width = lines.iter().map(Line::width).max().unwrap_or(0);
+    view.scroll.clamp(
+        lines.len(),
+        width,
+        inner_area.height as usize,
+        inner_area.width as usize,
+    );
+
+    f.render_widget(
+        Paragraph::new(lines)
+            .scroll((view.scroll.line as u16, view.scroll.column as u16))
+            .block(block),
+        area,
+    );
+}
@@ -1,11 +1,11 @@
 use ratatui::{
-    layout::{Alignment, Constraint, Direction, Layout},
+    layout::Alignment,
     style::{Color, Style},
     widgets::{Block, Borders, Clear, Paragraph},
     Frame,
 };
 
-use crate::ui::Component;
+use crate::ui::{centered_rect, Component};
 
 pub struct DebugPopup {
     percent_x: u16,
@@ -34,29 +34,7 @@ impl Component for DebugPopup {
             .alignment(Alignment::Left)
             .block(block);
 
-        let vertical_layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(
-                [
-                    Constraint::Percentage((100 - self.percent_y) / 2),
-                    Constraint::Percentage(self.percent_y),
-                    Constraint::Percentage((100 - self.percent_y) / 2),
-                ]
-                .as_ref(),
-            )
-            .split(size);
-
-        let area = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints(
-                [
-                    Constraint::Percentage((100 - self.percent_x) / 2),
-                    Constraint::Percentage(self.percent_x),
-                    Constraint::Percentage((100 - self.percent_x) / 2),
-                ]
-                .as_ref(),
-            )
-            .split(vertical_layout[1])[1];
+        let area = centered_rect(self.percent_x, self.percent_y, size);
 
         f.render_widget(Clear, area);
         f.render_widget(input, area);
@@ -0,0 +1,92 @@
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        block::{Position, Title},
+        Block, Paragraph,
+    },
+    Frame,
+};
+
+use crate::widgets::er_diagram::{CellKind, ErDiagram};
+
+fn style(kind: CellKind) -> Style {
+    match kind {
+        CellKind::Blank | CellKind::Table => Style::default(),
+        CellKind::Line => Style::default().fg(Color::DarkGray),
+        CellKind::FocusedLine => Style::default().fg(Color::Yellow),
+        CellKind::FocusedTable => Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+        CellKind::NeighborTable => Style::default().fg(Color::Cyan),
+        CellKind::ExternalTable => Style::default().fg(Color::DarkGray),
+    }
+}
+
+pub fn render(f: &mut Frame, diagram: &mut ErDiagram, block: Block, area: Rect) {
+    let scope = match (diagram.hops, diagram.focused_table()) {
+        (Some(hops), Some(table)) => format!(
+            "within {} hop{} of {}",
+            hops,
+            if hops == 1 { "" } else { "s" },
+            table
+        ),
+        _ => String::from("all tables"),
+    };
+    let block = block
+        .title(
+            Title::from(format!(
+                " {} · {} tables · {} ",
+                diagram.schema,
+                diagram.visible_count(),
+                scope
+            ))
+            .alignment(Alignment::Right),
+        )
+        .title(
+            Title::from(" [/]: focus table · +/-: hops · =: all · Enter: open · Tab: back ")
+                .position(Position::Bottom)
+                .alignment(Alignment::Right),
+        );
+
+    let inner_area = block.inner(area);
+    let (height, width) = (inner_area.height as usize, inner_area.width as usize);
+    if diagram.follow_focus {
+        diagram.follow_focus = false;
+        if let Some((x, y, box_width, box_height)) = diagram.focused_area() {
+            diagram.scroll.column = (x + box_width / 2).saturating_sub(width / 2);
+            diagram.scroll.line = (y + box_height / 2).saturating_sub(height / 2);
+        }
+    }
+    let canvas_width = diagram.canvas.first().map_or(0, Vec::len);
+    diagram
+        .scroll
+        .clamp(diagram.canvas.len(), canvas_width, height, width);
+
+    // Consecutive characters of the same kind share a span
+    let lines: Vec<Line> = diagram
+        .canvas
+        .iter()
+        .skip(diagram.scroll.line)
+        .take(height)
+        .map(|row| {
+            let mut spans = Vec::new();
+            let mut text = String::new();
+            let mut current = CellKind::Blank;
+            for (c, kind) in row.iter().skip(diagram.scroll.column).take(width) {
+                if *kind != current && !text.is_empty() {
+                    spans.push(Span::styled(std::mem::take(&mut text), style(current)));
+                }
+                current = *kind;
+                text.push(*c);
+            }
+            if !text.is_empty() {
+                spans.push(Span::styled(text, style(current)));
+            }
+            Line::from(spans)
+        })
+        .collect();
+
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
@@ -1,11 +1,72 @@
 use ratatui::{
-    layout::{Alignment, Constraint, Direction, Layout},
+    layout::Alignment,
     style::{Color, Style},
-    widgets::{Block, Borders, Clear, Paragraph},
+    widgets::{
+        block::{Position, Title},
+        Block, Borders, Clear, Paragraph,
+    },
     Frame,
 };
+use unicode_width::UnicodeWidthStr;
 
-use crate::ui::Component;
+use crate::{ui::centered_rect, widgets::text_scroll::TextScroll};
+
+const KEYBINDS: &str = "
+            1: Focus Side Bar
+            2: Focus Search
+            3: Focus Main View
+            j: Move down
+            k: Move up
+            Enter: Side Bar => Expand/collapse the database, schema or group, open the object
+            h/l: Side Bar => Collapse/expand, or go to the parent/first child
+            o: Side Bar => Open the table, view or the details of the object
+            c: Side Bar => Show the DDL of the object (Main View: of the table)
+            /: Side Bar => Fuzzy-filter databases, schemas and tables, Enter jumps to the best match
+            e: Side Bar => Draw the tables of the schema and their foreign keys
+            [/] +/- =: Diagram => Focus next/previous table, fewer/more hops around it, all tables
+            h/l: Main View => Previous/next column
+            g/G: Main View => First/last row
+            0/$: Main View => First/last column
+            PgUp/PgDn: Main View => Scroll a page
+            [/]: Main View => Previous/next page
+            {/}: Main View => First/last page
+            +/-: Main View => Larger/smaller pages
+            Enter: Main View => Inspect the selected cell
+            x: Main View => Toggle the expanded record view
+            t: Main View => Show the columns, constraints, indexes and triggers of the table
+            i: Main View => Filter rows, e.g. status = 'active' and name ~ foo
+            e: Main View => Stage an edit of the cell (Ctrl-n: NULL, Ctrl-d: DEFAULT)
+            a: Main View => Stage a new row
+            D: Main View => Stage/unstage deleting the selected row
+            R: Main View => Review, apply (in one transaction) or discard pending changes
+            U: Main View => Undo the last applied changes, unless their rows changed since
+            z/Z: Main View => Hide column / show hidden columns
+            </>: Main View => Move column left/right
+            p: Main View => Pin/unpin column
+            s/S: Main View => Sort by column / add column to sort (or click header)
+            /: Main View => Search the loaded rows (Tab toggles case while typing)
+            n/N: Main View => Next/previous match
+            C: Main View => Toggle case sensitive search
+            v: Main View => Start/stop a visual selection
+            yy/yr/yc: Main View => Copy cell (or selection)/row/column to the clipboard
+            Y: Main View => Copy the row(s) to the clipboard
+            T: Main View => Cycle the copy format: TSV, CSV, JSON, SQL IN
+            E: Main View => Export the result on screen to a file
+            W: Main View => Export the whole table (filtered and sorted) to a file
+                formats: csv, json, ndjson, markdown, insert
+                options: delimiter=, quote=needed|all, header=yes|no, table=
+            I: Main View => Import a CSV file into the table, or a new one
+            f: Main View => Open the row referenced by the column's foreign key
+            r: Main View => List rows referencing the selected row
+            H/L: Main View => Navigate back/forward
+            i: In Normal Mode => Edit Mode
+            esc: In Edit Mode => Normal Mode
+            :: Run a SQL query
+            Tab: Main View => Switch between table and query result
+            F: Main View => Fetch all rows of the query result
+            ?: Show/hide these binds (j/k, PgUp/PgDn: scroll, esc: close)
+            d: Show debug window
+            q: quit";
 
 pub struct KeybindsPopup {
     percent_x: u16,
@@ -19,54 +80,35 @@ impl KeybindsPopup {
             percent_y,
         }
     }
-}
 
-impl Component for KeybindsPopup {
-    fn render(&self, f: &mut Frame) {
+    // The binds don't fit on smaller screens, they scroll like the other
+    // text views.
+    pub fn render(&self, f: &mut Frame, scroll: &mut TextScroll) {
         let size = f.size();
 
-        let block = Block::default().borders(Borders::ALL).title("Keybinds");
-
-        let input = Paragraph::new(
-            "
-            1: Focus Side Bar
-            2: Focus Main View
-            3: Focus Search
-            j: Move down
-            k: Move up
-            i: In Normal Mode => Edit Mode
-            esc: In Edit Mode => Normal Mode
-            ?: Show Binds
-            d: Show debug window
-            q: quit",
-        )
-        .style(Style::default().fg(Color::Yellow))
-        .alignment(Alignment::Left)
-        .block(block);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Keybinds")
+            .title(
+                Title::from(" j/k: scroll · esc: close ")
+                    .position(Position::Bottom)
+                    .alignment(Alignment::Right),
+            );
 
-        let vertical_layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(
-                [
-                    Constraint::Percentage((100 - self.percent_y) / 2),
-                    Constraint::Percentage(self.percent_y),
-                    Constraint::Percentage((100 - self.percent_y) / 2),
-                ]
-                .as_ref(),
-            )
-            .split(size);
+        let area = centered_rect(self.percent_x, self.percent_y, size);
+        let inner_area = block.inner(area);
+        scroll.clamp(
+            KEYBINDS.lines().count(),
+            KEYBINDS.lines().map(|line| line.width()).max().unwrap_or(0),
+            inner_area.height as usize,
+            inner_area.width as usize,
+        );
 
-        let area = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints(
-                [
-                    Constraint::Percentage((100 - self.percent_x) / 2),
-                    Constraint::Percentage(self.percent_x),
-                    Constraint::Percentage((100 - self.percent_x) / 2),
-                ]
-                .as_ref(),
-            )
-            .split(vertical_layout[1])[1];
+        let input = Paragraph::new(KEYBINDS)
+            .style(Style::default().fg(Color::Yellow))
+            .alignment(Alignment::Left)
+            .scroll((scroll.line as u16, scroll.column as u16))
+            .block(block);
 
         f.render_widget(Clear, area);
         f.render_widget(input, area);
@@ -0,0 +1,196 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        block::{Position, Title},
+        Block, Borders, Clear, Paragraph, Wrap,
+    },
+    Frame,
+};
+
+use crate::{
+    ui::centered_rect,
+    widgets::{
+        data_grid::DataGrid,
+        import_wizard::{ImportStep, ImportWizard, SourceField},
+    },
+};
+
+pub fn render(f: &mut Frame, wizard: &mut ImportWizard) {
+    let area = centered_rect(80, 80, f.size());
+
+    let hint = match wizard.step {
+        ImportStep::Source => {
+            " tab: next field · space: toggle header · enter: preview · esc: cancel "
+        }
+        ImportStep::Mapping if wizard.creates_table => {
+            " j/k: column · h/l: type · enter: create and import · esc: back "
+        }
+        ImportStep::Mapping => " j/k: column · h/l: CSV column · enter: import · esc: back ",
+        ImportStep::Done(_) => " enter: close ",
+    };
+
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(" Import CSV ")
+        .title(
+            Title::from(hint)
+                .position(Position::Bottom)
+                .alignment(Alignment::Right),
+        );
+
+    if let Some(error) = &wizard.error {
+        block = block.title(
+            Title::from(Line::styled(
+                format!(" {} ", error),
+                Style::default().fg(Color::LightRed),
+            ))
+            .position(Position::Bottom)
+            .alignment(Alignment::Left),
+        );
+    }
+
+    let inner_area = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+
+    let label_style = Style::default().fg(Color::Yellow);
+    let selected_style = Style::default().bg(Color::DarkGray);
+    let muted_style = Style::default().fg(Color::DarkGray);
+    let error_style = Style::default().fg(Color::LightRed);
+
+    match &wizard.step {
+        ImportStep::Source => {
+            let header = if wizard.header { "yes" } else { "no" };
+            let fields = [
+                (SourceField::Path, "File", wizard.path.as_str()),
+                (SourceField::Table, "Table", wizard.table.as_str()),
+                (
+                    SourceField::Delimiter,
+                    "Delimiter",
+                    wizard.delimiter.as_str(),
+                ),
+                (
+                    SourceField::NullString,
+                    "NULL string",
+                    wizard.null_string.as_str(),
+                ),
+                (SourceField::Header, "Header row", header),
+            ];
+
+            let mut lines: Vec<Line> = fields
+                .iter()
+                .map(|(field, label, value)| {
+                    let style = if *field == wizard.field {
+                        selected_style
+                    } else {
+                        Style::default()
+                    };
+                    Line::from(vec![
+                        Span::styled(format!("{:>12}: ", label), label_style),
+                        Span::raw(value.to_string()),
+                    ])
+                    .style(style)
+                })
+                .collect();
+            lines.push(Line::default());
+            lines.push(Line::styled(
+                "A table that doesn't exist is created with column types inferred from the file.",
+                muted_style,
+            ));
+
+            f.render_widget(Paragraph::new(lines), inner_area);
+        }
+        ImportStep::Mapping => {
+            let list_height = (wizard.columns.len() as u16 + 2).min(inner_area.height / 2);
+            let areas = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(list_height), Constraint::Min(0)])
+                .split(inner_area);
+
+            let title = format!(
+                "{} rows from {} into {}{}",
+                wizard.records.len(),
+                wizard.path,
+                wizard.target,
+                if wizard.creates_table {
+                    " (new table)"
+                } else {
+                    ""
+                }
+            );
+
+            let first = wizard
+                .selected
+                .saturating_sub((list_height as usize).saturating_sub(2).saturating_sub(1));
+            let mut lines = vec![Line::styled(
+                title,
+                label_style.add_modifier(Modifier::BOLD),
+            )];
+            for (position, column) in wizard.columns.iter().enumerate().skip(first) {
+                let source = match wizard.mapping[position] {
+                    Some(index) => wizard.csv_columns[index].clone(),
+                    None => String::from("(not loaded)"),
+                };
+                let errors = wizard.column_errors(position);
+                let mut spans = vec![
+                    Span::styled(format!("{:<24} ", column.name), label_style),
+                    Span::styled(format!("{:<12} ", column.type_.name()), muted_style),
+                    Span::raw(format!("← {}", source)),
+                ];
+                if errors > 0 {
+                    spans.push(Span::styled(
+                        format!("  {} bad values", errors),
+                        error_style,
+                    ));
+                }
+
+                let line = Line::from(spans);
+                lines.push(if position == wizard.selected {
+                    line.style(selected_style)
+                } else {
+                    line
+                });
+            }
+            f.render_widget(Paragraph::new(lines), areas[0]);
+
+            let (columns, rows) = wizard.preview_rows();
+            f.render_stateful_widget(
+                DataGrid::new(&columns, &rows).block(
+                    Block::default()
+                        .borders(Borders::TOP)
+                        .title(" Preview ")
+                        .border_style(muted_style),
+                ),
+                areas[1],
+                &mut wizard.preview,
+            );
+        }
+        ImportStep::Done(report) => {
+            let mut lines = vec![Line::styled(
+                format!("Loaded {} rows into {}", report.loaded, wizard.target),
+                label_style.add_modifier(Modifier::BOLD),
+            )];
+
+            if report.bad_row_count > 0 {
+                lines.push(Line::styled(
+                    format!("Skipped {} bad rows:", report.bad_row_count),
+                    error_style,
+                ));
+                for (line, error) in &report.bad_rows {
+                    lines.push(Line::raw(format!("  line {}: {}", line, error)));
+                }
+                if report.bad_row_count > report.bad_rows.len() {
+                    lines.push(Line::styled(
+                        format!("  … {} more", report.bad_row_count - report.bad_rows.len()),
+                        muted_style,
+                    ));
+                }
+            }
+
+            f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner_area);
+        }
+    }
+}
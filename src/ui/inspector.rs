@@ -0,0 +1,103 @@
+use crate::{
+    ui::centered_rect,
+    widgets::{
+        cell_inspector::{CellInspector, InspectorContent, StyledLine},
+        data_grid::wrap_styled,
+    },
+};
+use ratatui::{
+    layout::Alignment,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{
+        block::{Position, Title},
+        Block, Borders, Clear, Paragraph,
+    },
+    Frame,
+};
+
+pub fn render(f: &mut Frame, inspector: &mut CellInspector) {
+    let area = centered_rect(80, 80, f.size());
+
+    let hint = if inspector.is_tree() {
+        " j/k: move · space/h/l: collapse/expand · esc: close "
+    } else {
+        " j/k: scroll · esc: close "
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(format!(" {} ", inspector.title))
+        .title(
+            Title::from(hint)
+                .position(Position::Bottom)
+                .alignment(Alignment::Right),
+        );
+
+    let inner_area = block.inner(area);
+    let width = (inner_area.width as usize).max(1);
+    let height = inner_area.height as usize;
+
+    let logical_lines: Vec<StyledLine> = match &inspector.content {
+        InspectorContent::Text(text) => text
+            .lines()
+            .map(|line| vec![(line.to_string(), Style::default())])
+            .collect(),
+        InspectorContent::Lines(lines) => lines.clone(),
+        InspectorContent::Json(_) => inspector
+            .tree_lines()
+            .into_iter()
+            .map(|tree_line| tree_line.line)
+            .collect(),
+    };
+
+    // Rows on screen along with the line they belong to
+    let rows: Vec<(usize, StyledLine)> = logical_lines
+        .iter()
+        .enumerate()
+        .flat_map(|(index, line)| {
+            wrap_styled(line, width)
+                .into_iter()
+                .map(move |row| (index, row))
+        })
+        .collect();
+
+    inspector.viewport_height = height;
+    inspector.scroll = inspector.scroll.min(rows.len().saturating_sub(height));
+
+    if inspector.is_tree() {
+        inspector.cursor = inspector.cursor.min(logical_lines.len().saturating_sub(1));
+        let first_row = rows
+            .iter()
+            .position(|(index, _)| *index == inspector.cursor)
+            .unwrap_or(0);
+        if first_row < inspector.scroll {
+            inspector.scroll = first_row;
+        } else if first_row >= inspector.scroll + height {
+            inspector.scroll = first_row + 1 - height;
+        }
+    }
+
+    let cursor_style = Style::default().bg(Color::DarkGray);
+    let lines: Vec<Line> = rows
+        .into_iter()
+        .skip(inspector.scroll)
+        .take(height)
+        .map(|(index, row)| {
+            let line = Line::from(
+                row.into_iter()
+                    .map(|(text, style)| Span::styled(text, style))
+                    .collect::<Vec<Span>>(),
+            );
+            if inspector.is_tree() && index == inspector.cursor {
+                line.style(cursor_style)
+            } else {
+                line
+            }
+        })
+        .collect();
+
+    f.render_widget(Clear, area);
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
@@ -1,21 +1,78 @@
+pub mod change_review;
+pub mod confirm_undo;
 pub mod datatable;
+pub mod ddl;
 pub mod debug;
+pub mod er_diagram;
 pub mod help_window;
+pub mod import_wizard;
+pub mod inspector;
+pub mod object_details;
+pub mod references;
+pub mod row_form;
 pub mod searchbar;
 pub mod sidebar;
+pub mod sql_highlight;
 pub mod statusline;
+pub mod structure;
 
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::Line,
     Frame,
 };
 
-use crate::app::App;
+use crate::{app::App, postgres::query::quote_literal};
 
 pub trait Component {
     fn render(&self, f: &mut Frame);
 }
 
+// Area of the given percentage of the screen, centered on it. Used to place
+// popups.
+pub fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let vertical_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_y) / 2),
+                Constraint::Percentage(percent_y),
+                Constraint::Percentage((100 - percent_y) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(area);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_x) / 2),
+                Constraint::Percentage(percent_x),
+                Constraint::Percentage((100 - percent_x) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(vertical_layout[1])[1]
+}
+
+// A statement followed by the values of its parameters, as shown before
+// it's run.
+pub fn statement_lines(sql: String, params: &[Option<String>]) -> Vec<Line<'static>> {
+    let muted_style = Style::default().fg(Color::DarkGray);
+
+    let mut lines = vec![Line::raw(sql)];
+    lines.extend(params.iter().enumerate().map(|(index, param)| {
+        let value = match param {
+            Some(param) => quote_literal(param),
+            None => String::from("NULL"),
+        };
+        Line::styled(format!("  ${} = {}", index + 1, value), muted_style)
+    }));
+    lines
+}
+
 pub fn draw(f: &mut Frame, app: &mut App) {
     let area = f.size();
 
@@ -51,8 +108,32 @@ pub fn draw(f: &mut Frame, app: &mut App) {
     statusline::render(f, app, statusline_area);
 
     if app.show_keybinds {
-        let p = help_window::KeybindsPopup::new(60, 40);
-        p.render(f);
+        let p = help_window::KeybindsPopup::new(60, 80);
+        p.render(f, &mut app.help_scroll);
+    }
+
+    if let Some(inspector) = app.inspector.as_mut() {
+        inspector::render(f, inspector);
+    }
+
+    if let Some(reference_picker) = app.reference_picker.as_ref() {
+        references::render(f, reference_picker);
+    }
+
+    if let Some(wizard) = app.import_wizard.as_mut() {
+        import_wizard::render(f, wizard);
+    }
+
+    if let Some(form) = app.row_form.as_ref() {
+        row_form::render(f, form);
+    }
+
+    if let Some(review) = app.change_review.as_ref() {
+        change_review::render(f, review, &app.changes);
+    }
+
+    if let (Some(statements), Some(changes)) = (app.pending_undo.as_ref(), app.undo_log.last()) {
+        confirm_undo::render(f, changes, statements);
     }
 
     if app.show_debug {
@@ -0,0 +1,59 @@
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{block::Title, Block, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::widgets::object_group::ObjectDetails;
+
+// Label and value of every property of the object, labels aligned.
+pub fn render(f: &mut Frame, details: &ObjectDetails, block: Block, area: Rect) {
+    let label_style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let label_width = details
+        .properties
+        .iter()
+        .map(|(label, _)| label.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let block = block.title(
+        Title::from(format!(
+            " {} {}.{} ",
+            details.kind.icon(),
+            details.schema,
+            details.name
+        ))
+        .alignment(Alignment::Right),
+    );
+
+    let mut lines: Vec<Line> = details
+        .properties
+        .iter()
+        .map(|(label, value)| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{:>width$}: ", label, width = label_width),
+                    label_style,
+                ),
+                Span::raw(value.clone()),
+            ])
+        })
+        .collect();
+    if lines.is_empty() {
+        lines.push(Line::styled(
+            "Nothing to show",
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+
+    f.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(block),
+        area,
+    );
+}
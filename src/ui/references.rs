@@ -0,0 +1,57 @@
+use ratatui::{
+    layout::Alignment,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{
+        block::{Position, Title},
+        Block, Borders, Clear, List, ListItem, ListState,
+    },
+    Frame,
+};
+
+use crate::{ui::centered_rect, widgets::reference_picker::ReferencePicker};
+
+pub fn render(f: &mut Frame, reference_picker: &ReferencePicker) {
+    let area = centered_rect(60, 40, f.size());
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(" Referencing rows ")
+        .title(
+            Title::from(" j/k: move · enter: open · esc: close ")
+                .position(Position::Bottom)
+                .alignment(Alignment::Right),
+        );
+
+    let items: Vec<ListItem> = reference_picker
+        .references
+        .iter()
+        .map(|reference| {
+            let foreign_key = &reference.foreign_key;
+            let count = match reference.count {
+                Some(count) => count.to_string(),
+                None => String::from("?"),
+            };
+            ListItem::new(Line::from(vec![
+                Span::raw(format!(
+                    "{} ({}) ",
+                    foreign_key.table,
+                    foreign_key.columns.join(", ")
+                )),
+                Span::styled(
+                    format!("{} · {} rows", foreign_key.name, count),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().bg(Color::DarkGray));
+    let mut state = ListState::default().with_selected(Some(reference_picker.selected));
+
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut state);
+}
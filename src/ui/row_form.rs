@@ -0,0 +1,111 @@
+use ratatui::{
+    layout::Alignment,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        block::{Position, Title},
+        Block, Borders, Clear, Paragraph,
+    },
+    Frame,
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{postgres::edit::CellValue, ui::centered_rect, widgets::row_form::RowForm};
+
+pub fn render(f: &mut Frame, form: &RowForm) {
+    let area = centered_rect(70, 70, f.size());
+
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(format!(" Insert into {} ", form.table))
+        .title(
+            Title::from(
+                " tab: field · ctrl-n: NULL · ctrl-d: DEFAULT · enter: insert · esc: cancel ",
+            )
+            .position(Position::Bottom)
+            .alignment(Alignment::Right),
+        );
+
+    if let Some(error) = &form.error {
+        block = block.title(
+            Title::from(Line::styled(
+                format!(" {} ", error),
+                Style::default().fg(Color::LightRed),
+            ))
+            .position(Position::Bottom)
+            .alignment(Alignment::Left),
+        );
+    }
+
+    let inner_area = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+
+    let label_style = Style::default().fg(Color::Yellow);
+    let keyword_style = Style::default()
+        .fg(Color::DarkGray)
+        .add_modifier(Modifier::ITALIC);
+    let muted_style = Style::default().fg(Color::DarkGray);
+    let selected_style = Style::default().bg(Color::DarkGray);
+
+    let label_width = form
+        .columns
+        .iter()
+        .map(|column| column.name.width())
+        .max()
+        .unwrap_or(0);
+
+    // Each column takes a line for its value and one for its definition
+    let visible = (inner_area.height as usize / 2).max(1);
+    let first = form.selected.saturating_sub(visible - 1);
+
+    let lines: Vec<Line> = form
+        .columns
+        .iter()
+        .zip(&form.details)
+        .zip(&form.values)
+        .enumerate()
+        .skip(first)
+        .take(visible)
+        .flat_map(|(index, ((column, details), value))| {
+            let value = match value {
+                CellValue::Text(text) => Span::raw(text.clone()),
+                CellValue::Null => Span::styled("NULL", keyword_style),
+                CellValue::Default => Span::styled("DEFAULT", keyword_style),
+            };
+            let value_line = Line::from(vec![
+                Span::styled(
+                    format!("{:>width$}: ", column.name, width = label_width),
+                    label_style,
+                ),
+                value,
+            ]);
+
+            let mut definition = details
+                .as_ref()
+                .map(|details| details.type_name.clone())
+                .unwrap_or_else(|| column.type_.name().to_string());
+            if let Some(details) = details {
+                if details.not_null {
+                    definition.push_str(" not null");
+                }
+                if let Some(default) = &details.default {
+                    definition.push_str(&format!(" default {}", default));
+                }
+            }
+            let definition_line = Line::styled(
+                format!("{:width$}  {}", "", definition, width = label_width),
+                muted_style,
+            );
+
+            if index == form.selected {
+                vec![value_line.style(selected_style), definition_line]
+            } else {
+                vec![value_line, definition_line]
+            }
+        })
+        .collect();
+
+    f.render_widget(Paragraph::new(lines), inner_area);
+}
@@ -1,27 +1,88 @@
-use crate::app::{App, FocusElement, InputMode};
+use crate::{
+    app::{App, FocusElement, InputMode, InputTarget},
+    postgres::edit::CellValue,
+};
 use ratatui::{
-    layout::Rect,
-    style::{Color, Style},
-    widgets::{Block, Borders, Paragraph},
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        block::{Position, Title},
+        Block, Borders, Paragraph,
+    },
     Frame,
 };
 
 use unicode_width::UnicodeWidthStr;
 
 pub fn render(f: &mut Frame, app: &App, area: Rect) {
+    let label = match (&app.input_target, &app.cell_edit) {
+        (InputTarget::Search, _) if app.search.case_sensitive => {
+            String::from("Search (case sensitive, Tab to toggle)")
+        }
+        (InputTarget::Search, _) => String::from("Search (Tab: case sensitive)"),
+        (InputTarget::Query, _) => String::from("Query"),
+        (InputTarget::Filter, _) => String::from("Filter"),
+        (InputTarget::Export, _) => String::from("Export result: format [option=value...] file"),
+        (InputTarget::ExportTable, _) => {
+            String::from("Export table: format [option=value...] file")
+        }
+        (InputTarget::Cell, Some(cell_edit)) => format!(
+            "Edit {} ({}) · Ctrl-n: NULL · Ctrl-d: DEFAULT",
+            cell_edit.column.name,
+            cell_edit.column.type_.name()
+        ),
+        (InputTarget::Cell, None) => String::from("Edit"),
+        (InputTarget::SaveDdl, _) => String::from("Save DDL to file"),
+        (InputTarget::ExplorerFilter, _) => {
+            String::from("Filter the explorer (Enter: jump to the best match)")
+        }
+    };
+
     let (render_color, title) = match app.focused_element {
-        FocusElement::SearchBar => (Color::Green, " Search (focused) "),
-        _ => (Color::Red, " Search "),
+        FocusElement::SearchBar => (Color::Green, format!(" {} (focused) ", label)),
+        _ => (Color::Red, format!(" {} ", label)),
     };
 
     let default_style = Style::default().fg(render_color);
 
-    let block = Block::default()
+    let mut block = Block::default()
         .title(title)
         .borders(Borders::ALL)
         .style(default_style);
 
-    let input = Paragraph::new(app.input.clone()).block(Block::default().borders(Borders::ALL));
+    if let Some(error) = &app.input_error {
+        block = block.title(
+            Title::from(Line::styled(
+                format!(" {} ", error),
+                Style::default().fg(Color::LightRed),
+            ))
+            .position(Position::Bottom)
+            .alignment(Alignment::Left),
+        );
+    }
+
+    // NULL or DEFAULT picked for the edited cell replaces the text
+    let keyword = match app
+        .cell_edit
+        .as_ref()
+        .and_then(|cell_edit| cell_edit.keyword.as_ref())
+    {
+        Some(CellValue::Null) => Some("NULL"),
+        Some(CellValue::Default) => Some("DEFAULT"),
+        _ => None,
+    };
+    let text = match keyword {
+        Some(keyword) => Line::from(Span::styled(
+            keyword,
+            Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC),
+        )),
+        None => Line::from(app.input.clone()),
+    };
+
+    let input = Paragraph::new(text).block(Block::default().borders(Borders::ALL));
 
     f.render_widget(input, area);
     f.render_widget(block, area);
@@ -26,17 +26,13 @@ pub fn render(f: &mut Frame, app: &mut App, area: Rect) {
         _ => (Color::Red, format!(" Explorer {}", database_name)),
     };
 
-    // Fetch data
-    let database_list = &app.cluster.databases;
-
-    // Try building the tree
     let border_block = Block::default()
         .title(title)
         .borders(Borders::ALL)
         .style(Style::default().fg(render_color));
 
     f.render_stateful_widget(
-        DatabaseTree::new(database_list.to_vec()).block(border_block),
+        DatabaseTree::new().block(border_block),
         area,
         &mut app.cluster,
     );
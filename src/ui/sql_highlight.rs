@@ -0,0 +1,288 @@
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+const KEYWORDS: &[&str] = &[
+    "add",
+    "aggregate",
+    "all",
+    "alter",
+    "always",
+    "and",
+    "as",
+    "asc",
+    "begin",
+    "by",
+    "cache",
+    "called",
+    "cascade",
+    "case",
+    "check",
+    "collate",
+    "column",
+    "comment",
+    "constraint",
+    "cost",
+    "create",
+    "cycle",
+    "data",
+    "declare",
+    "default",
+    "deferrable",
+    "deferred",
+    "definer",
+    "delete",
+    "desc",
+    "distinct",
+    "domain",
+    "each",
+    "else",
+    "end",
+    "enum",
+    "execute",
+    "exists",
+    "extension",
+    "false",
+    "first",
+    "for",
+    "foreign",
+    "from",
+    "full",
+    "function",
+    "generated",
+    "group",
+    "having",
+    "identity",
+    "if",
+    "immutable",
+    "immediate",
+    "in",
+    "increment",
+    "index",
+    "inherits",
+    "initially",
+    "inner",
+    "insert",
+    "instead",
+    "is",
+    "join",
+    "key",
+    "language",
+    "last",
+    "left",
+    "like",
+    "limit",
+    "materialized",
+    "maxvalue",
+    "minvalue",
+    "no",
+    "not",
+    "null",
+    "nulls",
+    "of",
+    "offset",
+    "on",
+    "options",
+    "or",
+    "order",
+    "outer",
+    "over",
+    "owned",
+    "owner",
+    "parallel",
+    "partition",
+    "primary",
+    "procedure",
+    "references",
+    "replace",
+    "restrict",
+    "return",
+    "returns",
+    "right",
+    "row",
+    "safe",
+    "schema",
+    "security",
+    "select",
+    "sequence",
+    "server",
+    "set",
+    "stable",
+    "start",
+    "statement",
+    "stored",
+    "strict",
+    "table",
+    "then",
+    "to",
+    "trigger",
+    "true",
+    "type",
+    "union",
+    "unique",
+    "unlogged",
+    "update",
+    "using",
+    "values",
+    "version",
+    "view",
+    "volatile",
+    "when",
+    "where",
+    "with",
+];
+
+// Styled spans of one line of SQL. Strings, quoted identifiers and comments
+// are only recognized within the line, good enough for reading DDL.
+pub fn highlight(line: &str) -> Line<'static> {
+    let keyword_style = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let string_style = Style::default().fg(Color::Green);
+    let number_style = Style::default().fg(Color::Magenta);
+    let comment_style = Style::default().fg(Color::DarkGray);
+
+    let chars: Vec<char> = line.chars().collect();
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut index = 0;
+
+    while index < chars.len() {
+        let c = chars[index];
+        let start = index;
+        let style = if c == '-' && chars.get(index + 1) == Some(&'-') {
+            index = chars.len();
+            Some(comment_style)
+        } else if c == '\'' || c == '"' {
+            index += 1;
+            while index < chars.len() {
+                if chars[index] == c {
+                    // A doubled quote is part of the text
+                    if chars.get(index + 1) == Some(&c) {
+                        index += 2;
+                        continue;
+                    }
+                    index += 1;
+                    break;
+                }
+                index += 1;
+            }
+            Some(if c == '\'' {
+                string_style
+            } else {
+                Style::default()
+            })
+        } else if c == '$' {
+            // Dollar quote markers of function bodies
+            index += 1;
+            while index < chars.len() && (chars[index].is_alphanumeric() || chars[index] == '_') {
+                index += 1;
+            }
+            if chars.get(index) == Some(&'$') {
+                index += 1;
+                Some(string_style)
+            } else {
+                None
+            }
+        } else if c.is_ascii_digit() {
+            while index < chars.len() && (chars[index].is_ascii_digit() || chars[index] == '.') {
+                index += 1;
+            }
+            Some(number_style)
+        } else if c.is_alphabetic() || c == '_' {
+            while index < chars.len() && (chars[index].is_alphanumeric() || chars[index] == '_') {
+                index += 1;
+            }
+            let word: String = chars[start..index].iter().collect();
+            KEYWORDS
+                .contains(&word.to_lowercase().as_str())
+                .then_some(keyword_style)
+        } else {
+            index += 1;
+            None
+        };
+
+        let token: String = chars[start..index].iter().collect();
+        match style {
+            Some(style) => {
+                if !plain.is_empty() {
+                    spans.push(Span::raw(std::mem::take(&mut plain)));
+                }
+                spans.push(Span::styled(token, style));
+            }
+            None => plain.push_str(&token),
+        }
+    }
+    if !plain.is_empty() {
+        spans.push(Span::raw(plain));
+    }
+
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(line: &Line) -> Vec<(String, Option<Color>)> {
+        line.spans
+            .iter()
+            .map(|span| (span.content.to_string(), span.style.fg))
+            .collect()
+    }
+
+    fn token(text: &str, color: Option<Color>) -> (String, Option<Color>) {
+        (text.to_string(), color)
+    }
+
+    #[test]
+    fn keywords_are_matched_as_whole_words() {
+        let line = highlight("CREATE TABLE created (id int)");
+
+        assert_eq!(
+            tokens(&line),
+            vec![
+                token("CREATE", Some(Color::Cyan)),
+                token(" ", None),
+                token("TABLE", Some(Color::Cyan)),
+                token(" created (id int)", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn strings_numbers_and_comments() {
+        let line = highlight("DEFAULT 'it''s' + 1.5 -- not 'a string'");
+
+        assert_eq!(
+            tokens(&line),
+            vec![
+                token("DEFAULT", Some(Color::Cyan)),
+                token(" ", None),
+                token("'it''s'", Some(Color::Green)),
+                token(" + ", None),
+                token("1.5", Some(Color::Magenta)),
+                token(" ", None),
+                token("-- not 'a string'", Some(Color::DarkGray)),
+            ]
+        );
+    }
+
+    #[test]
+    fn quoted_identifiers_and_dollar_quotes() {
+        let line = highlight(r#"AS $body$ "Select""#);
+
+        assert_eq!(
+            tokens(&line),
+            vec![
+                token("AS", Some(Color::Cyan)),
+                token(" ", None),
+                token("$body$", Some(Color::Green)),
+                token(" ", None),
+                token(r#""Select""#, None),
+            ]
+        );
+        assert!(highlight("").spans.is_empty());
+    }
+}
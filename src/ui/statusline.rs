@@ -8,6 +8,21 @@ use ratatui::{
 
 pub fn render(f: &mut Frame, app: &App, area: Rect) {
     let (title, color) = match app.input_mode {
+        _ if app.status_message.is_some() => (
+            format!(" {}", app.status_message.clone().unwrap_or_default()),
+            Color::Green,
+        ),
+        _ if app.export.is_some() => (
+            format!(" {}", app.export_progress().unwrap_or_default()),
+            Color::Yellow,
+        ),
+        InputMode::Normal if app.pending_yank => (
+            format!(
+                " Copy as {}: y cell or selection, r row, c column, any other key cancels.",
+                app.copy_format.name()
+            ),
+            Color::Yellow,
+        ),
         InputMode::Normal => (
             format!(
                 " Current mode: Normal. User: {}. Database: {}. Host: {}.",
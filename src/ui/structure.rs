@@ -0,0 +1,218 @@
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        block::{Position, Title},
+        Block, Paragraph,
+    },
+    Frame,
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::widgets::structure_view::StructureView;
+
+const COLUMN_GAP: &str = "  ";
+
+// A titled section with the cells of every row padded to the widest of their
+// column. The last column isn't padded, it usually holds a definition.
+fn section(title: &str, header: &[&str], rows: Vec<Vec<String>>) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::styled(
+        format!("{} ({})", title, rows.len()),
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    )];
+
+    if rows.is_empty() {
+        lines.push(Line::styled("  none", Style::default().fg(Color::DarkGray)));
+        lines.push(Line::default());
+        return lines;
+    }
+
+    let widths: Vec<usize> = (0..header.len())
+        .map(|index| {
+            rows.iter()
+                .map(|row| row[index].width())
+                .chain([header[index].width()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let pad = |cells: Vec<String>| {
+        let last = cells.len() - 1;
+        cells
+            .into_iter()
+            .enumerate()
+            .map(|(index, cell)| {
+                if index == last {
+                    cell
+                } else {
+                    let padding = widths[index].saturating_sub(cell.width());
+                    format!("{}{}{}", cell, " ".repeat(padding), COLUMN_GAP)
+                }
+            })
+            .collect::<String>()
+    };
+
+    lines.push(Line::styled(
+        format!(
+            "  {}",
+            pad(header.iter().map(|cell| cell.to_string()).collect())
+        ),
+        Style::default().fg(Color::DarkGray),
+    ));
+    lines.extend(
+        rows.into_iter()
+            .map(|row| Line::from(vec![Span::raw("  "), Span::raw(pad(row))])),
+    );
+    lines.push(Line::default());
+
+    lines
+}
+
+pub fn render(f: &mut Frame, view: &mut StructureView, block: Block, area: Rect) {
+    let structure = &view.structure;
+
+    let mut lines = section(
+        "Columns",
+        &["name", "type", "null", "default", "collation", "comment"],
+        structure
+            .columns
+            .iter()
+            .map(|column| {
+                vec![
+                    column.name.clone(),
+                    column.type_name.clone(),
+                    String::from(if column.not_null { "not null" } else { "" }),
+                    column.default.clone().unwrap_or_default(),
+                    column.collation.clone().unwrap_or_default(),
+                    column.comment.clone().unwrap_or_default(),
+                ]
+            })
+            .collect(),
+    );
+    lines.extend(section(
+        "Constraints",
+        &["name", "type", "definition"],
+        structure
+            .constraints
+            .iter()
+            .map(|constraint| {
+                vec![
+                    constraint.name.clone(),
+                    constraint.kind.clone(),
+                    constraint.definition.clone(),
+                ]
+            })
+            .collect(),
+    ));
+    lines.extend(section(
+        "Indexes",
+        &["name", "size", "definition"],
+        structure
+            .indexes
+            .iter()
+            .map(|index| {
+                vec![
+                    if index.is_valid {
+                        index.name.clone()
+                    } else {
+                        format!("{} (invalid)", index.name)
+                    },
+                    index.size.clone(),
+                    index.definition.clone(),
+                ]
+            })
+            .collect(),
+    ));
+    lines.extend(section(
+        "Triggers",
+        &["name", "definition"],
+        structure
+            .triggers
+            .iter()
+            .map(|trigger| {
+                vec![
+                    if trigger.is_enabled {
+                        trigger.name.clone()
+                    } else {
+                        format!("{} (disabled)", trigger.name)
+                    },
+                    trigger.definition.clone(),
+                ]
+            })
+            .collect(),
+    ));
+
+    let block = block
+        .title(Title::from(format!(" {} ", view.table)).alignment(Alignment::Right))
+        .title(
+            Title::from(" h/l: scroll sideways · t/Tab: back to the rows ")
+                .position(Position::Bottom)
+                .alignment(Alignment::Right),
+        );
+
+    let inner_area = block.inner(area);
+    let width = lines.iter().map(Line::width).max().unwrap_or(0);
+    view.scroll.clamp(
+        lines.len(),
+        width,
+        inner_area.height as usize,
+        inner_area.width as usize,
+    );
+
+    f.render_widget(
+        Paragraph::new(lines)
+            .scroll((view.scroll.line as u16, view.scroll.column as u16))
+            .block(block),
+        area,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(lines: &[Line]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cells_are_padded_but_the_last() {
+        let lines = section(
+            "Indexes",
+            &["name", "definition"],
+            vec![
+                vec![String::from("orders_pkey"), String::from("btree (id)")],
+                vec![String::from("ix"), String::from("btree (note)")],
+            ],
+        );
+
+        assert_eq!(
+            text(&lines),
+            vec![
+                "Indexes (2)",
+                "  name         definition",
+                "  orders_pkey  btree (id)",
+                "  ix           btree (note)",
+                "",
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_sections_say_so() {
+        let lines = section("Triggers", &["name", "definition"], Vec::new());
+
+        assert_eq!(text(&lines), vec!["Triggers (0)", "  none", ""]);
+    }
+}
@@ -0,0 +1,524 @@
+use ratatui::style::{Color, Modifier, Style};
+use std::collections::HashSet;
+use tokio_postgres::types::{Kind, Type};
+
+use crate::postgres::data::{Column, Value};
+
+const HEX_DUMP_WIDTH: usize = 16;
+const INDENT: &str = "  ";
+
+// A line of the inspector made of styled pieces of text.
+pub type StyledLine = Vec<(String, Style)>;
+
+pub enum InspectorContent {
+    Text(String),
+    Json(serde_json::Value),
+    Lines(Vec<StyledLine>),
+}
+
+// Full view of a single cell.
+//
+// JSON is shown as a tree where the cursor moves between lines and objects
+// or arrays can be collapsed, everything else is simply scrolled.
+pub struct CellInspector {
+    pub title: String,
+    pub content: InspectorContent,
+    pub scroll: usize,
+    pub cursor: usize,
+    pub viewport_height: usize,
+    collapsed: HashSet<Vec<usize>>,
+}
+
+// One line of the JSON tree, `path` is set on lines that open an object or
+// an array and identifies it for collapsing.
+pub struct TreeLine {
+    pub line: StyledLine,
+    pub path: Option<Vec<usize>>,
+}
+
+impl CellInspector {
+    pub fn new(column: &Column, value: &Value) -> Self {
+        let base_type = match column.type_.kind() {
+            Kind::Domain(base) => base,
+            _ => &column.type_,
+        };
+
+        let content = match value {
+            Value::Json(text) => match serde_json::from_str(text) {
+                Ok(json) => InspectorContent::Json(json),
+                Err(_) => InspectorContent::Text(text.clone()),
+            },
+            Value::Bytes(bytes) => InspectorContent::Lines(hex_dump(bytes)),
+            Value::Text(text) if *base_type == Type::XML => {
+                InspectorContent::Lines(indent_xml(text))
+            }
+            value => InspectorContent::Text(value.to_string()),
+        };
+
+        Self {
+            title: format!("{} ({})", column.name, column.type_.name()),
+            content,
+            scroll: 0,
+            cursor: 0,
+            viewport_height: 0,
+            collapsed: HashSet::new(),
+        }
+    }
+
+    pub fn is_tree(&self) -> bool {
+        matches!(self.content, InspectorContent::Json(_))
+    }
+
+    pub fn tree_lines(&self) -> Vec<TreeLine> {
+        let mut lines = Vec::new();
+        if let InspectorContent::Json(json) = &self.content {
+            flatten_json(
+                json,
+                None,
+                &mut Vec::new(),
+                0,
+                true,
+                &self.collapsed,
+                &mut lines,
+            );
+        }
+        lines
+    }
+
+    pub fn next_line(&mut self) {
+        if self.is_tree() {
+            self.cursor = (self.cursor + 1).min(self.tree_lines().len().saturating_sub(1));
+        } else {
+            self.scroll += 1;
+        }
+    }
+
+    pub fn prev_line(&mut self) {
+        if self.is_tree() {
+            self.cursor = self.cursor.saturating_sub(1);
+        } else {
+            self.scroll = self.scroll.saturating_sub(1);
+        }
+    }
+
+    pub fn page_down(&mut self) {
+        for _ in 0..self.viewport_height.max(1) {
+            self.next_line();
+        }
+    }
+
+    pub fn page_up(&mut self) {
+        for _ in 0..self.viewport_height.max(1) {
+            self.prev_line();
+        }
+    }
+
+    pub fn top(&mut self) {
+        self.cursor = 0;
+        self.scroll = 0;
+    }
+
+    pub fn bottom(&mut self) {
+        if self.is_tree() {
+            self.cursor = self.tree_lines().len().saturating_sub(1);
+        } else {
+            // Clamped to the content while rendering
+            self.scroll = usize::MAX;
+        }
+    }
+
+    // Collapse or expand the object or array opened on the cursor line.
+    pub fn toggle(&mut self) {
+        if let Some(path) = self.cursor_path() {
+            if !self.collapsed.remove(&path) {
+                self.collapsed.insert(path);
+            }
+        }
+    }
+
+    pub fn collapse(&mut self) {
+        if let Some(path) = self.cursor_path() {
+            self.collapsed.insert(path);
+        }
+    }
+
+    pub fn expand(&mut self) {
+        if let Some(path) = self.cursor_path() {
+            self.collapsed.remove(&path);
+        }
+    }
+
+    fn cursor_path(&self) -> Option<Vec<usize>> {
+        self.tree_lines()
+            .into_iter()
+            .nth(self.cursor)
+            .and_then(|line| line.path)
+    }
+}
+
+fn key_style() -> Style {
+    Style::default().fg(Color::Cyan)
+}
+
+fn punctuation_style() -> Style {
+    Style::default().fg(Color::Gray)
+}
+
+fn scalar_style(value: &serde_json::Value) -> Style {
+    match value {
+        serde_json::Value::String(_) => Style::default().fg(Color::Green),
+        serde_json::Value::Number(_) => Style::default().fg(Color::Magenta),
+        _ => Style::default().fg(Color::Yellow),
+    }
+}
+
+// Pretty print the JSON value into lines, leaving out the children of
+// collapsed objects and arrays.
+fn flatten_json(
+    value: &serde_json::Value,
+    key: Option<&str>,
+    path: &mut Vec<usize>,
+    depth: usize,
+    is_last: bool,
+    collapsed: &HashSet<Vec<usize>>,
+    lines: &mut Vec<TreeLine>,
+) {
+    let mut line: StyledLine = vec![(INDENT.repeat(depth), Style::default())];
+    if let Some(key) = key {
+        line.push((
+            serde_json::Value::String(key.to_string()).to_string(),
+            key_style(),
+        ));
+        line.push((String::from(": "), punctuation_style()));
+    }
+    let comma = if is_last { "" } else { "," };
+
+    let (open, close, length) = match value {
+        serde_json::Value::Object(object) => ("{", "}", object.len()),
+        serde_json::Value::Array(array) => ("[", "]", array.len()),
+        scalar => {
+            line.push((scalar.to_string(), scalar_style(scalar)));
+            line.push((comma.to_string(), punctuation_style()));
+            lines.push(TreeLine { line, path: None });
+            return;
+        }
+    };
+
+    if length == 0 {
+        line.push((format!("{}{}{}", open, close, comma), punctuation_style()));
+        lines.push(TreeLine { line, path: None });
+        return;
+    }
+
+    if collapsed.contains(path) {
+        let noun = match value {
+            serde_json::Value::Object(_) => "key",
+            _ => "item",
+        };
+        let count = format!(" {} {}{}", length, noun, if length == 1 { "" } else { "s" });
+        line.push((format!("{}…{}", open, close), punctuation_style()));
+        line.push((
+            count,
+            Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC),
+        ));
+        line.push((comma.to_string(), punctuation_style()));
+        lines.push(TreeLine {
+            line,
+            path: Some(path.clone()),
+        });
+        return;
+    }
+
+    line.push((open.to_string(), punctuation_style()));
+    lines.push(TreeLine {
+        line,
+        path: Some(path.clone()),
+    });
+
+    let children: Vec<(Option<&str>, &serde_json::Value)> = match value {
+        serde_json::Value::Object(object) => object
+            .iter()
+            .map(|(key, value)| (Some(key.as_str()), value))
+            .collect(),
+        serde_json::Value::Array(array) => array.iter().map(|value| (None, value)).collect(),
+        _ => Vec::new(),
+    };
+
+    for (index, (key, child)) in children.iter().enumerate() {
+        path.push(index);
+        flatten_json(
+            child,
+            *key,
+            path,
+            depth + 1,
+            index + 1 == length,
+            collapsed,
+            lines,
+        );
+        path.pop();
+    }
+
+    lines.push(TreeLine {
+        line: vec![
+            (INDENT.repeat(depth), Style::default()),
+            (format!("{}{}", close, comma), punctuation_style()),
+        ],
+        path: None,
+    });
+}
+
+// Offset, hex bytes and printable ASCII, 16 bytes to a line.
+fn hex_dump(bytes: &[u8]) -> Vec<StyledLine> {
+    let offset_style = Style::default().fg(Color::DarkGray);
+    let ascii_style = Style::default().fg(Color::Green);
+
+    bytes
+        .chunks(HEX_DUMP_WIDTH)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let hex = (0..HEX_DUMP_WIDTH)
+                .map(|position| match chunk.get(position) {
+                    Some(byte) => format!("{:02x}", byte),
+                    None => String::from("  "),
+                })
+                .collect::<Vec<String>>()
+                .chunks(HEX_DUMP_WIDTH / 2)
+                .map(|half| half.join(" "))
+                .collect::<Vec<String>>()
+                .join("  ");
+            let ascii: String = chunk
+                .iter()
+                .map(|byte| {
+                    if byte.is_ascii_graphic() || *byte == b' ' {
+                        *byte as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+
+            vec![
+                (format!("{:08x}  ", index * HEX_DUMP_WIDTH), offset_style),
+                (hex, Style::default()),
+                (format!("  |{}|", ascii), ascii_style),
+            ]
+        })
+        .collect()
+}
+
+// Put every tag on its own line, indented by its nesting level. Text that
+// sits directly between an opening and a closing tag stays on their line.
+fn indent_xml(xml: &str) -> Vec<StyledLine> {
+    let tag_style = Style::default().fg(Color::Cyan);
+
+    let mut tokens: Vec<String> = Vec::new();
+    let mut rest = xml;
+    while !rest.is_empty() {
+        let end = if rest.starts_with('<') {
+            rest.find('>').map(|end| end + 1).unwrap_or(rest.len())
+        } else {
+            rest.find('<').unwrap_or(rest.len())
+        };
+        let token = rest[..end].trim();
+        if !token.is_empty() {
+            tokens.push(token.to_string());
+        }
+        rest = &rest[end..];
+    }
+
+    let mut lines = Vec::new();
+    let mut depth: usize = 0;
+    let mut index = 0;
+    while index < tokens.len() {
+        let token = &tokens[index];
+        let is_tag = token.starts_with('<');
+        let is_closing = token.starts_with("</");
+        let is_opening = is_tag
+            && !is_closing
+            && !token.ends_with("/>")
+            && !token.starts_with("<?")
+            && !token.starts_with("<!");
+
+        if is_closing {
+            depth = depth.saturating_sub(1);
+        }
+
+        let indent = (INDENT.repeat(depth), Style::default());
+        let is_inline_text = is_opening
+            && tokens
+                .get(index + 2)
+                .is_some_and(|next| next.starts_with("</"))
+            && tokens
+                .get(index + 1)
+                .is_some_and(|text| !text.starts_with('<'));
+
+        if is_inline_text {
+            lines.push(vec![
+                indent,
+                (token.clone(), tag_style),
+                (tokens[index + 1].clone(), Style::default()),
+                (tokens[index + 2].clone(), tag_style),
+            ]);
+            index += 3;
+            continue;
+        }
+
+        let style = if is_tag { tag_style } else { Style::default() };
+        lines.push(vec![indent, (token.clone(), style)]);
+
+        if is_opening {
+            depth += 1;
+        }
+        index += 1;
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(line: &StyledLine) -> String {
+        line.iter().map(|(text, _)| text.as_str()).collect()
+    }
+
+    fn tree_text(inspector: &CellInspector) -> Vec<String> {
+        inspector
+            .tree_lines()
+            .iter()
+            .map(|tree_line| text(&tree_line.line))
+            .collect()
+    }
+
+    fn inspector(ty: Type, value: Value) -> CellInspector {
+        CellInspector::new(&Column::new(String::from("value"), ty), &value)
+    }
+
+    #[test]
+    fn json_is_shown_as_a_tree() {
+        let inspector = inspector(
+            Type::JSONB,
+            Value::Json(String::from(r#"{"a": [1, "x"], "b": {}, "c": null}"#)),
+        );
+
+        assert!(inspector.is_tree());
+        assert_eq!(inspector.title, "value (jsonb)");
+        assert_eq!(
+            tree_text(&inspector),
+            vec![
+                "{",
+                r#"  "a": ["#,
+                "    1,",
+                r#"    "x""#,
+                "  ],",
+                r#"  "b": {},"#,
+                r#"  "c": null"#,
+                "}",
+            ]
+        );
+    }
+
+    #[test]
+    fn objects_and_arrays_collapse_on_the_cursor_line() {
+        let mut inspector = inspector(
+            Type::JSON,
+            Value::Json(String::from(r#"{"a": [1, 2], "b": {"c": 3}}"#)),
+        );
+
+        inspector.next_line();
+        inspector.toggle();
+        assert_eq!(
+            tree_text(&inspector),
+            vec![
+                "{",
+                r#"  "a": […] 2 items,"#,
+                r#"  "b": {"#,
+                r#"    "c": 3"#,
+                "  }",
+                "}"
+            ]
+        );
+
+        inspector.bottom();
+        assert_eq!(inspector.cursor, 5);
+        inspector.prev_line();
+        inspector.prev_line();
+        inspector.prev_line();
+        inspector.collapse();
+        assert_eq!(
+            tree_text(&inspector),
+            vec!["{", r#"  "a": […] 2 items,"#, r#"  "b": {…} 1 key"#, "}"]
+        );
+
+        inspector.top();
+        inspector.next_line();
+        inspector.expand();
+        assert_eq!(tree_text(&inspector).len(), 7);
+
+        // Lines without children can't be collapsed
+        inspector.bottom();
+        inspector.toggle();
+        assert_eq!(tree_text(&inspector).len(), 7);
+    }
+
+    #[test]
+    fn invalid_json_is_shown_as_text() {
+        let inspector = inspector(Type::JSON, Value::Json(String::from("{oops")));
+
+        assert!(!inspector.is_tree());
+        assert!(matches!(&inspector.content, InspectorContent::Text(text) if text == "{oops"));
+    }
+
+    #[test]
+    fn text_scrolls_instead_of_moving_a_cursor() {
+        let mut inspector = inspector(Type::TEXT, Value::Text(String::from("a\nb")));
+        inspector.viewport_height = 3;
+
+        inspector.page_down();
+        assert_eq!((inspector.cursor, inspector.scroll), (0, 3));
+        inspector.prev_line();
+        assert_eq!(inspector.scroll, 2);
+        inspector.bottom();
+        assert_eq!(inspector.scroll, usize::MAX);
+        inspector.top();
+        assert_eq!(inspector.scroll, 0);
+    }
+
+    #[test]
+    fn bytes_are_shown_as_a_hex_dump() {
+        let mut bytes = b"Hello, world!\n\x00\xff".to_vec();
+        bytes.extend_from_slice(b"ab");
+
+        let lines = hex_dump(&bytes);
+
+        assert_eq!(
+            lines.iter().map(text).collect::<Vec<String>>(),
+            vec![
+                "00000000  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 0a 00 ff  |Hello, world!...|",
+                "00000010  61 62                                             |ab|",
+            ]
+        );
+    }
+
+    #[test]
+    fn xml_is_indented_by_nesting() {
+        let lines = indent_xml(r#"<?xml version="1.0"?><a><b>text</b><c/><d><e>1</e></d></a>"#);
+
+        assert_eq!(
+            lines.iter().map(text).collect::<Vec<String>>(),
+            vec![
+                r#"<?xml version="1.0"?>"#,
+                "<a>",
+                "  <b>text</b>",
+                "  <c/>",
+                "  <d>",
+                "    <e>1</e>",
+                "  </d>",
+                "</a>",
+            ]
+        );
+    }
+}
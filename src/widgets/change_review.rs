@@ -0,0 +1,24 @@
+// Review of the pending changes, one of them is selected to show its
+// statement and what it changes.
+#[derive(Debug, Default)]
+pub struct ChangeReview {
+    pub selected: usize,
+    pub error: Option<String>,
+}
+
+impl ChangeReview {
+    pub fn next(&mut self, count: usize) {
+        if self.selected + 1 < count {
+            self.selected += 1;
+        }
+    }
+
+    pub fn prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn clamp(&mut self, count: usize) {
+        self.selected = self.selected.min(count.saturating_sub(1));
+        self.error = None;
+    }
+}
@@ -0,0 +1,329 @@
+use std::collections::{HashMap, HashSet};
+
+use super::database_table::DatabaseTable;
+use crate::postgres::{
+    data::{Column, Generated, Value},
+    edit::CellValue,
+    query::{TableName, TableQuery},
+};
+
+#[derive(Debug, Clone)]
+pub enum ChangeKind {
+    // A cell with the value it had when it was loaded
+    Update {
+        column: String,
+        before: Value,
+        after: CellValue,
+    },
+    Insert(Vec<(String, CellValue)>),
+    // The row as it was loaded
+    Delete(Vec<(String, Value)>),
+}
+
+// A change waiting to be applied. Rows are identified by the text of their
+// key columns as loaded, which stays valid until the changes are applied.
+#[derive(Debug, Clone)]
+pub struct Change {
+    // Database the table is in, changes only apply while connected to it
+    pub database: String,
+    pub table: TableName,
+    // Columns of the table and its key, the statement returns the row it
+    // touched in the same columns
+    pub columns: Vec<Column>,
+    pub key_columns: Vec<String>,
+    // Columns filled by the server, which inserting a deleted row back has
+    // to leave out or override
+    pub generated_columns: Vec<(String, Generated)>,
+    pub key: Vec<(String, String)>,
+    pub kind: ChangeKind,
+    pub query: TableQuery,
+}
+
+impl Change {
+    pub fn sql(&self) -> String {
+        match self.kind {
+            ChangeKind::Update { .. } => self.query.to_update_sql(),
+            ChangeKind::Insert(_) => self.query.to_insert_sql(),
+            ChangeKind::Delete(_) => self.query.to_delete_sql(),
+        }
+    }
+
+    // Short description such as "UPDATE customers (id = 5)".
+    pub fn summary(&self) -> String {
+        let verb = match self.kind {
+            ChangeKind::Update { .. } => "UPDATE",
+            ChangeKind::Insert(_) => "INSERT",
+            ChangeKind::Delete(_) => "DELETE",
+        };
+        let key: Vec<String> = self
+            .key
+            .iter()
+            .map(|(column, value)| format!("{} = {}", column, value))
+            .collect();
+
+        if key.is_empty() {
+            format!("{} {}", verb, self.table)
+        } else {
+            format!("{} {} ({})", verb, self.table, key.join(", "))
+        }
+    }
+
+    fn is_row(&self, table: &TableName, key: &[(String, String)]) -> bool {
+        self.table == *table && self.key == key
+    }
+
+    fn is_update_of(&self, column: &str) -> bool {
+        matches!(&self.kind, ChangeKind::Update { column: updated, .. } if updated == column)
+    }
+}
+
+// How a cell of a staged value is shown in the grid.
+pub fn staged_value(value: &CellValue) -> Value {
+    match value {
+        CellValue::Text(text) => Value::Text(text.clone()),
+        CellValue::Null => Value::Null,
+        CellValue::Default => Value::Text(String::from("DEFAULT")),
+    }
+}
+
+// Loaded cells with a staged value and rows staged for deletion.
+#[derive(Debug, Default)]
+pub struct StagedCells {
+    pub values: HashMap<(usize, usize), Value>,
+    pub deleted_rows: HashSet<usize>,
+}
+
+// Edits, inserts and deletes collected until they are applied together in
+// one transaction or discarded.
+#[derive(Debug, Default)]
+pub struct ChangeSet {
+    pub changes: Vec<Change>,
+}
+
+impl ChangeSet {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    // Stage a new value for a cell, replacing an earlier one. Setting the
+    // cell back to the value it was loaded with drops the change.
+    pub fn stage_update(&mut self, change: Change) {
+        let (column, before, after) = match &change.kind {
+            ChangeKind::Update {
+                column,
+                before,
+                after,
+            } => (column, before, after),
+            _ => return,
+        };
+
+        self.changes.retain(|staged| {
+            !(staged.is_row(&change.table, &change.key) && staged.is_update_of(column))
+        });
+
+        let unchanged = match after {
+            CellValue::Text(text) => before.to_text().as_ref() == Some(text),
+            CellValue::Null => before.is_null(),
+            CellValue::Default => false,
+        };
+        if !unchanged {
+            self.changes.push(change);
+        }
+    }
+
+    pub fn stage_insert(&mut self, change: Change) {
+        self.changes.push(change);
+    }
+
+    // Stage the deletion of a row, or take it back when it already is.
+    // Returns whether the row is now staged for deletion.
+    pub fn toggle_delete(&mut self, change: Change) -> bool {
+        let staged = self.changes.iter().position(|staged| {
+            matches!(staged.kind, ChangeKind::Delete(_))
+                && staged.is_row(&change.table, &change.key)
+        });
+
+        match staged {
+            Some(index) => {
+                self.changes.remove(index);
+                false
+            }
+            None => {
+                self.changes.push(change);
+                true
+            }
+        }
+    }
+
+    pub fn is_deleted(&self, table: &TableName, key: &[(String, String)]) -> bool {
+        self.changes
+            .iter()
+            .any(|staged| matches!(staged.kind, ChangeKind::Delete(_)) && staged.is_row(table, key))
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.changes.len() {
+            self.changes.remove(index);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.changes.clear();
+    }
+
+    // Staged values and deletions of the rows loaded in the table.
+    pub fn staged_cells(&self, table: &DatabaseTable) -> StagedCells {
+        let mut staged_cells = StagedCells::default();
+        let changes: Vec<&Change> = self
+            .changes
+            .iter()
+            .filter(|change| change.table == table.name && !change.key.is_empty())
+            .collect();
+        if changes.is_empty() {
+            return staged_cells;
+        }
+
+        for row in 0..table.data.len() {
+            let key = match table.row_key(row) {
+                Some(key) => key,
+                None => continue,
+            };
+
+            for change in changes.iter().filter(|change| change.key == key) {
+                match &change.kind {
+                    ChangeKind::Update { column, after, .. } => {
+                        if let Some(index) = table.columns.iter().position(|c| &c.name == column) {
+                            staged_cells
+                                .values
+                                .insert((row, index), staged_value(after));
+                        }
+                    }
+                    ChangeKind::Delete(_) => {
+                        staged_cells.deleted_rows.insert(row);
+                    }
+                    ChangeKind::Insert(_) => {}
+                }
+            }
+        }
+
+        staged_cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postgres::{data::Column, query::TableName};
+    use tokio_postgres::types::Type;
+
+    fn table() -> DatabaseTable {
+        let mut table = DatabaseTable::new(
+            TableName::new("public", "orders"),
+            vec![
+                Column::new(String::from("id"), Type::INT4),
+                Column::new(String::from("note"), Type::TEXT),
+            ],
+        );
+        table.primary_key = vec![String::from("id")];
+        table.data = vec![
+            vec![Value::Int(1), Value::Text(String::from("a"))],
+            vec![Value::Int(2), Value::Null],
+        ];
+        table
+    }
+
+    fn update(table: &DatabaseTable, row: usize, value: CellValue) -> Change {
+        table.update_change("shop", row, 1, value).unwrap()
+    }
+
+    fn delete(table: &DatabaseTable, row: usize) -> Change {
+        table.delete_change("shop", row).unwrap()
+    }
+
+    fn text(text: &str) -> CellValue {
+        CellValue::Text(text.to_string())
+    }
+
+    #[test]
+    fn a_new_value_replaces_the_staged_one() {
+        let table = table();
+        let mut changes = ChangeSet::default();
+
+        changes.stage_update(update(&table, 0, text("b")));
+        changes.stage_update(update(&table, 1, text("c")));
+        changes.stage_update(update(&table, 0, CellValue::Null));
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(
+            changes.changes[0].summary(),
+            "UPDATE public.orders (id = 2)"
+        );
+        assert!(matches!(
+            &changes.changes[1].kind,
+            ChangeKind::Update {
+                after: CellValue::Null,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn setting_the_loaded_value_again_drops_the_change() {
+        let table = table();
+        let mut changes = ChangeSet::default();
+
+        changes.stage_update(update(&table, 0, text("b")));
+        changes.stage_update(update(&table, 0, text("a")));
+        changes.stage_update(update(&table, 1, CellValue::Null));
+
+        assert!(changes.is_empty());
+
+        changes.stage_update(update(&table, 1, CellValue::Default));
+        assert_eq!(changes.len(), 1);
+    }
+
+    #[test]
+    fn deleting_twice_takes_the_delete_back() {
+        let table = table();
+        let mut changes = ChangeSet::default();
+        let key = table.row_key(1).unwrap();
+
+        assert!(changes.toggle_delete(delete(&table, 1)));
+        assert!(changes.is_deleted(&table.name, &key));
+        assert_eq!(
+            changes.changes[0].sql(),
+            r#"DELETE FROM "public"."orders" WHERE "id" = $1::text::"pg_catalog"."int4""#
+        );
+
+        assert!(!changes.toggle_delete(delete(&table, 1)));
+        assert!(!changes.is_deleted(&table.name, &key));
+    }
+
+    #[test]
+    fn staged_cells_follow_the_loaded_rows() {
+        let mut table = table();
+        let mut changes = ChangeSet::default();
+        changes.stage_update(update(&table, 0, CellValue::Default));
+        changes.toggle_delete(delete(&table, 1));
+
+        // The rows moved since the changes were staged
+        table.data.reverse();
+        let staged = changes.staged_cells(&table);
+
+        assert_eq!(
+            staged.values,
+            HashMap::from([((1, 1), Value::Text(String::from("DEFAULT")))])
+        );
+        assert_eq!(staged.deleted_rows, HashSet::from([0]));
+
+        changes.remove(0);
+        changes.remove(5);
+        assert_eq!(changes.len(), 1);
+        changes.clear();
+        assert!(changes.staged_cells(&table).deleted_rows.is_empty());
+    }
+}
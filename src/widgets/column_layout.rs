@@ -0,0 +1,285 @@
+use serde_json::json;
+
+use crate::postgres::data::Column;
+
+// How the columns of a table are arranged in the grid: their order, which
+// are hidden and which are pinned to the left edge.
+//
+// Columns are referenced by name so the layout survives columns being added
+// to or dropped from the table. Columns missing from `order` are shown after
+// the ordered ones in table order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnLayout {
+    pub order: Vec<String>,
+    pub hidden: Vec<String>,
+    pub pinned: Vec<String>,
+}
+
+impl ColumnLayout {
+    pub fn is_default(&self) -> bool {
+        self.order.is_empty() && self.hidden.is_empty() && self.pinned.is_empty()
+    }
+
+    // Names of all columns, pinned or not, in the order they're shown.
+    fn full_order(&self, columns: &[Column]) -> Vec<String> {
+        let mut order: Vec<String> = self
+            .order
+            .iter()
+            .filter(|name| columns.iter().any(|column| &column.name == *name))
+            .cloned()
+            .collect();
+
+        for column in columns {
+            if !order.contains(&column.name) {
+                order.push(column.name.clone());
+            }
+        }
+
+        order
+    }
+
+    // Indexes of the visible columns in display order, pinned columns first.
+    pub fn display_order(&self, columns: &[Column]) -> Vec<usize> {
+        let index_of = |name: &String| columns.iter().position(|column| &column.name == name);
+
+        let pinned = self
+            .pinned
+            .iter()
+            .filter(|name| !self.hidden.contains(name))
+            .filter_map(index_of);
+
+        let rest = self
+            .full_order(columns)
+            .into_iter()
+            .filter(|name| !self.pinned.contains(name) && !self.hidden.contains(name))
+            .filter_map(|name| index_of(&name));
+
+        pinned.chain(rest).collect()
+    }
+
+    pub fn pinned_count(&self, columns: &[Column]) -> usize {
+        self.pinned
+            .iter()
+            .filter(|name| !self.hidden.contains(name))
+            .filter(|name| columns.iter().any(|column| &column.name == *name))
+            .count()
+    }
+
+    pub fn hide(&mut self, name: &str) {
+        if !self.hidden.iter().any(|hidden| hidden == name) {
+            self.hidden.push(name.to_string());
+        }
+    }
+
+    pub fn show_all(&mut self) {
+        self.hidden.clear();
+    }
+
+    pub fn toggle_pinned(&mut self, name: &str) {
+        match self.pinned.iter().position(|pinned| pinned == name) {
+            Some(index) => {
+                self.pinned.remove(index);
+            }
+            None => self.pinned.push(name.to_string()),
+        }
+    }
+
+    // Swap a column with its visible neighbour to the left or right. Pinned
+    // columns only move among the pinned ones.
+    pub fn move_column(&mut self, name: &str, to_right: bool, columns: &[Column]) {
+        let is_pinned = self.pinned.iter().any(|pinned| pinned == name);
+        let mut order = if is_pinned {
+            self.pinned.clone()
+        } else {
+            self.full_order(columns)
+        };
+
+        let is_movable = |other: &String| {
+            !self.hidden.contains(other) && (is_pinned || !self.pinned.contains(other))
+        };
+
+        let position = match order.iter().position(|other| other == name) {
+            Some(position) => position,
+            None => return,
+        };
+
+        let neighbour = if to_right {
+            (position + 1..order.len()).find(|index| is_movable(&order[*index]))
+        } else {
+            (0..position).rev().find(|index| is_movable(&order[*index]))
+        };
+
+        if let Some(neighbour) = neighbour {
+            order.swap(position, neighbour);
+            if is_pinned {
+                self.pinned = order;
+            } else {
+                self.order = order;
+            }
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "order": self.order,
+            "hidden": self.hidden,
+            "pinned": self.pinned,
+        })
+    }
+
+    pub fn from_json(value: &serde_json::Value) -> Self {
+        let names = |key: &str| -> Vec<String> {
+            value[key]
+                .as_array()
+                .map(|names| {
+                    names
+                        .iter()
+                        .filter_map(|name| name.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        Self {
+            order: names("order"),
+            hidden: names("hidden"),
+            pinned: names("pinned"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_postgres::types::Type;
+
+    fn columns(names: &[&str]) -> Vec<Column> {
+        names
+            .iter()
+            .map(|name| Column::new(name.to_string(), Type::TEXT))
+            .collect()
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn default_layout_keeps_table_order() {
+        let columns = columns(&["a", "b", "c"]);
+        let layout = ColumnLayout::default();
+
+        assert!(layout.is_default());
+        assert_eq!(layout.display_order(&columns), vec![0, 1, 2]);
+        assert_eq!(layout.pinned_count(&columns), 0);
+    }
+
+    #[test]
+    fn pinned_columns_come_first_and_hidden_ones_are_left_out() {
+        let columns = columns(&["a", "b", "c", "d", "e"]);
+        let layout = ColumnLayout {
+            order: Vec::new(),
+            hidden: names(&["b", "e"]),
+            pinned: names(&["d", "e", "gone"]),
+        };
+
+        assert_eq!(layout.display_order(&columns), vec![3, 0, 2]);
+        assert_eq!(layout.pinned_count(&columns), 1);
+    }
+
+    #[test]
+    fn stored_order_survives_added_and_dropped_columns() {
+        let layout = ColumnLayout {
+            order: names(&["c", "gone", "a"]),
+            ..ColumnLayout::default()
+        };
+
+        assert_eq!(
+            layout.display_order(&columns(&["a", "b", "c", "new"])),
+            vec![2, 0, 1, 3]
+        );
+    }
+
+    #[test]
+    fn move_column_swaps_with_its_neighbour() {
+        let columns = columns(&["a", "b", "c"]);
+        let mut layout = ColumnLayout::default();
+
+        layout.move_column("b", true, &columns);
+        assert_eq!(layout.order, names(&["a", "c", "b"]));
+        layout.move_column("a", false, &columns);
+        assert_eq!(layout.order, names(&["a", "c", "b"]));
+        layout.move_column("b", true, &columns);
+        assert_eq!(layout.display_order(&columns), vec![0, 2, 1]);
+        layout.move_column("missing", true, &columns);
+        assert_eq!(layout.order, names(&["a", "c", "b"]));
+    }
+
+    #[test]
+    fn move_column_skips_hidden_columns() {
+        let columns = columns(&["a", "b", "c"]);
+        let mut layout = ColumnLayout {
+            hidden: names(&["b"]),
+            ..ColumnLayout::default()
+        };
+
+        layout.move_column("c", false, &columns);
+        assert_eq!(layout.order, names(&["c", "b", "a"]));
+        assert_eq!(layout.display_order(&columns), vec![2, 0]);
+    }
+
+    #[test]
+    fn pinned_columns_only_move_among_themselves() {
+        let columns = columns(&["a", "b", "c", "d"]);
+        let mut layout = ColumnLayout::default();
+        layout.toggle_pinned("c");
+        layout.toggle_pinned("a");
+        assert_eq!(layout.display_order(&columns), vec![2, 0, 1, 3]);
+
+        layout.move_column("a", false, &columns);
+        assert_eq!(layout.pinned, names(&["a", "c"]));
+        assert!(layout.order.is_empty());
+
+        // The first unpinned column can't move in among the pinned ones
+        layout.move_column("b", false, &columns);
+        assert!(layout.order.is_empty());
+        layout.move_column("b", true, &columns);
+        assert_eq!(layout.order, names(&["a", "d", "c", "b"]));
+        assert_eq!(layout.display_order(&columns), vec![0, 2, 3, 1]);
+
+        layout.toggle_pinned("a");
+        assert_eq!(layout.pinned, names(&["c"]));
+        assert_eq!(layout.display_order(&columns), vec![2, 0, 3, 1]);
+    }
+
+    #[test]
+    fn hide_and_show_all() {
+        let columns = columns(&["a", "b"]);
+        let mut layout = ColumnLayout::default();
+
+        layout.hide("a");
+        layout.hide("a");
+        assert_eq!(layout.hidden, names(&["a"]));
+        assert_eq!(layout.display_order(&columns), vec![1]);
+        layout.show_all();
+        assert!(layout.is_default());
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let layout = ColumnLayout {
+            order: names(&["b", "a"]),
+            hidden: names(&["c"]),
+            pinned: names(&["a"]),
+        };
+
+        assert_eq!(ColumnLayout::from_json(&layout.to_json()), layout);
+        assert_eq!(
+            ColumnLayout::from_json(&json!({ "order": ["x", 1] })),
+            ColumnLayout {
+                order: names(&["x"]),
+                ..ColumnLayout::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,735 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, StatefulWidget, Widget},
+};
+use std::ops::RangeInclusive;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use super::{change_set::StagedCells, result_search::ResultSearch};
+use crate::postgres::{
+    data::{Column, Value},
+    query::SortKey,
+};
+
+const COLUMN_SEPARATOR: &str = " │ ";
+const PINNED_SEPARATOR: &str = " ┃ ";
+const MAX_COLUMN_WIDTH: usize = 40;
+const ELLIPSIS: char = '…';
+
+// Above this many rows the column widths are only measured on the rows that
+// are currently visible.
+const MEASURE_ALL_ROWS_LIMIT: usize = 1000;
+
+// Cursor and scroll position of a grid.
+//
+// The offsets are adjusted while rendering so that the cursor always stays
+// inside the visible area.
+#[derive(Debug, Clone, Default)]
+pub struct GridState {
+    pub selected_row: usize,
+    pub selected_column: usize,
+    pub row_offset: usize,
+    pub column_offset: usize,
+    pub viewport_height: usize,
+    // Screen line of the header and the horizontal extent of every column
+    // drawn on it during the last render, used to resolve mouse clicks.
+    pub header_y: Option<u16>,
+    pub header_columns: Vec<(u16, u16, usize)>,
+    // Row and column where the visual selection started, the selection is
+    // the rectangle between it and the cursor
+    pub selection_anchor: Option<(usize, usize)>,
+}
+
+impl GridState {
+    pub fn next_row(&mut self, row_count: usize) {
+        if self.selected_row + 1 < row_count {
+            self.selected_row += 1;
+        }
+    }
+
+    pub fn prev_row(&mut self) {
+        self.selected_row = self.selected_row.saturating_sub(1);
+    }
+
+    pub fn next_column(&mut self, column_count: usize) {
+        if self.selected_column + 1 < column_count {
+            self.selected_column += 1;
+        }
+    }
+
+    pub fn prev_column(&mut self) {
+        self.selected_column = self.selected_column.saturating_sub(1);
+    }
+
+    pub fn first_row(&mut self) {
+        self.selected_row = 0;
+    }
+
+    pub fn last_row(&mut self, row_count: usize) {
+        self.selected_row = row_count.saturating_sub(1);
+    }
+
+    pub fn first_column(&mut self) {
+        self.selected_column = 0;
+        self.column_offset = 0;
+    }
+
+    pub fn last_column(&mut self, column_count: usize) {
+        self.selected_column = column_count.saturating_sub(1);
+    }
+
+    pub fn page_down(&mut self, row_count: usize) {
+        let page = self.viewport_height.max(1);
+        self.selected_row = (self.selected_row + page).min(row_count.saturating_sub(1));
+    }
+
+    pub fn page_up(&mut self) {
+        let page = self.viewport_height.max(1);
+        self.selected_row = self.selected_row.saturating_sub(page);
+    }
+
+    // Index of the column whose header was drawn at the given position.
+    pub fn header_column_at(&self, x: u16, y: u16) -> Option<usize> {
+        if self.header_y != Some(y) {
+            return None;
+        }
+
+        self.header_columns
+            .iter()
+            .find(|(start, end, _)| *start <= x && x < *end)
+            .map(|(_, _, index)| *index)
+    }
+
+    // Keep the cursor inside the data after it has been replaced.
+    pub fn clamp(&mut self, row_count: usize, column_count: usize) {
+        self.selected_row = self.selected_row.min(row_count.saturating_sub(1));
+        self.selected_column = self.selected_column.min(column_count.saturating_sub(1));
+        if let Some((row, column)) = self.selection_anchor.as_mut() {
+            *row = (*row).min(row_count.saturating_sub(1));
+            *column = (*column).min(column_count.saturating_sub(1));
+        }
+    }
+
+    pub fn toggle_selection(&mut self) {
+        self.selection_anchor = match self.selection_anchor {
+            Some(_) => None,
+            None => Some((self.selected_row, self.selected_column)),
+        };
+    }
+
+    // Rows and column positions covered by the visual selection.
+    pub fn selection(&self) -> Option<(RangeInclusive<usize>, RangeInclusive<usize>)> {
+        let (anchor_row, anchor_column) = self.selection_anchor?;
+        Some((
+            anchor_row.min(self.selected_row)..=anchor_row.max(self.selected_row),
+            anchor_column.min(self.selected_column)..=anchor_column.max(self.selected_column),
+        ))
+    }
+
+    pub fn is_in_selection(&self, row: usize, position: usize) -> bool {
+        self.selection()
+            .is_some_and(|(rows, positions)| rows.contains(&row) && positions.contains(&position))
+    }
+}
+
+// Borrowed columns, rows and cursor of a result shown in a grid.
+pub struct GridView<'a> {
+    pub columns: &'a [Column],
+    pub rows: &'a [Vec<Value>],
+    pub revision: u64,
+    pub grid: &'a mut GridState,
+    // Indexes of the visible columns in the order they're shown, the grid's
+    // selected column is a position in this list
+    pub column_order: Vec<usize>,
+}
+
+impl<'a> GridView<'a> {
+    pub fn selected_column_index(&self) -> Option<usize> {
+        self.column_order.get(self.grid.selected_column).copied()
+    }
+
+    // Columns and values of a block of rows and column positions.
+    pub fn cells(
+        &self,
+        rows: RangeInclusive<usize>,
+        positions: RangeInclusive<usize>,
+    ) -> (Vec<&'a Column>, Vec<Vec<&'a Value>>) {
+        let columns: &'a [Column] = self.columns;
+        let data: &'a [Vec<Value>] = self.rows;
+        let indexes: Vec<usize> = positions
+            .filter_map(|position| self.column_order.get(position).copied())
+            .collect();
+
+        let block_columns = indexes.iter().map(|index| &columns[*index]).collect();
+        let block_rows = rows
+            .filter_map(|row| data.get(row))
+            .map(|row| {
+                indexes
+                    .iter()
+                    .map(|index| row.get(*index).unwrap_or(&Value::Null))
+                    .collect()
+            })
+            .collect();
+
+        (block_columns, block_rows)
+    }
+}
+
+pub struct DataGrid<'a> {
+    block: Option<Block<'a>>,
+    columns: &'a [Column],
+    rows: &'a [Vec<Value>],
+    sort: &'a [SortKey],
+    column_order: Option<&'a [usize]>,
+    pinned_columns: usize,
+    search: Option<&'a ResultSearch>,
+    staged: Option<&'a StagedCells>,
+}
+
+impl<'a> DataGrid<'a> {
+    pub fn new(columns: &'a [Column], rows: &'a [Vec<Value>]) -> DataGrid<'a> {
+        Self {
+            block: None,
+            columns,
+            rows,
+            sort: &[],
+            column_order: None,
+            pinned_columns: 0,
+            search: None,
+            staged: None,
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> DataGrid<'a> {
+        self.block = Some(block);
+        self
+    }
+
+    // Indexes of the columns to show in display order, the first
+    // `pinned_columns` of them stay in place while scrolling horizontally.
+    pub fn column_order(
+        mut self,
+        column_order: &'a [usize],
+        pinned_columns: usize,
+    ) -> DataGrid<'a> {
+        self.column_order = Some(column_order);
+        self.pinned_columns = pinned_columns;
+        self
+    }
+
+    // Highlight the cells matching the search.
+    pub fn search(mut self, search: Option<&'a ResultSearch>) -> DataGrid<'a> {
+        self.search = search;
+        self
+    }
+
+    // Show staged values in place of the loaded ones and strike out rows
+    // staged for deletion.
+    pub fn staged(mut self, staged: Option<&'a StagedCells>) -> DataGrid<'a> {
+        self.staged = staged;
+        self
+    }
+
+    pub fn sort(mut self, sort: &'a [SortKey]) -> DataGrid<'a> {
+        self.sort = sort;
+        self
+    }
+
+    // Header text of a column, with an arrow when the column is sorted and
+    // its position in the sort when sorting by more than one column.
+    fn header_label(&self, column: &Column) -> String {
+        match self.sort.iter().position(|key| key.column == column.name) {
+            Some(position) => {
+                let arrow = if self.sort[position].descending {
+                    '▼'
+                } else {
+                    '▲'
+                };
+                if self.sort.len() > 1 {
+                    format!("{} {}{}", column.name, arrow, position + 1)
+                } else {
+                    format!("{} {}", column.name, arrow)
+                }
+            }
+            None => column.name.clone(),
+        }
+    }
+
+    // Width of every column, based on the header and the measured rows and
+    // capped to MAX_COLUMN_WIDTH.
+    fn column_widths(&self, visible_rows: &[Vec<Value>]) -> Vec<usize> {
+        let measured_rows = if self.rows.len() <= MEASURE_ALL_ROWS_LIMIT {
+            self.rows
+        } else {
+            visible_rows
+        };
+
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                let content_width = measured_rows
+                    .iter()
+                    .filter_map(|row| row.get(index))
+                    .map(|value| cell_text(value).width())
+                    .max()
+                    .unwrap_or(0);
+
+                content_width
+                    .max(self.header_label(column).width())
+                    .clamp(1, MAX_COLUMN_WIDTH)
+            })
+            .collect()
+    }
+}
+
+// Single line text of a cell, line breaks and tabs would break the grid.
+pub fn cell_text(value: &Value) -> String {
+    value
+        .to_string()
+        .chars()
+        .map(|c| {
+            if c == '\n' || c == '\r' || c == '\t' {
+                ' '
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+// Cut text down to the given display width, marking the cut with an ellipsis.
+pub fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+
+    let mut truncated = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if used + char_width + 1 > width {
+            break;
+        }
+        truncated.push(c);
+        used += char_width;
+    }
+
+    if width > 0 {
+        truncated.push(ELLIPSIS);
+    }
+    truncated
+}
+
+// Pad text with spaces up to the given display width.
+pub fn pad(text: &str, width: usize) -> String {
+    format!("{}{}", text, " ".repeat(width.saturating_sub(text.width())))
+}
+
+// Break a line of styled pieces into rows of at most the given display
+// width. Tabs take a single column like spaces.
+pub fn wrap_styled<S: Copy>(line: &[(String, S)], width: usize) -> Vec<Vec<(String, S)>> {
+    let mut rows = vec![Vec::new()];
+    let mut used = 0;
+
+    for (text, style) in line {
+        let mut piece = String::new();
+        for c in text.chars() {
+            let c = if c == '\t' { ' ' } else { c };
+            let char_width = c.width().unwrap_or(0);
+            if used + char_width > width && used > 0 {
+                if !piece.is_empty() {
+                    rows.last_mut()
+                        .unwrap()
+                        .push((std::mem::take(&mut piece), *style));
+                }
+                rows.push(Vec::new());
+                used = 0;
+            }
+            piece.push(c);
+            used += char_width;
+        }
+        if !piece.is_empty() {
+            rows.last_mut().unwrap().push((piece, *style));
+        }
+    }
+
+    rows
+}
+
+// Break text into lines of the given display width, keeping its own line
+// breaks.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = text
+        .lines()
+        .flat_map(|line| wrap_styled(&[(line.to_string(), ())], width))
+        .map(|row| row.into_iter().map(|(piece, _)| piece).collect())
+        .collect();
+
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines
+}
+
+// Display positions of the columns that fit into the available width, the
+// pinned columns followed by the columns starting at the offset. With
+// allow_partial the last column is included even if it has to be cut off.
+fn visible_columns(
+    widths: &[usize],
+    pinned: usize,
+    offset: usize,
+    available_width: usize,
+    allow_partial: bool,
+) -> Vec<usize> {
+    let mut visible = Vec::new();
+    let mut used = 0;
+
+    let positions = (0..pinned.min(widths.len())).chain(offset.max(pinned)..widths.len());
+    for index in positions {
+        let width = widths[index];
+        let needed = if visible.is_empty() {
+            width
+        } else {
+            width + COLUMN_SEPARATOR.width()
+        };
+
+        if used + needed > available_width && !visible.is_empty() {
+            if allow_partial && used + COLUMN_SEPARATOR.width() < available_width {
+                visible.push(index);
+            }
+            break;
+        }
+
+        used += needed;
+        visible.push(index);
+    }
+
+    visible
+}
+
+impl<'a> StatefulWidget for DataGrid<'a> {
+    type State = GridState;
+
+    // Render a fixed header row followed by the rows in the viewport.
+    //
+    // The row offset is moved so the selected row stays visible, the column
+    // offset is moved so the selected column is the last column that still
+    // fits when scrolling to the right.
+    fn render(mut self, area: Rect, buf: &mut Buffer, state: &mut GridState) {
+        let inner_area = match self.block.take() {
+            Some(b) => {
+                let inner_area = b.inner(area);
+                b.render(area, buf);
+                inner_area
+            }
+            None => area,
+        };
+
+        state.header_y = None;
+        state.header_columns.clear();
+
+        let column_order: Vec<usize> = match self.column_order {
+            Some(column_order) => column_order.to_vec(),
+            None => (0..self.columns.len()).collect(),
+        };
+        let pinned = self.pinned_columns.min(column_order.len());
+
+        if inner_area.height < 3 || inner_area.width == 0 || column_order.is_empty() {
+            return;
+        }
+
+        state.clamp(self.rows.len(), column_order.len());
+
+        // Header and separator line take the first two lines
+        let viewport_height = (inner_area.height - 2) as usize;
+        state.viewport_height = viewport_height;
+
+        if state.selected_row < state.row_offset {
+            state.row_offset = state.selected_row;
+        } else if state.selected_row >= state.row_offset + viewport_height {
+            state.row_offset = state.selected_row + 1 - viewport_height;
+        }
+
+        let row_end = (state.row_offset + viewport_height).min(self.rows.len());
+        let visible_rows = &self.rows[state.row_offset.min(row_end)..row_end];
+
+        let column_widths = self.column_widths(visible_rows);
+        let widths: Vec<usize> = column_order
+            .iter()
+            .map(|index| column_widths[*index])
+            .collect();
+        let available_width = inner_area.width as usize;
+
+        // Pinned columns are always visible, only the others scroll
+        state.column_offset = state.column_offset.max(pinned);
+        if state.selected_column >= pinned && state.selected_column < state.column_offset {
+            state.column_offset = state.selected_column;
+        }
+        while state.column_offset < state.selected_column
+            && !visible_columns(&widths, pinned, state.column_offset, available_width, false)
+                .contains(&state.selected_column)
+        {
+            state.column_offset += 1;
+        }
+
+        let positions =
+            visible_columns(&widths, pinned, state.column_offset, available_width, true);
+
+        let header_style = Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD);
+        let separator_style = Style::default().fg(Color::DarkGray);
+        let null_style = Style::default()
+            .fg(Color::DarkGray)
+            .add_modifier(Modifier::ITALIC);
+        let selected_row_style = Style::default().bg(Color::DarkGray);
+        let selected_cell_style = Style::default().bg(Color::Blue).fg(Color::White);
+        let match_style = Style::default().bg(Color::Yellow).fg(Color::Black);
+        let visual_style = Style::default().bg(Color::Cyan).fg(Color::Black);
+        let staged_style = Style::default().bg(Color::Magenta).fg(Color::Black);
+        let deleted_style = Style::default()
+            .fg(Color::LightRed)
+            .add_modifier(Modifier::CROSSED_OUT);
+
+        let right_edge = inner_area.x + inner_area.width;
+        let draw_line = |buf: &mut Buffer,
+                         y: u16,
+                         cells: &mut dyn Iterator<Item = (usize, String, Style)>,
+                         line_style: Style|
+         -> Vec<(u16, u16, usize)> {
+            let mut spans = Vec::new();
+            let mut x = inner_area.x;
+            for (index, (position, text, style)) in cells.enumerate() {
+                if index > 0 {
+                    let separator = if index == pinned {
+                        PINNED_SEPARATOR
+                    } else {
+                        COLUMN_SEPARATOR
+                    };
+                    let (next_x, _) = buf.set_stringn(
+                        x,
+                        y,
+                        separator,
+                        (right_edge - x) as usize,
+                        line_style.patch(separator_style),
+                    );
+                    x = next_x;
+                }
+                if x >= right_edge {
+                    break;
+                }
+
+                let width = widths[position].min((right_edge - x) as usize);
+                let content = pad(&truncate(&text, width), width);
+                let (next_x, _) = buf.set_stringn(x, y, content, width, line_style.patch(style));
+                let end = next_x.max(x + width as u16);
+                spans.push((x, end, column_order[position]));
+                x = end;
+            }
+            spans
+        };
+
+        let mut header = positions.iter().map(|position| {
+            let column = &self.columns[column_order[*position]];
+            (*position, self.header_label(column), header_style)
+        });
+        state.header_y = Some(inner_area.y);
+        state.header_columns = draw_line(buf, inner_area.y, &mut header, Style::default());
+
+        buf.set_string(
+            inner_area.x,
+            inner_area.y + 1,
+            "─".repeat(inner_area.width as usize),
+            separator_style,
+        );
+
+        for (line, row) in visible_rows.iter().enumerate() {
+            let row_index = state.row_offset + line;
+            let is_selected_row = row_index == state.selected_row;
+            let mut line_style = if is_selected_row {
+                selected_row_style
+            } else {
+                Style::default()
+            };
+            if self
+                .staged
+                .is_some_and(|staged| staged.deleted_rows.contains(&row_index))
+            {
+                line_style = line_style.patch(deleted_style);
+            }
+
+            let y = inner_area.y + 2 + line as u16;
+            if is_selected_row {
+                buf.set_style(Rect::new(inner_area.x, y, inner_area.width, 1), line_style);
+            }
+
+            let mut cells = positions.iter().map(|position| {
+                let index = column_order[*position];
+                let staged_value = self
+                    .staged
+                    .and_then(|staged| staged.values.get(&(row_index, index)));
+                let value = staged_value
+                    .or_else(|| row.get(index))
+                    .unwrap_or(&Value::Null);
+                let style = if is_selected_row && *position == state.selected_column {
+                    selected_cell_style
+                } else if staged_value.is_some() {
+                    staged_style
+                } else if state.is_in_selection(row_index, *position) {
+                    visual_style
+                } else if self.search.is_some_and(|search| search.is_match(value)) {
+                    match_style
+                } else if value.is_null() {
+                    null_style
+                } else {
+                    Style::default()
+                };
+                (*position, cell_text(value), style)
+            });
+            draw_line(buf, y, &mut cells, line_style);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_postgres::types::Type;
+
+    fn columns(names: &[&str]) -> Vec<Column> {
+        names
+            .iter()
+            .map(|name| Column::new(name.to_string(), Type::TEXT))
+            .collect()
+    }
+
+    fn rows(count: usize, width: usize) -> Vec<Vec<Value>> {
+        (0..count)
+            .map(|row| {
+                (0..width)
+                    .map(|column| Value::Text(format!("{}-{}", row, column)))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn line(buf: &Buffer, y: u16) -> String {
+        (0..buf.area.width)
+            .map(|x| buf.get(x, y).symbol())
+            .collect::<String>()
+            .trim_end()
+            .to_string()
+    }
+
+    #[test]
+    fn wrap_keeps_line_breaks_and_splits_long_lines() {
+        assert_eq!(wrap("", 4), vec![""]);
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("ab\n\tc", 4), vec!["ab", " c"]);
+        assert_eq!(wrap("日本語", 4), vec!["日本", "語"]);
+    }
+
+    #[test]
+    fn wrap_styled_splits_pieces_across_rows() {
+        assert_eq!(
+            wrap_styled(&[(String::from("ab"), 1), (String::from("cde"), 2)], 3),
+            vec![
+                vec![(String::from("ab"), 1), (String::from("c"), 2)],
+                vec![(String::from("de"), 2)],
+            ]
+        );
+        assert_eq!(wrap_styled::<u8>(&[], 3), vec![Vec::new()]);
+    }
+
+    #[test]
+    fn cursor_stays_inside_the_data() {
+        let mut state = GridState {
+            viewport_height: 10,
+            ..GridState::default()
+        };
+
+        state.prev_row();
+        state.prev_column();
+        assert_eq!((state.selected_row, state.selected_column), (0, 0));
+
+        state.next_row(2);
+        state.next_row(2);
+        state.next_column(3);
+        assert_eq!((state.selected_row, state.selected_column), (1, 1));
+
+        state.last_column(3);
+        state.page_down(25);
+        assert_eq!((state.selected_row, state.selected_column), (11, 2));
+        state.page_down(25);
+        assert_eq!(state.selected_row, 21);
+        state.page_down(25);
+        assert_eq!(state.selected_row, 24);
+        state.page_up();
+        assert_eq!(state.selected_row, 14);
+
+        state.clamp(5, 1);
+        assert_eq!((state.selected_row, state.selected_column), (4, 0));
+        state.clamp(0, 0);
+        assert_eq!((state.selected_row, state.selected_column), (0, 0));
+    }
+
+    #[test]
+    fn cells_are_single_line_and_cut_to_width() {
+        assert_eq!(cell_text(&Value::Text("a\tb\r\nc".to_string())), "a b  c");
+        assert_eq!(truncate("orders", 10), "orders");
+        assert_eq!(truncate("orders", 4), "ord…");
+        assert_eq!(truncate("日本語", 4), "日…");
+        assert_eq!(truncate("orders", 0), "");
+        assert_eq!(pad("日本", 6), "日本  ");
+        assert_eq!(pad("orders", 3), "orders");
+    }
+
+    #[test]
+    fn render_scrolls_to_the_cursor() {
+        let columns = columns(&["id", "name"]);
+        let rows = rows(20, 2);
+        let mut state = GridState {
+            selected_row: 10,
+            ..GridState::default()
+        };
+        let area = Rect::new(0, 0, 20, 6);
+        let mut buf = Buffer::empty(area);
+
+        DataGrid::new(&columns, &rows).render(area, &mut buf, &mut state);
+
+        assert_eq!(state.viewport_height, 4);
+        assert_eq!(state.row_offset, 7);
+        assert_eq!(line(&buf, 0), "id   │ name");
+        assert_eq!(line(&buf, 2), "7-0  │ 7-1");
+        assert_eq!(line(&buf, 5), "10-0 │ 10-1");
+
+        state.first_row();
+        DataGrid::new(&columns, &rows).render(area, &mut buf, &mut state);
+        assert_eq!(state.row_offset, 0);
+    }
+
+    #[test]
+    fn render_scrolls_right_to_the_selected_column() {
+        let columns = columns(&["a", "b", "c", "d"]);
+        let rows = vec![vec![Value::Text("x".repeat(8)); 4]];
+        let mut state = GridState {
+            selected_column: 3,
+            ..GridState::default()
+        };
+        let area = Rect::new(0, 0, 20, 3);
+        let mut buf = Buffer::empty(area);
+
+        DataGrid::new(&columns, &rows).render(area, &mut buf, &mut state);
+
+        assert_eq!(state.column_offset, 2);
+        assert_eq!(line(&buf, 0), "c        │ d");
+
+        state.first_column();
+        DataGrid::new(&columns, &rows).render(area, &mut buf, &mut state);
+        assert_eq!(state.column_offset, 0);
+        assert_eq!(line(&buf, 0), "a        │ b");
+    }
+}
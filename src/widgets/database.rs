@@ -1,24 +1,26 @@
-use super::database_table::DatabaseTable;
+use super::database_schema::DatabaseSchema;
 
+// A database of the server. Its schemas are read the first time it's
+// expanded.
 #[derive(Debug, Clone, Default)]
 pub struct Database {
     pub name: String,
-    pub tables: Vec<DatabaseTable>,
+    pub schemas: Vec<DatabaseSchema>,
     pub is_focused: bool,
     pub is_connected: bool,
+    pub is_expanded: bool,
+    pub is_loaded: bool,
 }
 
 impl Database {
-    pub fn new(name: String, tables: Vec<DatabaseTable>) -> Database {
+    pub fn new(name: String, schemas: Vec<DatabaseSchema>) -> Database {
         Self {
             name,
-            tables,
+            schemas,
             is_focused: false,
             is_connected: false,
+            is_expanded: false,
+            is_loaded: false,
         }
     }
-
-    pub fn create_table(&mut self, table: DatabaseTable) {
-        self.tables.push(table)
-    }
 }
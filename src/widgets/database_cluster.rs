@@ -1,211 +1,750 @@
-use std::collections::HashMap;
+use std::{cmp::Reverse, collections::HashMap};
 
-use super::{database::Database, database_table::DatabaseTable};
+use super::{
+    database::Database, database_schema::DatabaseSchema, database_table::DatabaseTable,
+    fuzzy::fuzzy_match, object_group::SchemaObject,
+};
+use crate::postgres::{catalog::ObjectKind, query::TableName};
 
-pub enum TreeElement {
-    Database,
-    Table,
+// A line of the tree by index: a database, one of its schemas, one of the
+// schema's groups or an object of the group.
+pub type TreeItem = (usize, Option<usize>, Option<usize>, Option<usize>);
+
+// Children of the focused line that have to be read from the server the
+// first time it's expanded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnloadedChildren {
+    Schemas,
+    Objects(String, ObjectKind),
 }
 
+// Databases of the server, each expanded into its schemas, the groups of
+// objects in them and their objects. Every line is expanded and collapsed
+// on its own, the children being loaded the first time. Tables are only
+// opened from the database the connection is open to, the selected table
+// being indexed within it.
 pub struct DatabaseCluster {
     pub databases: Vec<Database>,
-    pub tables_map: HashMap<String, Vec<String>>,
     pub current_connected_database: Option<usize>,
+    pub current_selected_schema: Option<usize>,
+    pub current_selected_group: Option<usize>,
     pub current_selected_table: Option<usize>,
     pub current_focused_database: Option<usize>,
-    pub current_focused_table: Option<usize>,
-    pub current_focused_element: Option<TreeElement>,
+    pub current_focused_schema: Option<usize>,
+    pub current_focused_group: Option<usize>,
+    pub current_focused_item: Option<usize>,
+    // Explorer filter with the lines matching it and the positions of their
+    // matched characters, matched again when the filter or the loaded lines
+    // change rather than on every render
+    pub filter: String,
+    pub filter_matches: HashMap<TreeItem, Vec<usize>>,
+    pub best_filter_match: Option<TreeItem>,
 }
 
 impl DatabaseCluster {
     pub fn new(databases: Vec<Database>) -> Self {
         Self {
             databases,
-            tables_map: HashMap::default(),
             current_connected_database: None,
+            current_selected_schema: None,
+            current_selected_group: None,
             current_selected_table: None,
             current_focused_database: None,
-            current_focused_table: None,
-            current_focused_element: None,
+            current_focused_schema: None,
+            current_focused_group: None,
+            current_focused_item: None,
+            filter: String::new(),
+            filter_matches: HashMap::new(),
+            best_filter_match: None,
         }
     }
 
-    pub fn next(&mut self) {
-        match self.current_connected_database {
-            Some(_current_connected_database) => self.next_table(),
-            None => self.next_database(),
-        };
-    }
+    // Every line of the tree under expanded lines, in the order the tree
+    // shows them.
+    fn visible_items(&self) -> Vec<TreeItem> {
+        let mut items = Vec::new();
+        for (database_index, database) in self.databases.iter().enumerate() {
+            items.push((database_index, None, None, None));
+            if !database.is_expanded {
+                continue;
+            }
 
-    pub fn prev(&mut self) {
-        match self.current_connected_database {
-            Some(_current_connected_database) => self.prev_table(),
-            None => self.prev_database(),
-        };
-    }
+            for (schema_index, schema) in database.schemas.iter().enumerate() {
+                items.push((database_index, Some(schema_index), None, None));
+                if !schema.is_expanded {
+                    continue;
+                }
 
-    pub fn next_database(&mut self) {
-        let next_database_index = match self.current_focused_database {
-            Some(focused_db_index) => {
-                self.databases[focused_db_index].is_focused = false;
-                let number_of_databases = self.databases.len();
-                if focused_db_index >= number_of_databases - 1 {
-                    0
-                } else {
-                    focused_db_index + 1
+                for (group_index, group) in schema.groups.iter().enumerate() {
+                    items.push((database_index, Some(schema_index), Some(group_index), None));
+                    if group.is_expanded {
+                        items.extend((0..group.len()).map(|index| {
+                            (
+                                database_index,
+                                Some(schema_index),
+                                Some(group_index),
+                                Some(index),
+                            )
+                        }));
+                    }
                 }
             }
-            None => 0,
-        };
+        }
+        items
+    }
 
-        self.current_focused_database = Some(next_database_index);
-        self.databases[next_database_index].is_focused = true;
+    fn focused_item(&self) -> Option<TreeItem> {
+        Some((
+            self.current_focused_database?,
+            self.current_focused_schema,
+            self.current_focused_group,
+            self.current_focused_item,
+        ))
     }
 
-    pub fn prev_database(&mut self) {
-        let prev_database_index = match self.current_focused_database {
-            Some(focused_db_index) => {
-                self.databases[focused_db_index].is_focused = false;
-                let number_of_databases = self.databases.len();
-                if focused_db_index <= 0 {
-                    number_of_databases - 1
-                } else {
-                    focused_db_index - 1
+    // Move the focus to a database, a schema, a group or an object.
+    fn focus_item(&mut self, (database_index, schema_index, group_index, item_index): TreeItem) {
+        for (index, database) in self.databases.iter_mut().enumerate() {
+            let is_database = index == database_index;
+            database.is_focused = is_database && schema_index.is_none();
+
+            for (index, schema) in database.schemas.iter_mut().enumerate() {
+                let is_schema = is_database && schema_index == Some(index);
+                schema.is_focused = is_schema && group_index.is_none();
+
+                for (index, group) in schema.groups.iter_mut().enumerate() {
+                    let is_group = is_schema && group_index == Some(index);
+                    group.is_focused = is_group && item_index.is_none();
+                    group.focus_item(item_index.filter(|_| is_group));
                 }
             }
-            None => 0,
-        };
+        }
 
-        self.current_focused_database = Some(prev_database_index);
-        self.databases[prev_database_index].is_focused = true;
+        self.current_focused_database = Some(database_index);
+        self.current_focused_schema = schema_index;
+        self.current_focused_group = group_index;
+        self.current_focused_item = item_index;
     }
 
-    pub fn next_table(&mut self) {
-        let connected_database_index = match self.current_connected_database {
-            Some(connected_database_index) => connected_database_index,
-            None => return (),
+    pub fn next(&mut self) {
+        let items = self.visible_items();
+        if items.is_empty() {
+            return;
+        }
+
+        let focused_item = self.focused_item();
+        let next_index = items
+            .iter()
+            .position(|item| Some(*item) == focused_item)
+            .map_or(0, |index| (index + 1) % items.len());
+
+        self.focus_item(items[next_index]);
+    }
+
+    pub fn prev(&mut self) {
+        let items = self.visible_items();
+        if items.is_empty() {
+            return;
+        }
+
+        let focused_item = self.focused_item();
+        let prev_index = items
+            .iter()
+            .position(|item| Some(*item) == focused_item)
+            .map_or(0, |index| (index + items.len() - 1) % items.len());
+
+        self.focus_item(items[prev_index]);
+    }
+
+    // Expanded state of a database, a schema or a group. None for objects.
+    fn is_expanded_mut(
+        &mut self,
+        (database_index, schema_index, group_index, item_index): TreeItem,
+    ) -> Option<&mut bool> {
+        let database = self.databases.get_mut(database_index)?;
+        let schema = match schema_index {
+            Some(schema_index) => database.schemas.get_mut(schema_index)?,
+            None => return Some(&mut database.is_expanded),
+        };
+        let group = match group_index {
+            Some(group_index) => schema.groups.get_mut(group_index)?,
+            None => return Some(&mut schema.is_expanded),
         };
 
-        let current_database = &mut self.databases[connected_database_index];
-        if current_database.tables.len() == 0 {
-            return ();
+        item_index.is_none().then_some(&mut group.is_expanded)
+    }
+
+    // Expand or collapse the focused line. Returns false when the focus is
+    // on an object.
+    pub fn toggle_focused_item(&mut self) -> bool {
+        let focused_item = match self.focused_item() {
+            Some(focused_item) => focused_item,
+            None => return false,
         };
 
-        let next_table_index = match self.current_focused_table {
-            Some(focused_table_index) => {
-                current_database.tables[focused_table_index].is_focused = false;
+        match self.is_expanded_mut(focused_item) {
+            Some(is_expanded) => {
+                *is_expanded = !*is_expanded;
+                true
+            }
+            None => false,
+        }
+    }
 
-                let number_of_tables = current_database.tables.len();
+    // Expand the focused line, or move to its first child when it's already
+    // expanded. Returns false when the focus is on an object.
+    pub fn expand_focused_item(&mut self) -> bool {
+        let focused_item = match self.focused_item() {
+            Some(focused_item) => focused_item,
+            None => return false,
+        };
 
-                if focused_table_index >= number_of_tables - 1 {
-                    0
-                } else {
-                    focused_table_index + 1
+        match self.is_expanded_mut(focused_item) {
+            Some(is_expanded) if !*is_expanded => {
+                *is_expanded = true;
+                true
+            }
+            Some(_) => {
+                let items = self.visible_items();
+                let first_child = items
+                    .iter()
+                    .position(|item| *item == focused_item)
+                    .and_then(|index| items.get(index + 1))
+                    .filter(|item| parent(**item) == Some(focused_item));
+                if let Some(first_child) = first_child {
+                    self.focus_item(*first_child);
                 }
+                true
             }
-            None => 0,
+            None => false,
+        }
+    }
+
+    // Collapse the focused line, or move to its parent when it's collapsed
+    // or an object.
+    pub fn collapse_focused_item(&mut self) {
+        let focused_item = match self.focused_item() {
+            Some(focused_item) => focused_item,
+            None => return,
         };
 
-        self.current_focused_table = Some(next_table_index);
-        current_database.tables[next_table_index].is_focused = true;
+        match self.is_expanded_mut(focused_item) {
+            Some(is_expanded) if *is_expanded => *is_expanded = false,
+            _ => {
+                if let Some(parent) = parent(focused_item) {
+                    self.focus_item(parent);
+                }
+            }
+        }
     }
 
-    pub fn prev_table(&mut self) {
-        let connected_database_index = match self.current_connected_database {
-            Some(connected_database_index) => connected_database_index,
-            None => return (),
+    // What the focused line needs loaded when it's expanded but its
+    // children haven't been read yet.
+    pub fn unloaded_focused_children(&self) -> Option<UnloadedChildren> {
+        let database = &self.databases[self.current_focused_database?];
+        let schema = match self.current_focused_schema {
+            Some(schema_index) => &database.schemas[schema_index],
+            None => {
+                return (database.is_expanded && !database.is_loaded)
+                    .then_some(UnloadedChildren::Schemas)
+            }
         };
+        let group = &schema.groups[self.current_focused_group?];
 
-        let current_database = &mut self.databases[connected_database_index];
-        if current_database.tables.len() == 0 {
-            return ();
-        };
+        (self.current_focused_item.is_none() && group.is_expanded && !group.is_loaded)
+            .then(|| UnloadedChildren::Objects(schema.name.clone(), group.kind))
+    }
 
-        let prev_table_index = match self.current_focused_table {
-            Some(focused_table_index) => {
-                current_database.tables[focused_table_index].is_focused = false;
+    pub fn load_focused_database(&mut self, schemas: Vec<DatabaseSchema>) {
+        let database = match self.current_focused_database {
+            Some(database_index) => &mut self.databases[database_index],
+            None => return,
+        };
 
-                let number_of_tables = current_database.tables.len();
+        database.schemas = schemas;
+        database.is_loaded = true;
+        self.refresh_filter();
+    }
 
-                if focused_table_index <= 0 {
-                    number_of_tables - 1
-                } else {
-                    focused_table_index - 1
-                }
+    pub fn load_focused_group(&mut self, items: Vec<(u32, String)>) {
+        let (database_index, schema_index, group_index) = match (
+            self.current_focused_database,
+            self.current_focused_schema,
+            self.current_focused_group,
+        ) {
+            (Some(database_index), Some(schema_index), Some(group_index)) => {
+                (database_index, schema_index, group_index)
             }
-            // This panics when there are no tables in the database
-            None => 0,
+            _ => return,
         };
 
-        self.current_focused_table = Some(prev_table_index);
-        current_database.tables[prev_table_index].is_focused = true;
+        let schema = &mut self.databases[database_index].schemas[schema_index];
+        schema.groups[group_index].set_items(&schema.name, items);
+        self.refresh_filter();
+    }
+
+    // The focused object when it isn't a relation, with its schema and kind.
+    pub fn focused_object(&self) -> Option<(String, ObjectKind, SchemaObject)> {
+        let schema =
+            &self.databases[self.current_focused_database?].schemas[self.current_focused_schema?];
+        let group = &schema.groups[self.current_focused_group?];
+        let object = group.objects.get(self.current_focused_item?)?;
+
+        Some((schema.name.clone(), group.kind, object.clone()))
     }
 
-    pub fn toggle_focused_database(&mut self) {
+    // Mark the database the connection was opened to. The selected table
+    // belongs to the previous one, so it's dropped.
+    pub fn connect(&mut self, database_index: usize) {
+        if self.current_connected_database == Some(database_index) {
+            return;
+        }
+
+        self.current_selected_schema = None;
+        self.current_selected_group = None;
         self.current_selected_table = None;
-        self.current_focused_table = None;
+        for (index, database) in self.databases.iter_mut().enumerate() {
+            database.is_connected = index == database_index;
+        }
+        self.current_connected_database = Some(database_index);
+    }
+
+    pub fn connected_database_name(&self) -> Option<&str> {
+        self.databases
+            .get(self.current_connected_database?)
+            .map(|database| database.name.as_str())
+    }
+
+    fn schema_index(&self, schema_name: &str) -> Option<usize> {
+        self.databases[self.current_connected_database?]
+            .schemas
+            .iter()
+            .position(|schema| schema.name == schema_name)
+    }
+
+    // Group and index of a relation in the connected database.
+    fn find_table(&self, table_name: &TableName) -> Option<(usize, usize, usize)> {
+        let schema_index = self.schema_index(&table_name.schema)?;
+        let schema = &self.databases[self.current_connected_database?].schemas[schema_index];
 
-        let focused_database_index = match self.current_focused_database {
-            Some(focused_database_index) => focused_database_index,
-            None => return (),
+        schema
+            .groups
+            .iter()
+            .enumerate()
+            .find_map(|(group_index, group)| {
+                let table_index = group
+                    .tables
+                    .iter()
+                    .position(|table| table.name == *table_name)?;
+                Some((schema_index, group_index, table_index))
+            })
+    }
+
+    pub fn has_table(&self, table_name: &TableName) -> bool {
+        self.find_table(table_name).is_some()
+    }
+
+    // Add a table created from within the app to its schema in the connected
+    // database, keeping the tables sorted and the selection on the same
+    // tables.
+    pub fn add_table(&mut self, table_name: &TableName) {
+        let (connected_database_index, schema_index) = match (
+            self.current_connected_database,
+            self.schema_index(&table_name.schema),
+        ) {
+            (Some(connected_database_index), Some(schema_index)) => {
+                (connected_database_index, schema_index)
+            }
+            _ => return,
         };
 
-        match self.current_connected_database {
-            Some(connected_database_index) => {
-                if focused_database_index == connected_database_index {
-                    self.databases[connected_database_index].is_connected = false;
-                    self.current_connected_database = None;
-                } else {
-                    self.databases[focused_database_index].is_connected = true;
-                    self.databases[connected_database_index].is_connected = false;
-                    self.current_connected_database = Some(focused_database_index);
+        let schema = &mut self.databases[connected_database_index].schemas[schema_index];
+        let group_index = match schema
+            .groups
+            .iter()
+            .position(|group| group.kind == ObjectKind::Table)
+        {
+            Some(group_index) => group_index,
+            None => return,
+        };
+
+        let group = &mut schema.groups[group_index];
+        let position = group
+            .tables
+            .iter()
+            .position(|table| table.name.name > table_name.name)
+            .unwrap_or(group.tables.len());
+        group
+            .tables
+            .insert(position, DatabaseTable::new(table_name.clone(), Vec::new()));
+
+        let in_group = Some((schema_index, group_index));
+        let is_focus_connected = self.current_focused_database == Some(connected_database_index);
+        for (group, index) in [
+            (
+                self.current_selected_schema
+                    .zip(self.current_selected_group),
+                &mut self.current_selected_table,
+            ),
+            (
+                self.current_focused_schema
+                    .zip(self.current_focused_group)
+                    .filter(|_| is_focus_connected),
+                &mut self.current_focused_item,
+            ),
+        ] {
+            if let Some(index) = index.as_mut().filter(|_| group == in_group) {
+                if *index >= position {
+                    *index += 1;
                 }
             }
-            None => {
-                self.databases[focused_database_index].is_connected = true;
-                self.current_connected_database = Some(focused_database_index);
-            }
-        };
+        }
+        self.refresh_filter();
     }
 
-    pub fn select_focused_table(&mut self) -> Option<&mut DatabaseTable> {
-        match self.current_connected_database {
-            Some(current_db_index) => {
-                let current_database = &mut self.databases[current_db_index];
-
-                match self.current_focused_table {
-                    Some(current_table_index) => {
-                        let current_table = &mut current_database.tables[current_table_index];
-                        self.current_selected_table = Some(current_table_index);
-                        Some(current_table)
-                    }
-                    None => None,
-                }
+    // Move the focus to a line, expanding the lines it's under.
+    pub fn reveal_item(&mut self, item: TreeItem) {
+        let mut ancestor = parent(item);
+        while let Some(parent_item) = ancestor {
+            if let Some(is_expanded) = self.is_expanded_mut(parent_item) {
+                *is_expanded = true;
             }
-            None => None,
+            ancestor = parent(parent_item);
         }
+        self.focus_item(item);
+    }
+
+    // Move the focus to the relation with the given name in the connected
+    // database, expanding its database, schema and group.
+    pub fn focus_table(&mut self, table_name: &TableName) -> bool {
+        let (connected_database_index, (schema_index, group_index, table_index)) =
+            match (self.current_connected_database, self.find_table(table_name)) {
+                (Some(connected_database_index), Some(found)) => (connected_database_index, found),
+                _ => return false,
+            };
+
+        self.reveal_item((
+            connected_database_index,
+            Some(schema_index),
+            Some(group_index),
+            Some(table_index),
+        ));
+
+        true
     }
 
-    pub fn get_current_selected_table(&self) -> Option<DatabaseTable> {
-        match self.current_connected_database {
-            Some(current_db) => {
-                let current_database = &self.databases[current_db];
-                match self.current_selected_table {
-                    Some(current_table_index) => {
-                        let current_table = current_database.tables[current_table_index].clone();
-                        Some(current_table)
+    pub fn focused_table_name(&self) -> Option<TableName> {
+        let schema =
+            &self.databases[self.current_focused_database?].schemas[self.current_focused_schema?];
+        let table = schema.groups[self.current_focused_group?]
+            .tables
+            .get(self.current_focused_item?)?;
+
+        Some(table.name.clone())
+    }
+
+    // Every loaded line matching the pattern in the order of the tree, with
+    // its score, the length of its name and the positions of the matched
+    // characters.
+    fn matches(&self, pattern: &str) -> Vec<(TreeItem, i64, usize, Vec<usize>)> {
+        let mut matches = Vec::new();
+        let mut add_match = |name: &str, item: TreeItem| {
+            if let Some((score, positions)) = fuzzy_match(pattern, name) {
+                matches.push((item, score, name.chars().count(), positions));
+            }
+        };
+
+        for (database_index, database) in self.databases.iter().enumerate() {
+            add_match(&database.name, (database_index, None, None, None));
+
+            for (schema_index, schema) in database.schemas.iter().enumerate() {
+                add_match(
+                    &schema.name,
+                    (database_index, Some(schema_index), None, None),
+                );
+                for (group_index, group) in schema.groups.iter().enumerate() {
+                    for index in 0..group.len() {
+                        add_match(
+                            group.item_name(index),
+                            (
+                                database_index,
+                                Some(schema_index),
+                                Some(group_index),
+                                Some(index),
+                            ),
+                        );
                     }
-                    None => None,
                 }
             }
-            None => None,
         }
+
+        matches
     }
 
-    pub fn get_current_data(&self) -> Option<Vec<String>> {
-        match self.get_current_selected_table() {
-            Some(current_table) => Some(current_table.data),
-            None => None,
+    // The line matching the explorer filter best: the highest score, then
+    // the shortest name, then the first in the tree. Only loaded databases
+    // and groups are searched.
+    pub fn best_match(&self, pattern: &str) -> Option<TreeItem> {
+        best_of(&self.matches(pattern))
+    }
+
+    pub fn set_filter(&mut self, filter: &str) {
+        self.filter = filter.to_string();
+        self.refresh_filter();
+    }
+
+    fn refresh_filter(&mut self) {
+        if self.filter.is_empty() {
+            self.filter_matches.clear();
+            self.best_filter_match = None;
+            return;
         }
+
+        let matches = self.matches(&self.filter);
+        let best_filter_match = best_of(&matches);
+        let filter_matches = matches
+            .into_iter()
+            .map(|(item, _, _, positions)| (item, positions))
+            .collect();
+
+        self.filter_matches = filter_matches;
+        self.best_filter_match = best_filter_match;
+    }
+
+    // Schema of whatever is focused in the explorer.
+    pub fn focused_schema_name(&self) -> Option<String> {
+        let schema =
+            &self.databases[self.current_focused_database?].schemas[self.current_focused_schema?];
+
+        Some(schema.name.clone())
+    }
+
+    // Select the focused relation when it's in the connected database.
+    pub fn select_focused_table(&mut self) -> Option<&mut DatabaseTable> {
+        let connected_database_index = self
+            .current_connected_database
+            .filter(|index| self.current_focused_database == Some(*index))?;
+        let current_database = &mut self.databases[connected_database_index];
+        let schema_index = self.current_focused_schema?;
+        let group_index = self.current_focused_group?;
+        let table_index = self.current_focused_item?;
+
+        let current_table = current_database
+            .schemas
+            .get_mut(schema_index)?
+            .groups
+            .get_mut(group_index)?
+            .tables
+            .get_mut(table_index)?;
+        self.current_selected_schema = Some(schema_index);
+        self.current_selected_group = Some(group_index);
+        self.current_selected_table = Some(table_index);
+        Some(current_table)
+    }
+
+    pub fn get_current_selected_table_mut(&mut self) -> Option<&mut DatabaseTable> {
+        let current_database = &mut self.databases[self.current_connected_database?];
+        current_database
+            .schemas
+            .get_mut(self.current_selected_schema?)?
+            .groups
+            .get_mut(self.current_selected_group?)?
+            .tables
+            .get_mut(self.current_selected_table?)
+    }
+}
+
+// The first of the best scored matches, preferring shorter names.
+fn best_of(matches: &[(TreeItem, i64, usize, Vec<usize>)]) -> Option<TreeItem> {
+    matches
+        .iter()
+        .enumerate()
+        .max_by_key(|(order, (_, score, length, _))| (*score, Reverse(*length), Reverse(*order)))
+        .map(|(_, (item, ..))| *item)
+}
+
+// The line a line of the tree is under, none for databases.
+fn parent((database_index, schema_index, group_index, item_index): TreeItem) -> Option<TreeItem> {
+    match (schema_index, group_index, item_index) {
+        (None, _, _) => None,
+        (Some(_), None, _) => Some((database_index, None, None, None)),
+        (Some(_), Some(_), None) => Some((database_index, schema_index, None, None)),
+        (Some(_), Some(_), Some(_)) => Some((database_index, schema_index, group_index, None)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema(name: &str, tables: &[&str]) -> DatabaseSchema {
+        DatabaseSchema::new(
+            name.to_string(),
+            tables
+                .iter()
+                .map(|table| DatabaseTable::new(TableName::new(name, table), Vec::new()))
+                .collect(),
+        )
+    }
+
+    fn cluster() -> DatabaseCluster {
+        let mut cluster = DatabaseCluster::new(vec![
+            Database::new(String::from("shop"), Vec::new()),
+            Database::new(String::from("postgres"), Vec::new()),
+        ]);
+        cluster.next();
+        cluster
+    }
+
+    #[test]
+    fn filter_keeps_the_matches_and_the_best_one() {
+        let mut cluster = cluster();
+        cluster.load_focused_database(vec![schema("public", &["order_lines", "orders"])]);
+
+        cluster.set_filter("ord");
+        assert_eq!(
+            cluster.filter_matches,
+            HashMap::from([
+                ((0, Some(0), Some(0), Some(0)), vec![0, 1, 2]),
+                ((0, Some(0), Some(0), Some(1)), vec![0, 1, 2]),
+            ])
+        );
+        // The shorter name wins a tie
+        assert_eq!(
+            cluster.best_filter_match,
+            Some((0, Some(0), Some(0), Some(1)))
+        );
+        assert_eq!(cluster.best_match("ord"), cluster.best_filter_match);
+
+        cluster.set_filter("");
+        assert!(cluster.filter_matches.is_empty());
+        assert_eq!(cluster.best_filter_match, None);
+    }
+
+    #[test]
+    fn filter_is_matched_again_when_lines_are_loaded_or_added() {
+        let mut cluster = cluster();
+        cluster.set_filter("items");
+        assert!(cluster.filter_matches.is_empty());
+
+        cluster.load_focused_database(vec![schema("public", &["items"])]);
+        assert_eq!(
+            cluster.best_filter_match,
+            Some((0, Some(0), Some(0), Some(0)))
+        );
+
+        cluster.connect(0);
+        cluster.add_table(&TableName::new("public", "archived_items"));
+        assert_eq!(cluster.filter_matches.len(), 2);
+        assert_eq!(
+            cluster.best_filter_match,
+            Some((0, Some(0), Some(0), Some(1)))
+        );
+    }
+
+    #[test]
+    fn next_and_prev_without_databases_do_nothing() {
+        let mut cluster = DatabaseCluster::new(Vec::new());
+        cluster.next();
+        cluster.prev();
+
+        assert_eq!(cluster.focused_item(), None);
+        assert!(!cluster.expand_focused_item());
+        assert_eq!(cluster.unloaded_focused_children(), None);
+    }
+
+    #[test]
+    fn next_and_prev_wrap_around_empty_databases_and_schemas() {
+        let mut cluster = cluster();
+        assert!(cluster.toggle_focused_item());
+        cluster.load_focused_database(Vec::new());
+
+        cluster.next();
+        assert_eq!(cluster.focused_item(), Some((1, None, None, None)));
+        cluster.next();
+        assert_eq!(cluster.focused_item(), Some((0, None, None, None)));
+
+        cluster.load_focused_database(vec![schema("public", &[])]);
+        cluster.prev();
+        assert_eq!(cluster.focused_item(), Some((1, None, None, None)));
+        cluster.prev();
+        let last_group = cluster.databases[0].schemas[0].groups.len() - 1;
+        assert_eq!(
+            cluster.focused_item(),
+            Some((0, Some(0), Some(last_group), None))
+        );
+        cluster.next();
+        assert_eq!(cluster.focused_item(), Some((1, None, None, None)));
+    }
+
+    #[test]
+    fn expanding_an_expanded_line_moves_to_its_first_child() {
+        let mut cluster = cluster();
+        assert!(cluster.expand_focused_item());
+        assert!(cluster.databases[0].is_expanded);
+        assert_eq!(cluster.focused_item(), Some((0, None, None, None)));
+
+        cluster.load_focused_database(vec![schema("public", &["orders"])]);
+        assert!(cluster.expand_focused_item());
+        assert_eq!(cluster.focused_item(), Some((0, Some(0), None, None)));
+        assert!(cluster.expand_focused_item());
+        assert_eq!(cluster.focused_item(), Some((0, Some(0), Some(0), None)));
+        assert!(cluster.expand_focused_item());
+        assert_eq!(cluster.focused_item(), Some((0, Some(0), Some(0), Some(0))));
+
+        assert!(!cluster.expand_focused_item());
+        assert_eq!(cluster.focused_item(), Some((0, Some(0), Some(0), Some(0))));
+    }
+
+    #[test]
+    fn collapsing_a_collapsed_line_moves_to_its_parent() {
+        let mut cluster = cluster();
+        cluster.toggle_focused_item();
+        cluster.load_focused_database(vec![schema("public", &["orders"])]);
+        cluster.reveal_item((0, Some(0), Some(0), Some(0)));
+
+        cluster.collapse_focused_item();
+        assert_eq!(cluster.focused_item(), Some((0, Some(0), Some(0), None)));
+        cluster.collapse_focused_item();
+        assert!(!cluster.databases[0].schemas[0].groups[0].is_expanded);
+        assert_eq!(cluster.focused_item(), Some((0, Some(0), Some(0), None)));
+        cluster.collapse_focused_item();
+        assert_eq!(cluster.focused_item(), Some((0, Some(0), None, None)));
+        cluster.collapse_focused_item();
+        cluster.collapse_focused_item();
+        assert_eq!(cluster.focused_item(), Some((0, None, None, None)));
+        cluster.collapse_focused_item();
+        assert!(!cluster.databases[0].is_expanded);
+        cluster.collapse_focused_item();
+        assert_eq!(cluster.focused_item(), Some((0, None, None, None)));
+    }
+
+    #[test]
+    fn children_are_unloaded_until_they_are_read() {
+        let mut cluster = cluster();
+        assert_eq!(cluster.unloaded_focused_children(), None);
+
+        cluster.toggle_focused_item();
+        assert_eq!(
+            cluster.unloaded_focused_children(),
+            Some(UnloadedChildren::Schemas)
+        );
+        cluster.load_focused_database(vec![schema("public", &["orders"])]);
+        assert_eq!(cluster.unloaded_focused_children(), None);
+
+        // Tables are read with the schemas, the other groups on their own
+        cluster.reveal_item((0, Some(0), Some(0), None));
+        assert_eq!(cluster.unloaded_focused_children(), None);
+        cluster.reveal_item((0, Some(0), Some(1), None));
+        assert_eq!(cluster.unloaded_focused_children(), None);
+
+        let kind = cluster.databases[0].schemas[0].groups[1].kind;
+        cluster.toggle_focused_item();
+        assert_eq!(
+            cluster.unloaded_focused_children(),
+            Some(UnloadedChildren::Objects(String::from("public"), kind))
+        );
+        cluster.load_focused_group(vec![(1, String::from("totals"))]);
+        assert_eq!(cluster.unloaded_focused_children(), None);
     }
 }
@@ -0,0 +1,37 @@
+use super::{database_table::DatabaseTable, object_group::ObjectGroup};
+use crate::postgres::catalog::{ObjectKind, OBJECT_KINDS};
+
+#[derive(Debug, Clone, Default)]
+pub struct DatabaseSchema {
+    pub name: String,
+    // A group for every object kind, tables first
+    pub groups: Vec<ObjectGroup>,
+    pub is_focused: bool,
+    pub is_expanded: bool,
+}
+
+impl DatabaseSchema {
+    // Only public starts expanded, other schemas are opened when needed.
+    // Tables are listed open, the other groups are loaded on demand.
+    pub fn new(name: String, tables: Vec<DatabaseTable>) -> DatabaseSchema {
+        let mut groups: Vec<ObjectGroup> = OBJECT_KINDS
+            .iter()
+            .map(|kind| ObjectGroup::new(*kind))
+            .collect();
+        if let Some(group) = groups
+            .iter_mut()
+            .find(|group| group.kind == ObjectKind::Table)
+        {
+            group.tables = tables;
+            group.is_loaded = true;
+            group.is_expanded = true;
+        }
+
+        Self {
+            is_expanded: name == "public",
+            name,
+            groups,
+            is_focused: false,
+        }
+    }
+}
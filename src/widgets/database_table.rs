@@ -1,29 +1,383 @@
 use cli_log::info;
 
+use super::{
+    change_set::{Change, ChangeKind},
+    column_layout::ColumnLayout,
+    data_grid::GridState,
+    navigation::PagePosition,
+    result_search::next_revision,
+};
+use crate::postgres::{
+    data::{Column, ColumnDetails, ForeignKey, Generated, Value},
+    edit::{match_row, CellValue},
+    filter::Filter,
+    query::{
+        cycle_sort, page_query, quote_identifier, sort_order, PageQuery, PageRequest, Pagination,
+        SortKey, TableName, TableQuery,
+    },
+};
+
 #[derive(Debug, Clone, Default)]
 pub struct DatabaseTable {
-    pub name: String,
-    pub columns: Vec<String>,
+    pub name: TableName,
+    pub columns: Vec<Column>,
+    pub primary_key: Vec<String>,
+    pub sort: Vec<SortKey>,
+    pub filter: Option<Filter>,
+    pub layout: ColumnLayout,
+    pub foreign_keys: Vec<ForeignKey>,
+    pub referenced_by: Vec<ForeignKey>,
+    pub column_details: Vec<ColumnDetails>,
     pub is_focused: bool,
-    pub data: Vec<String>,
+    pub data: Vec<Vec<Value>>,
+    // Changes whenever the data is replaced
+    pub revision: u64,
+    pub grid: GridState,
+    pub pagination: Pagination,
 }
 
 impl DatabaseTable {
-    pub fn new(name: String, columns: Vec<String>) -> Self {
+    pub fn new(name: TableName, columns: Vec<Column>) -> Self {
         Self {
             name,
             columns,
+            primary_key: Vec::new(),
+            sort: Vec::new(),
+            filter: None,
+            layout: ColumnLayout::default(),
+            foreign_keys: Vec::new(),
+            referenced_by: Vec::new(),
+            column_details: Vec::new(),
             is_focused: false,
             data: Vec::new(),
+            revision: 0,
+            grid: GridState::default(),
+            pagination: Pagination::default(),
         }
     }
 
-    pub fn set_columns(&mut self, columns: Vec<String>) {
+    // The filter is checked again against the new columns and dropped if it
+    // no longer applies.
+    pub fn set_columns(&mut self, columns: Vec<Column>) {
         self.columns = columns;
+        self.filter = self
+            .filter
+            .take()
+            .and_then(|filter| Filter::parse(&filter.source, &self.columns).ok());
     }
 
-    pub fn set_data(&mut self, data: Vec<String>) {
-        info!("Setting data: {:?}", data);
+    pub fn set_data(&mut self, data: Vec<Vec<Value>>) {
+        info!("Setting data: {} rows", data.len());
         self.data = data;
+        self.revision = next_revision();
+        self.grid.clamp(self.data.len(), self.column_order().len());
+    }
+
+    pub fn column_order(&self) -> Vec<usize> {
+        self.layout.display_order(&self.columns)
+    }
+
+    pub fn pinned_columns(&self) -> usize {
+        self.layout.pinned_count(&self.columns)
+    }
+
+    pub fn selected_column_index(&self) -> Option<usize> {
+        self.column_order().get(self.grid.selected_column).copied()
+    }
+
+    // Change the column layout with the selected column and keep it selected
+    // wherever it ends up. Hiding it selects its neighbour instead.
+    pub fn change_layout(&mut self, change: impl FnOnce(&mut ColumnLayout, &str, &[Column])) {
+        let column_index = match self.selected_column_index() {
+            Some(column_index) => column_index,
+            None => return,
+        };
+
+        let name = self.columns[column_index].name.clone();
+        change(&mut self.layout, &name, &self.columns);
+
+        if let Some(position) = self
+            .column_order()
+            .iter()
+            .position(|index| *index == column_index)
+        {
+            self.grid.selected_column = position;
+        }
+        self.grid.clamp(self.data.len(), self.column_order().len());
+    }
+
+    // Foreign key the selected column is part of.
+    pub fn selected_foreign_key(&self) -> Option<&ForeignKey> {
+        let column = &self.columns[self.selected_column_index()?];
+        self.foreign_keys
+            .iter()
+            .find(|foreign_key| foreign_key.columns.contains(&column.name))
+    }
+
+    // Text of the given columns in the selected row, None when any of them
+    // is NULL.
+    pub fn selected_row_text(&self, column_names: &[String]) -> Option<Vec<String>> {
+        self.row_text(self.grid.selected_row, column_names)
+    }
+
+    // Key columns of a loaded row along with their text.
+    pub fn row_key(&self, row: usize) -> Option<Vec<(String, String)>> {
+        let text = self.row_text(row, &self.primary_key)?;
+        Some(self.primary_key.iter().cloned().zip(text).collect())
+    }
+
+    fn row_text(&self, row: usize, column_names: &[String]) -> Option<Vec<String>> {
+        let row = self.data.get(row)?;
+        column_names
+            .iter()
+            .map(|name| {
+                let index = self
+                    .columns
+                    .iter()
+                    .position(|column| &column.name == name)?;
+                row.get(index)?.to_text()
+            })
+            .collect()
+    }
+
+    pub fn uses_keyset(&self) -> bool {
+        !self.primary_key.is_empty() && self.sort.is_empty()
+    }
+
+    pub fn cycle_sort(&mut self, column_index: usize, multi_column: bool) {
+        if let Some(column) = self.columns.get(column_index) {
+            cycle_sort(&mut self.sort, &column.name, multi_column);
+        }
+    }
+
+    // Query for the rows of the table that match the filter.
+    pub fn base_query(&self) -> TableQuery {
+        let mut query = TableQuery::new(&self.name, &self.columns);
+        if let Some(filter) = &self.filter {
+            filter.apply(&mut query);
+        }
+        query
+    }
+
+    // Every row of the table matching the filter in the current sort, with
+    // values written inline as COPY takes no parameters.
+    pub fn export_query(&self) -> TableQuery {
+        let mut query = TableQuery {
+            inline_params: true,
+            ..TableQuery::new(&self.name, &self.columns)
+        };
+        query.select_list = String::from("*");
+        if let Some(filter) = &self.filter {
+            filter.apply(&mut query);
+        }
+        query.order_by = sort_order(&self.sort);
+        query
+    }
+
+    // UPDATE setting one cell of a loaded row, the row is matched by its key.
+    pub fn update_query(
+        &self,
+        row: usize,
+        column_index: usize,
+        value: &CellValue,
+    ) -> Result<TableQuery, String> {
+        let column = &self.columns[column_index];
+        value.check(column)?;
+        let row = self
+            .data
+            .get(row)
+            .ok_or_else(|| String::from("the row is no longer loaded"))?;
+
+        let mut query = TableQuery::new(&self.name, &self.columns);
+        let value = value.bind(&mut query, &column.type_);
+        query
+            .assignments
+            .push((quote_identifier(&column.name), value));
+        match_row(
+            &mut query,
+            &self.name,
+            &self.columns,
+            &self.primary_key,
+            row,
+        )?;
+
+        Ok(query)
+    }
+
+    pub fn update_change(
+        &self,
+        database: &str,
+        row: usize,
+        column_index: usize,
+        value: CellValue,
+    ) -> Result<Change, String> {
+        let query = self.update_query(row, column_index, &value)?;
+        Ok(Change {
+            database: database.to_string(),
+            table: self.name.clone(),
+            columns: self.columns.clone(),
+            key_columns: self.primary_key.clone(),
+            generated_columns: self.generated_columns(),
+            key: self.row_key(row).unwrap_or_default(),
+            kind: ChangeKind::Update {
+                column: self.columns[column_index].name.clone(),
+                before: self.data[row][column_index].clone(),
+                after: value,
+            },
+            query,
+        })
+    }
+
+    fn generated_columns(&self) -> Vec<(String, Generated)> {
+        self.column_details
+            .iter()
+            .filter_map(|details| Some((details.name.clone(), details.generated?)))
+            .collect()
+    }
+
+    pub fn delete_change(&self, database: &str, row: usize) -> Result<Change, String> {
+        let query = self.delete_query(row)?;
+        Ok(Change {
+            database: database.to_string(),
+            table: self.name.clone(),
+            columns: self.columns.clone(),
+            key_columns: self.primary_key.clone(),
+            generated_columns: self.generated_columns(),
+            key: self.row_key(row).unwrap_or_default(),
+            kind: ChangeKind::Delete(
+                self.columns
+                    .iter()
+                    .map(|column| column.name.clone())
+                    .zip(self.data[row].iter().cloned())
+                    .collect(),
+            ),
+            query,
+        })
+    }
+
+    // DELETE of a loaded row, matched by its key.
+    pub fn delete_query(&self, row: usize) -> Result<TableQuery, String> {
+        let row = self
+            .data
+            .get(row)
+            .ok_or_else(|| String::from("no row is selected"))?;
+
+        let mut query = TableQuery::new(&self.name, &self.columns);
+        match_row(
+            &mut query,
+            &self.name,
+            &self.columns,
+            &self.primary_key,
+            row,
+        )?;
+
+        Ok(query)
+    }
+
+    pub fn page_query(&self, request: PageRequest) -> Option<PageQuery> {
+        page_query(
+            self.base_query(),
+            &self.columns,
+            &self.primary_key,
+            &self.sort,
+            &self.data,
+            &self.pagination,
+            request,
+        )
+    }
+
+    // Where the current page starts, none while it's empty.
+    pub fn page_position(&self) -> Option<PagePosition> {
+        Some(PagePosition {
+            page_size: self.pagination.page_size,
+            first_row: self.pagination.first_row,
+            key_row: self.data.first()?.clone(),
+        })
+    }
+
+    // Query reading a page remembered by its position again, by the key of
+    // its first row or by its offset.
+    pub fn restore_page_query(&mut self, page: &PagePosition) -> Option<PageQuery> {
+        self.pagination.page_size = page.page_size;
+        self.pagination.first_row = page.first_row;
+        self.pagination.page_index = page.first_row.div_ceil(page.page_size);
+
+        page_query(
+            self.base_query(),
+            &self.columns,
+            &self.primary_key,
+            &self.sort,
+            std::slice::from_ref(&page.key_row),
+            &self.pagination,
+            PageRequest::Reload,
+        )
+    }
+
+    // Show a fetched page. Moving past either end of the table returns no
+    // rows, in which case the current page is kept.
+    pub fn set_page(&mut self, page_query: PageQuery, mut rows: Vec<Vec<Value>>) {
+        let moves_away = matches!(
+            page_query.request,
+            PageRequest::Next | PageRequest::Previous
+        );
+        if rows.is_empty() && moves_away && !self.data.is_empty() {
+            return;
+        }
+
+        if page_query.reversed {
+            rows.reverse();
+        }
+
+        let page_size = self.pagination.page_size;
+        let total = self.pagination.estimated_total;
+
+        // Pages read by key are positioned relative to the current page, the
+        // last page is read backwards from the end of the table.
+        let first_row = match (page_query.query.offset, page_query.request) {
+            (Some(offset), _) => offset,
+            (None, PageRequest::First) => 0,
+            (None, PageRequest::Next) => self.pagination.first_row + self.data.len(),
+            (None, PageRequest::Previous) => self.pagination.first_row.saturating_sub(rows.len()),
+            (None, PageRequest::Last) if rows.len() < page_size => 0,
+            (None, PageRequest::Last) => {
+                (total.unwrap_or(0).max(0) as usize).saturating_sub(rows.len())
+            }
+            (None, PageRequest::Reload) => self.pagination.first_row,
+        };
+
+        // A short page read in key order is the end of the table, which makes
+        // the total exact.
+        if (!page_query.reversed || page_query.request == PageRequest::Last)
+            && rows.len() < page_size
+        {
+            self.pagination.estimated_total = Some((first_row + rows.len()) as i64);
+        } else if let Some(total) = total {
+            let seen = (first_row + rows.len()) as i64;
+            self.pagination.estimated_total = Some(total.max(seen));
+        }
+
+        if page_query.request != PageRequest::Reload {
+            self.grid.first_row();
+            self.grid.selection_anchor = None;
+        }
+
+        self.pagination.first_row = first_row;
+        self.pagination.page_index = first_row.div_ceil(page_size);
+        self.set_data(rows);
+    }
+
+    // "rows X–Y of ~N" for the current page.
+    pub fn page_summary(&self) -> String {
+        if self.data.is_empty() {
+            return String::from("no rows");
+        }
+
+        let first_row = self.pagination.first_row + 1;
+        let last_row = first_row + self.data.len() - 1;
+
+        match self.pagination.estimated_total {
+            Some(total) => format!("rows {}–{} of ~{}", first_row, last_row, total),
+            None => format!("rows {}–{}", first_row, last_row),
+        }
     }
 }
@@ -1,86 +1,49 @@
-use super::{database::Database, database_cluster::DatabaseCluster};
+use super::database_cluster::{DatabaseCluster, TreeItem};
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     widgets::{Block, StatefulWidget, Widget},
 };
-use std::cmp::{max, min};
+use std::cmp::min;
+use unicode_width::UnicodeWidthChar;
 
+// The explorer tree, drawn from the cluster it's rendered with. While the
+// cluster has a filter only what fuzzy-matches it is shown, with the matched
+// characters highlighted.
 pub struct DatabaseTree<'a> {
     block: Option<Block<'a>>,
-    cluster: DatabaseCluster,
 }
 
 impl<'a> DatabaseTree<'a> {
-    pub fn new(databases: Vec<Database>) -> DatabaseTree<'a> {
-        Self {
-            block: None,
-            cluster: DatabaseCluster::new(databases),
-        }
+    pub fn new() -> DatabaseTree<'a> {
+        Self { block: None }
     }
 
     pub fn block(mut self, block: Block<'a>) -> DatabaseTree<'a> {
         self.block = Some(block);
         self
     }
-}
-
-#[derive(Debug, Clone, Default)]
-struct BufferLine {
-    x: u16,
-    y: u16,
-    content: String,
-    width: usize,
-    style: Style,
-}
-
-impl<'a> StatefulWidget for DatabaseTree<'a> {
-    type State = DatabaseCluster;
-
-    // Implement the rendering logic of the database tree.
-    //
-    // Iterate over all databases and expand tables for the currently
-    // connected database (database.is_connected).
-    //
-    // Calculate the offset for scrolling when # of lines > height_of_tree
-    //
-    //    [0, 1, 2]              current display (height_of_tree = 3)
-    //        ^                  y_center_of_tree
-    // [1, 2, 3, 4, 5, 6, 7]     total items (lines_to_draw)
-    //        ^                  y_current
-    //     ^                     start_of_slice = y_current - height_of_tree / 2
-    //           ^               end_of_slice = y_current + height_of_tree / 2
-    //    [2, 3, 4]              lines_to_draw[start_of_slice...end_of_slice]
-    //
-    //
-    // TODO: refactor this so that we render in one iteration
-    //
-    fn render(mut self, area: Rect, buf: &mut Buffer, _cluster: &mut DatabaseCluster) {
-        let inner_area = match self.block.take() {
-            Some(b) => {
-                let inner_area = b.inner(area);
-                b.render(area, buf);
-                inner_area
-            }
-            None => area,
-        };
 
+    // Lines of the databases and of whatever is under the expanded lines,
+    // with the position of the focused line.
+    fn tree_lines(cluster: &DatabaseCluster, inner_area: Rect) -> (Vec<BufferLine>, usize) {
         let x = inner_area.x;
         let mut y = inner_area.y;
 
         let mut lines_to_draw: Vec<BufferLine> = Vec::new();
         let mut current_focused_index_position: usize = 0;
 
-        for database in self.cluster.databases.iter() {
+        for database in cluster.databases.iter() {
+            let marker = if database.is_expanded { "▾ " } else { "▸ " };
             let (style, content): (Style, String) = if database.is_focused {
                 current_focused_index_position = (y - inner_area.y) as usize;
                 (
                     Style::default().bg(Color::Blue),
-                    String::from(">>>") + &database.name,
+                    String::from(">>>") + marker + &database.name,
                 )
             } else {
-                (Style::default(), database.name.to_string())
+                (Style::default(), String::from(marker) + &database.name)
             };
 
             lines_to_draw.push(BufferLine {
@@ -89,60 +52,356 @@ impl<'a> StatefulWidget for DatabaseTree<'a> {
                 content,
                 width: inner_area.width as usize,
                 style,
+                ..Default::default()
             });
 
-            if database.is_connected {
-                for table in database.tables.iter() {
+            if database.is_expanded {
+                for schema in database.schemas.iter() {
                     y += 1;
 
-                    let (style, content): (Style, String) = if table.is_focused {
+                    let marker = if schema.is_expanded { "▾ " } else { "▸ " };
+                    let (style, content): (Style, String) = if schema.is_focused {
                         current_focused_index_position = (y - inner_area.y) as usize;
                         (
                             Style::default().bg(Color::Blue),
-                            String::from(">>>") + &table.name,
+                            String::from(">>>") + marker + &schema.name,
                         )
                     } else {
-                        (Style::default(), table.name.to_string())
+                        (Style::default(), String::from(marker) + &schema.name)
                     };
 
                     lines_to_draw.push(BufferLine {
                         x: x + 3,
                         y,
                         content,
-                        width: inner_area.width as usize,
+                        width: (inner_area.width as usize).saturating_sub(3),
                         style,
+                        ..Default::default()
                     });
+
+                    if !schema.is_expanded {
+                        continue;
+                    }
+
+                    for group in schema.groups.iter() {
+                        y += 1;
+
+                        let marker = if group.is_expanded { "▾ " } else { "▸ " };
+                        let (style, content): (Style, String) = if group.is_focused {
+                            current_focused_index_position = (y - inner_area.y) as usize;
+                            (
+                                Style::default().bg(Color::Blue),
+                                String::from(">>>") + marker + &group.title(),
+                            )
+                        } else {
+                            (Style::default(), String::from(marker) + &group.title())
+                        };
+
+                        lines_to_draw.push(BufferLine {
+                            x: x + 6,
+                            y,
+                            content,
+                            width: (inner_area.width as usize).saturating_sub(6),
+                            style,
+                            ..Default::default()
+                        });
+
+                        if !group.is_expanded {
+                            continue;
+                        }
+
+                        for index in 0..group.len() {
+                            y += 1;
+
+                            let name = group.item_name(index);
+                            let (style, content): (Style, String) = if group.is_item_focused(index)
+                            {
+                                current_focused_index_position = (y - inner_area.y) as usize;
+                                (Style::default().bg(Color::Blue), String::from(">>>") + name)
+                            } else {
+                                (Style::default(), name.to_string())
+                            };
+
+                            lines_to_draw.push(BufferLine {
+                                x: x + 9,
+                                y,
+                                content,
+                                width: (inner_area.width as usize).saturating_sub(9),
+                                style,
+                                ..Default::default()
+                            });
+                        }
+                    }
                 }
             }
             y += 1;
         }
 
-        let total_lines = lines_to_draw.len();
-        let height_of_tree = (inner_area.height - inner_area.y) as usize;
-        let radius_of_tree = height_of_tree as usize / 2;
-        let y_current = current_focused_index_position + inner_area.y as usize;
-
-        if total_lines > height_of_tree && y_current > radius_of_tree {
-            let offset = y_current - radius_of_tree;
-            let start_of_slice = max(0, offset);
-            let end_of_slice = min(total_lines, height_of_tree + offset + 1);
-            let offset_lines = &lines_to_draw[start_of_slice..end_of_slice];
-
-            for line in offset_lines {
-                buf.set_stringn(
-                    line.x,
-                    line.y - offset as u16,
-                    &line.content,
-                    line.width,
-                    line.style,
-                );
+        (lines_to_draw, current_focused_index_position)
+    }
+
+    // Lines matching the filter with the databases, schemas and groups they
+    // are in, expanded or not. The best match is shown as focused, it's
+    // where Enter jumps to.
+    fn filtered_lines(cluster: &DatabaseCluster, inner_area: Rect) -> (Vec<BufferLine>, usize) {
+        let mut filtered_lines = Vec::new();
+
+        for (database_index, database) in cluster.databases.iter().enumerate() {
+            let mut database_lines = Vec::new();
+
+            for (schema_index, schema) in database.schemas.iter().enumerate() {
+                let mut schema_lines = Vec::new();
+
+                for (group_index, group) in schema.groups.iter().enumerate() {
+                    let items: Vec<FilteredLine> = (0..group.len())
+                        .filter_map(|index| {
+                            let item = (
+                                database_index,
+                                Some(schema_index),
+                                Some(group_index),
+                                Some(index),
+                            );
+                            Some(FilteredLine {
+                                indent: 9,
+                                marker: "",
+                                name: group.item_name(index).to_string(),
+                                positions: cluster.filter_matches.get(&item)?,
+                                item: Some(item),
+                            })
+                        })
+                        .collect();
+                    if items.is_empty() {
+                        continue;
+                    }
+
+                    schema_lines.push(FilteredLine {
+                        indent: 6,
+                        marker: "▾ ",
+                        name: group.title(),
+                        positions: &[],
+                        item: None,
+                    });
+                    schema_lines.extend(items);
+                }
+
+                let schema_item = (database_index, Some(schema_index), None, None);
+                let schema_match = cluster.filter_matches.get(&schema_item);
+                if schema_match.is_none() && schema_lines.is_empty() {
+                    continue;
+                }
+
+                database_lines.push(FilteredLine {
+                    indent: 3,
+                    marker: if schema_lines.is_empty() {
+                        "▸ "
+                    } else {
+                        "▾ "
+                    },
+                    name: schema.name.clone(),
+                    positions: schema_match.map_or(&[], Vec::as_slice),
+                    item: Some(schema_item),
+                });
+                database_lines.extend(schema_lines);
             }
-        } else {
-            let end_of_slice = min(total_lines, height_of_tree + 1);
-            let offset_lines = &lines_to_draw[0..end_of_slice];
-            for line in offset_lines {
-                buf.set_stringn(line.x, line.y, &line.content, line.width, line.style);
+
+            let database_item = (database_index, None, None, None);
+            let database_match = cluster.filter_matches.get(&database_item);
+            if database_match.is_none() && database_lines.is_empty() {
+                continue;
             }
+
+            filtered_lines.push(FilteredLine {
+                indent: 0,
+                marker: if database_lines.is_empty() {
+                    "▸ "
+                } else {
+                    "▾ "
+                },
+                name: database.name.clone(),
+                positions: database_match.map_or(&[], Vec::as_slice),
+                item: Some(database_item),
+            });
+            filtered_lines.extend(database_lines);
+        }
+
+        if filtered_lines.is_empty() {
+            let line = BufferLine {
+                x: inner_area.x,
+                y: inner_area.y,
+                content: format!("Nothing matches {}", cluster.filter),
+                width: inner_area.width as usize,
+                style: Style::default().fg(Color::DarkGray),
+                ..Default::default()
+            };
+            return (vec![line], 0);
         }
+
+        let best_match = cluster.best_filter_match;
+        let mut best_match_position = 0;
+        let lines_to_draw = filtered_lines
+            .into_iter()
+            .enumerate()
+            .map(|(position, line)| {
+                let is_best_match = line.item.is_some() && line.item == best_match;
+                let prefix = if is_best_match {
+                    best_match_position = position;
+                    String::from(">>>") + line.marker
+                } else {
+                    String::from(line.marker)
+                };
+                let offset = prefix.chars().count();
+
+                BufferLine {
+                    x: inner_area.x + line.indent,
+                    y: inner_area.y + position as u16,
+                    content: prefix + &line.name,
+                    width: (inner_area.width as usize).saturating_sub(line.indent as usize),
+                    style: if is_best_match {
+                        Style::default().bg(Color::Blue)
+                    } else {
+                        Style::default()
+                    },
+                    highlights: line
+                        .positions
+                        .iter()
+                        .map(|position| position + offset)
+                        .collect(),
+                }
+            })
+            .collect();
+
+        (lines_to_draw, best_match_position)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct BufferLine {
+    x: u16,
+    y: u16,
+    content: String,
+    width: usize,
+    style: Style,
+    // Characters of the content matching the filter
+    highlights: Vec<usize>,
+}
+
+// A line of the filtered tree, before it's placed.
+struct FilteredLine<'a> {
+    indent: u16,
+    marker: &'static str,
+    name: String,
+    positions: &'a [usize],
+    item: Option<TreeItem>,
+}
+
+impl<'a> StatefulWidget for DatabaseTree<'a> {
+    type State = DatabaseCluster;
+
+    // Implement the rendering logic of the database tree.
+    //
+    // Iterate over all databases and expand the schemas of the expanded
+    // databases, the groups of objects of the expanded schemas and the
+    // objects of the expanded groups.
+    //
+    // Calculate the offset for scrolling when # of lines > height_of_tree
+    //
+    //    [0, 1, 2]              current display (height_of_tree = 3)
+    //        ^                  y_center_of_tree
+    // [1, 2, 3, 4, 5, 6, 7]     total items (lines_to_draw)
+    //        ^                  current_focused_index_position
+    //     ^                     start_of_slice = position - height_of_tree / 2
+    //           ^               end_of_slice = start_of_slice + height_of_tree
+    //    [2, 3, 4]              lines_to_draw[start_of_slice..end_of_slice]
+    //
+    //
+    // TODO: refactor this so that we render in one iteration
+    //
+    fn render(mut self, area: Rect, buf: &mut Buffer, cluster: &mut DatabaseCluster) {
+        let inner_area = match self.block.take() {
+            Some(b) => {
+                let inner_area = b.inner(area);
+                b.render(area, buf);
+                inner_area
+            }
+            None => area,
+        };
+
+        let (lines_to_draw, current_focused_index_position) = if cluster.filter.is_empty() {
+            Self::tree_lines(cluster, inner_area)
+        } else {
+            Self::filtered_lines(cluster, inner_area)
+        };
+
+        let total_lines = lines_to_draw.len();
+        let height_of_tree = inner_area.height as usize;
+        let radius_of_tree = height_of_tree / 2;
+
+        let offset = if total_lines > height_of_tree {
+            current_focused_index_position.saturating_sub(radius_of_tree)
+        } else {
+            0
+        };
+        let start_of_slice = min(offset, total_lines);
+        let end_of_slice = min(total_lines, offset + height_of_tree);
+
+        for line in &lines_to_draw[start_of_slice..end_of_slice] {
+            draw_line(buf, line, line.y - offset as u16);
+        }
+    }
+}
+
+fn draw_line(buf: &mut Buffer, line: &BufferLine, y: u16) {
+    buf.set_stringn(line.x, y, &line.content, line.width, line.style);
+
+    let highlight_style = line.style.fg(Color::Yellow).add_modifier(Modifier::BOLD);
+    let mut offset = 0;
+    for (index, c) in line.content.chars().enumerate() {
+        if offset >= line.width {
+            break;
+        }
+        if line.highlights.contains(&index) {
+            buf.get_mut(line.x + offset as u16, y)
+                .set_style(highlight_style);
+        }
+        offset += c.width().unwrap_or(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::database::Database;
+
+    fn cluster(count: usize) -> DatabaseCluster {
+        DatabaseCluster::new(
+            (0..count)
+                .map(|index| Database::new(format!("db{}", index), Vec::new()))
+                .collect(),
+        )
+    }
+
+    fn line(buf: &Buffer, y: u16) -> String {
+        (0..buf.area.width)
+            .map(|x| buf.get(x, y).symbol())
+            .collect::<String>()
+            .trim()
+            .to_string()
+    }
+
+    #[test]
+    fn lines_stay_in_a_pane_below_the_top_of_the_screen() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 20, 30));
+        let mut cluster = cluster(10);
+        for _ in 0..6 {
+            cluster.next();
+        }
+
+        DatabaseTree::new().render(Rect::new(0, 20, 20, 4), &mut buf, &mut cluster);
+
+        let lines: Vec<String> = (18..26).map(|y| line(&buf, y)).collect();
+        assert!(lines[..2].iter().all(String::is_empty), "{:?}", lines);
+        assert!(lines[2].contains("db3"), "{:?}", lines);
+        assert!(lines[5].contains("db6"), "{:?}", lines);
+        assert!(lines[6..].iter().all(String::is_empty), "{:?}", lines);
     }
 }
@@ -0,0 +1,19 @@
+use super::text_scroll::TextScroll;
+
+// Reconstructed DDL of an object, `name` is used for the title and the file
+// it's saved to.
+pub struct DdlView {
+    pub name: String,
+    pub sql: String,
+    pub scroll: TextScroll,
+}
+
+impl DdlView {
+    pub fn new(name: String, sql: String) -> Self {
+        Self {
+            name,
+            sql,
+            scroll: TextScroll::default(),
+        }
+    }
+}
@@ -0,0 +1,756 @@
+use std::collections::{hash_map::Entry, HashMap, HashSet, VecDeque};
+
+use super::text_scroll::TextScroll;
+use crate::postgres::{data::ForeignKey, query::TableName, structure::DiagramColumn};
+
+// Columns listed in a box before the rest are summed up
+const MAX_COLUMNS: usize = 12;
+// Tables of a layer are split into more columns past this height
+const MAX_COLUMN_HEIGHT: usize = 60;
+const BOX_GAP: usize = 1;
+const CHANNEL_SPACING: usize = 2;
+const GUTTER_PADDING: usize = 3;
+
+const UP: u8 = 1;
+const DOWN: u8 = 2;
+const LEFT: u8 = 4;
+const RIGHT: u8 = 8;
+
+// A table in the diagram. Tables of other schemas only show up as the other
+// end of a foreign key, without their columns.
+pub struct DiagramTable {
+    pub name: TableName,
+    pub columns: Vec<DiagramColumn>,
+    pub is_external: bool,
+}
+
+// What a character of the canvas belongs to, for styling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellKind {
+    Blank,
+    Line,
+    FocusedLine,
+    Table,
+    FocusedTable,
+    NeighborTable,
+    ExternalTable,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Placement {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl Placement {
+    fn title_row(&self) -> usize {
+        self.y + 1
+    }
+}
+
+// Box-and-arrow diagram of the tables of a schema. Referenced tables are
+// placed left of the tables referencing them, every foreign key is a line
+// from the referencing table to an arrow on the referenced one. The diagram
+// can be limited to the tables a number of foreign keys away from the
+// focused one.
+pub struct ErDiagram {
+    pub schema: String,
+    tables: Vec<DiagramTable>,
+    // Referencing table, referenced table and the first column of the key
+    foreign_keys: Vec<(usize, usize, String)>,
+    pub focused: usize,
+    pub hops: Option<usize>,
+    pub scroll: TextScroll,
+    // Bring the focused table into view on the next render
+    pub follow_focus: bool,
+    pub canvas: Vec<Vec<(char, CellKind)>>,
+    placements: HashMap<usize, Placement>,
+}
+
+impl ErDiagram {
+    pub fn new(
+        schema: String,
+        tables: Vec<(String, Vec<DiagramColumn>)>,
+        foreign_keys: Vec<ForeignKey>,
+        focus: Option<&TableName>,
+    ) -> Self {
+        let mut tables: Vec<DiagramTable> = tables
+            .into_iter()
+            .map(|(name, columns)| DiagramTable {
+                name: TableName::new(&schema, &name),
+                columns,
+                is_external: false,
+            })
+            .collect();
+
+        let index_of = |tables: &mut Vec<DiagramTable>, name: &TableName| match tables
+            .iter()
+            .position(|table| table.name == *name)
+        {
+            Some(index) => index,
+            None => {
+                tables.push(DiagramTable {
+                    name: name.clone(),
+                    columns: Vec::new(),
+                    is_external: true,
+                });
+                tables.len() - 1
+            }
+        };
+        let foreign_keys = foreign_keys
+            .into_iter()
+            .map(|foreign_key| {
+                (
+                    index_of(&mut tables, &foreign_key.table),
+                    index_of(&mut tables, &foreign_key.referenced_table),
+                    foreign_key.columns.first().cloned().unwrap_or_default(),
+                )
+            })
+            .collect();
+
+        let focused = focus
+            .and_then(|focus| tables.iter().position(|table| table.name == *focus))
+            .unwrap_or(0);
+
+        let mut diagram = Self {
+            schema,
+            tables,
+            foreign_keys,
+            focused,
+            hops: None,
+            scroll: TextScroll::default(),
+            follow_focus: true,
+            canvas: Vec::new(),
+            placements: HashMap::new(),
+        };
+        diagram.layout();
+        diagram
+    }
+
+    pub fn focused_table(&self) -> Option<&TableName> {
+        self.tables.get(self.focused).map(|table| &table.name)
+    }
+
+    pub fn visible_count(&self) -> usize {
+        self.placements.len()
+    }
+
+    // Number of foreign keys between the focused table and every table
+    // connected to it, whichever way they point.
+    fn distances(&self) -> HashMap<usize, usize> {
+        let mut distances = HashMap::from([(self.focused, 0)]);
+        let mut queue = VecDeque::from([self.focused]);
+
+        while let Some(index) = queue.pop_front() {
+            let distance = distances[&index];
+            for (source, target, _) in self.foreign_keys.iter() {
+                let neighbor = if *source == index {
+                    *target
+                } else if *target == index {
+                    *source
+                } else {
+                    continue;
+                };
+                if let Entry::Vacant(entry) = distances.entry(neighbor) {
+                    entry.insert(distance + 1);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        distances
+    }
+
+    fn visible_tables(&self) -> HashSet<usize> {
+        match self.hops {
+            Some(hops) => self
+                .distances()
+                .into_iter()
+                .filter(|(_, distance)| *distance <= hops)
+                .map(|(index, _)| index)
+                .collect(),
+            None => (0..self.tables.len()).collect(),
+        }
+    }
+
+    fn is_neighbor(&self, index: usize) -> bool {
+        self.foreign_keys.iter().any(|(source, target, _)| {
+            (*source == self.focused && *target == index)
+                || (*target == self.focused && *source == index)
+        })
+    }
+
+    // Move the focus to the next or previous table in the diagram.
+    pub fn focus_next(&mut self, forward: bool) {
+        let mut visible: Vec<usize> = self.placements.keys().copied().collect();
+        visible.sort_unstable();
+        let position = match visible.iter().position(|index| *index == self.focused) {
+            Some(position) => position,
+            None => return,
+        };
+
+        self.focused = if forward {
+            visible[(position + 1) % visible.len()]
+        } else {
+            visible[(position + visible.len() - 1) % visible.len()]
+        };
+        self.follow_focus = true;
+        self.layout();
+    }
+
+    // Show one foreign key less around the focused table, starting from
+    // everything that's connected to it.
+    pub fn fewer_hops(&mut self) {
+        let farthest = self.distances().into_values().max().unwrap_or(0);
+        self.hops = Some(match self.hops {
+            Some(hops) => hops.saturating_sub(1).max(1),
+            None => farthest.saturating_sub(1).max(1),
+        });
+        self.follow_focus = true;
+        self.layout();
+    }
+
+    pub fn more_hops(&mut self) {
+        let farthest = self.distances().into_values().max().unwrap_or(0);
+        self.hops = match self.hops {
+            Some(hops) if hops + 1 < farthest => Some(hops + 1),
+            _ => None,
+        };
+        self.follow_focus = true;
+        self.layout();
+    }
+
+    pub fn all_tables(&mut self) {
+        self.hops = None;
+        self.follow_focus = true;
+        self.layout();
+    }
+
+    // Position and size of the focused table on the canvas.
+    pub fn focused_area(&self) -> Option<(usize, usize, usize, usize)> {
+        let placement = self.placements.get(&self.focused)?;
+        Some((placement.x, placement.y, placement.width, placement.height))
+    }
+
+    // Lines of a table's box between its borders.
+    fn box_lines(&self, index: usize) -> Vec<String> {
+        let table = &self.tables[index];
+        let title = if table.is_external {
+            table.name.to_string()
+        } else {
+            table.name.name.clone()
+        };
+
+        let mut lines = vec![title];
+        lines.extend(table.columns.iter().take(MAX_COLUMNS).map(|column| {
+            let marker = if column.is_primary_key {
+                "#"
+            } else if column.is_foreign_key {
+                "→"
+            } else {
+                " "
+            };
+            format!("{} {} {}", marker, column.name, column.type_name)
+        }));
+        if table.columns.len() > MAX_COLUMNS {
+            lines.push(format!("  … {} more", table.columns.len() - MAX_COLUMNS));
+        }
+        lines
+    }
+
+    // Layer of every table: one more than the highest layer of the tables
+    // it references, foreign keys that close a cycle being ignored.
+    fn layers(&self, visible: &HashSet<usize>) -> HashMap<usize, usize> {
+        fn visit(
+            index: usize,
+            edges: &[(usize, usize)],
+            layers: &mut HashMap<usize, usize>,
+            in_progress: &mut HashSet<usize>,
+        ) -> usize {
+            if let Some(layer) = layers.get(&index) {
+                return *layer;
+            }
+            in_progress.insert(index);
+
+            let mut layer = 0;
+            for (_, target) in edges.iter().filter(|(source, _)| *source == index) {
+                if !in_progress.contains(target) {
+                    layer = layer.max(visit(*target, edges, layers, in_progress) + 1);
+                }
+            }
+
+            in_progress.remove(&index);
+            layers.insert(index, layer);
+            layer
+        }
+
+        let edges: Vec<(usize, usize)> = self
+            .foreign_keys
+            .iter()
+            .filter(|(source, target, _)| {
+                source != target && visible.contains(source) && visible.contains(target)
+            })
+            .map(|(source, target, _)| (*source, *target))
+            .collect();
+
+        let mut layers = HashMap::new();
+        let mut in_progress = HashSet::new();
+        let mut indexes: Vec<usize> = visible.iter().copied().collect();
+        indexes.sort_unstable();
+        for index in indexes {
+            visit(index, &edges, &mut layers, &mut in_progress);
+        }
+        layers
+    }
+
+    // Place the visible tables and draw them with their foreign keys.
+    fn layout(&mut self) {
+        let visible = self.visible_tables();
+        let layers = self.layers(&visible);
+        let layer_count = layers.values().max().map_or(0, |layer| layer + 1);
+
+        let box_lines: HashMap<usize, Vec<String>> = visible
+            .iter()
+            .map(|index| (*index, self.box_lines(*index)))
+            .collect();
+        let box_size = |index: usize| {
+            let lines = &box_lines[&index];
+            let width = lines
+                .iter()
+                .map(|line| line.chars().count())
+                .max()
+                .unwrap_or(0)
+                + 4;
+            let height = if lines.len() > 1 { lines.len() + 3 } else { 3 };
+            (width, height)
+        };
+
+        // Columns of tables, each layer taking one or more
+        let mut columns: Vec<Vec<usize>> = Vec::new();
+        for layer in 0..layer_count {
+            let mut indexes: Vec<usize> = layers
+                .iter()
+                .filter(|(_, table_layer)| **table_layer == layer)
+                .map(|(index, _)| *index)
+                .collect();
+            indexes.sort_unstable();
+
+            let mut column = Vec::new();
+            let mut height = 0;
+            for index in indexes {
+                let (_, box_height) = box_size(index);
+                if !column.is_empty() && height + box_height > MAX_COLUMN_HEIGHT {
+                    columns.push(std::mem::take(&mut column));
+                    height = 0;
+                }
+                column.push(index);
+                height += box_height + BOX_GAP;
+            }
+            if !column.is_empty() {
+                columns.push(column);
+            }
+        }
+
+        let edges: Vec<&(usize, usize, String)> = self
+            .foreign_keys
+            .iter()
+            .filter(|(source, target, _)| visible.contains(source) && visible.contains(target))
+            .collect();
+
+        // Tables are stacked in their column, the lines to the tables of a
+        // column run down the gutter right of it, one channel per table
+        self.placements.clear();
+        let mut channels: HashMap<usize, usize> = HashMap::new();
+        let mut x = 1;
+        let mut canvas_height = 0;
+        for column in columns.iter() {
+            let width = column
+                .iter()
+                .map(|index| box_size(*index).0)
+                .max()
+                .unwrap_or(0);
+
+            let mut y = 0;
+            for index in column.iter() {
+                let (box_width, box_height) = box_size(*index);
+                self.placements.insert(
+                    *index,
+                    Placement {
+                        x,
+                        y,
+                        width: box_width,
+                        height: box_height,
+                    },
+                );
+                y += box_height + BOX_GAP;
+            }
+            canvas_height = canvas_height.max(y);
+
+            let targets: Vec<usize> = column
+                .iter()
+                .copied()
+                .filter(|index| edges.iter().any(|(_, target, _)| target == index))
+                .collect();
+            for (channel, index) in targets.iter().enumerate() {
+                channels.insert(
+                    *index,
+                    x + width + GUTTER_PADDING + channel * CHANNEL_SPACING,
+                );
+            }
+            x += width + GUTTER_PADDING * 2 + targets.len() * CHANNEL_SPACING;
+        }
+        let canvas_width = x;
+
+        let mut bits = vec![vec![0u8; canvas_width]; canvas_height];
+        let mut focused_lines = vec![vec![false; canvas_width]; canvas_height];
+        let mut arrows = Vec::new();
+        let mut ports = Vec::new();
+        let mut draw_segment =
+            |(x1, y1): (usize, usize), (x2, y2): (usize, usize), focused: bool| {
+                if y1 == y2 {
+                    let (start, end) = (x1.min(x2), x1.max(x2));
+                    for x in start..=end {
+                        if x > start {
+                            bits[y1][x] |= LEFT;
+                        }
+                        if x < end {
+                            bits[y1][x] |= RIGHT;
+                        }
+                        focused_lines[y1][x] |= focused;
+                    }
+                } else {
+                    let (start, end) = (y1.min(y2), y1.max(y2));
+                    for y in start..=end {
+                        if y > start {
+                            bits[y][x1] |= UP;
+                        }
+                        if y < end {
+                            bits[y][x1] |= DOWN;
+                        }
+                        focused_lines[y][x1] |= focused;
+                    }
+                }
+            };
+
+        for (source, target, column) in edges.iter() {
+            let (source_box, target_box) = (self.placements[source], self.placements[target]);
+            let channel = channels[target];
+
+            let column_row = self.tables[*source]
+                .columns
+                .iter()
+                .take(MAX_COLUMNS)
+                .position(|table_column| table_column.name == *column)
+                .map(|position| source_box.y + 3 + position);
+            let source_row = column_row.unwrap_or(source_box.title_row());
+            let source_x = if source_box.x > channel {
+                ports.push((source_box.x, source_row, '┤'));
+                source_box.x - 1
+            } else {
+                ports.push((source_box.x + source_box.width - 1, source_row, '├'));
+                source_box.x + source_box.width
+            };
+            let target_row = target_box.title_row();
+            let arrow_x = target_box.x + target_box.width;
+
+            let focused = *source == self.focused || *target == self.focused;
+            draw_segment((source_x, source_row), (channel, source_row), focused);
+            draw_segment((channel, source_row), (channel, target_row), focused);
+            draw_segment((channel, target_row), (arrow_x, target_row), focused);
+            arrows.push((arrow_x, target_row, focused));
+        }
+
+        let mut canvas: Vec<Vec<(char, CellKind)>> = bits
+            .iter()
+            .zip(focused_lines.iter())
+            .map(|(row, focused_row)| {
+                row.iter()
+                    .zip(focused_row.iter())
+                    .map(|(bits, focused)| match line_char(*bits) {
+                        Some(c) if *focused => (c, CellKind::FocusedLine),
+                        Some(c) => (c, CellKind::Line),
+                        None => (' ', CellKind::Blank),
+                    })
+                    .collect()
+            })
+            .collect();
+        for (x, y, focused) in arrows {
+            canvas[y][x] = (
+                '◄',
+                if focused {
+                    CellKind::FocusedLine
+                } else {
+                    CellKind::Line
+                },
+            );
+        }
+
+        for (index, placement) in self.placements.iter() {
+            let kind = if *index == self.focused {
+                CellKind::FocusedTable
+            } else if self.is_neighbor(*index) {
+                CellKind::NeighborTable
+            } else if self.tables[*index].is_external {
+                CellKind::ExternalTable
+            } else {
+                CellKind::Table
+            };
+            draw_box(&mut canvas, placement, &box_lines[index], kind);
+        }
+        // Lines leave the boxes through a joint in their border
+        for (x, y, c) in ports {
+            canvas[y][x].0 = c;
+        }
+
+        self.canvas = canvas;
+    }
+}
+
+fn line_char(bits: u8) -> Option<char> {
+    let c = match bits {
+        0 => return None,
+        b if b == UP | DOWN | LEFT | RIGHT => '┼',
+        b if b == UP | DOWN | RIGHT => '├',
+        b if b == UP | DOWN | LEFT => '┤',
+        b if b == LEFT | RIGHT | DOWN => '┬',
+        b if b == LEFT | RIGHT | UP => '┴',
+        b if b == DOWN | RIGHT => '┌',
+        b if b == DOWN | LEFT => '┐',
+        b if b == UP | RIGHT => '└',
+        b if b == UP | LEFT => '┘',
+        b if b & (UP | DOWN) != 0 => '│',
+        _ => '─',
+    };
+    Some(c)
+}
+
+// A box with the title on top, separated from the columns.
+fn draw_box(
+    canvas: &mut [Vec<(char, CellKind)>],
+    placement: &Placement,
+    lines: &[String],
+    kind: CellKind,
+) {
+    let Placement {
+        x,
+        y,
+        width,
+        height,
+    } = *placement;
+    let right = x + width - 1;
+    let bottom = y + height - 1;
+
+    let separator = (lines.len() > 1).then_some(y + 2);
+    for (row, cells) in canvas.iter_mut().enumerate().skip(y).take(height) {
+        for (column, cell) in cells.iter_mut().enumerate().skip(x).take(width) {
+            let is_edge = row == y || row == bottom || Some(row) == separator;
+            let c = match (row, column) {
+                (row, column) if row == y && column == x => '┌',
+                (row, column) if row == y && column == right => '┐',
+                (row, column) if row == bottom && column == x => '└',
+                (row, column) if row == bottom && column == right => '┘',
+                (_, column) if is_edge && column == x => '├',
+                (_, column) if is_edge && column == right => '┤',
+                _ if is_edge => '─',
+                (_, column) if column == x || column == right => '│',
+                _ => ' ',
+            };
+            *cell = (c, kind);
+        }
+    }
+
+    for (position, line) in lines.iter().enumerate() {
+        let row = if position == 0 {
+            y + 1
+        } else {
+            y + 2 + position
+        };
+        for (offset, c) in line.chars().enumerate() {
+            canvas[row][x + 2 + offset] = (c, kind);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str, is_primary_key: bool, is_foreign_key: bool) -> DiagramColumn {
+        DiagramColumn {
+            name: name.to_string(),
+            type_name: String::from("int4"),
+            is_primary_key,
+            is_foreign_key,
+        }
+    }
+
+    fn foreign_key(table: &str, column: &str, referenced_table: TableName) -> ForeignKey {
+        ForeignKey {
+            name: format!("{}_{}_fkey", table, column),
+            table: TableName::new("public", table),
+            columns: vec![column.to_string()],
+            referenced_table,
+            referenced_columns: vec![String::from("id")],
+        }
+    }
+
+    // customers ← orders ← lines → products, and orders → auth.users
+    fn diagram(focus: &str) -> ErDiagram {
+        let table = |name: &str, columns: Vec<DiagramColumn>| (name.to_string(), columns);
+        ErDiagram::new(
+            String::from("public"),
+            vec![
+                table("customers", vec![column("id", true, false)]),
+                table(
+                    "lines",
+                    vec![
+                        column("order_id", true, true),
+                        column("product_id", false, true),
+                    ],
+                ),
+                table(
+                    "orders",
+                    vec![
+                        column("id", true, false),
+                        column("customer_id", false, true),
+                        column("user_id", false, true),
+                    ],
+                ),
+                table("products", vec![column("id", true, false)]),
+            ],
+            vec![
+                foreign_key("lines", "order_id", TableName::new("public", "orders")),
+                foreign_key("lines", "product_id", TableName::new("public", "products")),
+                foreign_key(
+                    "orders",
+                    "customer_id",
+                    TableName::new("public", "customers"),
+                ),
+                foreign_key("orders", "user_id", TableName::new("auth", "users")),
+            ],
+            Some(&TableName::new("public", focus)),
+        )
+    }
+
+    fn x_of(diagram: &ErDiagram, name: &str) -> usize {
+        let index = diagram
+            .tables
+            .iter()
+            .position(|table| table.name.name == name)
+            .unwrap();
+        diagram.placements[&index].x
+    }
+
+    fn text(diagram: &ErDiagram) -> String {
+        diagram
+            .canvas
+            .iter()
+            .map(|row| row.iter().map(|(c, _)| *c).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn referenced_tables_are_placed_left() {
+        let diagram = diagram("orders");
+
+        assert_eq!(diagram.visible_count(), 5);
+        assert_eq!(x_of(&diagram, "customers"), x_of(&diagram, "users"));
+        assert_eq!(x_of(&diagram, "customers"), x_of(&diagram, "products"));
+        assert!(x_of(&diagram, "customers") < x_of(&diagram, "orders"));
+        assert!(x_of(&diagram, "orders") < x_of(&diagram, "lines"));
+    }
+
+    #[test]
+    fn boxes_list_the_columns_with_their_keys() {
+        let diagram = diagram("orders");
+        let canvas = text(&diagram);
+
+        assert!(canvas.contains("│ orders"));
+        assert!(canvas.contains("# id int4"));
+        assert!(canvas.contains("→ customer_id int4"));
+        // Tables of other schemas are qualified and have no columns
+        assert!(canvas.contains("│ auth.users │"));
+        assert_eq!(canvas.matches('◄').count(), 4);
+
+        let (x, y, width, height) = diagram.focused_area().unwrap();
+        assert_eq!(x, x_of(&diagram, "orders"));
+        assert_eq!(
+            (width, height),
+            ("→ customer_id int4".chars().count() + 4, 7)
+        );
+        assert_eq!(diagram.canvas[y][x], ('┌', CellKind::FocusedTable));
+    }
+
+    #[test]
+    fn hops_limit_the_tables_around_the_focused_one() {
+        let mut diagram = diagram("customers");
+
+        // lines and users are two keys away, products three
+        diagram.fewer_hops();
+        assert_eq!(diagram.hops, Some(2));
+        assert_eq!(diagram.visible_count(), 4);
+
+        diagram.fewer_hops();
+        diagram.fewer_hops();
+        assert_eq!(diagram.hops, Some(1));
+        assert_eq!(diagram.visible_count(), 2);
+
+        diagram.more_hops();
+        diagram.more_hops();
+        assert_eq!(diagram.hops, None);
+        assert_eq!(diagram.visible_count(), 5);
+    }
+
+    #[test]
+    fn focus_cycles_through_the_visible_tables() {
+        let mut diagram = diagram("customers");
+        diagram.fewer_hops();
+        diagram.fewer_hops();
+
+        diagram.focus_next(true);
+        assert_eq!(diagram.focused_table().unwrap().name, "orders");
+        // The tables around orders are visible now
+        assert_eq!(diagram.visible_count(), 4);
+        diagram.focus_next(true);
+        assert_eq!(diagram.focused_table().unwrap().name, "users");
+        diagram.focus_next(false);
+        assert_eq!(diagram.focused_table().unwrap().name, "orders");
+        diagram.focus_next(false);
+        assert_eq!(diagram.focused_table().unwrap().name, "lines");
+    }
+
+    #[test]
+    fn cycles_and_self_references_are_laid_out() {
+        let diagram = ErDiagram::new(
+            String::from("public"),
+            vec![
+                (String::from("a"), vec![column("b_id", false, true)]),
+                (String::from("b"), vec![column("a_id", false, true)]),
+                (String::from("c"), vec![column("parent_id", false, true)]),
+            ],
+            vec![
+                foreign_key("a", "b_id", TableName::new("public", "b")),
+                foreign_key("b", "a_id", TableName::new("public", "a")),
+                foreign_key("c", "parent_id", TableName::new("public", "c")),
+            ],
+            None,
+        );
+
+        assert_eq!(diagram.visible_count(), 3);
+        assert_eq!(diagram.focused_table().unwrap().name, "a");
+        assert_ne!(x_of(&diagram, "a"), x_of(&diagram, "b"));
+    }
+
+    #[test]
+    fn joined_lines() {
+        assert_eq!(line_char(0), None);
+        assert_eq!(line_char(LEFT | RIGHT), Some('─'));
+        assert_eq!(line_char(UP), Some('│'));
+        assert_eq!(line_char(UP | RIGHT), Some('└'));
+        assert_eq!(line_char(UP | DOWN | LEFT), Some('┤'));
+        assert_eq!(line_char(UP | DOWN | LEFT | RIGHT), Some('┼'));
+    }
+}
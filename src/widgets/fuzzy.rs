@@ -0,0 +1,112 @@
+// Positions of the characters of the text matching the pattern in order,
+// ignoring case, and how well they match: characters starting a word and
+// runs of consecutive characters count more, gaps between them count
+// against. None when the text doesn't contain every character of the
+// pattern.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<(i64, Vec<usize>)> {
+    let lowercase = |c: char| c.to_lowercase().next().unwrap_or(c);
+    let pattern: Vec<char> = pattern.chars().map(lowercase).collect();
+    let text: Vec<char> = text.chars().map(lowercase).collect();
+
+    let first = match pattern.first() {
+        Some(first) => *first,
+        None => return Some((0, Vec::new())),
+    };
+
+    // Every place the first character matches is tried, the rest of the
+    // pattern being matched as early as possible after it
+    let mut best: Option<(i64, Vec<usize>)> = None;
+    for start in (0..text.len()).filter(|index| text[*index] == first) {
+        let mut positions = vec![start];
+        for c in pattern[1..].iter() {
+            let from = positions[positions.len() - 1] + 1;
+            match text[from..].iter().position(|t| t == c) {
+                Some(offset) => positions.push(from + offset),
+                None => break,
+            }
+        }
+        // Starting later can't match more of the pattern
+        if positions.len() < pattern.len() {
+            break;
+        }
+
+        let score = score(&text, &positions);
+        if best
+            .as_ref()
+            .is_none_or(|(best_score, _)| score > *best_score)
+        {
+            best = Some((score, positions));
+        }
+    }
+
+    best
+}
+
+fn score(text: &[char], positions: &[usize]) -> i64 {
+    let mut score = 0;
+    for (index, position) in positions.iter().enumerate() {
+        score += 1;
+        if *position == 0 || !text[position - 1].is_alphanumeric() {
+            score += 8;
+        }
+        if index > 0 {
+            match position - positions[index - 1] - 1 {
+                0 => score += 5,
+                gap => score -= gap.min(5) as i64,
+            }
+        }
+    }
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions(pattern: &str, text: &str) -> Option<Vec<usize>> {
+        fuzzy_match(pattern, text).map(|(_, positions)| positions)
+    }
+
+    fn score(pattern: &str, text: &str) -> i64 {
+        fuzzy_match(pattern, text).unwrap().0
+    }
+
+    #[test]
+    fn empty_pattern_matches_anything() {
+        assert_eq!(fuzzy_match("", "orders"), Some((0, Vec::new())));
+        assert_eq!(fuzzy_match("", ""), Some((0, Vec::new())));
+    }
+
+    #[test]
+    fn every_character_has_to_match_in_order() {
+        assert_eq!(positions("odr", "orders"), Some(vec![0, 2, 4]));
+        assert_eq!(positions("sro", "orders"), None);
+        assert_eq!(positions("orderss", "orders"), None);
+        assert_eq!(positions("x", ""), None);
+    }
+
+    #[test]
+    fn case_is_ignored() {
+        assert_eq!(positions("ORD", "orders"), Some(vec![0, 1, 2]));
+        assert_eq!(positions("mc", "Mixed Case"), Some(vec![0, 6]));
+    }
+
+    #[test]
+    fn positions_count_characters() {
+        assert_eq!(positions("éx", "café_x"), Some(vec![3, 5]));
+    }
+
+    #[test]
+    fn best_start_is_picked() {
+        // The later match starts a word and is consecutive
+        assert_eq!(positions("or", "color_order"), Some(vec![6, 7]));
+    }
+
+    #[test]
+    fn word_starts_and_runs_score_higher() {
+        assert!(score("abc", "abcx") > score("abc", "axbxc"));
+        assert!(score("ol", "order_lines") > score("ol", "pool"));
+        assert!(score("ci", "customer_id") > score("ci", "acid"));
+        assert!(score("ab", "a_b") > score("ab", "axxxxxxb"));
+    }
+}
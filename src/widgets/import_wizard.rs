@@ -0,0 +1,523 @@
+use std::fs;
+use tokio_postgres::types::Type;
+
+use super::data_grid::GridState;
+use crate::postgres::{
+    data::{Column, Value},
+    export::expand_home,
+    filter::check_value,
+    import::{
+        copy_csv_line, copy_from_sql, create_table_sql, infer_type, parse_csv, CsvOptions,
+        CsvRecord, IMPORT_TYPES,
+    },
+    query::TableName,
+};
+
+const PREVIEW_ROWS: usize = 10;
+// Rows looked at to infer the column types of a new table
+const INFERENCE_ROWS: usize = 1000;
+// Bad rows listed in the report, the rest are only counted
+const REPORTED_BAD_ROWS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceField {
+    Path,
+    Table,
+    Delimiter,
+    NullString,
+    Header,
+}
+
+const SOURCE_FIELDS: [SourceField; 5] = [
+    SourceField::Path,
+    SourceField::Table,
+    SourceField::Delimiter,
+    SourceField::NullString,
+    SourceField::Header,
+];
+
+pub struct ImportReport {
+    pub loaded: u64,
+    pub bad_rows: Vec<(usize, String)>,
+    pub bad_row_count: usize,
+}
+
+pub enum ImportStep {
+    // Pick the file, target table and how to read the file
+    Source,
+    // Preview the rows and map CSV columns to table columns
+    Mapping,
+    Done(ImportReport),
+}
+
+// Steps of loading a CSV file into a table. The file is previewed and its
+// columns are mapped onto the columns of the table, or of a new table whose
+// column types are inferred from the data.
+pub struct ImportWizard {
+    pub step: ImportStep,
+    pub field: SourceField,
+    pub path: String,
+    pub table: String,
+    // Schema of a table entered without one
+    pub default_schema: String,
+    // The table parsed from `table` when the file was loaded
+    pub target: TableName,
+    pub delimiter: String,
+    pub null_string: String,
+    pub header: bool,
+    pub error: Option<String>,
+    // Names of the CSV columns and its records without the header
+    pub csv_columns: Vec<String>,
+    pub records: Vec<CsvRecord>,
+    // Columns of the target table, inferred when it's created
+    pub columns: Vec<Column>,
+    pub creates_table: bool,
+    // CSV column loaded into each table column, None leaves it out
+    pub mapping: Vec<Option<usize>>,
+    pub selected: usize,
+    pub preview: GridState,
+}
+
+impl ImportWizard {
+    pub fn new(table: String, default_schema: String) -> Self {
+        Self {
+            step: ImportStep::Source,
+            field: SourceField::Path,
+            path: String::new(),
+            table,
+            default_schema,
+            target: TableName::default(),
+            delimiter: String::from(","),
+            null_string: String::new(),
+            header: true,
+            error: None,
+            csv_columns: Vec::new(),
+            records: Vec::new(),
+            columns: Vec::new(),
+            creates_table: false,
+            mapping: Vec::new(),
+            selected: 0,
+            preview: GridState::default(),
+        }
+    }
+
+    pub fn next_field(&mut self, forward: bool) {
+        let position = SOURCE_FIELDS
+            .iter()
+            .position(|field| *field == self.field)
+            .unwrap_or(0);
+        let position = if forward {
+            (position + 1) % SOURCE_FIELDS.len()
+        } else {
+            (position + SOURCE_FIELDS.len() - 1) % SOURCE_FIELDS.len()
+        };
+        self.field = SOURCE_FIELDS[position];
+    }
+
+    fn field_text(&mut self) -> Option<&mut String> {
+        match self.field {
+            SourceField::Path => Some(&mut self.path),
+            SourceField::Table => Some(&mut self.table),
+            SourceField::Delimiter => Some(&mut self.delimiter),
+            SourceField::NullString => Some(&mut self.null_string),
+            SourceField::Header => None,
+        }
+    }
+
+    pub fn type_char(&mut self, c: char) {
+        self.error = None;
+        match self.field_text() {
+            Some(text) => text.push(c),
+            None if c == ' ' => self.header = !self.header,
+            None => {}
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        self.error = None;
+        if let Some(text) = self.field_text() {
+            text.pop();
+        }
+    }
+
+    pub fn table_name(&self) -> Result<TableName, String> {
+        if self.table.trim().is_empty() {
+            return Err(String::from("enter the table to import into"));
+        }
+        TableName::parse(&self.table, &self.default_schema)
+    }
+
+    fn csv_options(&self) -> Result<CsvOptions, String> {
+        let delimiter = match self.delimiter.as_str() {
+            "tab" | "\\t" => '\t',
+            delimiter if delimiter.chars().count() == 1 => delimiter.chars().next().unwrap_or(','),
+            _ => return Err(String::from("the delimiter must be one character or tab")),
+        };
+
+        Ok(CsvOptions {
+            delimiter,
+            header: self.header,
+            null_string: self.null_string.clone(),
+        })
+    }
+
+    // Read the file and map it onto the table with the given columns, or
+    // onto a new table when there's none.
+    pub fn load(&mut self, table_columns: Option<Vec<Column>>) -> Result<(), String> {
+        self.target = self.table_name()?;
+
+        let options = self.csv_options()?;
+        let path = expand_home(&self.path);
+        let text =
+            fs::read_to_string(&path).map_err(|error| format!("{}: {}", path.display(), error))?;
+
+        self.load_text(&text, &options, table_columns)
+    }
+
+    fn load_text(
+        &mut self,
+        text: &str,
+        options: &CsvOptions,
+        table_columns: Option<Vec<Column>>,
+    ) -> Result<(), String> {
+        let mut records = parse_csv(text, options);
+        let width = records
+            .iter()
+            .map(|record| record.fields.len())
+            .max()
+            .unwrap_or(0);
+        if width == 0 {
+            return Err(String::from("the file has no rows"));
+        }
+
+        self.csv_columns = if options.header {
+            let header = records.remove(0);
+            (0..width)
+                .map(|index| match header.fields.get(index) {
+                    Some(Some(name)) if !name.is_empty() => name.clone(),
+                    _ => format!("column{}", index + 1),
+                })
+                .collect()
+        } else {
+            (0..width)
+                .map(|index| format!("column{}", index + 1))
+                .collect()
+        };
+        self.records = records;
+
+        match table_columns {
+            Some(columns) => {
+                // Match columns by name, by position without a header
+                self.mapping = columns
+                    .iter()
+                    .enumerate()
+                    .map(|(index, column)| {
+                        if options.header {
+                            self.csv_columns
+                                .iter()
+                                .position(|name| name.eq_ignore_ascii_case(&column.name))
+                        } else {
+                            (index < width).then_some(index)
+                        }
+                    })
+                    .collect();
+                self.columns = columns;
+                self.creates_table = false;
+            }
+            None => {
+                self.columns = (0..width)
+                    .map(|index| {
+                        let values: Vec<&str> = self
+                            .records
+                            .iter()
+                            .take(INFERENCE_ROWS)
+                            .filter_map(|record| record.fields.get(index)?.as_deref())
+                            .collect();
+                        Column::new(self.csv_columns[index].clone(), infer_type(&values))
+                    })
+                    .collect();
+                self.mapping = (0..width).map(Some).collect();
+                self.creates_table = true;
+            }
+        }
+
+        self.selected = 0;
+        self.preview = GridState::default();
+        self.step = ImportStep::Mapping;
+        Ok(())
+    }
+
+    pub fn next_column(&mut self, forward: bool) {
+        if forward {
+            if self.selected + 1 < self.columns.len() {
+                self.selected += 1;
+            }
+        } else {
+            self.selected = self.selected.saturating_sub(1);
+        }
+    }
+
+    // Map the selected column to the next CSV column, or give a column of a
+    // new table the next type.
+    pub fn cycle(&mut self, forward: bool) {
+        if self.columns.is_empty() {
+            return;
+        }
+
+        if self.creates_table {
+            let column = &mut self.columns[self.selected];
+            let position = IMPORT_TYPES
+                .iter()
+                .position(|ty| *ty == column.type_)
+                .unwrap_or(0);
+            let position = if forward {
+                (position + 1) % IMPORT_TYPES.len()
+            } else {
+                (position + IMPORT_TYPES.len() - 1) % IMPORT_TYPES.len()
+            };
+            column.type_ = IMPORT_TYPES[position].clone();
+            return;
+        }
+
+        // None comes after the last CSV column
+        let choices = self.csv_columns.len() + 1;
+        let current = self.mapping[self.selected].unwrap_or(self.csv_columns.len());
+        let next = if forward {
+            (current + 1) % choices
+        } else {
+            (current + choices - 1) % choices
+        };
+        self.mapping[self.selected] = (next < self.csv_columns.len()).then_some(next);
+    }
+
+    // Why the record can't be loaded, if it can't.
+    pub fn check_record(&self, record: &CsvRecord) -> Result<(), String> {
+        if record.fields.len() != self.csv_columns.len() {
+            return Err(format!(
+                "has {} fields, expected {}",
+                record.fields.len(),
+                self.csv_columns.len()
+            ));
+        }
+
+        for (column, csv_index) in self.columns.iter().zip(&self.mapping) {
+            let value = csv_index.and_then(|index| record.fields[index].as_deref());
+            if let Some(value) = value {
+                check_value(&column.type_, value)
+                    .map_err(|error| format!("{} {}", column.name, error))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Number of records with a value the column's type rejects.
+    pub fn column_errors(&self, position: usize) -> usize {
+        let (column, csv_index) = match (self.columns.get(position), self.mapping.get(position)) {
+            (Some(column), Some(Some(csv_index))) => (column, *csv_index),
+            _ => return 0,
+        };
+
+        self.records
+            .iter()
+            .filter(|record| {
+                record
+                    .fields
+                    .get(csv_index)
+                    .and_then(|value| value.as_deref())
+                    .is_some_and(|value| check_value(&column.type_, value).is_err())
+            })
+            .count()
+    }
+
+    // Columns and rows of the preview, mapped onto the table.
+    pub fn preview_rows(&self) -> (Vec<Column>, Vec<Vec<Value>>) {
+        let columns = self
+            .columns
+            .iter()
+            .zip(&self.mapping)
+            .filter(|(_, csv_index)| csv_index.is_some())
+            .map(|(column, _)| Column::new(column.name.clone(), Type::TEXT))
+            .collect();
+
+        let rows = self
+            .records
+            .iter()
+            .take(PREVIEW_ROWS)
+            .map(|record| {
+                self.mapping
+                    .iter()
+                    .flatten()
+                    .map(|index| match record.fields.get(*index).cloned().flatten() {
+                        Some(value) => Value::Text(value),
+                        None => Value::Null,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        (columns, rows)
+    }
+
+    pub fn create_sql(&self) -> Option<String> {
+        let columns: Vec<(String, Type)> = self
+            .columns
+            .iter()
+            .map(|column| (column.name.clone(), column.type_.clone()))
+            .collect();
+        self.creates_table
+            .then(|| create_table_sql(&self.target, &columns))
+    }
+
+    pub fn copy_sql(&self) -> Option<String> {
+        let columns: Vec<&str> = self
+            .columns
+            .iter()
+            .zip(&self.mapping)
+            .filter(|(_, csv_index)| csv_index.is_some())
+            .map(|(column, _)| column.name.as_str())
+            .collect();
+
+        (!columns.is_empty()).then(|| copy_from_sql(&self.target, &columns))
+    }
+
+    // The records that pass the checks as CSV for COPY, and the report of
+    // the ones that don't.
+    pub fn copy_data(&self) -> (Vec<u8>, ImportReport) {
+        let mut data = Vec::new();
+        let mut report = ImportReport {
+            loaded: 0,
+            bad_rows: Vec::new(),
+            bad_row_count: 0,
+        };
+
+        for record in &self.records {
+            match self.check_record(record) {
+                Ok(()) => {
+                    let fields: Vec<Option<&str>> = self
+                        .mapping
+                        .iter()
+                        .flatten()
+                        .map(|index| record.fields[*index].as_deref())
+                        .collect();
+                    data.extend_from_slice(copy_csv_line(&fields).as_bytes());
+                }
+                Err(error) => {
+                    report.bad_row_count += 1;
+                    if report.bad_rows.len() < REPORTED_BAD_ROWS {
+                        report.bad_rows.push((record.line, error));
+                    }
+                }
+            }
+        }
+
+        (data, report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(header: bool) -> CsvOptions {
+        CsvOptions {
+            delimiter: ',',
+            header,
+            null_string: String::new(),
+        }
+    }
+
+    fn orders_columns() -> Vec<Column> {
+        vec![
+            Column::new(String::from("id"), Type::INT4),
+            Column::new(String::from("note"), Type::TEXT),
+            Column::new(String::from("total"), Type::NUMERIC),
+        ]
+    }
+
+    fn wizard(text: &str, header: bool) -> ImportWizard {
+        let mut wizard = ImportWizard::new(String::from("orders"), String::from("public"));
+        wizard
+            .load_text(text, &options(header), Some(orders_columns()))
+            .unwrap();
+        wizard
+    }
+
+    fn record(line: usize, fields: &[Option<&str>]) -> CsvRecord {
+        CsvRecord {
+            line,
+            fields: fields
+                .iter()
+                .map(|field| field.map(str::to_string))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn columns_are_mapped_by_name_with_a_header() {
+        let wizard = wizard("Total,ID,comment\n9.50,1,gift\n", true);
+
+        assert_eq!(wizard.csv_columns, vec!["Total", "ID", "comment"]);
+        assert_eq!(wizard.mapping, vec![Some(1), None, Some(0)]);
+        assert_eq!(wizard.records.len(), 1);
+        assert!(!wizard.creates_table);
+    }
+
+    #[test]
+    fn columns_are_mapped_by_position_without_a_header() {
+        let wizard = wizard("1,gift\n2,\n", false);
+
+        assert_eq!(wizard.csv_columns, vec!["column1", "column2"]);
+        assert_eq!(wizard.mapping, vec![Some(0), Some(1), None]);
+        assert_eq!(wizard.records.len(), 2);
+    }
+
+    #[test]
+    fn cycling_wraps_through_none() {
+        let mut wizard = wizard("1,gift\n", false);
+        wizard.selected = 1;
+
+        wizard.cycle(true);
+        assert_eq!(wizard.mapping[1], None);
+        wizard.cycle(true);
+        assert_eq!(wizard.mapping[1], Some(0));
+        wizard.cycle(false);
+        assert_eq!(wizard.mapping[1], None);
+        wizard.cycle(false);
+        assert_eq!(wizard.mapping[1], Some(1));
+    }
+
+    #[test]
+    fn records_with_wrong_field_counts_or_values_are_rejected() {
+        let wizard = wizard("1,gift\n", false);
+
+        assert_eq!(wizard.check_record(&record(1, &[Some("2"), None])), Ok(()));
+        assert_eq!(
+            wizard.check_record(&record(2, &[Some("2")])),
+            Err(String::from("has 1 fields, expected 2"))
+        );
+        assert_eq!(
+            wizard.check_record(&record(3, &[Some("two"), Some("gift")])),
+            Err(String::from("id expects a int4, got \"two\""))
+        );
+    }
+
+    #[test]
+    fn only_the_first_bad_rows_are_reported() {
+        let mut wizard = wizard("1,gift\n", false);
+        wizard.records = (0..REPORTED_BAD_ROWS + 5)
+            .map(|line| record(line + 1, &[Some("bad"), None]))
+            .chain([record(100, &[Some("7"), Some("a \"note\"")])])
+            .collect();
+
+        let (data, report) = wizard.copy_data();
+
+        assert_eq!(
+            String::from_utf8(data).unwrap(),
+            "\"7\",\"a \"\"note\"\"\"\n"
+        );
+        assert_eq!(report.bad_row_count, REPORTED_BAD_ROWS + 5);
+        assert_eq!(report.bad_rows.len(), REPORTED_BAD_ROWS);
+        assert_eq!(report.bad_rows[0].0, 1);
+    }
+}
@@ -1,4 +1,24 @@
+pub mod cell_inspector;
+pub mod change_review;
+pub mod change_set;
+pub mod column_layout;
+pub mod data_grid;
 pub mod database;
 pub mod database_cluster;
+pub mod database_schema;
 pub mod database_table;
 pub mod database_tree;
+pub mod ddl_view;
+pub mod er_diagram;
+pub mod fuzzy;
+pub mod import_wizard;
+pub mod navigation;
+pub mod object_group;
+pub mod query_result;
+pub mod record_view;
+pub mod reference_picker;
+pub mod result_search;
+pub mod row_form;
+pub mod structure_view;
+pub mod text_scroll;
+pub mod undo_log;
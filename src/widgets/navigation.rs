@@ -0,0 +1,151 @@
+use crate::postgres::{data::Value, query::TableName};
+
+// Where the user was looking: a table, its filter, the page and the selected
+// cell on it.
+#[derive(Debug, Clone)]
+pub struct TableLocation {
+    pub table_name: TableName,
+    pub filter: Option<String>,
+    pub page: Option<PagePosition>,
+    pub selected_row: usize,
+    pub selected_column: usize,
+}
+
+// A page of a table: its size, the position of its first row and that row,
+// whose key finds the page again when paging by key.
+#[derive(Debug, Clone)]
+pub struct PagePosition {
+    pub page_size: usize,
+    pub first_row: usize,
+    pub key_row: Vec<Value>,
+}
+
+// Back and forward stacks of the locations visited by following foreign
+// keys, like a browser history.
+#[derive(Debug, Default)]
+pub struct NavigationHistory {
+    back: Vec<TableLocation>,
+    forward: Vec<TableLocation>,
+}
+
+impl NavigationHistory {
+    // Remember the location being left. Visiting somewhere new drops the
+    // forward history.
+    pub fn visit(&mut self, current: TableLocation) {
+        self.back.push(current);
+        self.forward.clear();
+    }
+
+    pub fn back(&mut self, current: TableLocation) -> Option<TableLocation> {
+        let location = self.back.pop()?;
+        self.forward.push(current);
+        Some(location)
+    }
+
+    pub fn forward(&mut self, current: TableLocation) -> Option<TableLocation> {
+        let location = self.forward.pop()?;
+        self.back.push(current);
+        Some(location)
+    }
+
+    // Forget every location, they belong to the database being left.
+    pub fn clear(&mut self) {
+        self.back.clear();
+        self.forward.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(table_name: &str) -> TableLocation {
+        TableLocation {
+            table_name: TableName::new("public", table_name),
+            filter: None,
+            page: None,
+            selected_row: 0,
+            selected_column: 0,
+        }
+    }
+
+    fn table_name(location: Option<TableLocation>) -> Option<String> {
+        location.map(|location| location.table_name.name)
+    }
+
+    #[test]
+    fn back_and_forward_walk_the_visited_tables() {
+        let mut history = NavigationHistory::default();
+        assert!(table_name(history.back(location("orders"))).is_none());
+
+        history.visit(location("orders"));
+        history.visit(location("customers"));
+
+        assert_eq!(
+            table_name(history.back(location("addresses"))).as_deref(),
+            Some("customers")
+        );
+        assert_eq!(
+            table_name(history.back(location("customers"))).as_deref(),
+            Some("orders")
+        );
+        assert!(table_name(history.back(location("orders"))).is_none());
+
+        assert_eq!(
+            table_name(history.forward(location("orders"))).as_deref(),
+            Some("customers")
+        );
+        assert_eq!(
+            table_name(history.forward(location("customers"))).as_deref(),
+            Some("addresses")
+        );
+        assert!(table_name(history.forward(location("addresses"))).is_none());
+    }
+
+    #[test]
+    fn visiting_drops_the_forward_history() {
+        let mut history = NavigationHistory::default();
+        history.visit(location("orders"));
+        history.back(location("customers"));
+
+        history.visit(location("orders"));
+
+        assert!(table_name(history.forward(location("products"))).is_none());
+        assert_eq!(
+            table_name(history.back(location("products"))).as_deref(),
+            Some("orders")
+        );
+    }
+
+    #[test]
+    fn going_forward_with_what_came_back_undoes_the_move() {
+        let mut history = NavigationHistory::default();
+        history.visit(location("orders"));
+
+        let orders = history.back(location("customers")).unwrap();
+        // Opening orders failed, customers stays the current table
+        assert_eq!(
+            table_name(history.forward(orders)).as_deref(),
+            Some("customers")
+        );
+
+        assert!(table_name(history.forward(location("customers"))).is_none());
+        assert_eq!(
+            table_name(history.back(location("customers"))).as_deref(),
+            Some("orders")
+        );
+    }
+
+    #[test]
+    fn clearing_forgets_both_directions() {
+        let mut history = NavigationHistory::default();
+        history.visit(location("orders"));
+        history.visit(location("customers"));
+        history.back(location("addresses"));
+
+        history.clear();
+
+        assert!(table_name(history.back(location("products"))).is_none());
+        assert!(table_name(history.forward(location("products"))).is_none());
+    }
+}
@@ -0,0 +1,167 @@
+use super::database_table::DatabaseTable;
+use crate::postgres::{catalog::ObjectKind, query::TableName};
+
+// An object that isn't a relation, e.g. a function, identified by its oid.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaObject {
+    pub oid: u32,
+    pub name: String,
+    pub is_focused: bool,
+}
+
+// The objects of one kind in a schema. Relations go into `tables` so they
+// can be browsed like tables, everything else into `objects`. Groups are
+// loaded the first time they're expanded, except for tables which come with
+// the schema.
+#[derive(Debug, Clone)]
+pub struct ObjectGroup {
+    pub kind: ObjectKind,
+    pub tables: Vec<DatabaseTable>,
+    pub objects: Vec<SchemaObject>,
+    pub is_loaded: bool,
+    pub is_focused: bool,
+    pub is_expanded: bool,
+}
+
+impl ObjectGroup {
+    pub fn new(kind: ObjectKind) -> Self {
+        Self {
+            kind,
+            tables: Vec::new(),
+            objects: Vec::new(),
+            is_loaded: false,
+            is_focused: false,
+            is_expanded: false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        if self.kind.is_relation() {
+            self.tables.len()
+        } else {
+            self.objects.len()
+        }
+    }
+
+    pub fn item_name(&self, index: usize) -> &str {
+        if self.kind.is_relation() {
+            &self.tables[index].name.name
+        } else {
+            &self.objects[index].name
+        }
+    }
+
+    pub fn is_item_focused(&self, index: usize) -> bool {
+        if self.kind.is_relation() {
+            self.tables[index].is_focused
+        } else {
+            self.objects[index].is_focused
+        }
+    }
+
+    pub fn focus_item(&mut self, index: Option<usize>) {
+        for (table_index, table) in self.tables.iter_mut().enumerate() {
+            table.is_focused = Some(table_index) == index;
+        }
+        for (object_index, object) in self.objects.iter_mut().enumerate() {
+            object.is_focused = Some(object_index) == index;
+        }
+    }
+
+    pub fn set_items(&mut self, schema_name: &str, items: Vec<(u32, String)>) {
+        if self.kind.is_relation() {
+            self.tables = items
+                .into_iter()
+                .map(|(_, name)| DatabaseTable::new(TableName::new(schema_name, &name), Vec::new()))
+                .collect();
+        } else {
+            self.objects = items
+                .into_iter()
+                .map(|(oid, name)| SchemaObject {
+                    oid,
+                    name,
+                    is_focused: false,
+                })
+                .collect();
+        }
+        self.is_loaded = true;
+    }
+
+    // "▦ Tables (12)", the count once the group is loaded.
+    pub fn title(&self) -> String {
+        if self.is_loaded {
+            format!(
+                "{} {} ({})",
+                self.kind.icon(),
+                self.kind.group_name(),
+                self.len()
+            )
+        } else {
+            format!("{} {}", self.kind.icon(), self.kind.group_name())
+        }
+    }
+}
+
+// What the detail view shows for an object that isn't a relation.
+#[derive(Debug, Clone)]
+pub struct ObjectDetails {
+    pub kind: ObjectKind,
+    pub schema: String,
+    pub name: String,
+    pub properties: Vec<(String, String)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(names: &[&str]) -> Vec<(u32, String)> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (index as u32 + 100, name.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn relations_become_tables_of_the_schema() {
+        let mut group = ObjectGroup::new(ObjectKind::View);
+        assert_eq!(group.title(), "◇ Views");
+
+        group.set_items("sales", items(&["totals", "recent"]));
+
+        assert!(group.is_loaded);
+        assert!(group.objects.is_empty());
+        assert_eq!(group.tables[1].name, TableName::new("sales", "recent"));
+        assert_eq!(group.item_name(0), "totals");
+        assert_eq!(group.title(), "◇ Views (2)");
+    }
+
+    #[test]
+    fn other_objects_keep_their_oid() {
+        let mut group = ObjectGroup::new(ObjectKind::Function);
+        group.set_items("public", items(&["add(integer, integer)"]));
+
+        assert!(group.tables.is_empty());
+        assert_eq!(group.objects[0].oid, 100);
+        assert_eq!(group.item_name(0), "add(integer, integer)");
+        assert_eq!(group.title(), "ƒ Functions (1)");
+
+        let mut empty = ObjectGroup::new(ObjectKind::Extension);
+        empty.set_items("public", Vec::new());
+        assert_eq!(empty.title(), "⊕ Extensions (0)");
+    }
+
+    #[test]
+    fn one_item_is_focused_at_a_time() {
+        let mut group = ObjectGroup::new(ObjectKind::Sequence);
+        group.set_items("public", items(&["a_seq", "b_seq"]));
+
+        group.focus_item(Some(1));
+        assert!(!group.is_item_focused(0));
+        assert!(group.is_item_focused(1));
+
+        group.focus_item(None);
+        assert!(!group.is_item_focused(1));
+    }
+}
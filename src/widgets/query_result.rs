@@ -0,0 +1,189 @@
+use tokio_postgres::Error;
+
+use super::{data_grid::GridState, result_search::next_revision};
+use crate::postgres::{
+    cursor::QueryCursor,
+    data::{Column, Value},
+};
+
+// Rows fetched from the cursor at a time.
+pub const FETCH_SIZE: usize = 500;
+
+// Hard limit on the memory a single result may occupy, fetching stops once
+// it is reached.
+pub const MEMORY_CAP_BYTES: usize = 128 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchState {
+    MoreAvailable,
+    Complete,
+    MemoryCapReached,
+}
+
+// Result of an ad-hoc query, fetched in chunks while the user scrolls.
+pub struct QueryResult {
+    pub sql: String,
+    pub columns: Vec<Column>,
+    pub rows: Vec<Vec<Value>>,
+    // Changes whenever rows are added
+    pub revision: u64,
+    pub grid: GridState,
+    pub fetch_state: FetchState,
+    cursor: Option<QueryCursor>,
+    estimated_bytes: usize,
+}
+
+impl QueryResult {
+    pub fn new(sql: String, columns: Vec<Column>, cursor: Option<QueryCursor>) -> Self {
+        let fetch_state = match cursor {
+            Some(_) => FetchState::MoreAvailable,
+            None => FetchState::Complete,
+        };
+
+        Self {
+            sql,
+            columns,
+            rows: Vec::new(),
+            revision: 0,
+            grid: GridState::default(),
+            fetch_state,
+            cursor,
+            estimated_bytes: 0,
+        }
+    }
+
+    // Add rows until the memory cap is hit. Rows past the cap are dropped and
+    // the cursor is closed.
+    pub fn append(&mut self, rows: Vec<Vec<Value>>) {
+        self.revision = next_revision();
+        for row in rows {
+            let row_size: usize = row.iter().map(Value::estimated_size).sum();
+
+            if self.estimated_bytes + row_size > MEMORY_CAP_BYTES {
+                self.fetch_state = FetchState::MemoryCapReached;
+                self.cursor = None;
+                return;
+            }
+
+            self.estimated_bytes += row_size;
+            self.rows.push(row);
+        }
+    }
+
+    pub async fn fetch_more(&mut self) -> Result<(), Error> {
+        let cursor = match self.cursor.as_mut() {
+            Some(cursor) => cursor,
+            None => return Ok(()),
+        };
+
+        let rows = cursor.fetch(&self.columns, FETCH_SIZE).await?;
+        self.append_fetched(rows);
+
+        Ok(())
+    }
+
+    // Add rows read from the cursor, closing it once it has no more.
+    pub fn append_fetched(&mut self, rows: Vec<Vec<Value>>) {
+        let is_exhausted = self
+            .cursor
+            .as_ref()
+            .is_none_or(|cursor| cursor.is_exhausted);
+
+        self.append(rows);
+
+        if is_exhausted && self.fetch_state == FetchState::MoreAvailable {
+            self.fetch_state = FetchState::Complete;
+            self.cursor = None;
+        }
+    }
+
+    pub async fn fetch_all(&mut self) -> Result<(), Error> {
+        while self.fetch_state == FetchState::MoreAvailable {
+            self.fetch_more().await?;
+        }
+
+        Ok(())
+    }
+
+    // Fetch the next chunk once the cursor gets within a screen of the end.
+    pub fn needs_more(&self) -> bool {
+        self.fetch_state == FetchState::MoreAvailable
+            && self.grid.selected_row + self.grid.viewport_height.max(1) * 2 >= self.rows.len()
+    }
+
+    pub fn summary(&self) -> String {
+        let rows = format!(
+            "{} row{}",
+            self.rows.len(),
+            if self.rows.len() == 1 { "" } else { "s" }
+        );
+        match self.fetch_state {
+            FetchState::MoreAvailable => format!("fetched {}, more available (F: fetch all)", rows),
+            FetchState::Complete => rows,
+            FetchState::MemoryCapReached => format!(
+                "fetched {}, stopped at the {} MB memory cap",
+                rows,
+                MEMORY_CAP_BYTES / (1024 * 1024)
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_postgres::types::Type;
+
+    fn result() -> QueryResult {
+        QueryResult::new(
+            String::from("SELECT 1"),
+            vec![Column::new(String::from("value"), Type::BYTEA)],
+            None,
+        )
+    }
+
+    #[test]
+    fn result_without_cursor_is_complete() {
+        let mut result = result();
+        result.append(vec![vec![Value::Int(1)], vec![Value::Int(2)]]);
+
+        assert_eq!(result.fetch_state, FetchState::Complete);
+        assert!(!result.needs_more());
+        assert_eq!(result.summary(), "2 rows");
+    }
+
+    #[test]
+    fn rows_past_the_memory_cap_are_dropped() {
+        let mut result = result();
+        result.fetch_state = FetchState::MoreAvailable;
+        result.append(vec![
+            vec![Value::Int(1)],
+            vec![Value::Bytes(vec![0; MEMORY_CAP_BYTES])],
+            vec![Value::Int(3)],
+        ]);
+
+        assert_eq!(result.rows, vec![vec![Value::Int(1)]]);
+        assert_eq!(result.fetch_state, FetchState::MemoryCapReached);
+        assert!(!result.needs_more());
+        assert_eq!(
+            result.summary(),
+            "fetched 1 row, stopped at the 128 MB memory cap"
+        );
+    }
+
+    #[test]
+    fn more_rows_are_needed_near_the_end() {
+        let mut result = result();
+        result.fetch_state = FetchState::MoreAvailable;
+        result.append((0..100).map(|index| vec![Value::Int(index)]).collect());
+        result.grid.viewport_height = 20;
+
+        assert!(!result.needs_more());
+        result.grid.selected_row = 60;
+        assert!(result.needs_more());
+        assert_eq!(
+            result.summary(),
+            "fetched 100 rows, more available (F: fetch all)"
+        );
+    }
+}
@@ -0,0 +1,346 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, StatefulWidget, Widget},
+};
+use unicode_width::UnicodeWidthStr;
+
+use super::{
+    change_set::StagedCells,
+    data_grid::{pad, truncate, wrap, GridState},
+    result_search::ResultSearch,
+};
+use crate::postgres::data::{Column, Value};
+
+const FIELD_SEPARATOR: &str = " │ ";
+const MAX_NAME_WIDTH: usize = 30;
+const MAX_TYPE_WIDTH: usize = 20;
+
+// The selected row of a result shown vertically, one line per column like
+// psql's expanded display.
+//
+// It shares the grid's state: the selected row is the record on screen and
+// the selected column is the highlighted field, the column offset is used as
+// the first field shown.
+pub struct RecordView<'a> {
+    block: Option<Block<'a>>,
+    columns: &'a [Column],
+    rows: &'a [Vec<Value>],
+    column_order: Option<&'a [usize]>,
+    first_row: usize,
+    search: Option<&'a ResultSearch>,
+    staged: Option<&'a StagedCells>,
+}
+
+impl<'a> RecordView<'a> {
+    pub fn new(columns: &'a [Column], rows: &'a [Vec<Value>]) -> RecordView<'a> {
+        Self {
+            block: None,
+            columns,
+            rows,
+            column_order: None,
+            first_row: 0,
+            search: None,
+            staged: None,
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> RecordView<'a> {
+        self.block = Some(block);
+        self
+    }
+
+    // Indexes of the columns to show, in display order.
+    pub fn column_order(mut self, column_order: &'a [usize]) -> RecordView<'a> {
+        self.column_order = Some(column_order);
+        self
+    }
+
+    // Highlight the values matching the search.
+    pub fn search(mut self, search: Option<&'a ResultSearch>) -> RecordView<'a> {
+        self.search = search;
+        self
+    }
+
+    // Show staged values in place of the loaded ones.
+    pub fn staged(mut self, staged: Option<&'a StagedCells>) -> RecordView<'a> {
+        self.staged = staged;
+        self
+    }
+
+    // Position of the first row of the result in the whole table, used to
+    // number the records of a page.
+    pub fn first_row(mut self, first_row: usize) -> RecordView<'a> {
+        self.first_row = first_row;
+        self
+    }
+}
+
+impl<'a> StatefulWidget for RecordView<'a> {
+    type State = GridState;
+
+    fn render(mut self, area: Rect, buf: &mut Buffer, state: &mut GridState) {
+        let inner_area = match self.block.take() {
+            Some(b) => {
+                let inner_area = b.inner(area);
+                b.render(area, buf);
+                inner_area
+            }
+            None => area,
+        };
+
+        let column_order: Vec<usize> = match self.column_order {
+            Some(column_order) => column_order.to_vec(),
+            None => (0..self.columns.len()).collect(),
+        };
+
+        if inner_area.height < 2 || inner_area.width == 0 || column_order.is_empty() {
+            return;
+        }
+
+        state.clamp(self.rows.len(), column_order.len());
+        let row = match self.rows.get(state.selected_row) {
+            Some(row) => row,
+            None => return,
+        };
+
+        let name_style = Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD);
+        let type_style = Style::default().fg(Color::DarkGray);
+        let separator_style = Style::default().fg(Color::DarkGray);
+        let null_style = Style::default()
+            .fg(Color::DarkGray)
+            .add_modifier(Modifier::ITALIC);
+        let selected_field_style = Style::default().bg(Color::DarkGray);
+        let selected_value_style = Style::default().bg(Color::Blue).fg(Color::White);
+        let match_style = Style::default().bg(Color::Yellow).fg(Color::Black);
+        let visual_style = Style::default().bg(Color::Cyan).fg(Color::Black);
+        let staged_style = Style::default().bg(Color::Magenta).fg(Color::Black);
+        let deleted_style = Style::default()
+            .fg(Color::LightRed)
+            .add_modifier(Modifier::CROSSED_OUT);
+        let is_deleted = self
+            .staged
+            .is_some_and(|staged| staged.deleted_rows.contains(&state.selected_row));
+        let staged_value = |index: usize| {
+            self.staged
+                .and_then(|staged| staged.values.get(&(state.selected_row, index)))
+        };
+
+        let header = format!(
+            "-[ RECORD {} of {} ]",
+            self.first_row + state.selected_row + 1,
+            self.first_row + self.rows.len()
+        );
+        let header = format!(
+            "{}{}",
+            header,
+            "-".repeat((inner_area.width as usize).saturating_sub(header.width()))
+        );
+        buf.set_stringn(
+            inner_area.x,
+            inner_area.y,
+            header,
+            inner_area.width as usize,
+            separator_style,
+        );
+
+        let name_width = column_order
+            .iter()
+            .map(|index| self.columns[*index].name.width())
+            .max()
+            .unwrap_or(0)
+            .min(MAX_NAME_WIDTH);
+        let type_width = column_order
+            .iter()
+            .map(|index| self.columns[*index].type_.name().width())
+            .max()
+            .unwrap_or(0)
+            .min(MAX_TYPE_WIDTH);
+        let value_x = name_width + 1 + type_width + FIELD_SEPARATOR.width();
+        let value_width = (inner_area.width as usize).saturating_sub(value_x).max(1);
+
+        let fields: Vec<Vec<String>> = column_order
+            .iter()
+            .map(|index| {
+                let value = staged_value(*index)
+                    .or_else(|| row.get(*index))
+                    .unwrap_or(&Value::Null);
+                wrap(&value.to_string(), value_width)
+            })
+            .collect();
+
+        // Scroll so the selected field is fully visible
+        let viewport_height = (inner_area.height - 1) as usize;
+        if state.selected_column < state.column_offset {
+            state.column_offset = state.selected_column;
+        }
+        while state.column_offset < state.selected_column
+            && fields[state.column_offset..=state.selected_column]
+                .iter()
+                .map(Vec::len)
+                .sum::<usize>()
+                > viewport_height
+        {
+            state.column_offset += 1;
+        }
+
+        let bottom = inner_area.y + inner_area.height;
+        let mut y = inner_area.y + 1;
+        for (position, lines) in fields.iter().enumerate().skip(state.column_offset) {
+            if y >= bottom {
+                break;
+            }
+
+            let index = column_order[position];
+            let column = &self.columns[index];
+            let is_selected = position == state.selected_column;
+            let value = staged_value(index)
+                .or_else(|| row.get(index))
+                .unwrap_or(&Value::Null);
+            let line_style = if is_selected {
+                selected_field_style
+            } else {
+                Style::default()
+            };
+            let value_style = if is_selected {
+                selected_value_style
+            } else if staged_value(index).is_some() {
+                staged_style
+            } else if is_deleted {
+                deleted_style
+            } else if state.is_in_selection(state.selected_row, position) {
+                visual_style
+            } else if self.search.is_some_and(|search| search.is_match(value)) {
+                match_style
+            } else if value.is_null() {
+                null_style
+            } else {
+                Style::default()
+            };
+
+            for (line_index, line) in lines.iter().enumerate() {
+                if y >= bottom {
+                    break;
+                }
+
+                if is_selected {
+                    buf.set_style(Rect::new(inner_area.x, y, inner_area.width, 1), line_style);
+                }
+
+                let (name, type_name) = if line_index == 0 {
+                    (
+                        truncate(&column.name, name_width),
+                        truncate(column.type_.name(), type_width),
+                    )
+                } else {
+                    (String::new(), String::new())
+                };
+
+                let label = format!("{} ", pad(&name, name_width));
+                let (x, _) = buf.set_stringn(
+                    inner_area.x,
+                    y,
+                    label,
+                    inner_area.width as usize,
+                    line_style.patch(name_style),
+                );
+                let (x, _) = buf.set_stringn(
+                    x,
+                    y,
+                    pad(&type_name, type_width),
+                    (inner_area.x + inner_area.width).saturating_sub(x) as usize,
+                    line_style.patch(type_style),
+                );
+                let (x, _) = buf.set_stringn(
+                    x,
+                    y,
+                    FIELD_SEPARATOR,
+                    (inner_area.x + inner_area.width).saturating_sub(x) as usize,
+                    line_style.patch(separator_style),
+                );
+                buf.set_stringn(
+                    x,
+                    y,
+                    line,
+                    (inner_area.x + inner_area.width).saturating_sub(x) as usize,
+                    line_style.patch(value_style),
+                );
+
+                y += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_postgres::types::Type;
+
+    fn line(buf: &Buffer, y: u16) -> String {
+        (0..buf.area.width)
+            .map(|x| buf.get(x, y).symbol())
+            .collect::<String>()
+            .trim_end()
+            .to_string()
+    }
+
+    #[test]
+    fn record_is_shown_one_field_per_line() {
+        let columns = vec![
+            Column::new(String::from("id"), Type::INT4),
+            Column::new(String::from("note"), Type::TEXT),
+        ];
+        let rows = vec![
+            vec![Value::Int(1), Value::Text(String::from("first"))],
+            vec![Value::Int(2), Value::Null],
+        ];
+        let mut state = GridState {
+            selected_row: 1,
+            ..GridState::default()
+        };
+        let area = Rect::new(0, 0, 30, 4);
+        let mut buf = Buffer::empty(area);
+
+        RecordView::new(&columns, &rows)
+            .first_row(50)
+            .render(area, &mut buf, &mut state);
+
+        assert_eq!(line(&buf, 0), "-[ RECORD 52 of 52 ]----------");
+        assert_eq!(line(&buf, 1), "id   int4 │ 2");
+        assert_eq!(line(&buf, 2), "note text │ NULL");
+    }
+
+    #[test]
+    fn long_values_wrap_and_scroll_to_the_selected_field() {
+        let columns = vec![
+            Column::new(String::from("a"), Type::TEXT),
+            Column::new(String::from("b"), Type::TEXT),
+        ];
+        let rows = vec![vec![
+            Value::Text(String::from("0123456789")),
+            Value::Text(String::from("x")),
+        ]];
+        let mut state = GridState {
+            selected_column: 1,
+            ..GridState::default()
+        };
+        let area = Rect::new(0, 0, 14, 3);
+        let mut buf = Buffer::empty(area);
+
+        RecordView::new(&columns, &rows).render(area, &mut buf, &mut state);
+
+        assert_eq!(state.column_offset, 1);
+        assert_eq!(line(&buf, 1), "b text │ x");
+
+        state.selected_column = 0;
+        RecordView::new(&columns, &rows).render(area, &mut buf, &mut state);
+
+        assert_eq!(state.column_offset, 0);
+        assert_eq!(line(&buf, 1), "a text │ 01234");
+        assert_eq!(line(&buf, 2), "       │ 56789");
+    }
+}
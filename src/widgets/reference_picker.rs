@@ -0,0 +1,39 @@
+use crate::postgres::data::ForeignKey;
+
+// A foreign key pointing at the current row along with the filter that
+// selects the referencing rows and how many there are, if they could be
+// counted.
+pub struct Reference {
+    pub foreign_key: ForeignKey,
+    pub filter: String,
+    pub count: Option<i64>,
+}
+
+// List of the tables with rows referencing the current row.
+pub struct ReferencePicker {
+    pub references: Vec<Reference>,
+    pub selected: usize,
+}
+
+impl ReferencePicker {
+    pub fn new(references: Vec<Reference>) -> Self {
+        Self {
+            references,
+            selected: 0,
+        }
+    }
+
+    pub fn next(&mut self) {
+        if self.selected + 1 < self.references.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn selected_reference(&self) -> Option<&Reference> {
+        self.references.get(self.selected)
+    }
+}
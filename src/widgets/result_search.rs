@@ -0,0 +1,269 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::postgres::data::Value;
+
+// A new revision for rows that were just loaded or added to, telling cached
+// matches apart from rows they weren't found in.
+pub fn next_revision() -> u64 {
+    static REVISION: AtomicU64 = AtomicU64::new(1);
+    REVISION.fetch_add(1, Ordering::Relaxed)
+}
+
+// What a list of matches was found for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MatchKey {
+    pattern: String,
+    case_sensitive: bool,
+    revision: u64,
+    column_order: Vec<usize>,
+}
+
+// Search for a piece of text in the cells of the loaded rows, done on the
+// client unlike the filter. NULLs never match.
+#[derive(Debug, Clone, Default)]
+pub struct ResultSearch {
+    pub pattern: String,
+    pub case_sensitive: bool,
+    // Matches are found again only when the pattern, the case setting, the
+    // rows or the column order change, not on every render
+    cached: Option<(MatchKey, Vec<(usize, usize)>)>,
+}
+
+impl ResultSearch {
+    pub fn is_match(&self, value: &Value) -> bool {
+        if self.pattern.is_empty() || value.is_null() {
+            return false;
+        }
+
+        let text = value.to_string();
+        if self.case_sensitive {
+            text.contains(&self.pattern)
+        } else {
+            text.to_lowercase().contains(&self.pattern.to_lowercase())
+        }
+    }
+
+    // Row and column position of every match, row by row in display order.
+    // The rows are only looked at when they aren't the revision the matches
+    // were last found in.
+    pub fn matches(
+        &mut self,
+        rows: &[Vec<Value>],
+        revision: u64,
+        column_order: &[usize],
+    ) -> &[(usize, usize)] {
+        let key = MatchKey {
+            pattern: self.pattern.clone(),
+            case_sensitive: self.case_sensitive,
+            revision,
+            column_order: column_order.to_vec(),
+        };
+
+        if self
+            .cached
+            .as_ref()
+            .is_none_or(|(cached_key, _)| *cached_key != key)
+        {
+            let matches = rows
+                .iter()
+                .enumerate()
+                .flat_map(|(row_index, row)| {
+                    column_order
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, index)| {
+                            row.get(**index).is_some_and(|value| self.is_match(value))
+                        })
+                        .map(move |(position, _)| (row_index, position))
+                })
+                .collect();
+            self.cached = Some((key, matches));
+        }
+
+        self.cached
+            .as_ref()
+            .map(|(_, matches)| matches.as_slice())
+            .unwrap_or_default()
+    }
+
+    // The match after (or before) the given cell, wrapping around the ends.
+    // With `inclusive` the cell itself counts.
+    pub fn find(
+        &mut self,
+        rows: &[Vec<Value>],
+        revision: u64,
+        column_order: &[usize],
+        from: (usize, usize),
+        forward: bool,
+        inclusive: bool,
+    ) -> Option<(usize, usize)> {
+        let matches = self.matches(rows, revision, column_order);
+
+        let found = if forward {
+            matches
+                .iter()
+                .find(|cell| **cell > from || (inclusive && **cell == from))
+                .or(matches.first())
+        } else {
+            matches
+                .iter()
+                .rev()
+                .find(|cell| **cell < from || (inclusive && **cell == from))
+                .or(matches.last())
+        };
+
+        found.copied()
+    }
+
+    // "pattern · 3 of 17" for the match under the cursor.
+    pub fn summary(
+        &mut self,
+        rows: &[Vec<Value>],
+        revision: u64,
+        column_order: &[usize],
+        cursor: (usize, usize),
+    ) -> String {
+        let case = if self.case_sensitive { " (case)" } else { "" };
+        let prefix = format!("/{}{}", self.pattern, case);
+        let matches = self.matches(rows, revision, column_order);
+
+        match matches.iter().position(|cell| *cell == cursor) {
+            Some(index) => format!("{} · {} of {}", prefix, index + 1, matches.len()),
+            None if matches.is_empty() => format!("{} · no matches", prefix),
+            None => format!(
+                "{} · {} match{}",
+                prefix,
+                matches.len(),
+                if matches.len() == 1 { "" } else { "es" }
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search(pattern: &str, case_sensitive: bool) -> ResultSearch {
+        ResultSearch {
+            pattern: pattern.to_string(),
+            case_sensitive,
+            cached: None,
+        }
+    }
+
+    fn rows() -> Vec<Vec<Value>> {
+        vec![
+            vec![
+                Value::Int(1),
+                Value::Text(String::from("Apple")),
+                Value::Null,
+            ],
+            vec![
+                Value::Int(2),
+                Value::Text(String::from("banana")),
+                Value::Null,
+            ],
+            vec![
+                Value::Int(3),
+                Value::Text(String::from("pineapple")),
+                Value::Text(String::from("APPLE pie")),
+            ],
+        ]
+    }
+
+    #[test]
+    fn matching_ignores_case_unless_asked_to() {
+        assert!(search("apple", false).is_match(&Value::Text(String::from("Apple"))));
+        assert!(!search("apple", true).is_match(&Value::Text(String::from("Apple"))));
+        assert!(search("2", false).is_match(&Value::Int(12)));
+        assert!(!search("null", false).is_match(&Value::Null));
+        assert!(!search("", false).is_match(&Value::Text(String::new())));
+    }
+
+    #[test]
+    fn matches_follow_the_display_order() {
+        let rows = rows();
+
+        assert_eq!(
+            search("apple", false).matches(&rows, 1, &[0, 1, 2]),
+            vec![(0, 1), (2, 1), (2, 2)]
+        );
+        assert_eq!(
+            search("apple", false).matches(&rows, 1, &[2, 1]),
+            vec![(0, 1), (2, 0), (2, 1)]
+        );
+    }
+
+    #[test]
+    fn find_wraps_around_the_ends() {
+        let rows = rows();
+        let order = [0, 1, 2];
+        let mut apple = search("apple", false);
+
+        assert_eq!(
+            apple.find(&rows, 1, &order, (0, 1), true, false),
+            Some((2, 1))
+        );
+        assert_eq!(
+            apple.find(&rows, 1, &order, (0, 1), true, true),
+            Some((0, 1))
+        );
+        assert_eq!(
+            apple.find(&rows, 1, &order, (2, 2), true, false),
+            Some((0, 1))
+        );
+        assert_eq!(
+            apple.find(&rows, 1, &order, (0, 1), false, false),
+            Some((2, 2))
+        );
+        assert_eq!(
+            apple.find(&rows, 1, &order, (1, 0), false, false),
+            Some((0, 1))
+        );
+        assert_eq!(
+            search("cherry", false).find(&rows, 1, &order, (0, 0), true, true),
+            None
+        );
+    }
+
+    #[test]
+    fn summary_counts_the_matches() {
+        let rows = rows();
+        let order = [0, 1, 2];
+
+        assert_eq!(
+            search("apple", false).summary(&rows, 1, &order, (2, 1)),
+            "/apple · 2 of 3"
+        );
+        assert_eq!(
+            search("apple", false).summary(&rows, 1, &order, (1, 1)),
+            "/apple · 3 matches"
+        );
+        assert_eq!(
+            search("APPLE", true).summary(&rows, 1, &order, (0, 0)),
+            "/APPLE (case) · 1 match"
+        );
+        assert_eq!(
+            search("cherry", false).summary(&rows, 1, &order, (0, 0)),
+            "/cherry · no matches"
+        );
+    }
+
+    #[test]
+    fn matches_are_found_again_only_when_something_changes() {
+        let rows = rows();
+        let mut apple = search("apple", false);
+        assert_eq!(apple.matches(&rows, 1, &[0, 1, 2]).len(), 3);
+
+        // The same revision of other rows reuses the matches
+        assert_eq!(apple.matches(&[], 1, &[0, 1, 2]).len(), 3);
+        assert!(apple.matches(&[], 2, &[0, 1, 2]).is_empty());
+        assert_eq!(apple.matches(&rows, 3, &[1]).len(), 2);
+
+        apple.case_sensitive = true;
+        assert_eq!(apple.matches(&rows, 3, &[1]), vec![(2, 0)]);
+        apple.pattern = String::from("APPLE");
+        assert!(apple.matches(&rows, 3, &[1]).is_empty());
+    }
+}
@@ -0,0 +1,280 @@
+use super::change_set::{Change, ChangeKind};
+use crate::postgres::{
+    data::{Column, ColumnDetails},
+    edit::CellValue,
+    query::{quote_identifier, TableName, TableQuery},
+};
+
+// Form for a new row with a field for each column of the table. Columns
+// with a default start at DEFAULT, nullable ones at NULL.
+pub struct RowForm {
+    pub database: String,
+    pub table: TableName,
+    pub columns: Vec<Column>,
+    pub key_columns: Vec<String>,
+    pub details: Vec<Option<ColumnDetails>>,
+    pub values: Vec<CellValue>,
+    pub selected: usize,
+    pub error: Option<String>,
+}
+
+impl RowForm {
+    pub fn new(
+        database: String,
+        table: TableName,
+        columns: Vec<Column>,
+        key_columns: Vec<String>,
+        details: Vec<ColumnDetails>,
+    ) -> Self {
+        let details: Vec<Option<ColumnDetails>> = columns
+            .iter()
+            .map(|column| {
+                details
+                    .iter()
+                    .find(|details| details.name == column.name)
+                    .cloned()
+            })
+            .collect();
+
+        let values = details
+            .iter()
+            .map(|details| match details {
+                Some(details) if details.generated.is_some() || details.default.is_some() => {
+                    CellValue::Default
+                }
+                Some(details) if details.not_null => CellValue::Text(String::new()),
+                _ => CellValue::Null,
+            })
+            .collect();
+
+        Self {
+            database,
+            table,
+            columns,
+            key_columns,
+            details,
+            values,
+            selected: 0,
+            error: None,
+        }
+    }
+
+    pub fn next_field(&mut self, forward: bool) {
+        let count = self.columns.len().max(1);
+        self.selected = if forward {
+            (self.selected + 1) % count
+        } else {
+            (self.selected + count - 1) % count
+        };
+    }
+
+    fn is_generated(&self) -> bool {
+        self.details[self.selected]
+            .as_ref()
+            .is_some_and(|details| details.generated.is_some())
+    }
+
+    // Typing into a NULL or DEFAULT field starts a new value.
+    pub fn type_char(&mut self, c: char) {
+        self.error = None;
+        if self.is_generated() {
+            self.error = Some(format!(
+                "{} is generated by the server",
+                self.columns[self.selected].name
+            ));
+            return;
+        }
+
+        match &mut self.values[self.selected] {
+            CellValue::Text(text) => text.push(c),
+            value => *value = CellValue::Text(c.to_string()),
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        self.error = None;
+        if let CellValue::Text(text) = &mut self.values[self.selected] {
+            text.pop();
+        }
+    }
+
+    pub fn set_value(&mut self, value: CellValue) {
+        self.error = None;
+        if !self.is_generated() {
+            self.values[self.selected] = value;
+        }
+    }
+
+    // INSERT of the row, columns at DEFAULT are left out of it.
+    pub fn insert_query(&self) -> Result<TableQuery, String> {
+        let mut query = TableQuery::new(&self.table, &self.columns);
+
+        for (column, value) in self.columns.iter().zip(&self.values) {
+            if *value == CellValue::Default {
+                continue;
+            }
+            value.check(column)?;
+            let value = value.bind(&mut query, &column.type_);
+            query
+                .assignments
+                .push((quote_identifier(&column.name), value));
+        }
+
+        Ok(query)
+    }
+
+    pub fn insert_change(&self) -> Result<Change, String> {
+        let query = self.insert_query()?;
+        Ok(Change {
+            database: self.database.clone(),
+            table: self.table.clone(),
+            columns: self.columns.clone(),
+            key_columns: self.key_columns.clone(),
+            generated_columns: self
+                .details
+                .iter()
+                .flatten()
+                .filter_map(|details| Some((details.name.clone(), details.generated?)))
+                .collect(),
+            key: Vec::new(),
+            kind: ChangeKind::Insert(
+                self.columns
+                    .iter()
+                    .map(|column| column.name.clone())
+                    .zip(self.values.iter().cloned())
+                    .filter(|(_, value)| *value != CellValue::Default)
+                    .collect(),
+            ),
+            query,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postgres::data::Generated;
+    use tokio_postgres::types::Type;
+
+    fn details(
+        name: &str,
+        not_null: bool,
+        default: Option<&str>,
+        generated: bool,
+    ) -> ColumnDetails {
+        ColumnDetails {
+            name: name.to_string(),
+            type_name: String::new(),
+            not_null,
+            default: default.map(str::to_string),
+            generated: generated.then_some(Generated::Stored),
+        }
+    }
+
+    fn form() -> RowForm {
+        let columns = vec![
+            Column::new(String::from("id"), Type::INT4),
+            Column::new(String::from("total"), Type::INT4),
+            Column::new(String::from("name"), Type::TEXT),
+            Column::new(String::from("note"), Type::TEXT),
+        ];
+        RowForm::new(
+            String::from("shop"),
+            TableName::new("public", "orders"),
+            columns,
+            vec![String::from("id")],
+            vec![
+                details("id", true, Some("nextval('orders_id_seq')"), false),
+                details("total", true, None, true),
+                details("name", true, None, false),
+                details("note", false, None, false),
+            ],
+        )
+    }
+
+    #[test]
+    fn fields_start_at_default_empty_or_null() {
+        let form = form();
+
+        assert_eq!(
+            form.values,
+            vec![
+                CellValue::Default,
+                CellValue::Default,
+                CellValue::Text(String::new()),
+                CellValue::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn generated_columns_cant_be_given_a_value() {
+        let mut form = form();
+        form.next_field(true);
+
+        form.type_char('1');
+        assert_eq!(
+            form.error.as_deref(),
+            Some("total is generated by the server")
+        );
+
+        form.set_value(CellValue::Null);
+        assert_eq!(form.values[1], CellValue::Default);
+    }
+
+    #[test]
+    fn typing_replaces_null_and_default() {
+        let mut form = form();
+        form.next_field(false);
+        assert_eq!(form.selected, 3);
+
+        form.type_char('h');
+        form.type_char('i');
+        form.backspace();
+        assert_eq!(form.values[3], CellValue::Text(String::from("h")));
+
+        form.next_field(true);
+        form.type_char('7');
+        assert_eq!(form.selected, 0);
+        assert_eq!(form.values[0], CellValue::Text(String::from("7")));
+    }
+
+    #[test]
+    fn insert_leaves_out_default_columns() {
+        let mut form = form();
+        form.selected = 2;
+        form.type_char('a');
+
+        let query = form.insert_query().unwrap();
+
+        assert_eq!(
+            query.to_insert_sql(),
+            r#"INSERT INTO "public"."orders" ("name", "note") VALUES ($1::text::"pg_catalog"."text", NULL)"#
+        );
+        assert_eq!(query.params, vec![Some(String::from("a"))]);
+
+        let change = form.insert_change().unwrap();
+        assert_eq!(change.database, "shop");
+        assert_eq!(
+            change.generated_columns,
+            vec![(String::from("total"), Generated::Stored)]
+        );
+    }
+
+    #[test]
+    fn insert_checks_the_values() {
+        let mut form = form();
+        form.type_char('x');
+
+        assert_eq!(
+            form.insert_query().unwrap_err(),
+            "id expects a int4, got \"x\""
+        );
+
+        form.values = vec![CellValue::Default; 4];
+        assert_eq!(
+            form.insert_query().unwrap().to_insert_sql(),
+            r#"INSERT INTO "public"."orders" DEFAULT VALUES"#
+        );
+    }
+}
@@ -0,0 +1,19 @@
+use super::text_scroll::TextScroll;
+use crate::postgres::{query::TableName, structure::TableStructure};
+
+// The structure tab of a relation.
+pub struct StructureView {
+    pub table: TableName,
+    pub structure: TableStructure,
+    pub scroll: TextScroll,
+}
+
+impl StructureView {
+    pub fn new(table: TableName, structure: TableStructure) -> Self {
+        Self {
+            table,
+            structure,
+            scroll: TextScroll::default(),
+        }
+    }
+}
@@ -0,0 +1,93 @@
+const HORIZONTAL_STEP: usize = 8;
+
+// Position of a read-only text in a view, scrolled by lines and sideways for
+// long lines. Clamped to the text while rendering.
+#[derive(Debug, Clone, Default)]
+pub struct TextScroll {
+    pub line: usize,
+    pub column: usize,
+    pub viewport_height: usize,
+}
+
+impl TextScroll {
+    pub fn next_line(&mut self) {
+        self.line += 1;
+    }
+
+    pub fn prev_line(&mut self) {
+        self.line = self.line.saturating_sub(1);
+    }
+
+    pub fn page_down(&mut self) {
+        self.line += self.viewport_height.max(1);
+    }
+
+    pub fn page_up(&mut self) {
+        self.line = self.line.saturating_sub(self.viewport_height.max(1));
+    }
+
+    pub fn left(&mut self) {
+        self.column = self.column.saturating_sub(HORIZONTAL_STEP);
+    }
+
+    pub fn right(&mut self) {
+        self.column += HORIZONTAL_STEP;
+    }
+
+    pub fn top(&mut self) {
+        self.line = 0;
+    }
+
+    pub fn bottom(&mut self) {
+        self.line = usize::MAX;
+    }
+
+    // Keep the text in view given its size and the size of the view.
+    pub fn clamp(&mut self, line_count: usize, line_width: usize, height: usize, width: usize) {
+        self.viewport_height = height;
+        self.line = self.line.min(line_count.saturating_sub(height));
+        self.column = self.column.min(line_width.saturating_sub(width));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_and_pages_stop_at_the_top() {
+        let mut scroll = TextScroll {
+            viewport_height: 10,
+            ..TextScroll::default()
+        };
+
+        scroll.page_down();
+        scroll.next_line();
+        assert_eq!(scroll.line, 11);
+
+        scroll.prev_line();
+        scroll.page_up();
+        scroll.prev_line();
+        assert_eq!(scroll.line, 0);
+
+        scroll.right();
+        scroll.right();
+        scroll.left();
+        assert_eq!(scroll.column, HORIZONTAL_STEP);
+    }
+
+    #[test]
+    fn clamping_keeps_the_text_in_view() {
+        let mut scroll = TextScroll::default();
+        scroll.bottom();
+        scroll.right();
+
+        scroll.clamp(30, 50, 10, 45);
+        assert_eq!((scroll.line, scroll.column), (20, 5));
+        assert_eq!(scroll.viewport_height, 10);
+
+        // Text that fits isn't scrolled
+        scroll.clamp(5, 20, 10, 45);
+        assert_eq!((scroll.line, scroll.column), (0, 0));
+    }
+}
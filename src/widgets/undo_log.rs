@@ -0,0 +1,355 @@
+use super::change_set::{Change, ChangeKind};
+use crate::postgres::{
+    data::{Column, Generated, Value},
+    edit::match_image,
+    query::{quote_identifier, TableName, TableQuery},
+};
+
+// What an applied change did to its row.
+#[derive(Debug, Clone)]
+pub enum AppliedKind {
+    // The edited column with the value it had, and the row it produced
+    Update {
+        column: String,
+        before: Value,
+        after: Vec<Value>,
+    },
+    Insert(Vec<Value>),
+    // The row as it was deleted
+    Delete(Vec<Value>),
+}
+
+// A change applied in this session along with the row images needed to take
+// it back.
+#[derive(Debug, Clone)]
+pub struct AppliedChange {
+    pub database: String,
+    pub table: TableName,
+    pub columns: Vec<Column>,
+    pub key_columns: Vec<String>,
+    pub generated_columns: Vec<(String, Generated)>,
+    pub kind: AppliedKind,
+}
+
+impl AppliedChange {
+    // The change with the row its statement returned.
+    pub fn new(change: &Change, row: Vec<Value>) -> Self {
+        let kind = match &change.kind {
+            ChangeKind::Update { column, before, .. } => AppliedKind::Update {
+                column: column.clone(),
+                before: before.clone(),
+                after: row,
+            },
+            ChangeKind::Insert(_) => AppliedKind::Insert(row),
+            ChangeKind::Delete(_) => AppliedKind::Delete(row),
+        };
+
+        Self {
+            database: change.database.clone(),
+            table: change.table.clone(),
+            columns: change.columns.clone(),
+            key_columns: change.key_columns.clone(),
+            generated_columns: change.generated_columns.clone(),
+            kind,
+        }
+    }
+
+    // Statement taking the change back. Updates and inserts only touch the
+    // row while it still is as the change left it.
+    pub fn inverse(&self) -> Result<(String, TableQuery), String> {
+        let mut query = TableQuery::new(&self.table, &self.columns);
+
+        let sql = match &self.kind {
+            AppliedKind::Update {
+                column,
+                before,
+                after,
+            } => {
+                let index = self
+                    .columns
+                    .iter()
+                    .position(|c| &c.name == column)
+                    .ok_or_else(|| format!("{} no longer has a column {}", self.table, column))?;
+                let value = query.bind(before.to_text(), &self.columns[index].type_);
+                query.assignments.push((quote_identifier(column), value));
+                match_image(
+                    &mut query,
+                    &self.table,
+                    &self.columns,
+                    &self.key_columns,
+                    after,
+                )?;
+                query.to_update_sql()
+            }
+            AppliedKind::Insert(after) => {
+                match_image(
+                    &mut query,
+                    &self.table,
+                    &self.columns,
+                    &self.key_columns,
+                    after,
+                )?;
+                query.to_delete_sql()
+            }
+            // Stored generated columns are computed again, identity columns
+            // get their old value back.
+            AppliedKind::Delete(before) => {
+                for (column, value) in self.columns.iter().zip(before) {
+                    match self.generated(&column.name) {
+                        Some(Generated::Stored) => continue,
+                        Some(Generated::AlwaysIdentity) => query.overriding_system_value = true,
+                        None => {}
+                    }
+                    let value = query.bind(value.to_text(), &column.type_);
+                    query
+                        .assignments
+                        .push((quote_identifier(&column.name), value));
+                }
+                query.to_insert_sql()
+            }
+        };
+
+        Ok((query.returning(sql), query))
+    }
+
+    fn generated(&self, column: &str) -> Option<Generated> {
+        self.generated_columns
+            .iter()
+            .find(|(name, _)| name == column)
+            .map(|(_, generated)| *generated)
+    }
+
+    pub fn summary(&self) -> String {
+        let (verb, row) = match &self.kind {
+            AppliedKind::Update { column, after, .. } => (format!("restore {} of", column), after),
+            AppliedKind::Insert(after) => (String::from("delete the inserted"), after),
+            AppliedKind::Delete(before) => (String::from("insert back the deleted"), before),
+        };
+
+        let key: Vec<String> = self
+            .key_columns
+            .iter()
+            .filter_map(|name| {
+                let index = self.columns.iter().position(|c| &c.name == name)?;
+                Some(format!("{} = {}", name, row.get(index)?))
+            })
+            .collect();
+
+        format!("{} row of {} ({})", verb, self.table, key.join(", "))
+    }
+}
+
+// Change sets applied in this session, undone last first.
+#[derive(Debug, Default)]
+pub struct UndoLog {
+    pub entries: Vec<Vec<AppliedChange>>,
+}
+
+impl UndoLog {
+    pub fn push(&mut self, entry: Vec<AppliedChange>) {
+        if !entry.is_empty() {
+            self.entries.push(entry);
+        }
+    }
+
+    pub fn last(&self) -> Option<&Vec<AppliedChange>> {
+        self.entries.last()
+    }
+
+    // Database the last change set was applied to.
+    pub fn last_database(&self) -> Option<&str> {
+        self.entries
+            .last()?
+            .first()
+            .map(|change| change.database.as_str())
+    }
+
+    pub fn pop(&mut self) {
+        self.entries.pop();
+    }
+
+    // Inverse statements of the last change set, in reverse order.
+    pub fn undo_statements(&self) -> Option<Result<Vec<(String, TableQuery)>, String>> {
+        let entry = self.entries.last()?;
+        Some(entry.iter().rev().map(AppliedChange::inverse).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_postgres::types::Type;
+
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new(String::from("id"), Type::INT4),
+            Column::new(String::from("name"), Type::TEXT),
+            Column::new(String::from("doubled"), Type::INT4),
+        ]
+    }
+
+    fn applied(kind: AppliedKind) -> AppliedChange {
+        AppliedChange {
+            database: String::from("shop"),
+            table: TableName::new("public", "orders"),
+            columns: columns(),
+            key_columns: vec![String::from("id")],
+            generated_columns: Vec::new(),
+            kind,
+        }
+    }
+
+    fn row(id: i64, name: Option<&str>, doubled: i64) -> Vec<Value> {
+        vec![
+            Value::Int(id),
+            name.map_or(Value::Null, |name| Value::Text(name.to_string())),
+            Value::Int(doubled),
+        ]
+    }
+
+    fn params(values: &[Option<&str>]) -> Vec<Option<String>> {
+        values.iter().map(|value| value.map(String::from)).collect()
+    }
+
+    // Conditions matching every column, with placeholders from `first` on.
+    fn match_image_sql(first: usize) -> String {
+        format!(
+            r#""id"::text IS NOT DISTINCT FROM (${}::text::"pg_catalog"."int4")::text AND "name"::text IS NOT DISTINCT FROM (${}::text::"pg_catalog"."text")::text AND "doubled"::text IS NOT DISTINCT FROM (${}::text::"pg_catalog"."int4")::text"#,
+            first,
+            first + 1,
+            first + 2
+        )
+    }
+
+    #[test]
+    fn update_is_taken_back_while_the_row_is_unchanged() {
+        let change = applied(AppliedKind::Update {
+            column: String::from("name"),
+            before: Value::Null,
+            after: row(1, Some("new"), 2),
+        });
+        let (sql, query) = change.inverse().unwrap();
+
+        assert_eq!(
+            sql,
+            format!(
+                r#"UPDATE "public"."orders" SET "name" = $1::text::"pg_catalog"."text" WHERE "id" = $2::text::"pg_catalog"."int4" AND {} RETURNING *"#,
+                match_image_sql(3)
+            )
+        );
+        assert_eq!(
+            query.params,
+            params(&[None, Some("1"), Some("1"), Some("new"), Some("2")])
+        );
+    }
+
+    #[test]
+    fn update_of_a_dropped_column_is_refused() {
+        let change = applied(AppliedKind::Update {
+            column: String::from("gone"),
+            before: Value::Int(1),
+            after: row(1, None, 2),
+        });
+
+        assert_eq!(
+            change.inverse().unwrap_err(),
+            "public.orders no longer has a column gone"
+        );
+    }
+
+    #[test]
+    fn insert_is_taken_back_by_deleting_the_unchanged_row() {
+        let change = applied(AppliedKind::Insert(row(7, None, 14)));
+        let (sql, query) = change.inverse().unwrap();
+
+        assert_eq!(
+            sql,
+            format!(
+                r#"DELETE FROM "public"."orders" WHERE "id" = $1::text::"pg_catalog"."int4" AND {} RETURNING *"#,
+                match_image_sql(2)
+            )
+        );
+        assert_eq!(
+            query.params,
+            params(&[Some("7"), Some("7"), None, Some("14")])
+        );
+    }
+
+    #[test]
+    fn inverse_needs_a_key() {
+        let mut change = applied(AppliedKind::Insert(row(7, None, 14)));
+        change.key_columns.clear();
+
+        assert!(change.inverse().unwrap_err().contains("has no primary key"));
+    }
+
+    #[test]
+    fn delete_is_taken_back_by_inserting_the_row() {
+        let change = applied(AppliedKind::Delete(row(3, Some("x"), 6)));
+        let (sql, query) = change.inverse().unwrap();
+
+        assert_eq!(
+            sql,
+            r#"INSERT INTO "public"."orders" ("id", "name", "doubled") VALUES ($1::text::"pg_catalog"."int4", $2::text::"pg_catalog"."text", $3::text::"pg_catalog"."int4") RETURNING *"#
+        );
+        assert_eq!(query.params, params(&[Some("3"), Some("x"), Some("6")]));
+    }
+
+    #[test]
+    fn deleted_row_with_generated_columns() {
+        let mut change = applied(AppliedKind::Delete(row(3, None, 6)));
+        change.generated_columns = vec![
+            (String::from("id"), Generated::AlwaysIdentity),
+            (String::from("doubled"), Generated::Stored),
+        ];
+        let (sql, query) = change.inverse().unwrap();
+
+        assert_eq!(
+            sql,
+            r#"INSERT INTO "public"."orders" ("id", "name") OVERRIDING SYSTEM VALUE VALUES ($1::text::"pg_catalog"."int4", $2::text::"pg_catalog"."text") RETURNING *"#
+        );
+        assert_eq!(query.params, params(&[Some("3"), None]));
+    }
+
+    #[test]
+    fn summaries_name_the_row() {
+        let update = applied(AppliedKind::Update {
+            column: String::from("name"),
+            before: Value::Null,
+            after: row(1, Some("new"), 2),
+        });
+        assert_eq!(
+            update.summary(),
+            "restore name of row of public.orders (id = 1)"
+        );
+        assert_eq!(
+            applied(AppliedKind::Delete(row(3, None, 6))).summary(),
+            "insert back the deleted row of public.orders (id = 3)"
+        );
+    }
+
+    #[test]
+    fn last_change_set_is_undone_first_in_reverse() {
+        let mut undo_log = UndoLog::default();
+        assert!(undo_log.undo_statements().is_none());
+        assert_eq!(undo_log.last_database(), None);
+
+        undo_log.push(vec![applied(AppliedKind::Insert(row(1, None, 2)))]);
+        undo_log.push(Vec::new());
+        undo_log.push(vec![
+            applied(AppliedKind::Insert(row(2, None, 4))),
+            applied(AppliedKind::Delete(row(3, None, 6))),
+        ]);
+        assert_eq!(undo_log.entries.len(), 2);
+        assert_eq!(undo_log.last_database(), Some("shop"));
+
+        let statements = undo_log.undo_statements().unwrap().unwrap();
+        assert!(statements[0].0.starts_with("INSERT"));
+        assert!(statements[1].0.starts_with("DELETE"));
+
+        undo_log.pop();
+        let statements = undo_log.undo_statements().unwrap().unwrap();
+        assert_eq!(statements.len(), 1);
+        assert_eq!(statements[0].1.params[0], Some(String::from("1")));
+    }
+}